@@ -0,0 +1,143 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides typed change events observed with `WatchCommand`.
+
+use std::marker::PhantomData;
+
+use crate::conversions::FromDoc;
+use crate::errors::{ConversionError, HuusError};
+
+/// A change of a single document in a watched collection.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeEvent<Data> {
+    Insert {
+        id: bson::Bson,
+        document: Data,
+    },
+
+    /// The document is present only if the full document lookup was requested and the document
+    /// still exists.
+    Update {
+        id: bson::Bson,
+        updated_fields: bson::Document,
+        removed_fields: Vec<String>,
+        document: Option<Data>,
+    },
+
+    Replace {
+        id: bson::Bson,
+        document: Data,
+    },
+
+    Delete {
+        id: bson::Bson,
+    },
+
+    /// Events concerning the whole collection, like `drop`, `rename` or `invalidate`.
+    Other {
+        operation_type: String,
+    },
+}
+
+impl<Data> ChangeEvent<Data>
+where
+    Data: FromDoc,
+{
+    /// Converts the event document reported by the database.
+    pub fn from_doc(mut event: bson::Document) -> Result<Self, HuusError> {
+        let operation_type = match event.get_str("operationType") {
+            Ok(operation_type) => operation_type.to_string(),
+            Err(_) => return Err(ConversionError::missing_key("operationType".to_string()).into()),
+        };
+        let id = match &operation_type[..] {
+            "insert" | "update" | "replace" | "delete" => get_document_id(&event)?,
+            _ => return Ok(ChangeEvent::Other { operation_type }),
+        };
+        let document = match event.remove("fullDocument") {
+            Some(bson::Bson::Document(document)) => Some(Data::from_doc(document)?),
+            Some(bson::Bson::Null) | None => None,
+            Some(_) => return Err(ConversionError::wrong_type("fullDocument".to_string()).into()),
+        };
+
+        match &operation_type[..] {
+            "insert" => Ok(ChangeEvent::Insert { id, document: require_document(document)? }),
+            "replace" => Ok(ChangeEvent::Replace { id, document: require_document(document)? }),
+            "update" => {
+                let description = match event.get_document("updateDescription") {
+                    Ok(description) => description,
+                    Err(_) => {
+                        let key = "updateDescription".to_string();
+                        return Err(ConversionError::missing_key(key).into());
+                    }
+                };
+                let updated_fields =
+                    description.get_document("updatedFields").cloned().unwrap_or_default();
+                let removed_fields = match description.get_array("removedFields") {
+                    Ok(fields) => fields
+                        .iter()
+                        .filter_map(|field| field.as_str().map(|field| field.to_string()))
+                        .collect(),
+                    Err(_) => Vec::new(),
+                };
+                Ok(ChangeEvent::Update { id, updated_fields, removed_fields, document })
+            }
+            _ => Ok(ChangeEvent::Delete { id }),
+        }
+    }
+}
+
+/// Reads the ID of the changed document from the event.
+fn get_document_id(event: &bson::Document) -> Result<bson::Bson, ConversionError> {
+    match event.get_document("documentKey").ok().and_then(|key| key.get("_id")) {
+        Some(id) => Ok(id.clone()),
+        None => Err(ConversionError::missing_key("documentKey".to_string())),
+    }
+}
+
+/// Fails if the event does not contain the full document.
+fn require_document<Data>(document: Option<Data>) -> Result<Data, ConversionError> {
+    document.ok_or_else(|| ConversionError::missing_key("fullDocument".to_string()))
+}
+
+/// Iterates over the change events reported by the database converting them to
+/// `ChangeEvent<Data>`. Iterating waits for new events; an interrupted stream may be continued by a
+/// new `WatchCommand` resuming after the last seen event.
+pub struct ChangeStream<Data, Events> {
+    events: Events,
+    resume_token: Option<bson::Document>,
+    phantom: PhantomData<Data>,
+}
+
+impl<Data, Events> ChangeStream<Data, Events> {
+    /// Constructs a new `ChangeStream` from an iterator over raw event documents.
+    pub fn new(events: Events) -> Self {
+        Self { events, resume_token: None, phantom: PhantomData }
+    }
+
+    /// Returns the token identifying the last returned event.
+    pub fn get_resume_token(&self) -> Option<&bson::Document> {
+        self.resume_token.as_ref()
+    }
+}
+
+impl<Data, Events, Error> Iterator for ChangeStream<Data, Events>
+where
+    Data: FromDoc,
+    Events: Iterator<Item = Result<bson::Document, Error>>,
+    HuusError: From<Error>,
+{
+    type Item = Result<ChangeEvent<Data>, HuusError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.events.next()? {
+            Ok(event) => {
+                if let Ok(token) = event.get_document("_id") {
+                    self.resume_token = Some(token.clone());
+                }
+                Some(ChangeEvent::from_doc(event))
+            }
+            Err(error) => Some(Err(error.into())),
+        }
+    }
+}
@@ -8,309 +8,3336 @@ use std::marker::PhantomData;
 
 use bson::{bson, doc};
 
-use crate::conversions::FromDoc;
-use crate::errors::HuusError;
+#[cfg(feature = "sync-driver")]
+use crate::changes::ChangeStream;
+use crate::conversions::{FromDoc, HuusFromBson};
+#[cfg(feature = "sync-driver")]
+use crate::cursor::TypedCursor;
+#[cfg(feature = "sync-driver")]
+use crate::driver::{self, Documents};
+#[cfg(feature = "sync-driver")]
+use crate::errors::UpdateError;
+use crate::errors::{ConversionError, HuusError, WriteError};
+use crate::json::{self, JsonMode};
+use crate::shell;
+#[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+use crate::types::Joined;
+use crate::updates::{BuildArrayFilters, PipelineUpdate, Update};
 
 // -------------------------------------------------------------------------------------------------
 
-pub mod options {
-    pub fn find(limit: u32) -> mongo_driver::CommandAndFindOptions {
-        let mut options = mongo_driver::CommandAndFindOptions::default();
+/// Language-specific rules for string comparison, e.g. for case-insensitive matching.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collation {
+    pub(crate) locale: String,
+    pub(crate) strength: Option<i32>,
+    pub(crate) case_level: Option<bool>,
+    pub(crate) case_first: Option<String>,
+    pub(crate) numeric_ordering: Option<bool>,
+    pub(crate) alternate: Option<String>,
+    pub(crate) max_variable: Option<String>,
+    pub(crate) backwards: Option<bool>,
+}
+
+impl Collation {
+    pub fn new(locale: String) -> Self {
+        Self {
+            locale,
+            strength: None,
+            case_level: None,
+            case_first: None,
+            numeric_ordering: None,
+            alternate: None,
+            max_variable: None,
+            backwards: None,
+        }
+    }
+
+    /// Sets the level of comparison, from 1 (base characters only) to 5 (identical).
+    pub fn with_strength(mut self, strength: i32) -> Self {
+        self.strength = Some(strength);
+        self
+    }
+
+    pub fn with_case_level(mut self, case_level: bool) -> Self {
+        self.case_level = Some(case_level);
+        self
+    }
+
+    /// Sets the sort order of case differences: `"upper"`, `"lower"` or `"off"`.
+    pub fn with_case_first(mut self, case_first: String) -> Self {
+        self.case_first = Some(case_first);
+        self
+    }
+
+    pub fn with_numeric_ordering(mut self, numeric_ordering: bool) -> Self {
+        self.numeric_ordering = Some(numeric_ordering);
+        self
+    }
+
+    /// Sets whether whitespace and punctuation are considered: `"non-ignorable"` or `"shifted"`.
+    pub fn with_alternate(mut self, alternate: String) -> Self {
+        self.alternate = Some(alternate);
+        self
+    }
+
+    /// Sets which characters are ignored with `"shifted"` alternate: `"punct"` or `"space"`.
+    pub fn with_max_variable(mut self, max_variable: String) -> Self {
+        self.max_variable = Some(max_variable);
+        self
+    }
+
+    pub fn with_backwards(mut self, backwards: bool) -> Self {
+        self.backwards = Some(backwards);
+        self
+    }
+
+    /// Returns the collation document as expected by the database.
+    pub fn to_doc(&self) -> bson::Document {
+        let mut document = doc! { "locale": self.locale.clone() };
+        if let Some(strength) = self.strength {
+            document.insert("strength", strength);
+        }
+        if let Some(case_level) = self.case_level {
+            document.insert("caseLevel", case_level);
+        }
+        if let Some(case_first) = &self.case_first {
+            document.insert("caseFirst", case_first.clone());
+        }
+        if let Some(numeric_ordering) = self.numeric_ordering {
+            document.insert("numericOrdering", numeric_ordering);
+        }
+        if let Some(alternate) = &self.alternate {
+            document.insert("alternate", alternate.clone());
+        }
+        if let Some(max_variable) = &self.max_variable {
+            document.insert("maxVariable", max_variable.clone());
+        }
+        if let Some(backwards) = self.backwards {
+            document.insert("backwards", backwards);
+        }
+        document
+    }
+}
+
+/// Consistency and isolation level of the data returned by read commands.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReadConcern {
+    Local,
+    Available,
+    Majority,
+    Linearizable,
+    Snapshot,
+}
+
+impl ReadConcern {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadConcern::Local => "local",
+            ReadConcern::Available => "available",
+            ReadConcern::Majority => "majority",
+            ReadConcern::Linearizable => "linearizable",
+            ReadConcern::Snapshot => "snapshot",
+        }
+    }
+
+    /// Returns the read concern document as expected by the database.
+    pub fn to_doc(&self) -> bson::Document {
+        doc! { "level": self.as_str() }
+    }
+}
+
+/// Level of acknowledgment requested from the database for write commands.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteConcern {
+    pub(crate) w: Option<bson::Bson>,
+    pub(crate) journal: Option<bool>,
+    pub(crate) timeout_ms: Option<u32>,
+}
+
+impl WriteConcern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests acknowledgment from the given number of replica set members. Zero disables
+    /// acknowledgment altogether.
+    pub fn with_nodes(mut self, nodes: u32) -> Self {
+        self.w = Some(bson::Bson::I32(nodes as i32));
+        self
+    }
+
+    /// Requests acknowledgment from the majority of replica set members.
+    pub fn with_majority(mut self) -> Self {
+        self.w = Some(bson::Bson::String("majority".to_string()));
+        self
+    }
+
+    /// Requests acknowledgment that the write was written to the on-disk journal.
+    pub fn with_journal(mut self, journal: bool) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Sets the time limit for the acknowledgment.
+    pub fn with_timeout_ms(mut self, timeout_ms: u32) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Returns the write concern document as expected by the database.
+    pub fn to_doc(&self) -> bson::Document {
+        let mut document = bson::Document::new();
+        if let Some(w) = &self.w {
+            document.insert_bson("w".to_string(), w.clone());
+        }
+        if let Some(journal) = self.journal {
+            document.insert("j", journal);
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            document.insert("wtimeout", timeout_ms as i64);
+        }
+        document
+    }
+}
+
+/// Replica set members the read commands are directed to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReadPreference {
+    Primary,
+    PrimaryPreferred,
+    Secondary,
+    SecondaryPreferred,
+    Nearest,
+}
+
+impl ReadPreference {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadPreference::Primary => "primary",
+            ReadPreference::PrimaryPreferred => "primaryPreferred",
+            ReadPreference::Secondary => "secondary",
+            ReadPreference::SecondaryPreferred => "secondaryPreferred",
+            ReadPreference::Nearest => "nearest",
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Common interface of the commands sent to the database as a single database command.
+pub trait Command {
+    /// Returns the database command.
+    fn get_command(&self) -> bson::Document;
+
+    /// Returns the database command as MongoDB Extended JSON.
+    fn to_extended_json(&self, mode: JsonMode) -> String {
+        json::to_extended_json(&self.get_command(), mode)
+    }
+
+    /// Returns the `mongosh` snippet equivalent to this command.
+    fn to_shell(&self) -> String {
+        format!("db.runCommand({})", shell::document_to_shell(&self.get_command()))
+    }
+}
+
+/// Implements `Display` showing the `mongosh` snippet of the given commands.
+macro_rules! impl_display {
+    ($($name:ident $(<$($param:ident: $bound:path),+>)?),+ $(,)?) => {
+        $(
+            impl$(<$($param: $bound),+>)? std::fmt::Display for $name$(<$($param),+>)? {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(&self.to_shell())
+                }
+            }
+        )+
+    };
+}
+
+pub(crate) use impl_display;
+
+impl_display!(
+    CreateCollectionCommand,
+    DropCollectionCommand,
+    CreateIndexesCommand,
+    ListIndexesCommand,
+    DropIndexesCommand,
+    FindOneCommand<Data: FromDoc>,
+    FindCommand<Data: FromDoc>,
+    DistinctCommand<Value: HuusFromBson>,
+    CountDocumentsCommand,
+    EstimatedCountCommand,
+    LookupCommand<Data: FromDoc, Foreign: FromDoc>,
+    AggregateCommand,
+    WatchCommand<Data: FromDoc>,
+    InsertCommand,
+    InsertManyCommand,
+    UpdateCommand,
+    ReplaceOneCommand,
+    DeleteCommand,
+    BulkWriteCommand,
+    FindAndModifyCommand<Data: FromDoc>,
+    ExplainCommand,
+);
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct CreateCollectionCommand {
+    pub(crate) collection_name: String,
+    pub(crate) capped_size: Option<u64>,
+    pub(crate) max_documents: Option<u64>,
+    pub(crate) validator: Option<bson::Document>,
+    pub(crate) collation: Option<Collation>,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl CreateCollectionCommand {
+    pub fn new(collection_name: String) -> Self {
+        Self {
+            collection_name,
+            capped_size: None,
+            max_documents: None,
+            validator: None,
+            collation: None,
+            write_concern: None,
+        }
+    }
+
+    /// Makes the collection capped to the given size in bytes. The oldest documents are removed
+    /// when the size is exceeded.
+    pub fn with_capped(mut self, size: u64) -> Self {
+        self.capped_size = Some(size);
+        self
+    }
+
+    /// Limits the number of documents in a capped collection. Has no effect if the collection is
+    /// not capped.
+    pub fn with_max_documents(mut self, max_documents: u64) -> Self {
+        self.max_documents = Some(max_documents);
+        self
+    }
+
+    /// Sets the document validating inserted and updated documents, e.g. a `$jsonSchema`.
+    pub fn with_validator(mut self, validator: bson::Document) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Sets the default collation of the collection.
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_validator(&self) -> Option<&bson::Document> {
+        self.validator.as_ref()
+    }
+
+    /// Creates the collection unless it already exists. An existing collection is not altered.
+    /// Creates the collection unless it already exists.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<(), HuusError> {
+        if !driver::has_collection(db, &self.collection_name)? {
+            driver::run(db, self.get_command(), None)?;
+        }
+        Ok(())
+    }
+}
+
+impl Command for CreateCollectionCommand {
+    /// Returns the `create` database command.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! { "create": self.collection_name.clone() };
+        if let Some(size) = self.capped_size {
+            command.insert("capped", true);
+            command.insert("size", size as i64);
+            if let Some(max_documents) = self.max_documents {
+                command.insert("max", max_documents as i64);
+            }
+        }
+        if let Some(validator) = &self.validator {
+            command.insert("validator", validator.clone());
+        }
+        if let Some(collation) = &self.collation {
+            command.insert("collation", collation.to_doc());
+        }
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let mut options = self.get_command();
+        let name = options.remove("create").unwrap_or(bson::Bson::Null);
+        format!("db{}", shell::call("createCollection", vec![name], options))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct DropCollectionCommand {
+    pub(crate) collection_name: String,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl DropCollectionCommand {
+    pub fn new(collection_name: String) -> Self {
+        Self { collection_name, write_concern: None }
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    /// Drops the collection if it exists.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<(), HuusError> {
+        if driver::has_collection(db, &self.collection_name)? {
+            driver::run(db, self.get_command(), None)?;
+        }
+        Ok(())
+    }
+}
+
+impl Command for DropCollectionCommand {
+    /// Returns the `drop` database command.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! { "drop": self.collection_name.clone() };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let options = get_write_options(&self.write_concern);
+        shell::collection(&self.collection_name) + &shell::call("drop", Vec::new(), options)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Options of a single index created by `CreateIndexesCommand`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexOptions {
+    pub(crate) name: Option<String>,
+    pub(crate) unique: bool,
+    pub(crate) sparse: bool,
+    pub(crate) expire_after_seconds: Option<u32>,
+    pub(crate) partial_filter: Option<bson::Document>,
+}
+
+impl IndexOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the index. By default the name is made of the keys and their directions,
+    /// e.g. `a_1_b_-1`.
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Rejects documents with a value of the keys already present in the collection.
+    pub fn with_unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    /// Skips documents missing the indexed field.
+    pub fn with_sparse(mut self, sparse: bool) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Makes the database remove documents the given time after the date stored in the indexed
+    /// field.
+    pub fn with_expire_after_seconds(mut self, expire_after_seconds: u32) -> Self {
+        self.expire_after_seconds = Some(expire_after_seconds);
+        self
+    }
+
+    /// Indexes only the documents matching the filter.
+    pub fn with_partial_filter(mut self, partial_filter: bson::Document) -> Self {
+        self.partial_filter = Some(partial_filter);
+        self
+    }
+}
+
+/// Specification of a single index created by `CreateIndexesCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexModel {
+    pub(crate) keys: bson::Document,
+    pub(crate) options: IndexOptions,
+}
+
+impl IndexModel {
+    /// Constructs an index over the given keys, e.g. `{ "a": 1, "b": -1 }`.
+    pub fn new(keys: bson::Document) -> Self {
+        Self { keys, options: IndexOptions::new() }
+    }
+
+    /// Constructs a text index over the given fields.
+    pub fn text(fields: Vec<String>) -> Self {
+        let mut keys = bson::Document::new();
+        for field in fields {
+            keys.insert(field, "text");
+        }
+        Self::new(keys)
+    }
+
+    pub fn with_options(mut self, options: IndexOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn get_keys(&self) -> &bson::Document {
+        &self.keys
+    }
+
+    pub fn get_options(&self) -> &IndexOptions {
+        &self.options
+    }
+
+    /// Returns the name given in the options or the default one made of the keys.
+    pub fn get_name(&self) -> String {
+        if let Some(name) = &self.options.name {
+            return name.clone();
+        }
+        let mut parts = Vec::with_capacity(2 * self.keys.len());
+        for (key, value) in self.keys.iter() {
+            parts.push(key.clone());
+            parts.push(match value {
+                bson::Bson::String(kind) => kind.clone(),
+                bson::Bson::FloatingPoint(direction) => (*direction as i64).to_string(),
+                value => value.to_string(),
+            });
+        }
+        parts.join("_")
+    }
+
+    /// Returns the index specification as expected by the `createIndexes` command.
+    pub fn to_doc(&self) -> bson::Document {
+        let mut document = doc! { "name": self.get_name(), "key": self.keys.clone() };
+        if self.options.unique {
+            document.insert("unique", true);
+        }
+        if self.options.sparse {
+            document.insert("sparse", true);
+        }
+        if let Some(expire_after_seconds) = self.options.expire_after_seconds {
+            document.insert("expireAfterSeconds", expire_after_seconds as i64);
+        }
+        if let Some(partial_filter) = &self.options.partial_filter {
+            document.insert("partialFilterExpression", partial_filter.clone());
+        }
+        document
+    }
+
+    /// Checks if the existing index was created from this specification.
+    pub fn matches(&self, index: &IndexInfo) -> bool {
+        let keys_match = match index.get_text_fields() {
+            Some(mut fields) => {
+                let mut declared: Vec<String> = Vec::with_capacity(self.keys.len());
+                for (key, value) in self.keys.iter() {
+                    if value.as_str() != Some("text") {
+                        return false;
+                    }
+                    declared.push(key.clone());
+                }
+                fields.sort();
+                declared.sort();
+                fields == declared
+            }
+            None => {
+                self.keys.len() == index.keys.len()
+                    && self.keys.iter().zip(index.keys.iter()).all(|(declared, existing)| {
+                        declared.0 == existing.0 && is_same_key_kind(declared.1, existing.1)
+                    })
+            }
+        };
+        let expire_after_seconds = self.options.expire_after_seconds.map(i64::from);
+        keys_match
+            && index.name == self.get_name()
+            && index.unique == self.options.unique
+            && index.sparse == self.options.sparse
+            && index.expire_after_seconds == expire_after_seconds
+            && index.partial_filter == self.options.partial_filter
+    }
+}
+
+/// Compares the directions or kinds of index keys ignoring the numeric type of the directions.
+fn is_same_key_kind(declared: &bson::Bson, existing: &bson::Bson) -> bool {
+    fn to_number(value: &bson::Bson) -> Option<f64> {
+        match value {
+            bson::Bson::I32(number) => Some(f64::from(*number)),
+            bson::Bson::I64(number) => Some(*number as f64),
+            bson::Bson::FloatingPoint(number) => Some(*number),
+            _ => None,
+        }
+    }
+    match (to_number(declared), to_number(existing)) {
+        (Some(declared), Some(existing)) => declared == existing,
+        _ => declared == existing,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CreateIndexesCommand {
+    pub(crate) collection_name: String,
+    pub(crate) indexes: Vec<IndexModel>,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl CreateIndexesCommand {
+    /// Constructs a command creating a text index over the given fields named after the
+    /// collection. Use `new_models` to create other kinds of indexes.
+    pub fn new(collection_name: String, indexed_fields: Vec<String>) -> Self {
+        let indexes = if indexed_fields.is_empty() {
+            Vec::new()
+        } else {
+            let options = IndexOptions::new().with_name(collection_name.clone());
+            vec![IndexModel::text(indexed_fields).with_options(options)]
+        };
+        Self::new_models(collection_name, indexes)
+    }
+
+    pub fn new_models(collection_name: String, indexes: Vec<IndexModel>) -> Self {
+        Self { collection_name, indexes, write_concern: None }
+    }
+
+    pub fn with_index(mut self, index: IndexModel) -> Self {
+        self.indexes.push(index);
+        self
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_indexes(&self) -> &Vec<IndexModel> {
+        &self.indexes
+    }
+
+    /// Checks if there are no indexes to create. The database refuses such a command so executing
+    /// it does nothing.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<(), HuusError> {
+        if !self.is_empty() {
+            driver::run(db, self.get_command(), None)?;
+        }
+        Ok(())
+    }
+}
+
+impl Command for CreateIndexesCommand {
+    /// Returns the `createIndexes` database command.
+    fn get_command(&self) -> bson::Document {
+        let indexes = self.indexes.iter().map(|index| bson::Bson::Document(index.to_doc()));
+        let mut command = doc! {
+            "createIndexes": self.collection_name.clone(),
+            "indexes": indexes.collect::<Vec<_>>(),
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Description of an existing index as reported by `ListIndexesCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexInfo {
+    pub name: String,
+
+    /// Key specification. Text indexes are reported with internal keys (`_fts` and `_ftsx`).
+    pub keys: bson::Document,
+
+    /// Weights of the fields of a text index.
+    pub weights: Option<bson::Document>,
+
+    pub unique: bool,
+    pub sparse: bool,
+    pub expire_after_seconds: Option<i64>,
+    pub partial_filter: Option<bson::Document>,
+}
+
+impl IndexInfo {
+    /// Reads the index description from an element of the `listIndexes` reply.
+    pub fn from_doc(document: &bson::Document) -> Result<Self, ConversionError> {
+        let name = match document.get_str("name") {
+            Ok(name) => name.to_string(),
+            Err(_) => return Err(ConversionError::missing_key("name".to_string())),
+        };
+        let keys = match document.get_document("key") {
+            Ok(keys) => keys.clone(),
+            Err(_) => return Err(ConversionError::missing_key("key".to_string())),
+        };
+        let expire_after_seconds = match document.get("expireAfterSeconds") {
+            Some(bson::Bson::I32(seconds)) => Some(i64::from(*seconds)),
+            Some(bson::Bson::I64(seconds)) => Some(*seconds),
+            Some(bson::Bson::FloatingPoint(seconds)) => Some(*seconds as i64),
+            Some(_) => return Err(ConversionError::wrong_type("expireAfterSeconds".to_string())),
+            None => None,
+        };
+        Ok(Self {
+            name,
+            keys,
+            weights: document.get_document("weights").ok().cloned(),
+            unique: document.get_bool("unique").unwrap_or(false),
+            sparse: document.get_bool("sparse").unwrap_or(false),
+            expire_after_seconds,
+            partial_filter: document.get_document("partialFilterExpression").ok().cloned(),
+        })
+    }
+
+    /// Returns the fields of a text index or `None` if this is not a text index.
+    pub fn get_text_fields(&self) -> Option<Vec<String>> {
+        if self.keys.get_str("_fts") == Ok("text") {
+            let weights = self.weights.iter().flat_map(|weights| weights.keys());
+            Some(weights.cloned().collect())
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListIndexesCommand {
+    pub(crate) collection_name: String,
+}
+
+impl ListIndexesCommand {
+    pub fn new(collection_name: String) -> Self {
+        Self { collection_name }
+    }
+
+    /// Returns the indexes of the collection. The collection has to exist. A collection has at
+    /// most 64 indexes so all of them are returned in the first batch.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Vec<IndexInfo>, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+
+    /// Reads the indexes from the reply to the `listIndexes` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<Vec<IndexInfo>, HuusError> {
+        let batch = get_first_batch(reply)?;
+        let mut result = Vec::with_capacity(batch.len());
+        for index in batch {
+            match index {
+                bson::Bson::Document(index) => result.push(IndexInfo::from_doc(index)?),
+                _ => return Err(ConversionError::wrong_type("firstBatch".to_string()).into()),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Command for ListIndexesCommand {
+    /// Returns the `listIndexes` database command.
+    fn get_command(&self) -> bson::Document {
+        doc! { "listIndexes": self.collection_name.clone(), "cursor": {} }
+    }
+
+    fn to_shell(&self) -> String {
+        let call = shell::call("getIndexes", Vec::new(), bson::Document::new());
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct DropIndexesCommand {
+    pub(crate) collection_name: String,
+    pub(crate) index_name: Option<String>,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl DropIndexesCommand {
+    /// Constructs a command dropping the index with the given name.
+    pub fn new(collection_name: String, index_name: String) -> Self {
+        Self { collection_name, index_name: Some(index_name), write_concern: None }
+    }
+
+    /// Constructs a command dropping all indexes except the one on `_id`.
+    pub fn all(collection_name: String) -> Self {
+        Self { collection_name, index_name: None, write_concern: None }
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    /// Returns the name of the dropped index or `None` if all indexes are dropped.
+    pub fn get_index_name(&self) -> Option<&str> {
+        self.index_name.as_deref()
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<(), HuusError> {
+        driver::run(db, self.get_command(), None)?;
+        Ok(())
+    }
+}
+
+impl Command for DropIndexesCommand {
+    /// Returns the `dropIndexes` database command.
+    fn get_command(&self) -> bson::Document {
+        let index = self.index_name.clone().unwrap_or_else(|| "*".to_string());
+        let mut command = doc! {
+            "dropIndexes": self.collection_name.clone(),
+            "index": index,
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let call = match &self.index_name {
+            Some(name) => {
+                let arguments = vec![bson::Bson::String(name.clone())];
+                shell::call("dropIndex", arguments, get_write_options(&self.write_concern))
+            }
+            None => {
+                let arguments = vec![bson::Bson::String("*".to_string())];
+                shell::call("dropIndexes", arguments, get_write_options(&self.write_concern))
+            }
+        };
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Operations bringing the indexes of a collection to the ones declared in the schema.
+#[derive(Debug, PartialEq)]
+pub struct IndexSync {
+    pub(crate) create: Option<CreateIndexesCommand>,
+    pub(crate) outdated: Vec<DropIndexesCommand>,
+    pub(crate) undeclared: Vec<DropIndexesCommand>,
+    pub(crate) drop_undeclared: bool,
+}
+
+impl IndexSync {
+    /// Compares the existing indexes with the declared ones. Declared indexes which differ from
+    /// the existing ones with the same name are recreated. Other indexes which are not declared
+    /// are kept unless `with_drop_undeclared` is set. The index on `_id` is always kept.
+    pub fn new(collection_name: String, declared: Vec<IndexModel>, existing: &[IndexInfo]) -> Self {
+        let mut outdated = Vec::new();
+        let mut undeclared = Vec::new();
+        let mut missing = declared;
+        for index in existing {
+            if index.name == "_id_" {
+                continue;
+            }
+            match missing.iter().position(|model| model.matches(index)) {
+                Some(position) => {
+                    missing.remove(position);
+                }
+                None => {
+                    let command =
+                        DropIndexesCommand::new(collection_name.clone(), index.name.clone());
+                    if missing.iter().any(|model| model.get_name() == index.name) {
+                        outdated.push(command);
+                    } else {
+                        undeclared.push(command);
+                    }
+                }
+            }
+        }
+
+        let create = if missing.is_empty() {
+            None
+        } else {
+            Some(CreateIndexesCommand::new_models(collection_name, missing))
+        };
+        Self { create, outdated, undeclared, drop_undeclared: false }
+    }
+
+    /// Specifies if the indexes which are not declared in the schema should be dropped. They are
+    /// kept by default as they may have been created outside of the schema on purpose.
+    pub fn with_drop_undeclared(mut self, drop_undeclared: bool) -> Self {
+        self.drop_undeclared = drop_undeclared;
+        self
+    }
+
+    /// Returns the command creating the missing declared indexes.
+    pub fn get_create(&self) -> Option<&CreateIndexesCommand> {
+        self.create.as_ref()
+    }
+
+    /// Returns the commands dropping the outdated indexes and, if requested, the undeclared ones.
+    pub fn get_drop(&self) -> Vec<&DropIndexesCommand> {
+        let undeclared = if self.drop_undeclared { &self.undeclared[..] } else { &[] };
+        self.outdated.iter().chain(undeclared).collect()
+    }
+
+    /// Returns the commands dropping the indexes which are not declared in the schema, whether
+    /// they are going to be executed or not.
+    pub fn get_undeclared(&self) -> &Vec<DropIndexesCommand> {
+        &self.undeclared
+    }
+
+    /// Checks if the indexes already match the declared ones, ignoring the undeclared indexes
+    /// which are kept.
+    pub fn is_empty(&self) -> bool {
+        self.create.is_none() && self.get_drop().is_empty()
+    }
+
+    /// Drops the outdated indexes first so that an index with the same name can be recreated.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<(), HuusError> {
+        for command in self.get_drop() {
+            command.execute(db)?;
+        }
+        if let Some(command) = &self.create {
+            command.execute(db)?;
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct FindOneCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) projection: Option<bson::Document>,
+    pub(crate) sort: Option<bson::Document>,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+    pub(crate) phantom: PhantomData<Data>,
+}
+
+impl<Data> FindOneCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub fn new(collection_name: String, filter: bson::Document) -> Self {
+        Self {
+            collection_name,
+            filter,
+            projection: None,
+            sort: None,
+            read_concern: None,
+            read_preference: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_projection(mut self, projection: bson::Document) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
+    /// Sets the order in which the documents are searched. The first document in this order is
+    /// returned.
+    pub fn with_sort(mut self, sort: bson::Document) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    pub fn get_projection(&self) -> Option<&bson::Document> {
+        self.projection.as_ref()
+    }
+
+    pub fn get_sort(&self) -> Option<&bson::Document> {
+        self.sort.as_ref()
+    }
+
+    /// Returns the query in the legacy form wrapping the filter together with the query modifiers
+    /// for sorting and read concern.
+    pub fn get_query(&self) -> bson::Document {
+        if self.sort.is_none() && self.read_concern.is_none() {
+            return self.filter.clone();
+        }
+
+        let mut query = doc! { "$query": self.filter.clone() };
+        if let Some(sort) = &self.sort {
+            query.insert("$orderby", sort.clone());
+        }
+        if let Some(read_concern) = &self.read_concern {
+            query.insert("$readConcern", read_concern.to_doc());
+        }
+        query
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Option<Data>, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), self.read_preference)?)
+    }
+
+    /// Reads the found document from the reply to the `find` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<Option<Data>, HuusError> {
+        match get_first_batch(reply)?.first() {
+            Some(bson::Bson::Document(document)) => Ok(Some(Data::from_doc(document.clone())?)),
+            Some(_) => Err(ConversionError::wrong_type("firstBatch".to_string()).into()),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<Data> Command for FindOneCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Returns the `find` database command equivalent to this query.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! {
+            "find": self.collection_name.clone(),
+            "filter": self.filter.clone(),
+            "limit": 1i64,
+            "singleBatch": true,
+        };
+        if let Some(projection) = &self.projection {
+            command.insert("projection", projection.clone());
+        }
+        if let Some(sort) = &self.sort {
+            command.insert("sort", sort.clone());
+        }
+        if let Some(read_concern) = &self.read_concern {
+            command.insert("readConcern", read_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let mut options = get_read_options(self.read_concern, self.read_preference);
+        if let Some(sort) = &self.sort {
+            options.insert("sort", sort.clone());
+        }
+        let mut arguments = vec![bson::Bson::Document(self.filter.clone())];
+        if self.projection.is_some() || !options.is_empty() {
+            let projection = self.projection.clone().unwrap_or_default();
+            arguments.push(bson::Bson::Document(projection));
+        }
+        shell::collection(&self.collection_name) + &shell::call("findOne", arguments, options)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Index the database is forced to use when searching for the documents.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Hint {
+    /// Index identified by its name.
+    Name(String),
+
+    /// Index identified by its key specification, e.g. `{ "field": 1 }`.
+    Keys(bson::Document),
+}
+
+impl Hint {
+    pub fn to_bson(&self) -> bson::Bson {
+        match self {
+            Hint::Name(name) => bson::Bson::String(name.clone()),
+            Hint::Keys(keys) => bson::Bson::Document(keys.clone()),
+        }
+    }
+}
+
+/// Options modifying how the documents are searched for and returned by `FindCommand`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FindOptions {
+    pub(crate) sort: Option<bson::Document>,
+    pub(crate) limit: Option<u32>,
+    pub(crate) skip: Option<u32>,
+    pub(crate) projection: Option<bson::Document>,
+    pub(crate) batch_size: Option<u32>,
+    pub(crate) max_time_ms: Option<u32>,
+    pub(crate) collation: Option<Collation>,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+    pub(crate) hint: Option<Hint>,
+}
+
+impl FindOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sort(mut self, sort: bson::Document) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_skip(mut self, skip: u32) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+
+    pub fn with_projection(mut self, projection: bson::Document) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Sets the time limit for processing the query on the server side.
+    pub fn with_max_time_ms(mut self, max_time_ms: u32) -> Self {
+        self.max_time_ms = Some(max_time_ms);
+        self
+    }
+
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    /// Forces the database to use the given index.
+    pub fn with_hint(mut self, hint: Hint) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    pub fn get_sort(&self) -> Option<&bson::Document> {
+        self.sort.as_ref()
+    }
+
+    pub fn get_limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    pub fn get_skip(&self) -> Option<u32> {
+        self.skip
+    }
+
+    pub fn get_projection(&self) -> Option<&bson::Document> {
+        self.projection.as_ref()
+    }
+
+    pub fn get_batch_size(&self) -> Option<u32> {
+        self.batch_size
+    }
+
+    pub fn get_max_time_ms(&self) -> Option<u32> {
+        self.max_time_ms
+    }
+
+    pub fn get_collation(&self) -> Option<&Collation> {
+        self.collation.as_ref()
+    }
+
+    pub fn get_read_concern(&self) -> Option<ReadConcern> {
+        self.read_concern
+    }
+
+    pub fn get_read_preference(&self) -> Option<ReadPreference> {
+        self.read_preference
+    }
+
+    pub fn get_hint(&self) -> Option<&Hint> {
+        self.hint.as_ref()
+    }
+
+    /// Returns the options passed to the driver.
+    #[cfg(feature = "sync-driver")]
+    fn to_driver_options(&self) -> Result<mongodb::options::FindOptions, ConversionError> {
+        let mut options = mongodb::options::FindOptions::default();
+        options.sort = self.sort.as_ref().map(driver::to_driver).transpose()?;
+        options.limit = self.limit.map(i64::from);
+        options.skip = self.skip.map(u64::from);
+        options.projection = self.projection.as_ref().map(driver::to_driver).transpose()?;
+        options.batch_size = self.batch_size;
+        options.max_time = self.max_time_ms.map(|ms| std::time::Duration::from_millis(ms.into()));
+        options.collation = self.collation.as_ref().map(driver::get_collation).transpose()?;
+        options.read_concern = self.read_concern.map(driver::get_read_concern).transpose()?;
+        options.selection_criteria = driver::get_selection_criteria(self.read_preference);
+        options.hint = self.hint.as_ref().map(driver::get_hint).transpose()?;
+        Ok(options)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FindCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) options: FindOptions,
+    pub(crate) phantom: PhantomData<Data>,
+}
+
+impl<Data> FindCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub fn new(collection_name: String, filter: bson::Document, limit: Option<u32>) -> Self {
+        let mut options = FindOptions::new();
         options.limit = limit;
+        Self::new_with_options(collection_name, filter, options)
+    }
+
+    pub fn new_with_options(
+        collection_name: String,
+        filter: bson::Document,
+        options: FindOptions,
+    ) -> Self {
+        Self { collection_name, filter, options, phantom: PhantomData }
+    }
+
+    pub fn with_options(mut self, options: FindOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.options.limit = Some(limit);
+        self
+    }
+
+    pub fn with_skip(mut self, skip: u32) -> Self {
+        self.options.skip = Some(skip);
+        self
+    }
+
+    pub fn with_sort(mut self, sort: bson::Document) -> Self {
+        self.options.sort = Some(sort);
+        self
+    }
+
+    pub fn with_projection(mut self, projection: bson::Document) -> Self {
+        self.options.projection = Some(projection);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.options.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Sets the time limit for processing the query on the server side.
+    pub fn with_max_time_ms(mut self, max_time_ms: u32) -> Self {
+        self.options.max_time_ms = Some(max_time_ms);
+        self
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.options.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.options.read_preference = Some(read_preference);
+        self
+    }
+
+    /// Forces the database to use the given index.
+    pub fn with_hint(mut self, hint: Hint) -> Self {
+        self.options.hint = Some(hint);
+        self
+    }
+
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    pub fn get_options(&self) -> &FindOptions {
+        &self.options
+    }
+
+    pub fn get_projection(&self) -> Option<&bson::Document> {
+        self.options.get_projection()
+    }
+
+    pub fn get_sort(&self) -> Option<&bson::Document> {
+        self.options.get_sort()
+    }
+
+    /// Returns the query in the legacy form wrapping the filter together with the query modifiers
+    /// for sorting, time limits, collation, read concern and hints.
+    pub fn get_query(&self) -> bson::Document {
+        let options = &self.options;
+        if options.sort.is_none()
+            && options.max_time_ms.is_none()
+            && options.collation.is_none()
+            && options.read_concern.is_none()
+            && options.hint.is_none()
+        {
+            return self.filter.clone();
+        }
+
+        let mut query = doc! { "$query": self.filter.clone() };
+        if let Some(sort) = &self.options.sort {
+            query.insert("$orderby", sort.clone());
+        }
+        if let Some(max_time_ms) = self.options.max_time_ms {
+            query.insert("$maxTimeMS", max_time_ms as i64);
+        }
+        if let Some(collation) = &self.options.collation {
+            query.insert("$collation", collation.to_doc());
+        }
+        if let Some(read_concern) = &self.options.read_concern {
+            query.insert("$readConcern", read_concern.to_doc());
+        }
+        if let Some(hint) = &self.options.hint {
+            query.insert_bson("$hint".to_string(), hint.to_bson());
+        }
+        query
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Vec<Data>, HuusError> {
+        let mut result = if let Some(limit) = self.options.limit {
+            Vec::with_capacity(limit as usize)
+        } else {
+            Vec::new()
+        };
+        for entry in self.iterate(db)? {
+            result.push(entry?);
+        }
+        Ok(result)
+    }
+
+    /// Returns a cursor converting the found documents lazily. Unlike `execute` a document which
+    /// fails to convert does not fail the whole query.
+    #[cfg(feature = "sync-driver")]
+    pub fn iterate(
+        &self,
+        db: &mongodb::sync::Database,
+    ) -> Result<TypedCursor<Data, Documents>, HuusError> {
+        let collection = db.collection::<mongodb::bson::Document>(&self.collection_name);
+        let filter = driver::to_driver(&self.filter)?;
+        let cursor = collection.find(filter, self.options.to_driver_options()?)?;
+        Ok(TypedCursor::new(Documents::new(cursor)))
+    }
+}
+
+impl<Data> Command for FindCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Returns the `find` database command equivalent to this query.
+    fn get_command(&self) -> bson::Document {
+        let options = &self.options;
+        let mut command = doc! {
+            "find": self.collection_name.clone(),
+            "filter": self.filter.clone(),
+        };
+        if let Some(sort) = &options.sort {
+            command.insert("sort", sort.clone());
+        }
+        if let Some(projection) = &options.projection {
+            command.insert("projection", projection.clone());
+        }
+        if let Some(skip) = options.skip {
+            command.insert("skip", skip as i64);
+        }
+        if let Some(limit) = options.limit {
+            command.insert("limit", limit as i64);
+        }
+        if let Some(batch_size) = options.batch_size {
+            command.insert("batchSize", batch_size as i64);
+        }
+        if let Some(max_time_ms) = options.max_time_ms {
+            command.insert("maxTimeMS", max_time_ms as i64);
+        }
+        if let Some(collation) = &options.collation {
+            command.insert("collation", collation.to_doc());
+        }
+        if let Some(read_concern) = &options.read_concern {
+            command.insert("readConcern", read_concern.to_doc());
+        }
+        if let Some(hint) = &options.hint {
+            command.insert_bson("hint".to_string(), hint.to_bson());
+        }
+        command
+    }
+
+    /// Returns the `mongosh` snippet equivalent to this command, e.g.
+    /// `db.coll.find({ a: 1 }).sort({ b: -1 })`.
+    fn to_shell(&self) -> String {
+        let options = &self.options;
+        let mut arguments = vec![bson::Bson::Document(self.filter.clone())];
+        if let Some(projection) = &options.projection {
+            arguments.push(bson::Bson::Document(projection.clone()));
+        }
+        let mut snippet = shell::collection(&self.collection_name);
+        snippet += &shell::call("find", arguments, bson::Document::new());
+        let mut modifiers = Vec::new();
+        if let Some(sort) = &options.sort {
+            modifiers.push(("sort", bson::Bson::Document(sort.clone())));
+        }
+        if let Some(skip) = options.skip {
+            modifiers.push(("skip", bson::Bson::I32(skip as i32)));
+        }
+        if let Some(limit) = options.limit {
+            modifiers.push(("limit", bson::Bson::I32(limit as i32)));
+        }
+        if let Some(batch_size) = options.batch_size {
+            modifiers.push(("batchSize", bson::Bson::I32(batch_size as i32)));
+        }
+        if let Some(max_time_ms) = options.max_time_ms {
+            modifiers.push(("maxTimeMS", bson::Bson::I32(max_time_ms as i32)));
+        }
+        if let Some(collation) = &options.collation {
+            modifiers.push(("collation", bson::Bson::Document(collation.to_doc())));
+        }
+        if let Some(hint) = &options.hint {
+            modifiers.push(("hint", hint.to_bson()));
+        }
+        if let Some(read_concern) = options.read_concern {
+            modifiers.push(("readConcern", bson::Bson::String(read_concern.as_str().to_string())));
+        }
+        if let Some(read_preference) = options.read_preference {
+            let mode = bson::Bson::String(read_preference.as_str().to_string());
+            modifiers.push(("readPref", mode));
+        }
+        for (method, argument) in modifiers {
+            snippet += &shell::call(method, vec![argument], bson::Document::new());
+        }
+        snippet
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct DistinctCommand<Value>
+where
+    Value: HuusFromBson,
+{
+    pub(crate) collection_name: String,
+    pub(crate) key: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+    pub(crate) phantom: PhantomData<Value>,
+}
+
+impl<Value> DistinctCommand<Value>
+where
+    Value: HuusFromBson,
+{
+    pub fn new(collection_name: String, key: String, filter: bson::Document) -> Self {
+        Self {
+            collection_name,
+            key,
+            filter,
+            read_concern: None,
+            read_preference: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Vec<Value>, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), self.read_preference)?)
+    }
+
+    /// Reads the values from the reply to the `distinct` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<Vec<Value>, HuusError> {
+        let values = match reply.get_array("values") {
+            Ok(values) => values.clone(),
+            Err(_) => return Err(ConversionError::wrong_type("values".to_string()).into()),
+        };
+        let mut result = Vec::with_capacity(values.len());
+        for value in values {
+            result.push(Value::huus_from_bson(value)?);
+        }
+        Ok(result)
+    }
+}
+
+impl<Value> Command for DistinctCommand<Value>
+where
+    Value: HuusFromBson,
+{
+    /// Returns the `distinct` database command.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! {
+            "distinct": self.collection_name.clone(),
+            "key": self.key.clone(),
+            "query": self.filter.clone(),
+        };
+        if let Some(read_concern) = &self.read_concern {
+            command.insert("readConcern", read_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let arguments =
+            vec![bson::Bson::String(self.key.clone()), bson::Bson::Document(self.filter.clone())];
+        let options = get_read_options(self.read_concern, self.read_preference);
+        shell::collection(&self.collection_name) + &shell::call("distinct", arguments, options)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct CountDocumentsCommand {
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) collation: Option<Collation>,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+}
+
+impl CountDocumentsCommand {
+    pub fn new(collection_name: String, filter: bson::Document) -> Self {
+        Self { collection_name, filter, collation: None, read_concern: None, read_preference: None }
+    }
+
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<u64, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), self.read_preference)?)
+    }
+
+    /// Reads the count from the reply to the counting `aggregate` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<u64, HuusError> {
+        match get_first_batch(reply)?.first() {
+            Some(bson::Bson::Document(result)) => Ok(get_count(result)?),
+            Some(_) => Err(ConversionError::wrong_type("firstBatch".to_string()).into()),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Command for CountDocumentsCommand {
+    /// Returns the `aggregate` database command counting the documents matching the filter.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! {
+            "aggregate": self.collection_name.clone(),
+            "pipeline": [
+                { "$match": self.filter.clone() },
+                { "$group": { "_id": 1, "n": { "$sum": 1 } } },
+            ],
+            "cursor": {},
+        };
+        if let Some(collation) = &self.collation {
+            command.insert("collation", collation.to_doc());
+        }
+        if let Some(read_concern) = &self.read_concern {
+            command.insert("readConcern", read_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let mut options = get_read_options(self.read_concern, self.read_preference);
+        if let Some(collation) = &self.collation {
+            options.insert("collation", collation.to_doc());
+        }
+        let arguments = vec![bson::Bson::Document(self.filter.clone())];
+        let call = shell::call("countDocuments", arguments, options);
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct EstimatedCountCommand {
+    pub(crate) collection_name: String,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+}
+
+impl EstimatedCountCommand {
+    pub fn new(collection_name: String) -> Self {
+        Self { collection_name, read_concern: None, read_preference: None }
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<u64, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), self.read_preference)?)
+    }
+
+    /// Reads the count from the reply to the `count` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<u64, HuusError> {
+        Ok(get_count(reply)?)
+    }
+}
+
+impl Command for EstimatedCountCommand {
+    /// Returns the `count` database command. Without a query the count is taken from the
+    /// collection metadata.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! { "count": self.collection_name.clone() };
+        if let Some(read_concern) = &self.read_concern {
+            command.insert("readConcern", read_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let options = get_read_options(self.read_concern, self.read_preference);
+        let call = shell::call("estimatedDocumentCount", Vec::new(), options);
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct LookupCommand<Data, Foreign>
+where
+    Data: FromDoc,
+    Foreign: FromDoc,
+{
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) from: String,
+    pub(crate) local_field: String,
+    pub(crate) foreign_field: String,
+    pub(crate) as_field: String,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+    pub(crate) phantom: PhantomData<(Data, Foreign)>,
+}
+
+impl<Data, Foreign> LookupCommand<Data, Foreign>
+where
+    Data: FromDoc,
+    Foreign: FromDoc,
+{
+    pub fn new(
+        collection_name: String,
+        filter: bson::Document,
+        from: String,
+        local_field: String,
+        foreign_field: String,
+        as_field: String,
+    ) -> Self {
+        Self {
+            collection_name,
+            filter,
+            from,
+            local_field,
+            foreign_field,
+            as_field,
+            read_concern: None,
+            read_preference: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    /// Returns the aggregation pipeline selecting the documents matching the filter and joining
+    /// them with the documents from the other collection.
+    pub fn get_pipeline(&self) -> bson::Document {
+        doc! {
+            "pipeline": [
+                { "$match": self.filter.clone() },
+                {
+                    "$lookup": {
+                        "from": self.from.clone(),
+                        "localField": self.local_field.clone(),
+                        "foreignField": self.foreign_field.clone(),
+                        "as": self.as_field.clone(),
+                    }
+                },
+            ],
+        }
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(
+        &self,
+        db: &mongodb::sync::Database,
+    ) -> Result<Vec<Joined<Data, Foreign>>, HuusError> {
+        let mut options = mongodb::options::AggregateOptions::default();
+        options.read_concern = self.read_concern.map(driver::get_read_concern).transpose()?;
+        options.selection_criteria = driver::get_selection_criteria(self.read_preference);
+        let documents =
+            driver::aggregate(db, &self.collection_name, &self.get_pipeline(), options)?;
+        let mut result = Vec::new();
+        for entry in documents {
+            result.push(self.split(entry?)?);
+        }
+        Ok(result)
+    }
+
+    /// Returns the options sent together with the pipeline.
+    fn get_options(&self) -> bson::Document {
+        let mut options = bson::Document::new();
+        if let Some(read_concern) = &self.read_concern {
+            options.insert("readConcern", read_concern.to_doc());
+        }
         options
     }
 
-    pub fn update_many() -> mongo_driver::collection::UpdateOptions {
-        let mut options = mongo_driver::collection::UpdateOptions::default();
-        options.update_flags.add(mongo_driver::flags::UpdateFlag::MultiUpdate);
-        options
+    /// Separates the joined documents from the local document.
+    #[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+    pub(crate) fn split(
+        &self,
+        mut document: bson::Document,
+    ) -> Result<Joined<Data, Foreign>, HuusError> {
+        let joined = match document.remove(&self.as_field) {
+            Some(bson::Bson::Array(array)) => array,
+            _ => return Err(ConversionError::wrong_type(self.as_field.clone()).into()),
+        };
+        let mut foreign = Vec::with_capacity(joined.len());
+        for element in joined {
+            match element {
+                bson::Bson::Document(document) => foreign.push(Foreign::from_doc(document)?),
+                _ => return Err(ConversionError::wrong_type(self.as_field.clone()).into()),
+            }
+        }
+        Ok(Joined::new(Data::from_doc(document)?, foreign))
+    }
+}
+
+impl<Data, Foreign> Command for LookupCommand<Data, Foreign>
+where
+    Data: FromDoc,
+    Foreign: FromDoc,
+{
+    /// Returns the `aggregate` database command equivalent to this query.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! { "aggregate": self.collection_name.clone() };
+        for (key, value) in self.get_pipeline().into_iter().chain(self.get_options()) {
+            command.insert_bson(key, value);
+        }
+        command.insert("cursor", bson::Document::new());
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let pipeline = self.get_pipeline().remove("pipeline").unwrap_or(bson::Bson::Null);
+        let options = get_read_options(self.read_concern, self.read_preference);
+        shell::collection(&self.collection_name)
+            + &shell::call("aggregate", vec![pipeline], options)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct AggregateCommand {
+    pub(crate) collection_name: String,
+    pub(crate) stages: Vec<bson::Document>,
+    pub(crate) allow_disk_use: bool,
+    pub(crate) batch_size: Option<u32>,
+    pub(crate) max_time_ms: Option<u32>,
+    pub(crate) read_concern: Option<ReadConcern>,
+    pub(crate) read_preference: Option<ReadPreference>,
+}
+
+impl AggregateCommand {
+    pub fn new(collection_name: String, stages: Vec<bson::Document>) -> Self {
+        Self {
+            collection_name,
+            stages,
+            allow_disk_use: false,
+            batch_size: None,
+            max_time_ms: None,
+            read_concern: None,
+            read_preference: None,
+        }
+    }
+
+    /// Allows the stages to write temporary data to disk when they exceed the memory limit.
+    pub fn with_allow_disk_use(mut self, allow_disk_use: bool) -> Self {
+        self.allow_disk_use = allow_disk_use;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Sets the time limit for processing the aggregation on the server side.
+    pub fn with_max_time_ms(mut self, max_time_ms: u32) -> Self {
+        self.max_time_ms = Some(max_time_ms);
+        self
+    }
+
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Self {
+        self.read_concern = Some(read_concern);
+        self
+    }
+
+    pub fn with_read_preference(mut self, read_preference: ReadPreference) -> Self {
+        self.read_preference = Some(read_preference);
+        self
+    }
+
+    pub fn get_stages(&self) -> &Vec<bson::Document> {
+        &self.stages
+    }
+
+    pub fn get_pipeline(&self) -> bson::Document {
+        let stages: Vec<bson::Bson> = self.stages.iter().cloned().map(bson::Bson::from).collect();
+        doc! { "pipeline": stages }
+    }
+
+    /// Returns the options sent together with the pipeline.
+    pub fn get_options(&self) -> Option<bson::Document> {
+        let mut options = bson::Document::new();
+        if self.allow_disk_use {
+            options.insert("allowDiskUse", true);
+        }
+        if let Some(batch_size) = self.batch_size {
+            options.insert("batchSize", batch_size as i64);
+        }
+        if let Some(max_time_ms) = self.max_time_ms {
+            options.insert("maxTimeMS", max_time_ms as i64);
+        }
+        if let Some(read_concern) = &self.read_concern {
+            options.insert("readConcern", read_concern.to_doc());
+        }
+        if options.is_empty() {
+            None
+        } else {
+            Some(options)
+        }
+    }
+
+    /// Returns a command explaining how the database executes this command.
+    pub fn explain(&self, verbosity: Verbosity) -> ExplainCommand {
+        ExplainCommand::new(self.get_command(), verbosity)
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Vec<bson::Document>, HuusError> {
+        let mut options = mongodb::options::AggregateOptions::default();
+        options.allow_disk_use = Some(self.allow_disk_use).filter(|allow| *allow);
+        options.batch_size = self.batch_size;
+        options.max_time = self.max_time_ms.map(|ms| std::time::Duration::from_millis(ms.into()));
+        options.read_concern = self.read_concern.map(driver::get_read_concern).transpose()?;
+        options.selection_criteria = driver::get_selection_criteria(self.read_preference);
+        driver::aggregate(db, &self.collection_name, &self.get_pipeline(), options)?.collect()
+    }
+}
+
+impl Command for AggregateCommand {
+    /// Returns the `aggregate` database command equivalent to this aggregation.
+    fn get_command(&self) -> bson::Document {
+        let stages: Vec<bson::Bson> = self.stages.iter().cloned().map(bson::Bson::from).collect();
+        let mut command = doc! {
+            "aggregate": self.collection_name.clone(),
+            "pipeline": stages,
+            "cursor": {},
+        };
+        if let Some(batch_size) = self.batch_size {
+            command.insert("cursor", doc! { "batchSize": batch_size as i64 });
+        }
+        if self.allow_disk_use {
+            command.insert("allowDiskUse", true);
+        }
+        if let Some(max_time_ms) = self.max_time_ms {
+            command.insert("maxTimeMS", max_time_ms as i64);
+        }
+        if let Some(read_concern) = &self.read_concern {
+            command.insert("readConcern", read_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let mut options = get_read_options(self.read_concern, self.read_preference);
+        if self.allow_disk_use {
+            options.insert("allowDiskUse", true);
+        }
+        if let Some(batch_size) = self.batch_size {
+            options.insert("batchSize", batch_size as i32);
+        }
+        if let Some(max_time_ms) = self.max_time_ms {
+            options.insert("maxTimeMS", max_time_ms as i32);
+        }
+        let stages = self.stages.iter().cloned().map(bson::Bson::Document).collect();
+        let call = shell::call("aggregate", vec![bson::Bson::Array(stages)], options);
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Observes changes of the documents in a collection.
+#[derive(Debug, PartialEq)]
+pub struct WatchCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub(crate) collection_name: String,
+    pub(crate) stages: Vec<bson::Document>,
+    pub(crate) full_document: bool,
+    pub(crate) resume_after: Option<bson::Document>,
+    pub(crate) phantom: PhantomData<Data>,
+}
+
+impl<Data> WatchCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub fn new(collection_name: String) -> Self {
+        Self {
+            collection_name,
+            stages: Vec::new(),
+            full_document: false,
+            resume_after: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the stages filtering or transforming the change events. The stages must keep the
+    /// fields required by `ChangeEvent`.
+    pub fn with_stages(mut self, stages: Vec<bson::Document>) -> Self {
+        self.stages = stages;
+        self
+    }
+
+    /// Requests the current version of the document to be looked up for update events.
+    pub fn with_full_document(mut self, full_document: bool) -> Self {
+        self.full_document = full_document;
+        self
+    }
+
+    /// Starts observing after the event identified by the token obtained from `ChangeStream`.
+    pub fn with_resume_after(mut self, resume_token: bson::Document) -> Self {
+        self.resume_after = Some(resume_token);
+        self
+    }
+
+    pub fn get_pipeline(&self) -> bson::Document {
+        let mut change_stream = bson::Document::new();
+        if self.full_document {
+            change_stream.insert("fullDocument", "updateLookup");
+        }
+        if let Some(resume_after) = &self.resume_after {
+            change_stream.insert("resumeAfter", resume_after.clone());
+        }
+        let mut stages = vec![bson::Bson::Document(doc! { "$changeStream": change_stream })];
+        stages.extend(self.stages.iter().cloned().map(bson::Bson::Document));
+        doc! { "pipeline": stages }
+    }
+
+    /// Opens the change stream. The returned stream waits for new events and ends only when the
+    /// change stream is closed by the database.
+    #[cfg(feature = "sync-driver")]
+    pub fn iterate(
+        &self,
+        db: &mongodb::sync::Database,
+    ) -> Result<ChangeStream<Data, Documents>, HuusError> {
+        let options = mongodb::options::AggregateOptions::default();
+        let events = driver::aggregate(db, &self.collection_name, &self.get_pipeline(), options)?;
+        Ok(ChangeStream::new(events))
+    }
+}
+
+impl<Data> Command for WatchCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Returns the `aggregate` database command opening the change stream.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! { "aggregate": self.collection_name.clone() };
+        for (key, value) in self.get_pipeline() {
+            command.insert_bson(key, value);
+        }
+        command.insert("cursor", bson::Document::new());
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let mut options = bson::Document::new();
+        if self.full_document {
+            options.insert("fullDocument", "updateLookup");
+        }
+        if let Some(resume_after) = &self.resume_after {
+            options.insert("resumeAfter", resume_after.clone());
+        }
+        let stages = self.stages.iter().cloned().map(bson::Bson::Document).collect();
+        let call = shell::call("watch", vec![bson::Bson::Array(stages)], options);
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Outcome of `InsertCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsertResult {
+    /// ID of the inserted document.
+    pub inserted_id: bson::Bson,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InsertCommand {
+    pub(crate) collection_name: String,
+    pub(crate) document: bson::Document,
+    pub(crate) id: bson::Bson,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl InsertCommand {
+    pub fn new(collection_name: String, mut document: bson::Document) -> Self {
+        let id = match document.get("_id") {
+            Some(id) => id.clone(),
+            None => {
+                let id = bson::oid::ObjectId::new().expect("Generate new ObjectId");
+                document.insert("_id", id.clone());
+                bson::Bson::ObjectId(id)
+            }
+        };
+        Self { collection_name, document, id, write_concern: None }
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_document(&self) -> &bson::Document {
+        &self.document
+    }
+
+    /// Inserts the document. The `insert` database command is used when a write concern is set
+    /// because the driver supports only the default one.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<InsertResult, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+
+    /// Reads the outcome from the reply to the `insert` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<InsertResult, HuusError> {
+        check_write_errors(reply)?;
+        Ok(InsertResult { inserted_id: self.id.clone() })
+    }
+}
+
+impl Command for InsertCommand {
+    /// Returns the `insert` database command.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! {
+            "insert": self.collection_name.clone(),
+            "documents": [self.document.clone()],
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let arguments = vec![bson::Bson::Document(self.document.clone())];
+        let options = get_write_options(&self.write_concern);
+        shell::collection(&self.collection_name) + &shell::call("insertOne", arguments, options)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Code of the error reported when a unique index rejects a document.
+pub(crate) const DUPLICATE_KEY_ERROR: i32 = 11000;
+
+/// Outcome of `InsertManyCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsertManyResult {
+    /// IDs of the inserted documents.
+    pub inserted_ids: Vec<bson::Bson>,
+
+    /// IDs of the documents rejected because of a duplicate value of a unique key.
+    pub duplicate_ids: Vec<bson::Bson>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InsertManyCommand {
+    pub(crate) collection_name: String,
+    pub(crate) documents: Vec<bson::Document>,
+    pub(crate) ids: Vec<bson::Bson>,
+    pub(crate) ordered: bool,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl InsertManyCommand {
+    pub fn new(collection_name: String, documents: Vec<bson::Document>) -> Self {
+        let mut ids = Vec::with_capacity(documents.len());
+        let documents = documents
+            .into_iter()
+            .map(|document| {
+                let command = InsertCommand::new(String::new(), document);
+                ids.push(command.id);
+                command.document
+            })
+            .collect();
+        Self { collection_name, documents, ids, ordered: true, write_concern: None }
+    }
+
+    /// Specifies if the insertion should stop at the first failed document (the default) or
+    /// attempt to insert all of them.
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_documents(&self) -> &Vec<bson::Document> {
+        &self.documents
+    }
+
+    /// Interprets the reply of a failed insertion. Returns `None` if any of the documents failed
+    /// for reasons other than a duplicate key.
+    pub fn get_result(&self, reply: &bson::Document) -> Option<InsertManyResult> {
+        let mut failed = Vec::new();
+        if let Ok(errors) = reply.get_array("writeErrors") {
+            for error in errors {
+                let error = match error {
+                    bson::Bson::Document(error) => error,
+                    _ => return None,
+                };
+                match (error.get("index").and_then(get_index), error.get_i32("code")) {
+                    (Some(index), Ok(DUPLICATE_KEY_ERROR)) => failed.push(index),
+                    _ => return None,
+                }
+            }
+        }
+
+        let mut result = InsertManyResult { inserted_ids: Vec::new(), duplicate_ids: Vec::new() };
+        let attempted = match failed.iter().min() {
+            Some(first) if self.ordered => first + 1,
+            _ => self.ids.len(),
+        };
+        for (index, id) in self.ids.iter().enumerate().take(attempted) {
+            if failed.contains(&index) {
+                result.duplicate_ids.push(id.clone());
+            } else {
+                result.inserted_ids.push(id.clone());
+            }
+        }
+        Some(result)
+    }
+
+    /// Reads the outcome from the reply to the `insert` database command. Documents rejected
+    /// because of a duplicate key are reported in the result instead of failing.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<InsertManyResult, HuusError> {
+        match WriteError::from_reply(reply) {
+            Some(error) => self.get_result(reply).ok_or(HuusError::Write(error)),
+            None => {
+                Ok(InsertManyResult { inserted_ids: self.ids.clone(), duplicate_ids: Vec::new() })
+            }
+        }
+    }
+
+    /// Inserts the documents. Documents rejected because of a duplicate key are reported in the
+    /// result instead of failing the whole command.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<InsertManyResult, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+}
+
+impl Command for InsertManyCommand {
+    /// Returns the `insert` database command.
+    fn get_command(&self) -> bson::Document {
+        let documents: Vec<bson::Bson> =
+            self.documents.iter().cloned().map(bson::Bson::Document).collect();
+        let mut command = doc! {
+            "insert": self.collection_name.clone(),
+            "documents": documents,
+            "ordered": self.ordered,
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let documents = self.documents.iter().cloned().map(bson::Bson::Document).collect();
+        let mut options = get_write_options(&self.write_concern);
+        if !self.ordered {
+            options.insert("ordered", false);
+        }
+        let call = shell::call("insertMany", vec![bson::Bson::Array(documents)], options);
+        shell::collection(&self.collection_name) + &call
+    }
+}
+
+/// Reads an index of a document from a server reply.
+fn get_index(value: &bson::Bson) -> Option<usize> {
+    match value {
+        bson::Bson::I32(index) => Some(*index as usize),
+        bson::Bson::I64(index) => Some(*index as usize),
+        _ => None,
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Outcome of `UpdateCommand` and `ReplaceOneCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpdateResult {
+    /// Number of documents matching the filter.
+    pub matched: u64,
+
+    /// Number of documents actually changed.
+    pub modified: u64,
+
+    /// ID of the document inserted if none matched the filter of an upsert.
+    pub upserted_id: Option<bson::Bson>,
+}
+
+impl UpdateResult {
+    /// Reads the counts from the reply to the `update` database command.
+    pub fn from_reply(reply: &bson::Document) -> Result<Self, ConversionError> {
+        let upserted_id = match reply.get_array("upserted").ok().and_then(|u| u.first()) {
+            Some(bson::Bson::Document(upserted)) => upserted.get("_id").cloned(),
+            _ => None,
+        };
+        let count = get_count(reply)?;
+        Ok(Self {
+            matched: if upserted_id.is_some() { count - 1 } else { count },
+            modified: get_number(reply, "nModified")?,
+            upserted_id,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UpdateOptions {
+    UpdateOne,
+    UpdateMany,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UpdateCommand {
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) update: bson::Bson,
+    pub(crate) options: UpdateOptions,
+    pub(crate) array_filters: Vec<bson::Document>,
+    pub(crate) upsert: bool,
+    pub(crate) collation: Option<Collation>,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl UpdateCommand {
+    pub fn new(
+        collection_name: String,
+        filter: bson::Document,
+        update: bson::Document,
+        options: UpdateOptions,
+    ) -> Self {
+        let update = bson::Bson::Document(update);
+        Self {
+            collection_name,
+            filter,
+            update,
+            options,
+            array_filters: Vec::new(),
+            upsert: false,
+            collation: None,
+            write_concern: None,
+        }
+    }
+
+    pub fn new_pipeline(
+        collection_name: String,
+        filter: bson::Document,
+        pipeline: PipelineUpdate,
+        options: UpdateOptions,
+    ) -> Self {
+        let update = pipeline.into_bson();
+        Self {
+            collection_name,
+            filter,
+            update,
+            options,
+            array_filters: Vec::new(),
+            upsert: false,
+            collation: None,
+            write_concern: None,
+        }
+    }
+
+    /// Creates a command applying the update together with its array filters.
+    pub fn new_update(
+        collection_name: String,
+        filter: bson::Document,
+        update: Update,
+        options: UpdateOptions,
+    ) -> Self {
+        let array_filters = update.build_array_filters();
+        Self::new(collection_name, filter, update.into_doc(), options)
+            .with_array_filters(array_filters)
+    }
+
+    /// Sets the filters selecting array elements for the identified positional operators
+    /// (`$[<identifier>]`) used in the update.
+    pub fn with_array_filters(mut self, array_filters: Vec<bson::Document>) -> Self {
+        self.array_filters = array_filters;
+        self
+    }
+
+    /// Sets whether a new document is inserted if none matches the filter.
+    pub fn with_upsert(mut self, upsert: bool) -> Self {
+        self.upsert = upsert;
+        self
+    }
+
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_array_filters(&self) -> &Vec<bson::Document> {
+        &self.array_filters
+    }
+
+    /// Checks if the update document is empty. Such an update would not modify the documents as
+    /// intended so it is refused when executing the command.
+    pub fn is_empty(&self) -> bool {
+        match &self.update {
+            bson::Bson::Document(update) => update.is_empty(),
+            _ => false,
+        }
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<UpdateResult, HuusError> {
+        if self.is_empty() {
+            return Err(UpdateError::Empty.into());
+        }
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+
+    /// Reads the outcome from the reply to the `update` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<UpdateResult, HuusError> {
+        check_write_errors(reply)?;
+        Ok(UpdateResult::from_reply(reply)?)
+    }
+}
+
+impl Command for UpdateCommand {
+    /// Returns the `update` database command. The command is used instead of the driver's update
+    /// because the latter reports neither the counts of the updated documents nor the array
+    /// filters, collation, write concern and pipelines.
+    fn get_command(&self) -> bson::Document {
+        let mut statement = doc! {
+            "q": self.filter.clone(),
+            "u": self.update.clone(),
+            "multi": self.options == UpdateOptions::UpdateMany,
+        };
+        if !self.array_filters.is_empty() {
+            let filters = self.array_filters.iter().cloned().map(bson::Bson::Document).collect();
+            statement.insert("arrayFilters", bson::Bson::Array(filters));
+        }
+        if self.upsert {
+            statement.insert("upsert", true);
+        }
+        if let Some(collation) = &self.collation {
+            statement.insert("collation", collation.to_doc());
+        }
+        let mut command = doc! {
+            "update": self.collection_name.clone(),
+            "updates": [statement],
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let method = match self.options {
+            UpdateOptions::UpdateOne => "updateOne",
+            UpdateOptions::UpdateMany => "updateMany",
+        };
+        let arguments = vec![bson::Bson::Document(self.filter.clone()), self.update.clone()];
+        let mut options = get_write_options(&self.write_concern);
+        if self.upsert {
+            options.insert("upsert", true);
+        }
+        if !self.array_filters.is_empty() {
+            let filters = self.array_filters.iter().cloned().map(bson::Bson::Document).collect();
+            options.insert("arrayFilters", bson::Bson::Array(filters));
+        }
+        if let Some(collation) = &self.collation {
+            options.insert("collation", collation.to_doc());
+        }
+        shell::collection(&self.collection_name) + &shell::call(method, arguments, options)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+pub struct ReplaceOneCommand {
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) replacement: bson::Document,
+    pub(crate) upsert: bool,
+    pub(crate) write_concern: Option<WriteConcern>,
+}
+
+impl ReplaceOneCommand {
+    pub fn new(
+        collection_name: String,
+        filter: bson::Document,
+        replacement: bson::Document,
+        upsert: bool,
+    ) -> Self {
+        Self { collection_name, filter, replacement, upsert, write_concern: None }
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
     }
 
-    pub fn remove_one() -> mongo_driver::collection::RemoveOptions {
-        let mut options = mongo_driver::collection::RemoveOptions::default();
-        options.remove_flags.add(mongo_driver::flags::RemoveFlag::SingleRemove);
-        options
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    pub fn get_replacement(&self) -> &bson::Document {
+        &self.replacement
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<UpdateResult, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+
+    /// Reads the outcome from the reply to the `update` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<UpdateResult, HuusError> {
+        check_write_errors(reply)?;
+        Ok(UpdateResult::from_reply(reply)?)
+    }
+}
+
+impl Command for ReplaceOneCommand {
+    /// Returns the `update` database command replacing the first matching document.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! {
+            "update": self.collection_name.clone(),
+            "updates": [{
+                "q": self.filter.clone(),
+                "u": self.replacement.clone(),
+                "upsert": self.upsert,
+                "multi": false,
+            }],
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let arguments = vec![
+            bson::Bson::Document(self.filter.clone()),
+            bson::Bson::Document(self.replacement.clone()),
+        ];
+        let mut options = get_write_options(&self.write_concern);
+        if self.upsert {
+            options.insert("upsert", true);
+        }
+        shell::collection(&self.collection_name) + &shell::call("replaceOne", arguments, options)
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// Outcome of `DeleteCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeleteResult {
+    /// Number of deleted documents.
+    pub deleted: u64,
+}
+
 #[derive(Debug, PartialEq)]
-pub struct CreateCollectionCommand {
+pub enum DeleteOptions {
+    DeleteOne,
+    DeleteMany,
+}
+
+/// Former name of `DeleteOptions`.
+#[deprecated(note = "use `DeleteOptions` instead")]
+#[derive(Debug, PartialEq)]
+pub enum RemoveOptions {
+    RemoveOne,
+    RemoveMany,
+}
+
+#[allow(deprecated)]
+impl From<RemoveOptions> for DeleteOptions {
+    fn from(options: RemoveOptions) -> Self {
+        match options {
+            RemoveOptions::RemoveOne => DeleteOptions::DeleteOne,
+            RemoveOptions::RemoveMany => DeleteOptions::DeleteMany,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DeleteCommand {
     pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) options: DeleteOptions,
+    pub(crate) collation: Option<Collation>,
+    pub(crate) write_concern: Option<WriteConcern>,
 }
 
-impl CreateCollectionCommand {
-    pub fn new(collection_name: String) -> Self {
-        Self { collection_name }
+impl DeleteCommand {
+    pub fn new(
+        collection_name: String,
+        filter: bson::Document,
+        options: impl Into<DeleteOptions>,
+    ) -> Self {
+        Self {
+            collection_name,
+            filter,
+            options: options.into(),
+            collation: None,
+            write_concern: None,
+        }
+    }
+
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_filter(&self) -> &bson::Document {
+        &self.filter
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<DeleteResult, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+
+    /// Reads the outcome from the reply to the `delete` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<DeleteResult, HuusError> {
+        check_write_errors(reply)?;
+        Ok(DeleteResult { deleted: get_count(reply)? })
     }
+}
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<(), HuusError> {
-        if !db.has_collection(self.collection_name.clone())? {
-            db.create_collection(self.collection_name.clone(), None)?;
+impl Command for DeleteCommand {
+    /// Returns the `delete` database command.
+    fn get_command(&self) -> bson::Document {
+        let limit = match self.options {
+            DeleteOptions::DeleteOne => 1,
+            DeleteOptions::DeleteMany => 0,
+        };
+        let mut statement = doc! { "q": self.filter.clone(), "limit": limit };
+        if let Some(collation) = &self.collation {
+            statement.insert("collation", collation.to_doc());
         }
-        Ok(())
+        let mut command = doc! {
+            "delete": self.collection_name.clone(),
+            "deletes": [statement],
+        };
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
+    }
+
+    fn to_shell(&self) -> String {
+        let method = match self.options {
+            DeleteOptions::DeleteOne => "deleteOne",
+            DeleteOptions::DeleteMany => "deleteMany",
+        };
+        let arguments = vec![bson::Bson::Document(self.filter.clone())];
+        let mut options = get_write_options(&self.write_concern);
+        if let Some(collation) = &self.collation {
+            options.insert("collation", collation.to_doc());
+        }
+        shell::collection(&self.collection_name) + &shell::call(method, arguments, options)
     }
 }
 
+/// Former name of `DeleteCommand`.
+#[deprecated(note = "use `DeleteCommand` instead")]
+pub type RemoveCommand = DeleteCommand;
+
 // -------------------------------------------------------------------------------------------------
 
-#[derive(Debug, PartialEq)]
-pub struct DropCollectionCommand {
-    pub(crate) collection_name: String,
+/// Single operation of `BulkWriteCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WriteOperation {
+    Insert {
+        document: bson::Document,
+    },
+    Update {
+        filter: bson::Document,
+        update: bson::Document,
+        array_filters: Vec<bson::Document>,
+        multi: bool,
+        upsert: bool,
+    },
+    Replace {
+        filter: bson::Document,
+        replacement: bson::Document,
+        upsert: bool,
+    },
+    Delete {
+        filter: bson::Document,
+        multi: bool,
+    },
 }
 
-impl DropCollectionCommand {
-    pub fn new(collection_name: String) -> Self {
-        Self { collection_name }
+/// Summary of the changes made by `BulkWriteCommand`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BulkWriteResult {
+    pub inserted: u64,
+    pub matched: u64,
+    pub modified: u64,
+    pub deleted: u64,
+    pub upserted: u64,
+}
+
+impl BulkWriteResult {
+    /// Reads the counts from the reply of the driver.
+    pub fn from_reply(reply: &bson::Document) -> Result<Self, ConversionError> {
+        Ok(Self {
+            inserted: get_number(reply, "nInserted")?,
+            matched: get_number(reply, "nMatched")?,
+            modified: get_number(reply, "nModified")?,
+            deleted: get_number(reply, "nRemoved")?,
+            upserted: get_number(reply, "nUpserted")?,
+        })
     }
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<(), HuusError> {
-        if db.has_collection(self.collection_name.clone())? {
-            let mut collection = db.get_collection(self.collection_name.clone());
-            collection.drop()?;
+    /// Adds the counts from the reply to one of the database commands returned by
+    /// `BulkWriteCommand::get_commands`.
+    pub fn add_reply(
+        &mut self,
+        command: &bson::Document,
+        reply: &bson::Document,
+    ) -> Result<(), ConversionError> {
+        let count = get_count(reply)?;
+        if command.contains_key("insert") {
+            self.inserted += count;
+        } else if command.contains_key("update") {
+            let upserted = reply.get_array("upserted").map(|u| u.len() as u64).unwrap_or(0);
+            self.matched += count - upserted;
+            self.modified += get_number(reply, "nModified")?;
+            self.upserted += upserted;
+        } else {
+            self.deleted += count;
         }
         Ok(())
     }
 }
 
-// -------------------------------------------------------------------------------------------------
-
+/// Sends many write operations on a single collection to the database at once.
 #[derive(Debug, PartialEq)]
-pub struct CreateIndexesCommand {
-    pub(crate) command: Option<bson::Document>,
+pub struct BulkWriteCommand {
+    pub(crate) collection_name: String,
+    pub(crate) operations: Vec<WriteOperation>,
+    pub(crate) ordered: bool,
+    pub(crate) write_concern: Option<WriteConcern>,
 }
 
-impl CreateIndexesCommand {
-    pub fn new(collection_name: String, indexed_fields: Vec<String>) -> Self {
-        if indexed_fields.len() > 0 {
-            let mut keys = bson::Document::new();
-            for key in indexed_fields.iter() {
-                keys.insert_bson(key.clone(), bson::Bson::String("text".to_string()));
+impl BulkWriteCommand {
+    pub fn new(collection_name: String, operations: Vec<WriteOperation>) -> Self {
+        Self { collection_name, operations, ordered: true, write_concern: None }
+    }
+
+    /// Specifies if the execution should stop at the first failed operation (the default) or
+    /// attempt to execute all of them.
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn get_operations(&self) -> &Vec<WriteOperation> {
+        &self.operations
+    }
+
+    /// Returns the `insert`, `update` and `delete` database commands equivalent to the
+    /// operations. Consecutive operations of the same kind are sent in a single command.
+    pub fn get_commands(&self) -> Vec<bson::Document> {
+        let mut commands = Vec::new();
+        let mut operations = self.operations.iter().peekable();
+        while let Some(first) = operations.next() {
+            let mut statements = vec![Self::get_statement(first)];
+            while let Some(next) = operations.peek() {
+                if Self::get_kind(next) != Self::get_kind(first) {
+                    break;
+                }
+                statements.push(Self::get_statement(next));
+                operations.next();
             }
 
-            let command = doc! {
-                "createIndexes": collection_name.clone(),
-                "indexes": [{
-                    "name": collection_name.clone(),
-                    "key": keys,
-                }],
+            let statements: Vec<bson::Bson> =
+                statements.into_iter().map(bson::Bson::Document).collect();
+            let mut command = match Self::get_kind(first) {
+                "insert" => doc! {
+                    "insert": self.collection_name.clone(),
+                    "documents": statements,
+                },
+                "update" => doc! {
+                    "update": self.collection_name.clone(),
+                    "updates": statements,
+                },
+                _ => doc! {
+                    "delete": self.collection_name.clone(),
+                    "deletes": statements,
+                },
             };
+            command.insert("ordered", self.ordered);
+            if let Some(write_concern) = &self.write_concern {
+                command.insert("writeConcern", write_concern.to_doc());
+            }
+            commands.push(command);
+        }
+        commands
+    }
 
-            Self { command: Some(command) }
-        } else {
-            Self { command: None }
+    /// Returns the database commands as an array of MongoDB Extended JSON documents.
+    pub fn to_extended_json(&self, mode: JsonMode) -> String {
+        let commands = self.get_commands().into_iter().map(bson::Bson::Document).collect();
+        json::value_to_extended_json(&bson::Bson::Array(commands), mode)
+    }
+
+    /// Returns the `mongosh` snippet equivalent to this command.
+    pub fn to_shell(&self) -> String {
+        let operations = self.operations.iter().map(Self::get_shell_operation).collect();
+        let mut options = get_write_options(&self.write_concern);
+        if !self.ordered {
+            options.insert("ordered", false);
+        }
+        let call = shell::call("bulkWrite", vec![bson::Bson::Array(operations)], options);
+        shell::collection(&self.collection_name) + &call
+    }
+
+    /// Sends the operations grouped into database commands. Unordered operations continue after
+    /// a write error which is then reported at the end.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<BulkWriteResult, HuusError> {
+        let mut result = BulkWriteResult::default();
+        let mut first_error = None;
+        for command in self.get_commands() {
+            let reply = driver::run(db, command.clone(), None)?;
+            result.add_reply(&command, &reply)?;
+            if let Some(error) = WriteError::from_reply(&reply) {
+                if self.ordered {
+                    return Err(error.into());
+                }
+                first_error.get_or_insert(error);
+            }
+        }
+        match first_error {
+            Some(error) => Err(error.into()),
+            None => Ok(result),
         }
     }
 
-    pub fn get_command(&self) -> Option<&bson::Document> {
-        self.command.as_ref()
+    /// Returns the name of the database command executing the operation.
+    fn get_kind(operation: &WriteOperation) -> &'static str {
+        match operation {
+            WriteOperation::Insert { .. } => "insert",
+            WriteOperation::Update { .. } | WriteOperation::Replace { .. } => "update",
+            WriteOperation::Delete { .. } => "delete",
+        }
     }
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<(), HuusError> {
-        if let Some(command) = self.get_command() {
-            db.command_simple(command.clone(), None)?;
+    /// Returns the operation as an element of the database command.
+    fn get_statement(operation: &WriteOperation) -> bson::Document {
+        match operation {
+            WriteOperation::Insert { document } => document.clone(),
+            WriteOperation::Update { filter, update, array_filters, multi, upsert } => {
+                let mut statement = doc! {
+                    "q": filter.clone(),
+                    "u": update.clone(),
+                    "multi": *multi,
+                    "upsert": *upsert,
+                };
+                if !array_filters.is_empty() {
+                    let filters = array_filters.iter().cloned().map(bson::Bson::Document).collect();
+                    statement.insert("arrayFilters", bson::Bson::Array(filters));
+                }
+                statement
+            }
+            WriteOperation::Replace { filter, replacement, upsert } => doc! {
+                "q": filter.clone(),
+                "u": replacement.clone(),
+                "multi": false,
+                "upsert": *upsert,
+            },
+            WriteOperation::Delete { filter, multi } => doc! {
+                "q": filter.clone(),
+                "limit": if *multi { 0 } else { 1 },
+            },
         }
-        Ok(())
+    }
+
+    /// Returns the operation as an element of the `bulkWrite` shell method.
+    fn get_shell_operation(operation: &WriteOperation) -> bson::Bson {
+        let operation = match operation {
+            WriteOperation::Insert { document } => {
+                doc! { "insertOne": { "document": document.clone() } }
+            }
+            WriteOperation::Update { filter, update, array_filters, multi, upsert } => {
+                let method = if *multi { "updateMany" } else { "updateOne" };
+                let mut arguments = doc! { "filter": filter.clone(), "update": update.clone() };
+                if !array_filters.is_empty() {
+                    let filters = array_filters.iter().cloned().map(bson::Bson::Document).collect();
+                    arguments.insert("arrayFilters", bson::Bson::Array(filters));
+                }
+                if *upsert {
+                    arguments.insert("upsert", true);
+                }
+                doc! { method: arguments }
+            }
+            WriteOperation::Replace { filter, replacement, upsert } => {
+                let mut arguments =
+                    doc! { "filter": filter.clone(), "replacement": replacement.clone() };
+                if *upsert {
+                    arguments.insert("upsert", true);
+                }
+                doc! { "replaceOne": arguments }
+            }
+            WriteOperation::Delete { filter, multi } => {
+                let method = if *multi { "deleteMany" } else { "deleteOne" };
+                doc! { method: { "filter": filter.clone() } }
+            }
+        };
+        bson::Bson::Document(operation)
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
 #[derive(Debug, PartialEq)]
-pub struct FindOneCommand<Data>
+pub enum FindAndModifyAction {
+    Update(bson::Document),
+    Replace(bson::Document),
+    Delete,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReturnDocument {
+    Before,
+    After,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FindAndModifyCommand<Data>
 where
     Data: FromDoc,
 {
     pub(crate) collection_name: String,
     pub(crate) filter: bson::Document,
+    pub(crate) action: FindAndModifyAction,
+    pub(crate) sort: Option<bson::Document>,
+    pub(crate) return_document: ReturnDocument,
+    pub(crate) upsert: bool,
+    pub(crate) projection: Option<bson::Document>,
+    pub(crate) array_filters: Vec<bson::Document>,
+    pub(crate) write_concern: Option<WriteConcern>,
     pub(crate) phantom: PhantomData<Data>,
 }
 
-impl<Data> FindOneCommand<Data>
+impl<Data> FindAndModifyCommand<Data>
 where
     Data: FromDoc,
 {
-    pub fn new(collection_name: String, filter: bson::Document) -> Self {
-        Self { collection_name, filter, phantom: PhantomData }
+    pub fn new(
+        collection_name: String,
+        filter: bson::Document,
+        action: FindAndModifyAction,
+    ) -> Self {
+        Self {
+            collection_name,
+            filter,
+            action,
+            sort: None,
+            return_document: ReturnDocument::Before,
+            upsert: false,
+            projection: None,
+            array_filters: Vec::new(),
+            write_concern: None,
+            phantom: PhantomData,
+        }
     }
 
-    pub fn get_filter(&self) -> &bson::Document {
-        &self.filter
+    /// Sets the sort order deciding which document is modified if many match the filter.
+    pub fn with_sort(mut self, sort: bson::Document) -> Self {
+        self.sort = Some(sort);
+        self
     }
 
-    pub fn execute(
-        &self,
-        db: &mongo_driver::database::Database,
-    ) -> Result<Option<Data>, HuusError> {
-        let collection = db.get_collection(self.collection_name.as_bytes());
-        let filter = self.get_filter();
-        let options = self.get_options();
-        let response = collection.find(&filter, options.as_ref())?;
-        for entry in response {
-            return Ok(Some(Data::from_doc(entry?)?));
-        }
-        Ok(None)
+    /// Decides whether the document is returned as it was before or after the modification.
+    pub fn with_return_document(mut self, return_document: ReturnDocument) -> Self {
+        self.return_document = return_document;
+        self
     }
 
-    fn get_options(&self) -> Option<mongo_driver::CommandAndFindOptions> {
-        Some(options::find(1))
+    /// Sets whether a new document is inserted if none matches the filter.
+    pub fn with_upsert(mut self, upsert: bool) -> Self {
+        self.upsert = upsert;
+        self
     }
-}
 
-// -------------------------------------------------------------------------------------------------
+    /// Limits the fields of the returned document. The projection has to keep the fields required
+    /// by the data.
+    pub fn with_projection(mut self, projection: bson::Document) -> Self {
+        self.projection = Some(projection);
+        self
+    }
 
-#[derive(Debug, PartialEq)]
-pub struct FindCommand<Data>
-where
-    Data: FromDoc,
-{
-    pub(crate) collection_name: String,
-    pub(crate) filter: bson::Document,
-    pub(crate) limit: Option<u32>,
-    pub(crate) phantom: PhantomData<Data>,
-}
+    /// Sets the filters selecting array elements for the identified positional operators
+    /// (`$[<identifier>]`) used in the update.
+    pub fn with_array_filters(mut self, array_filters: Vec<bson::Document>) -> Self {
+        self.array_filters = array_filters;
+        self
+    }
 
-impl<Data> FindCommand<Data>
-where
-    Data: FromDoc,
-{
-    pub fn new(collection_name: String, filter: bson::Document, limit: Option<u32>) -> Self {
-        Self { collection_name, filter, limit, phantom: PhantomData }
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
     }
 
     pub fn get_filter(&self) -> &bson::Document {
         &self.filter
     }
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<Vec<Data>, HuusError> {
-        let collection = db.get_collection(self.collection_name.as_bytes());
-        let filter = self.get_filter();
-        let options = self.get_options();
-        let response = collection.find(&filter, options.as_ref())?;
-        let mut result = if let Some(limit) = self.limit {
-            Vec::with_capacity(limit as usize)
-        } else {
-            Vec::new()
+    /// Returns the found document or `None` if no document matched the filter.
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Option<Data>, HuusError> {
+        self.read_reply(&driver::run(db, self.get_command(), None)?)
+    }
+
+    /// Reads the found document from the reply to the `findAndModify` database command.
+    pub fn read_reply(&self, reply: &bson::Document) -> Result<Option<Data>, HuusError> {
+        match reply.get("value") {
+            Some(bson::Bson::Document(document)) => Ok(Some(Data::from_doc(document.clone())?)),
+            Some(bson::Bson::Null) | None => Ok(None),
+            Some(_) => Err(ConversionError::wrong_type("value".to_string()).into()),
+        }
+    }
+}
+
+impl<Data> Command for FindAndModifyCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Returns the `findAndModify` database command.
+    fn get_command(&self) -> bson::Document {
+        let mut command = doc! {
+            "findAndModify": self.collection_name.clone(),
+            "query": self.filter.clone(),
         };
-        for entry in response {
-            result.push(Data::from_doc(entry?)?);
+        if let Some(sort) = &self.sort {
+            command.insert("sort", sort.clone());
         }
-        Ok(result)
+        if let Some(projection) = &self.projection {
+            command.insert("fields", projection.clone());
+        }
+        match &self.action {
+            FindAndModifyAction::Update(document) | FindAndModifyAction::Replace(document) => {
+                command.insert("update", document.clone());
+                command.insert("new", self.return_document == ReturnDocument::After);
+                command.insert("upsert", self.upsert);
+                if !self.array_filters.is_empty() {
+                    let filters =
+                        self.array_filters.iter().cloned().map(bson::Bson::Document).collect();
+                    command.insert("arrayFilters", bson::Bson::Array(filters));
+                }
+            }
+            FindAndModifyAction::Delete => {
+                command.insert("remove", true);
+            }
+        }
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.to_doc());
+        }
+        command
     }
 
-    fn get_options(&self) -> Option<mongo_driver::CommandAndFindOptions> {
-        if let Some(limit) = self.limit {
-            Some(options::find(limit))
-        } else {
-            None
+    fn to_shell(&self) -> String {
+        let mut arguments = vec![bson::Bson::Document(self.filter.clone())];
+        let method = match &self.action {
+            FindAndModifyAction::Update(document) => {
+                arguments.push(bson::Bson::Document(document.clone()));
+                "findOneAndUpdate"
+            }
+            FindAndModifyAction::Replace(document) => {
+                arguments.push(bson::Bson::Document(document.clone()));
+                "findOneAndReplace"
+            }
+            FindAndModifyAction::Delete => "findOneAndDelete",
+        };
+        let mut options = get_write_options(&self.write_concern);
+        if let Some(sort) = &self.sort {
+            options.insert("sort", sort.clone());
+        }
+        if let Some(projection) = &self.projection {
+            options.insert("projection", projection.clone());
+        }
+        if self.action != FindAndModifyAction::Delete {
+            if self.upsert {
+                options.insert("upsert", true);
+            }
+            if self.return_document == ReturnDocument::After {
+                options.insert("returnDocument", "after");
+            }
+            if !self.array_filters.is_empty() {
+                let filters =
+                    self.array_filters.iter().cloned().map(bson::Bson::Document).collect();
+                options.insert("arrayFilters", bson::Bson::Array(filters));
+            }
         }
+        shell::collection(&self.collection_name) + &shell::call(method, arguments, options)
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-#[derive(Debug, PartialEq)]
-pub struct InsertCommand {
-    pub(crate) collection_name: String,
-    pub(crate) document: bson::Document,
-    pub(crate) id: bson::Bson,
+/// Amount of information returned by `ExplainCommand`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verbosity {
+    /// Describes only the plan selected by the query optimizer.
+    QueryPlanner,
+
+    /// Additionally executes the winning plan and reports its statistics.
+    ExecutionStats,
+
+    /// Additionally reports statistics of the rejected plans.
+    AllPlansExecution,
 }
 
-impl InsertCommand {
-    pub fn new(collection_name: String, mut document: bson::Document) -> Self {
-        let id = match document.get("_id") {
-            Some(id) => id.clone(),
-            None => {
-                let id = bson::oid::ObjectId::new().expect("Generate new ObjectId");
-                document.insert("_id", id.clone());
-                bson::Bson::ObjectId(id)
+impl Verbosity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verbosity::QueryPlanner => "queryPlanner",
+            Verbosity::ExecutionStats => "executionStats",
+            Verbosity::AllPlansExecution => "allPlansExecution",
+        }
+    }
+}
+
+/// Summary of the query plan reported by `ExplainCommand`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExplainResult {
+    /// The plan selected by the query optimizer.
+    pub winning_plan: bson::Document,
+
+    /// Names of the stages of the winning plan, starting from the outermost one.
+    pub stages: Vec<String>,
+
+    /// Names of the indexes used by the winning plan.
+    pub indexes: Vec<String>,
+
+    /// Execution statistics, if requested by the verbosity.
+    pub execution_stats: Option<bson::Document>,
+
+    /// The whole reply of the database.
+    pub reply: bson::Document,
+}
+
+impl ExplainResult {
+    /// Reads the winning plan from the reply of the database. Plans of aggregations are searched
+    /// for in the first stage of the pipeline.
+    pub fn from_reply(reply: bson::Document) -> Result<Self, ConversionError> {
+        let explained = match reply.get_array("stages").ok().and_then(|stages| stages.first()) {
+            Some(bson::Bson::Document(stage)) if !reply.contains_key("queryPlanner") => {
+                match stage.get_document("$cursor") {
+                    Ok(cursor) => cursor.clone(),
+                    Err(_) => return Err(ConversionError::wrong_type("$cursor".to_string())),
+                }
             }
+            _ => reply.clone(),
         };
-        Self { collection_name, document, id }
+        let planner = match explained.get_document("queryPlanner") {
+            Ok(planner) => planner,
+            Err(_) => return Err(ConversionError::missing_key("queryPlanner".to_string())),
+        };
+        let mut winning_plan = match planner.get_document("winningPlan") {
+            Ok(plan) => plan.clone(),
+            Err(_) => return Err(ConversionError::missing_key("winningPlan".to_string())),
+        };
+        if let Ok(plan) = winning_plan.get_document("queryPlan") {
+            winning_plan = plan.clone();
+        }
+
+        let mut stages = Vec::new();
+        let mut indexes = Vec::new();
+        collect_plan_stages(&winning_plan, &mut stages, &mut indexes);
+        let execution_stats = explained.get_document("executionStats").ok().cloned();
+        Ok(Self { winning_plan, stages, indexes, execution_stats, reply })
     }
 
-    pub fn get_document(&self) -> &bson::Document {
-        &self.document
+    /// Checks if the winning plan scans the whole collection.
+    pub fn is_collection_scan(&self) -> bool {
+        self.stages.iter().any(|stage| stage == "COLLSCAN")
     }
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<bson::Bson, HuusError> {
-        let collection = db.get_collection(self.collection_name.as_bytes());
-        collection.insert(&self.document, None)?;
-        Ok(self.id.clone())
+    /// Checks if the winning plan uses the index with the given name.
+    pub fn uses_index(&self, name: &str) -> bool {
+        self.indexes.iter().any(|index| index == name)
     }
 }
 
-// -------------------------------------------------------------------------------------------------
-
-#[derive(Debug, PartialEq)]
-pub enum UpdateOptions {
-    UpdateOne,
-    UpdateMany,
+/// Walks the plan tree collecting the names of the stages and the used indexes.
+fn collect_plan_stages(plan: &bson::Document, stages: &mut Vec<String>, indexes: &mut Vec<String>) {
+    if let Ok(stage) = plan.get_str("stage") {
+        stages.push(stage.to_string());
+    }
+    if let Ok(index) = plan.get_str("indexName") {
+        if !indexes.iter().any(|i| i == index) {
+            indexes.push(index.to_string());
+        }
+    }
+    if let Ok(input) = plan.get_document("inputStage") {
+        collect_plan_stages(input, stages, indexes);
+    }
+    if let Ok(inputs) = plan.get_array("inputStages") {
+        for input in inputs {
+            if let bson::Bson::Document(input) = input {
+                collect_plan_stages(input, stages, indexes);
+            }
+        }
+    }
 }
 
+/// Explains how the database executes a read command.
 #[derive(Debug, PartialEq)]
-pub struct UpdateCommand {
-    pub(crate) collection_name: String,
-    pub(crate) filter: bson::Document,
-    pub(crate) update: bson::Document,
-    pub(crate) options: UpdateOptions,
+pub struct ExplainCommand {
+    pub(crate) command: bson::Document,
+    pub(crate) verbosity: Verbosity,
 }
 
-impl UpdateCommand {
-    pub fn new(
-        collection_name: String,
-        filter: bson::Document,
-        update: bson::Document,
-        options: UpdateOptions,
-    ) -> Self {
-        Self { collection_name, filter, update, options }
+impl ExplainCommand {
+    /// Wraps the given read command. Use the `explain` methods of the read commands to obtain it.
+    pub fn new(command: bson::Document, verbosity: Verbosity) -> Self {
+        Self { command, verbosity }
     }
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<(), HuusError> {
-        let collection = db.get_collection(self.collection_name.as_bytes());
-        collection.update(&self.filter, &self.update, self.get_options().as_ref())?;
-        Ok(())
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<ExplainResult, HuusError> {
+        Ok(ExplainResult::from_reply(driver::run(db, self.get_command(), None)?)?)
     }
+}
 
-    fn get_options(&self) -> Option<mongo_driver::collection::UpdateOptions> {
-        match self.options {
-            UpdateOptions::UpdateOne => None,
-            UpdateOptions::UpdateMany => Some(options::update_many()),
+impl Command for ExplainCommand {
+    /// Returns the `explain` database command.
+    fn get_command(&self) -> bson::Document {
+        doc! {
+            "explain": self.command.clone(),
+            "verbosity": self.verbosity.as_str(),
         }
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-#[derive(Debug, PartialEq)]
-pub enum RemoveOptions {
-    RemoveOne,
-    RemoveMany,
+/// Reads the number of documents returned by the `count`, `update` or `delete` command or
+/// a counting aggregation.
+fn get_count(document: &bson::Document) -> Result<u64, ConversionError> {
+    get_number(document, "n")
 }
 
-#[derive(Debug, PartialEq)]
-pub struct RemoveCommand {
-    pub(crate) collection_name: String,
-    pub(crate) filter: bson::Document,
-    pub(crate) options: RemoveOptions,
+/// Returns the first batch of documents from the reply to a command opening a cursor.
+fn get_first_batch(reply: &bson::Document) -> Result<&bson::Array, ConversionError> {
+    match reply.get_document("cursor").and_then(|cursor| cursor.get_array("firstBatch")) {
+        Ok(batch) => Ok(batch),
+        Err(_) => Err(ConversionError::wrong_type("cursor".to_string())),
+    }
 }
 
-impl RemoveCommand {
-    pub fn new(collection_name: String, filter: bson::Document, options: RemoveOptions) -> Self {
-        Self { collection_name, filter, options }
+/// Fails if the reply to a write command reports a write error.
+fn check_write_errors(reply: &bson::Document) -> Result<(), WriteError> {
+    match WriteError::from_reply(reply) {
+        Some(error) => Err(error),
+        None => Ok(()),
     }
+}
 
-    pub fn execute(&self, db: &mongo_driver::database::Database) -> Result<(), HuusError> {
-        let collection = db.get_collection(self.collection_name.as_bytes());
-        collection.remove(&self.filter, self.get_options().as_ref())?;
-        Ok(())
+/// Returns the options of a `mongosh` read method.
+fn get_read_options(
+    read_concern: Option<ReadConcern>,
+    read_preference: Option<ReadPreference>,
+) -> bson::Document {
+    let mut options = bson::Document::new();
+    if let Some(read_concern) = read_concern {
+        options.insert("readConcern", read_concern.to_doc());
+    }
+    if let Some(read_preference) = read_preference {
+        options.insert("readPreference", read_preference.as_str());
+    }
+    options
+}
+
+/// Returns the options of a `mongosh` write method.
+fn get_write_options(write_concern: &Option<WriteConcern>) -> bson::Document {
+    let mut options = bson::Document::new();
+    if let Some(write_concern) = write_concern {
+        options.insert("writeConcern", write_concern.to_doc());
     }
+    options
+}
 
-    fn get_options(&self) -> Option<mongo_driver::collection::RemoveOptions> {
-        match self.options {
-            RemoveOptions::RemoveOne => Some(options::remove_one()),
-            RemoveOptions::RemoveMany => None,
-        }
+/// Reads a number of documents stored under the given key of a server reply.
+fn get_number(document: &bson::Document, key: &str) -> Result<u64, ConversionError> {
+    match document.get(key) {
+        Some(bson::Bson::I32(count)) => Ok(*count as u64),
+        Some(bson::Bson::I64(count)) => Ok(*count as u64),
+        Some(bson::Bson::FloatingPoint(count)) => Ok(*count as u64),
+        Some(_) => Err(ConversionError::wrong_type(key.to_string())),
+        None => Err(ConversionError::missing_key(key.to_string())),
     }
 }
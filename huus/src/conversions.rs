@@ -13,6 +13,12 @@ use crate::types;
 
 pub trait FromDoc: Sized {
     fn from_doc(document: bson::Document) -> Result<Self, ConversionError>;
+
+    /// Converts the document like `from_doc`, but fails if it contains keys not present in the
+    /// schema. Unknown keys are ignored by `from_doc`.
+    fn from_doc_strict(document: bson::Document) -> Result<Self, ConversionError> {
+        Self::from_doc(document)
+    }
 }
 
 pub trait IntoDoc: Sized {
@@ -82,6 +88,12 @@ where
 
 // -------------------------------------------------------------------------------------------------
 
+impl FromDoc for bson::Document {
+    fn from_doc(document: bson::Document) -> Result<Self, ConversionError> {
+        Ok(document)
+    }
+}
+
 impl IntoDoc for bson::Document {
     fn into_doc(self) -> bson::Document {
         self
@@ -90,6 +102,53 @@ impl IntoDoc for bson::Document {
 
 // -------------------------------------------------------------------------------------------------
 
+impl FromDoc for types::GeoPoint {
+    fn from_doc(document: bson::Document) -> Result<Self, ConversionError> {
+        match document.get_str("type") {
+            Ok("Point") => {}
+            Ok(value) => return Err(ConversionError::incorrect_value(value.to_string())),
+            Err(bson::ordered::ValueAccessError::NotPresent) => {
+                return Err(ConversionError::missing_key("type".to_string()))
+            }
+            Err(bson::ordered::ValueAccessError::UnexpectedType) => {
+                return Err(ConversionError::wrong_type("type".to_string()))
+            }
+        }
+
+        match document.get_array("coordinates") {
+            Ok(coordinates) => match coordinates.as_slice() {
+                [bson::Bson::FloatingPoint(longitude), bson::Bson::FloatingPoint(latitude)] => {
+                    Ok(types::GeoPoint::new(*longitude, *latitude))
+                }
+                _ => Err(ConversionError::wrong_type("coordinates".to_string())),
+            },
+            Err(bson::ordered::ValueAccessError::NotPresent) => {
+                Err(ConversionError::missing_key("coordinates".to_string()))
+            }
+            Err(bson::ordered::ValueAccessError::UnexpectedType) => {
+                Err(ConversionError::wrong_type("coordinates".to_string()))
+            }
+        }
+    }
+}
+
+impl IntoDoc for types::GeoPoint {
+    fn into_doc(self) -> bson::Document {
+        let mut document = bson::Document::new();
+        document.insert("type", bson::Bson::String("Point".to_string()));
+        document.insert(
+            "coordinates",
+            bson::Bson::Array(vec![
+                bson::Bson::FloatingPoint(self.longitude),
+                bson::Bson::FloatingPoint(self.latitude),
+            ]),
+        );
+        document
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 impl HuusFromBson for String {
     fn huus_from_bson(bson: bson::Bson) -> Result<Self, ConversionError> {
         match bson {
@@ -117,6 +176,42 @@ impl HuusFromBson for i64 {
     }
 }
 
+impl HuusFromBson for f64 {
+    fn huus_from_bson(bson: bson::Bson) -> Result<Self, ConversionError> {
+        match bson {
+            bson::Bson::FloatingPoint(value) => Ok(value),
+            _ => Err(ConversionError::wrong_type_for_unknown_key()),
+        }
+    }
+}
+
+impl HuusFromBson for bool {
+    fn huus_from_bson(bson: bson::Bson) -> Result<Self, ConversionError> {
+        match bson {
+            bson::Bson::Boolean(value) => Ok(value),
+            _ => Err(ConversionError::wrong_type_for_unknown_key()),
+        }
+    }
+}
+
+impl HuusFromBson for types::ObjectId {
+    fn huus_from_bson(bson: bson::Bson) -> Result<Self, ConversionError> {
+        match bson {
+            bson::Bson::ObjectId(value) => Ok(value),
+            _ => Err(ConversionError::wrong_type_for_unknown_key()),
+        }
+    }
+}
+
+impl HuusFromBson for types::Date {
+    fn huus_from_bson(bson: bson::Bson) -> Result<Self, ConversionError> {
+        match bson {
+            bson::Bson::UtcDatetime(value) => Ok(value),
+            _ => Err(ConversionError::wrong_type_for_unknown_key()),
+        }
+    }
+}
+
 impl<T> HuusFromBson for T
 where
     T: FromDoc,
@@ -299,6 +394,21 @@ impl HuusKey for String {
 
 // -------------------------------------------------------------------------------------------------
 
+/// Merges the fields of `other` into `document` overriding already present fields. Used by the
+/// `...(code)` syntax of the formulation macros. Panics if `other` contains a field (or a path
+/// starting with a field) not listed in `keys`.
+pub fn spread(document: &mut bson::Document, other: bson::Document, keys: &[&str]) {
+    for (key, value) in other {
+        let root = key.split('.').next().unwrap_or("");
+        if !keys.contains(&root) {
+            panic!("Huus: Field '{}' cannot be spread into this document", key);
+        }
+        document.insert_bson(key, value);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 #[cfg(test)]
 mod tests {
     use super::HuusIntoStruct;
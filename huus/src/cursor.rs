@@ -0,0 +1,40 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides lazy iteration over query results.
+
+use std::marker::PhantomData;
+
+use crate::conversions::FromDoc;
+use crate::errors::HuusError;
+
+/// Iterates over documents returned by the database converting each of them to `Data` only when
+/// requested. Documents which fail to convert are reported as errors without stopping the
+/// iteration.
+pub struct TypedCursor<Data, Documents> {
+    documents: Documents,
+    phantom: PhantomData<Data>,
+}
+
+impl<Data, Documents> TypedCursor<Data, Documents> {
+    /// Constructs a new `TypedCursor` from an iterator over raw documents.
+    pub fn new(documents: Documents) -> Self {
+        Self { documents, phantom: PhantomData }
+    }
+}
+
+impl<Data, Documents, Error> Iterator for TypedCursor<Data, Documents>
+where
+    Data: FromDoc,
+    Documents: Iterator<Item = Result<bson::Document, Error>>,
+    HuusError: From<Error>,
+{
+    type Item = Result<Data, HuusError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.documents.next()? {
+            Ok(document) => Some(Data::from_doc(document).map_err(|error| error.into())),
+            Err(error) => Some(Err(error.into())),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides conversions between this crate and the official `mongodb` driver used to execute the
+//! commands, and helpers running the commands with the synchronous driver.
+//!
+//! Documents are passed between the `bson` versions used by this crate and by the driver in their
+//! binary form.
+
+use mongodb::options::SelectionCriteria;
+
+use crate::commands::ReadPreference;
+#[cfg(feature = "sync-driver")]
+use crate::commands::{Collation, Hint, ReadConcern};
+use crate::errors::ConversionError;
+#[cfg(feature = "sync-driver")]
+use crate::errors::HuusError;
+
+/// Converts the document to the representation used by the driver.
+pub fn to_driver(document: &bson::Document) -> Result<mongodb::bson::Document, ConversionError> {
+    let mut bytes = Vec::new();
+    bson::encode_document(&mut bytes, document)
+        .map_err(|error| ConversionError::incorrect_value(error.to_string()))?;
+    mongodb::bson::Document::from_reader(&mut bytes.as_slice())
+        .map_err(|error| ConversionError::incorrect_value(error.to_string()))
+}
+
+/// Converts the document from the representation used by the driver.
+pub fn from_driver(document: &mongodb::bson::Document) -> Result<bson::Document, ConversionError> {
+    let mut bytes = Vec::new();
+    document
+        .to_writer(&mut bytes)
+        .map_err(|error| ConversionError::incorrect_value(error.to_string()))?;
+    bson::decode_document(&mut bytes.as_slice())
+        .map_err(|error| ConversionError::incorrect_value(error.to_string()))
+}
+
+/// Converts the read preference to the selection criteria of the driver.
+pub(crate) fn get_selection_criteria(
+    read_preference: Option<ReadPreference>,
+) -> Option<SelectionCriteria> {
+    use mongodb::options::ReadPreference as DriverReadPreference;
+    let options = Default::default();
+    let read_preference = match read_preference? {
+        ReadPreference::Primary => DriverReadPreference::Primary,
+        ReadPreference::PrimaryPreferred => DriverReadPreference::PrimaryPreferred { options },
+        ReadPreference::Secondary => DriverReadPreference::Secondary { options },
+        ReadPreference::SecondaryPreferred => DriverReadPreference::SecondaryPreferred { options },
+        ReadPreference::Nearest => DriverReadPreference::Nearest { options },
+    };
+    Some(SelectionCriteria::ReadPreference(read_preference))
+}
+
+/// Converts the collation to the options of the driver.
+#[cfg(feature = "sync-driver")]
+pub(crate) fn get_collation(
+    collation: &Collation,
+) -> Result<mongodb::options::Collation, ConversionError> {
+    mongodb::bson::from_document(to_driver(&collation.to_doc())?)
+        .map_err(|error| ConversionError::incorrect_value(error.to_string()))
+}
+
+/// Converts the read concern to the options of the driver.
+#[cfg(feature = "sync-driver")]
+pub(crate) fn get_read_concern(
+    read_concern: ReadConcern,
+) -> Result<mongodb::options::ReadConcern, ConversionError> {
+    mongodb::bson::from_document(to_driver(&read_concern.to_doc())?)
+        .map_err(|error| ConversionError::incorrect_value(error.to_string()))
+}
+
+/// Converts the index hint to the options of the driver.
+#[cfg(feature = "sync-driver")]
+pub(crate) fn get_hint(hint: &Hint) -> Result<mongodb::options::Hint, ConversionError> {
+    match hint {
+        Hint::Name(name) => Ok(mongodb::options::Hint::Name(name.clone())),
+        Hint::Keys(keys) => Ok(mongodb::options::Hint::Keys(to_driver(keys)?)),
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Iterates over the documents fetched with a cursor of the synchronous driver. The next batches
+/// are fetched from the database as the iteration proceeds.
+#[cfg(feature = "sync-driver")]
+pub struct Documents {
+    cursor: mongodb::sync::Cursor<mongodb::bson::Document>,
+}
+
+#[cfg(feature = "sync-driver")]
+impl Documents {
+    pub(crate) fn new(cursor: mongodb::sync::Cursor<mongodb::bson::Document>) -> Self {
+        Self { cursor }
+    }
+}
+
+#[cfg(feature = "sync-driver")]
+impl Iterator for Documents {
+    type Item = Result<bson::Document, HuusError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.cursor.next()? {
+            Ok(document) => Some(from_driver(&document).map_err(HuusError::from)),
+            Err(error) => Some(Err(error.into())),
+        }
+    }
+}
+
+/// Checks if the collection exists.
+#[cfg(feature = "sync-driver")]
+pub(crate) fn has_collection(
+    db: &mongodb::sync::Database,
+    collection_name: &str,
+) -> Result<bool, HuusError> {
+    let filter = mongodb::bson::doc! { "name": collection_name };
+    Ok(!db.list_collection_names(filter)?.is_empty())
+}
+
+/// Runs the database command and returns the reply.
+#[cfg(feature = "sync-driver")]
+pub(crate) fn run(
+    db: &mongodb::sync::Database,
+    command: bson::Document,
+    read_preference: Option<ReadPreference>,
+) -> Result<bson::Document, HuusError> {
+    let reply = db.run_command(to_driver(&command)?, get_selection_criteria(read_preference))?;
+    Ok(from_driver(&reply)?)
+}
+
+/// Runs the aggregation pipeline given as `{ pipeline: [...] }` on the collection and returns the
+/// fetched documents.
+#[cfg(feature = "sync-driver")]
+pub(crate) fn aggregate(
+    db: &mongodb::sync::Database,
+    collection_name: &str,
+    pipeline: &bson::Document,
+    options: mongodb::options::AggregateOptions,
+) -> Result<Documents, HuusError> {
+    use mongodb::bson::Bson;
+    let stages = match to_driver(pipeline)?.remove("pipeline") {
+        Some(Bson::Array(stages)) => stages,
+        _ => return Err(ConversionError::wrong_type("pipeline".to_string()).into()),
+    };
+    let mut pipeline = Vec::with_capacity(stages.len());
+    for stage in stages {
+        match stage {
+            Bson::Document(stage) => pipeline.push(stage),
+            _ => return Err(ConversionError::wrong_type("pipeline".to_string()).into()),
+        }
+    }
+    let collection = db.collection::<mongodb::bson::Document>(collection_name);
+    Ok(Documents::new(collection.aggregate(pipeline, options)?))
+}
@@ -3,12 +3,63 @@
 
 //! Errors specific to this crate.
 
+/// Error of decoding a field which has a type different from the one declared in the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeError {
+    pub path: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl DecodeError {
+    pub fn new(path: String, expected: String, found: Option<&bson::Bson>) -> Self {
+        let found = found.map_or("missing", get_type_name).to_string();
+        Self { path, expected, found }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Wrong type for key: '{}'. Expected '{}', found '{}'",
+            self.path, self.expected, self.found
+        )
+    }
+}
+
+/// Returns the name of the BSON type as used by the `$type` operator.
+fn get_type_name(value: &bson::Bson) -> &'static str {
+    match value {
+        bson::Bson::FloatingPoint(_) => "double",
+        bson::Bson::String(_) => "string",
+        bson::Bson::Array(_) => "array",
+        bson::Bson::Document(_) => "object",
+        bson::Bson::Boolean(_) => "bool",
+        bson::Bson::Null => "null",
+        bson::Bson::RegExp(..) => "regex",
+        bson::Bson::JavaScriptCode(_) => "javascript",
+        bson::Bson::JavaScriptCodeWithScope(..) => "javascriptWithScope",
+        bson::Bson::I32(_) => "int",
+        bson::Bson::I64(_) => "long",
+        bson::Bson::TimeStamp(_) => "timestamp",
+        bson::Bson::Binary(..) => "binData",
+        bson::Bson::ObjectId(_) => "objectId",
+        bson::Bson::UtcDatetime(_) => "date",
+        bson::Bson::Symbol(_) => "symbol",
+    }
+}
+
 #[derive(Debug)]
 pub enum ConversionError {
     MissingKey { key: String },
+    UnexpectedKey { key: String },
     WrongType { key: String },
     UnexpectedValue { value: String },
     IncorrectValue { value: String },
+    Decode(DecodeError),
 }
 
 impl ConversionError {
@@ -16,6 +67,10 @@ impl ConversionError {
         ConversionError::MissingKey { key }
     }
 
+    pub fn unexpected_key(key: String) -> Self {
+        ConversionError::UnexpectedKey { key }
+    }
+
     pub fn wrong_type(key: String) -> Self {
         ConversionError::WrongType { key }
     }
@@ -31,6 +86,39 @@ impl ConversionError {
     pub fn incorrect_value(value: String) -> Self {
         ConversionError::IncorrectValue { value }
     }
+
+    pub fn decode(key: String, expected: &str, found: Option<&bson::Bson>) -> Self {
+        ConversionError::Decode(DecodeError::new(key, expected.to_string(), found))
+    }
+
+    /// Places the key of the error under the given field. Used to report the full path of a field
+    /// of a nested structure.
+    pub fn at(self, field: &str) -> Self {
+        let prefix = |key: String| format!("{}.{}", field, key);
+        match self {
+            ConversionError::MissingKey { key } => ConversionError::MissingKey { key: prefix(key) },
+            ConversionError::UnexpectedKey { key } => {
+                ConversionError::UnexpectedKey { key: prefix(key) }
+            }
+            ConversionError::WrongType { key } if key == "<unknown>" => {
+                ConversionError::WrongType { key: field.to_string() }
+            }
+            ConversionError::WrongType { key } => ConversionError::WrongType { key: prefix(key) },
+            ConversionError::Decode(mut error) => {
+                error.path = prefix(error.path);
+                ConversionError::Decode(error)
+            }
+            error => error,
+        }
+    }
+
+    /// Runs the conversion of the value of the given field reporting errors at that field.
+    pub fn within<T, F>(field: &str, conversion: F) -> Result<T, Self>
+    where
+        F: FnOnce() -> Result<T, Self>,
+    {
+        conversion().map_err(|error| error.at(field))
+    }
 }
 
 impl std::error::Error for ConversionError {}
@@ -39,26 +127,145 @@ impl std::fmt::Display for ConversionError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ConversionError::MissingKey { key } => write!(f, "Missing key: '{}'", key),
+            ConversionError::UnexpectedKey { key } => write!(f, "Unexpected key: '{}'", key),
             ConversionError::WrongType { key } => write!(f, "Wrong type for key: '{}'", key),
             ConversionError::UnexpectedValue { value } => {
                 write!(f, "Unexpected value. Found: '{}'", value)
             }
             ConversionError::IncorrectValue { value } => write!(f, "Incorrect value: '{}'", value),
+            ConversionError::Decode(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Error detected while building an update, before it is sent to the database.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpdateError {
+    /// Two operators modify the same path or paths of which one is a prefix of the other.
+    Conflict { operator: String, path: String, other_operator: String, other_path: String },
+
+    /// The update does not contain any instruction.
+    Empty,
+
+    /// The field is not present in the schema.
+    UnknownField { path: String },
+
+    /// The fields have different types in the schema.
+    IncompatibleTypes { path: String, other_path: String },
+
+    /// The field is renamed to itself.
+    RenameToSelf { path: String },
+
+    /// The field cannot be modified, e.g. `_id`.
+    ImmutableField { path: String },
+}
+
+impl UpdateError {
+    pub fn conflict(
+        operator: String,
+        path: String,
+        other_operator: String,
+        other_path: String,
+    ) -> Self {
+        UpdateError::Conflict { operator, path, other_operator, other_path }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UpdateError::Conflict { operator, path, other_operator, other_path } => write!(
+                f,
+                "Update of '{}' with '{}' conflicts with update of '{}' with '{}'",
+                path, operator, other_path, other_operator
+            ),
+            UpdateError::Empty => write!(f, "The update is empty"),
+            UpdateError::UnknownField { path } => {
+                write!(f, "Field '{}' is not present in the schema", path)
+            }
+            UpdateError::IncompatibleTypes { path, other_path } => {
+                write!(f, "Fields '{}' and '{}' have different types", path, other_path)
+            }
+            UpdateError::RenameToSelf { path } => {
+                write!(f, "Field '{}' cannot be renamed to itself", path)
+            }
+            UpdateError::ImmutableField { path } => {
+                write!(f, "Field '{}' cannot be modified", path)
+            }
+        }
+    }
+}
+
+/// Error of describing a page of documents which cannot be fetched.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaginationError {
+    /// The page size is zero.
+    ZeroPageSize,
+
+    /// The number of documents preceding the page does not fit into `u32`.
+    Overflow { page: u32, page_size: u32 },
+}
+
+impl std::error::Error for PaginationError {}
+
+impl std::fmt::Display for PaginationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PaginationError::ZeroPageSize => write!(f, "The page size must not be zero"),
+            PaginationError::Overflow { page, page_size } => {
+                write!(f, "Page {} of size {} is out of range", page, page_size)
+            }
         }
     }
 }
 
+/// Error reported by the database in the reply to a write command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriteError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl WriteError {
+    /// Returns the first of the write errors contained in a reply to a write command.
+    pub fn from_reply(reply: &bson::Document) -> Option<Self> {
+        let errors = reply.get_array("writeErrors").ok()?;
+        match errors.first() {
+            Some(bson::Bson::Document(error)) => Some(Self {
+                code: error.get_i32("code").unwrap_or(0),
+                message: error.get_str("errmsg").unwrap_or("").to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Write failed with code {}: '{}'", self.code, self.message)
+    }
+}
+
 #[derive(Debug)]
 pub enum HuusError {
-    Mongo(mongo_driver::MongoError),
+    #[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+    Driver(mongodb::error::Error),
     Conversion(ConversionError),
+    Update(UpdateError),
+    Pagination(PaginationError),
+    Write(WriteError),
 }
 
 impl std::error::Error for HuusError {}
 
-impl From<mongo_driver::MongoError> for HuusError {
-    fn from(error: mongo_driver::MongoError) -> Self {
-        HuusError::Mongo(error)
+#[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+impl From<mongodb::error::Error> for HuusError {
+    fn from(error: mongodb::error::Error) -> Self {
+        HuusError::Driver(error)
     }
 }
 
@@ -68,11 +275,33 @@ impl From<ConversionError> for HuusError {
     }
 }
 
+impl From<UpdateError> for HuusError {
+    fn from(error: UpdateError) -> Self {
+        HuusError::Update(error)
+    }
+}
+
+impl From<PaginationError> for HuusError {
+    fn from(error: PaginationError) -> Self {
+        HuusError::Pagination(error)
+    }
+}
+
+impl From<WriteError> for HuusError {
+    fn from(error: WriteError) -> Self {
+        HuusError::Write(error)
+    }
+}
+
 impl std::fmt::Display for HuusError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            HuusError::Mongo(err) => write!(f, "MongoDB: {}", err),
+            #[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+            HuusError::Driver(err) => write!(f, "MongoDB: {}", err),
             HuusError::Conversion(err) => write!(f, "Huus: {}", err),
+            HuusError::Update(err) => write!(f, "Huus: {}", err),
+            HuusError::Pagination(err) => write!(f, "Huus: {}", err),
+            HuusError::Write(err) => write!(f, "MongoDB: {}", err),
         }
     }
 }
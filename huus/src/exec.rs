@@ -0,0 +1,295 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides asynchronous execution of the commands with the official `mongodb` driver.
+//!
+//! Each command gets an `execute_async` method being the counterpart of its `execute` method, e.g.
+//! `Doc3::find(filter).execute_async(&db).await` returns `Vec<Doc3Data>`. The commands are sent as
+//! database commands. Documents are converted for the driver with the functions of the `driver`
+//! module.
+
+use futures_util::stream::{Stream, StreamExt, TryStreamExt};
+use mongodb::options::{CursorType, RunCursorCommandOptions};
+
+use crate::changes::ChangeEvent;
+use crate::commands::{
+    AggregateCommand, BulkWriteCommand, BulkWriteResult, Command, CountDocumentsCommand,
+    CreateCollectionCommand, CreateIndexesCommand, DeleteCommand, DeleteResult, DistinctCommand,
+    DropCollectionCommand, DropIndexesCommand, EstimatedCountCommand, ExplainCommand,
+    ExplainResult, FindAndModifyCommand, FindCommand, FindOneCommand, IndexInfo, IndexSync,
+    InsertCommand, InsertManyCommand, InsertManyResult, InsertResult, ListIndexesCommand,
+    LookupCommand, ReadPreference, ReplaceOneCommand, UpdateCommand, UpdateResult, WatchCommand,
+};
+use crate::conversions::{FromDoc, HuusFromBson};
+use crate::driver::get_selection_criteria;
+use crate::errors::{HuusError, UpdateError, WriteError};
+use crate::pagination::{Page, PageCommand};
+use crate::types::Joined;
+
+pub use crate::driver::{from_driver, to_driver};
+pub use mongodb::Database;
+
+// -------------------------------------------------------------------------------------------------
+
+impl CreateCollectionCommand {
+    /// Creates the collection unless it already exists.
+    pub async fn execute_async(&self, db: &Database) -> Result<(), HuusError> {
+        if !has_collection(db, &self.collection_name).await? {
+            run(db, self.get_command(), None).await?;
+        }
+        Ok(())
+    }
+}
+
+impl DropCollectionCommand {
+    /// Drops the collection if it exists.
+    pub async fn execute_async(&self, db: &Database) -> Result<(), HuusError> {
+        if has_collection(db, &self.collection_name).await? {
+            run(db, self.get_command(), None).await?;
+        }
+        Ok(())
+    }
+}
+
+impl CreateIndexesCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<(), HuusError> {
+        if !self.is_empty() {
+            run(db, self.get_command(), None).await?;
+        }
+        Ok(())
+    }
+}
+
+impl ListIndexesCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<Vec<IndexInfo>, HuusError> {
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl DropIndexesCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<(), HuusError> {
+        run(db, self.get_command(), None).await?;
+        Ok(())
+    }
+}
+
+impl IndexSync {
+    pub async fn execute_async(&self, db: &Database) -> Result<(), HuusError> {
+        for command in self.get_drop() {
+            command.execute_async(db).await?;
+        }
+        if let Some(command) = &self.create {
+            command.execute_async(db).await?;
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<Data> FindOneCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub async fn execute_async(&self, db: &Database) -> Result<Option<Data>, HuusError> {
+        self.read_reply(&run(db, self.get_command(), self.read_preference).await?)
+    }
+}
+
+impl<Data> FindCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub async fn execute_async(&self, db: &Database) -> Result<Vec<Data>, HuusError> {
+        self.iterate_async(db).await?.try_collect().await
+    }
+
+    /// Returns a stream converting the found documents as they are fetched from the database.
+    pub async fn iterate_async(
+        &self,
+        db: &Database,
+    ) -> Result<impl Stream<Item = Result<Data, HuusError>>, HuusError> {
+        let read_preference = self.options.read_preference;
+        let documents = run_cursor(db, self.get_command(), read_preference, None).await?;
+        Ok(documents.map(|document| Ok(Data::from_doc(document?)?)))
+    }
+}
+
+impl<Value> DistinctCommand<Value>
+where
+    Value: HuusFromBson,
+{
+    pub async fn execute_async(&self, db: &Database) -> Result<Vec<Value>, HuusError> {
+        self.read_reply(&run(db, self.get_command(), self.read_preference).await?)
+    }
+}
+
+impl CountDocumentsCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<u64, HuusError> {
+        self.read_reply(&run(db, self.get_command(), self.read_preference).await?)
+    }
+}
+
+impl EstimatedCountCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<u64, HuusError> {
+        self.read_reply(&run(db, self.get_command(), self.read_preference).await?)
+    }
+}
+
+impl<Data, Foreign> LookupCommand<Data, Foreign>
+where
+    Data: FromDoc,
+    Foreign: FromDoc,
+{
+    pub async fn execute_async(
+        &self,
+        db: &Database,
+    ) -> Result<Vec<Joined<Data, Foreign>>, HuusError> {
+        run_cursor(db, self.get_command(), self.read_preference, None)
+            .await?
+            .map(|document| self.split(document?))
+            .try_collect()
+            .await
+    }
+}
+
+impl AggregateCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<Vec<bson::Document>, HuusError> {
+        run_cursor(db, self.get_command(), self.read_preference, None).await?.try_collect().await
+    }
+}
+
+impl<Data> WatchCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Opens the change stream. The returned stream waits for new events and ends only when the
+    /// change stream is closed by the database.
+    pub async fn iterate_async(
+        &self,
+        db: &Database,
+    ) -> Result<impl Stream<Item = Result<ChangeEvent<Data>, HuusError>>, HuusError> {
+        let cursor_type = Some(CursorType::TailableAwait);
+        let events = run_cursor(db, self.get_command(), None, cursor_type).await?;
+        Ok(events.map(|event| ChangeEvent::from_doc(event?)))
+    }
+}
+
+impl<Data> PageCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub async fn execute_async(&self, db: &Database) -> Result<Page<Data>, HuusError> {
+        self.build_page(self.get_find_command().execute_async(db).await?)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl InsertCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<InsertResult, HuusError> {
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl InsertManyCommand {
+    /// Inserts the documents. Documents rejected because of a duplicate key are reported in the
+    /// result instead of failing the whole command.
+    pub async fn execute_async(&self, db: &Database) -> Result<InsertManyResult, HuusError> {
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl UpdateCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<UpdateResult, HuusError> {
+        if self.is_empty() {
+            return Err(UpdateError::Empty.into());
+        }
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl ReplaceOneCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<UpdateResult, HuusError> {
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl DeleteCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<DeleteResult, HuusError> {
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl BulkWriteCommand {
+    /// Sends the operations grouped into database commands. Unordered operations continue after
+    /// a write error which is then reported at the end.
+    pub async fn execute_async(&self, db: &Database) -> Result<BulkWriteResult, HuusError> {
+        let mut result = BulkWriteResult::default();
+        let mut first_error = None;
+        for command in self.get_commands() {
+            let reply = run(db, command.clone(), None).await?;
+            result.add_reply(&command, &reply)?;
+            if let Some(error) = WriteError::from_reply(&reply) {
+                if self.ordered {
+                    return Err(error.into());
+                }
+                first_error.get_or_insert(error);
+            }
+        }
+        match first_error {
+            Some(error) => Err(error.into()),
+            None => Ok(result),
+        }
+    }
+}
+
+impl<Data> FindAndModifyCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Returns the found document or `None` if no document matched the filter.
+    pub async fn execute_async(&self, db: &Database) -> Result<Option<Data>, HuusError> {
+        self.read_reply(&run(db, self.get_command(), None).await?)
+    }
+}
+
+impl ExplainCommand {
+    pub async fn execute_async(&self, db: &Database) -> Result<ExplainResult, HuusError> {
+        Ok(ExplainResult::from_reply(run(db, self.get_command(), None).await?)?)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Checks if the collection exists.
+async fn has_collection(db: &Database, collection_name: &str) -> Result<bool, HuusError> {
+    let filter = mongodb::bson::doc! { "name": collection_name };
+    Ok(!db.list_collection_names(filter).await?.is_empty())
+}
+
+/// Runs the database command and returns the reply.
+async fn run(
+    db: &Database,
+    command: bson::Document,
+    read_preference: Option<ReadPreference>,
+) -> Result<bson::Document, HuusError> {
+    let criteria = get_selection_criteria(read_preference);
+    let reply = db.run_command(to_driver(&command)?, criteria).await?;
+    Ok(from_driver(&reply)?)
+}
+
+/// Runs the database command opening a cursor and returns the stream of the documents fetched
+/// with the cursor.
+async fn run_cursor(
+    db: &Database,
+    command: bson::Document,
+    read_preference: Option<ReadPreference>,
+    cursor_type: Option<CursorType>,
+) -> Result<impl Stream<Item = Result<bson::Document, HuusError>>, HuusError> {
+    let mut options = RunCursorCommandOptions::default();
+    options.selection_criteria = get_selection_criteria(read_preference);
+    options.cursor_type = cursor_type;
+    let cursor = db.run_cursor_command(to_driver(&command)?, options).await?;
+    Ok(cursor.map(|document| Ok(from_driver(&document?)?)))
+}
@@ -0,0 +1,314 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides policies applied to the execution of commands. They do not depend on the driver: the
+//! commands are run by the given closures, so they apply to both the synchronous and asynchronous
+//! execution.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::errors::HuusError;
+
+/// Codes of the server errors after which repeating the command may succeed: network failures,
+/// elections in the replica set, shutdowns and write conflicts of transactions.
+pub const TRANSIENT_ERROR_CODES: &[i32] = &[
+    6,     // HostUnreachable
+    7,     // HostNotFound
+    89,    // NetworkTimeout
+    91,    // ShutdownInProgress
+    112,   // WriteConflict
+    189,   // PrimarySteppedDown
+    251,   // NoSuchTransaction
+    262,   // ExceededTimeLimit
+    9001,  // SocketException
+    10107, // NotWritablePrimary
+    11600, // InterruptedAtShutdown
+    11602, // InterruptedDueToReplStateChange
+    13435, // NotPrimaryNoSecondaryOk
+    13436, // NotPrimaryOrSecondary
+];
+
+/// Describes when and how many times a failed command is executed again.
+///
+/// The delay between the attempts starts with the initial backoff and doubles after each attempt
+/// up to the maximal backoff.
+///
+/// Failures to communicate with the server are not retried by default: the reply of a write may be
+/// lost after the server applied it, so executing an insert, an `$inc` update or a
+/// `findAndModify` again would apply it twice. Enable them with `with_network_errors` only for
+/// reads and idempotent commands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) error_codes: Vec<i32>,
+    pub(crate) retry_network_errors: bool,
+}
+
+impl RetryPolicy {
+    /// Constructs a policy making at most three attempts and retrying on errors listed in
+    /// `TRANSIENT_ERROR_CODES`.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            error_codes: TRANSIENT_ERROR_CODES.to_vec(),
+            retry_network_errors: false,
+        }
+    }
+
+    /// Sets the number of attempts including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the codes of the server errors which cause a retry.
+    pub fn with_error_codes(mut self, error_codes: Vec<i32>) -> Self {
+        self.error_codes = error_codes;
+        self
+    }
+
+    /// Sets whether failures to communicate with the server cause a retry. Should be enabled only
+    /// for policies executing reads and idempotent commands.
+    pub fn with_network_errors(mut self, retry_network_errors: bool) -> Self {
+        self.retry_network_errors = retry_network_errors;
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn get_network_errors(&self) -> bool {
+        self.retry_network_errors
+    }
+
+    /// Returns the delay before the given retry. Retries are counted from one.
+    pub fn get_backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        match self.initial_backoff.checked_mul(factor) {
+            Some(backoff) if backoff < self.max_backoff => backoff,
+            _ => self.max_backoff,
+        }
+    }
+
+    /// Checks if the command failing with the given error should be executed again.
+    pub fn is_retryable(&self, error: &HuusError) -> bool {
+        match error {
+            HuusError::Write(error) => self.error_codes.contains(&error.code),
+            #[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+            HuusError::Driver(error) => match error.kind.as_ref() {
+                mongodb::error::ErrorKind::Io(_)
+                | mongodb::error::ErrorKind::ConnectionPoolCleared { .. } => {
+                    self.retry_network_errors
+                }
+                mongodb::error::ErrorKind::Command(error) => self.error_codes.contains(&error.code),
+                _ => false,
+            },
+            HuusError::Conversion(_) | HuusError::Update(_) | HuusError::Pagination(_) => false,
+        }
+    }
+
+    /// Executes the operation until it succeeds, fails with an error which is not retryable or
+    /// the attempts run out. The last error is returned in the latter cases.
+    pub fn execute<T, F>(&self, mut operation: F) -> Result<T, HuusError>
+    where
+        F: FnMut() -> Result<T, HuusError>,
+    {
+        let mut retry = 0;
+        loop {
+            match operation() {
+                Ok(result) => return Ok(result),
+                Err(error) => {
+                    retry += 1;
+                    if retry >= self.max_attempts || !self.is_retryable(&error) {
+                        return Err(error);
+                    }
+                    std::thread::sleep(self.get_backoff(retry));
+                }
+            }
+        }
+    }
+
+    /// Asynchronous counterpart of `execute`. The delays are awaited with the futures returned by
+    /// `sleep` so that the crate does not depend on any async runtime, e.g.
+    /// `policy.execute_async(|| command.execute_async(&db), tokio::time::sleep).await`.
+    pub async fn execute_async<T, F, Fut, S, SleepFut>(
+        &self,
+        mut operation: F,
+        mut sleep: S,
+    ) -> Result<T, HuusError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, HuusError>>,
+        S: FnMut(Duration) -> SleepFut,
+        SleepFut: Future<Output = ()>,
+    {
+        let mut retry = 0;
+        loop {
+            match operation().await {
+                Ok(result) => return Ok(result),
+                Err(error) => {
+                    retry += 1;
+                    if retry >= self.max_attempts || !self.is_retryable(&error) {
+                        return Err(error);
+                    }
+                    sleep(self.get_backoff(retry)).await;
+                }
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Receives notifications about the commands run by `Executor`, e.g. to log them, collect metrics
+/// or detect slow queries. The command is passed as its `mongosh` snippet. Each attempt of a
+/// retried command is reported separately.
+pub trait CommandObserver {
+    /// Called before the command is sent to the database.
+    fn on_start(&self, _command: &dyn std::fmt::Display) {}
+
+    /// Called after the command succeeded with the time it took.
+    fn on_success(&self, _command: &dyn std::fmt::Display, _duration: Duration) {}
+
+    /// Called after the command failed with the error and the time it took.
+    fn on_error(&self, _command: &dyn std::fmt::Display, _error: &HuusError, _duration: Duration) {}
+}
+
+/// Runs commands notifying the registered observers and retrying the failures according to the
+/// retry policy, if any. Observers have to be `Send` and `Sync` so that the executor can be shared
+/// by asynchronous tasks.
+#[derive(Default)]
+pub struct Executor {
+    observers: Vec<Box<dyn CommandObserver + Send + Sync>>,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an observer. Observers are notified in the order of registration.
+    pub fn with_observer<O>(mut self, observer: O) -> Self
+    where
+        O: CommandObserver + Send + Sync + 'static,
+    {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Runs the operation executing the command, e.g.
+    /// `executor.execute(&command, |command| command.execute(&db))`.
+    pub fn execute<C, T, F>(&self, command: &C, mut operation: F) -> Result<T, HuusError>
+    where
+        C: std::fmt::Display,
+        F: FnMut(&C) -> Result<T, HuusError>,
+    {
+        match &self.retry_policy {
+            Some(retry_policy) => retry_policy.execute(|| self.observe(command, &mut operation)),
+            None => self.observe(command, &mut operation),
+        }
+    }
+
+    /// Asynchronous counterpart of `execute`. Retries are delayed with the futures returned by
+    /// `sleep`, e.g.
+    /// `executor.execute_async(&command, |command| command.execute_async(&db), tokio::time::sleep)`.
+    pub async fn execute_async<'a, C, T, F, Fut, S, SleepFut>(
+        &self,
+        command: &'a C,
+        mut operation: F,
+        sleep: S,
+    ) -> Result<T, HuusError>
+    where
+        C: std::fmt::Display,
+        F: FnMut(&'a C) -> Fut,
+        Fut: Future<Output = Result<T, HuusError>>,
+        S: FnMut(Duration) -> SleepFut,
+        SleepFut: Future<Output = ()>,
+    {
+        let mut attempt = move || self.observe_async(command, operation(command));
+        match &self.retry_policy {
+            Some(retry_policy) => retry_policy.execute_async(attempt, sleep).await,
+            None => attempt().await,
+        }
+    }
+
+    /// Runs a single attempt of the operation notifying the observers.
+    fn observe<C, T, F>(&self, command: &C, operation: &mut F) -> Result<T, HuusError>
+    where
+        C: std::fmt::Display,
+        F: FnMut(&C) -> Result<T, HuusError>,
+    {
+        self.notify_start(command);
+        let start = Instant::now();
+        let result = operation(command);
+        self.notify_end(command, &result, start.elapsed());
+        result
+    }
+
+    /// Awaits a single attempt of the operation notifying the observers.
+    async fn observe_async<C, T, Fut>(&self, command: &C, attempt: Fut) -> Result<T, HuusError>
+    where
+        C: std::fmt::Display,
+        Fut: Future<Output = Result<T, HuusError>>,
+    {
+        self.notify_start(command);
+        let start = Instant::now();
+        let result = attempt.await;
+        self.notify_end(command, &result, start.elapsed());
+        result
+    }
+
+    fn notify_start(&self, command: &dyn std::fmt::Display) {
+        for observer in self.observers.iter() {
+            observer.on_start(command);
+        }
+    }
+
+    fn notify_end<T>(
+        &self,
+        command: &dyn std::fmt::Display,
+        result: &Result<T, HuusError>,
+        duration: Duration,
+    ) {
+        match result {
+            Ok(_) => {
+                for observer in self.observers.iter() {
+                    observer.on_success(command, duration);
+                }
+            }
+            Err(error) => {
+                for observer in self.observers.iter() {
+                    observer.on_error(command, error, duration);
+                }
+            }
+        }
+    }
+}
@@ -174,11 +174,17 @@ where
     fn eq(&mut self, value: B);
     fn gt(&mut self, value: B);
     fn gte(&mut self, value: B);
-    fn r#in(&mut self, value: Vec<B>);
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<B>;
     fn lt(&mut self, value: B);
     fn lte(&mut self, value: B);
     fn ne(&mut self, value: B);
-    fn nin(&mut self, value: Vec<B>);
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<B>;
 }
 
 #[derive(Clone, Debug)]
@@ -232,6 +238,66 @@ where
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
+pub trait ModuloFilter {
+    fn r#mod(&mut self, divisor: i64, remainder: i64);
+}
+
+/// Comparisons applicable only to integer fields. Kept apart from `Comparison`, which is shared by
+/// entries of all types, so that `$mod` cannot be built for strings, dates or floats.
+#[derive(Clone, Debug)]
+pub enum Modulo {
+    Mod(i64, i64),
+}
+
+impl BuildInnerFilter for Modulo {
+    fn build_filter(self, field: String) -> Filter {
+        match self {
+            Modulo::Mod(divisor, remainder) => {
+                Filter::with_field(field, bson!({ "$mod": [divisor, remainder] }))
+            }
+        }
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
+pub trait BitwiseFilter {
+    fn bits_all_set(&mut self, mask: u64);
+    fn bits_any_set(&mut self, mask: u64);
+    fn bits_all_clear(&mut self, mask: u64);
+    fn bits_any_clear(&mut self, mask: u64);
+}
+
+#[derive(Clone, Debug)]
+pub enum Bitwise {
+    BitsAllSet(u64),
+    BitsAnySet(u64),
+    BitsAllClear(u64),
+    BitsAnyClear(u64),
+}
+
+impl BuildInnerFilter for Bitwise {
+    fn build_filter(self, field: String) -> Filter {
+        // BSON has no unsigned integers. Reinterpreting the mask as signed keeps its bits intact.
+        match self {
+            Bitwise::BitsAllSet(mask) => {
+                Filter::with_field(field, bson!({ "$bitsAllSet": mask as i64 }))
+            }
+            Bitwise::BitsAnySet(mask) => {
+                Filter::with_field(field, bson!({ "$bitsAnySet": mask as i64 }))
+            }
+            Bitwise::BitsAllClear(mask) => {
+                Filter::with_field(field, bson!({ "$bitsAllClear": mask as i64 }))
+            }
+            Bitwise::BitsAnyClear(mask) => {
+                Filter::with_field(field, bson!({ "$bitsAnyClear": mask as i64 }))
+            }
+        }
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
 #[derive(Clone, Debug)]
 pub enum Logical<F>
 where
@@ -252,9 +318,7 @@ where
             Logical::And(filters) => {
                 Filter::with_field("$and".to_string(), inner_filters_into_array(filters, field))
             }
-            Logical::Not(filter) => {
-                Filter::with_field("$not".to_string(), filter.build_filter(field).into_bson())
-            }
+            Logical::Not(filter) => !filter.build_filter(field),
             Logical::Nor(filters) => {
                 Filter::with_field("$nor".to_string(), inner_filters_into_array(filters, field))
             }
@@ -265,23 +329,42 @@ where
     }
 }
 
+pub trait NegationFilter {
+    fn not(&mut self);
+}
+
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
 pub trait ElementFilter {
     fn exists(&mut self, exists: bool);
     fn with_type(&mut self, bson_type: types::Type);
+
+    /// Matches documents containing the field with explicit `null` value.
+    fn is_null(&mut self) {
+        self.with_type(types::Type::Null);
+    }
+
+    /// Matches documents not containing the field.
+    fn is_missing(&mut self) {
+        self.exists(false);
+    }
+
+    /// Matches documents containing the field with `null` value or not containing it at all.
+    fn is_null_or_missing(&mut self);
 }
 
 #[derive(Clone, Debug)]
 pub enum Element {
     Exists(bool),
     Type(types::Type),
+    NullOrMissing,
 }
 
 impl BuildInnerFilter for Element {
     fn build_filter(self, field: String) -> Filter {
         match self {
             Element::Exists(value) => Filter::with_field(field, bson!({ "$exists": value })),
+            Element::NullOrMissing => Filter::with_field(field, bson::Bson::Null),
             Element::Type(data_type) => {
                 Filter::with_field(field, bson!({ "$type": data_type as i32 }))
             }
@@ -291,24 +374,62 @@ impl BuildInnerFilter for Element {
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
-pub trait ArrayFilter<B> {
+pub trait RegexFilter {
+    fn regex(&mut self, pattern: &str, options: &str);
+}
+
+#[derive(Clone, Debug)]
+pub struct RegexEntry {
+    pub(crate) pattern: String,
+    pub(crate) options: String,
+}
+
+impl RegexEntry {
+    pub fn new(pattern: &str, options: &str) -> Self {
+        Self { pattern: pattern.to_string(), options: options.to_string() }
+    }
+
+    pub fn get_pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn get_options(&self) -> &str {
+        &self.options
+    }
+}
+
+impl BuildInnerFilter for RegexEntry {
+    fn build_filter(self, field: String) -> Filter {
+        Filter::with_field(field, bson!({ "$regex": self.pattern, "$options": self.options }))
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
+pub trait ArrayFilter<F, B> {
     fn all(&mut self, array: Vec<B>);
-    fn elem_match(&mut self, array: Vec<B>);
+    fn elem_match(&mut self, filter: F);
     fn size(&mut self, size: usize);
+    /// Matches arrays with at least `min` and at most `max` elements. Nothing matches if
+    /// `min > max`.
+    fn size_between(&mut self, min: usize, max: usize);
 }
 
 #[derive(Clone, Debug)]
-pub enum Array<B>
+pub enum Array<F, B>
 where
+    F: BuildInnerFilter,
     B: HuusIntoBson,
 {
     All(Vec<B>),
-    ElemMatch(Vec<B>),
+    ElemMatch(F),
     Size(usize),
+    SizeBetween(usize, usize),
 }
 
-impl<B> BuildInnerFilter for Array<B>
+impl<F, B> BuildInnerFilter for Array<F, B>
 where
+    F: BuildInnerFilter,
     B: HuusIntoBson,
 {
     fn build_filter(self, field: String) -> Filter {
@@ -316,29 +437,165 @@ where
             Array::All(values) => {
                 Filter::with_field(field, bson!({ "$all": vec_into_array(values) }))
             }
-            Array::ElemMatch(values) => {
-                Filter::with_field(field, bson!({ "$elemMatch": vec_into_array(values) }))
+            Array::ElemMatch(filter) => {
+                let conditions = elem_match_conditions(filter.build_filter(String::new()).doc);
+                Filter::with_field(field, bson!({ "$elemMatch": conditions }))
             }
             Array::Size(size) => Filter::with_field(field, bson!({ "$size": size as i32 })),
+            Array::SizeBetween(min, max) if min > max => {
+                Filter::with_field(field, bson!({ "$in": [] }))
+            }
+            Array::SizeBetween(min, max) if field.is_empty() || field.starts_with('.') => {
+                // Aggregation expressions inside `$elemMatch` refer to the whole document, not to
+                // the element, so the range is checked there by the presence of the elements at
+                // the bounding indexes.
+                let mut filter = Filter::empty();
+                if min > 0 {
+                    let first = format!("{}.{}", field, min - 1);
+                    filter.incorporate(Filter::with_field(first, bson!({ "$exists": true })));
+                }
+                let last = format!("{}.{}", field, max);
+                filter.incorporate(Filter::with_field(last, bson!({ "$exists": false })));
+                filter
+            }
+            Array::SizeBetween(min, max) => {
+                // `$size` matches only the exact size. `$and` stops at the type check so that
+                // `$size` is not evaluated for documents missing the field.
+                let path = format!("${}", field);
+                let size = bson!({ "$size": path.clone() });
+                let expression = bson!({ "$and": [
+                    { "$isArray": path },
+                    { "$gte": [size.clone(), min as i64] },
+                    { "$lte": [size, max as i64] },
+                ] });
+                Filter::with_field("$expr".to_string(), expression)
+            }
         }
     }
 }
 
+// Element filters built for an empty field name prefix nested fields with a dot and assign
+// conditions on the element itself to an empty key. Strip both to get `$elemMatch` conditions.
+fn elem_match_conditions(doc: bson::Document) -> bson::Document {
+    let mut result = bson::Document::new();
+    for (key, value) in doc {
+        if key.is_empty() {
+            match value {
+                bson::Bson::Document(conditions) => {
+                    for (operator, operand) in conditions {
+                        result.insert_bson(operator, operand);
+                    }
+                }
+                value => {
+                    result.insert_bson("$eq".to_string(), value);
+                }
+            }
+        } else if let Some(subkey) = key.strip_prefix('.') {
+            result.insert_bson(subkey.to_string(), value);
+        } else if key == "$and" || key == "$or" || key == "$nor" {
+            let value = match value {
+                bson::Bson::Array(array) => bson::Bson::Array(
+                    array
+                        .into_iter()
+                        .map(|element| match element {
+                            bson::Bson::Document(doc) => {
+                                bson::Bson::Document(elem_match_conditions(doc))
+                            }
+                            element => element,
+                        })
+                        .collect(),
+                ),
+                value => value,
+            };
+            result.insert_bson(key, value);
+        } else {
+            result.insert_bson(key, value);
+        }
+    }
+    result
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[derive(Clone, Debug)]
 pub enum F64Entry {
     Value(types::Double),
     Comparison(Comparison<types::Double>),
+    Not(Box<F64Entry>),
     Element(Element),
     Empty,
 }
 
+impl ComparisonFilter<types::Double> for F64Entry {
+    fn eq(&mut self, value: types::Double) {
+        *self = F64Entry::Comparison(Comparison::Eq(value));
+    }
+
+    fn gt(&mut self, value: types::Double) {
+        *self = F64Entry::Comparison(Comparison::Gt(value));
+    }
+
+    fn gte(&mut self, value: types::Double) {
+        *self = F64Entry::Comparison(Comparison::Gte(value));
+    }
+
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::Double>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = F64Entry::Comparison(Comparison::In(values));
+    }
+
+    fn lt(&mut self, value: types::Double) {
+        *self = F64Entry::Comparison(Comparison::Lt(value));
+    }
+
+    fn lte(&mut self, value: types::Double) {
+        *self = F64Entry::Comparison(Comparison::Lte(value));
+    }
+
+    fn ne(&mut self, value: types::Double) {
+        *self = F64Entry::Comparison(Comparison::Ne(value));
+    }
+
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::Double>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = F64Entry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl ElementFilter for F64Entry {
+    fn exists(&mut self, exists: bool) {
+        *self = F64Entry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = F64Entry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = F64Entry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for F64Entry {
+    fn not(&mut self) {
+        *self = F64Entry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for F64Entry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             F64Entry::Value(value) => Filter::with_field(field, bson::Bson::FloatingPoint(value)),
             F64Entry::Comparison(comparison) => comparison.build_filter(field),
+            F64Entry::Not(entry) => !entry.build_filter(field),
             F64Entry::Element(element) => element.build_filter(field),
             F64Entry::Empty => Filter::empty(),
         }
@@ -378,6 +635,8 @@ impl std::convert::From<f32> for F64Entry {
 pub enum StringEntry {
     Value(String),
     Comparison(Comparison<String>),
+    Regex(RegexEntry),
+    Not(Box<StringEntry>),
     Element(Element),
     Empty,
 }
@@ -395,8 +654,13 @@ impl ComparisonFilter<String> for StringEntry {
         *self = StringEntry::Comparison(Comparison::Gte(value));
     }
 
-    fn r#in(&mut self, value: Vec<String>) {
-        *self = StringEntry::Comparison(Comparison::In(value));
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = StringEntry::Comparison(Comparison::In(values));
     }
 
     fn lt(&mut self, value: String) {
@@ -411,8 +675,39 @@ impl ComparisonFilter<String> for StringEntry {
         *self = StringEntry::Comparison(Comparison::Ne(value));
     }
 
-    fn nin(&mut self, value: Vec<String>) {
-        *self = StringEntry::Comparison(Comparison::Nin(value));
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = StringEntry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl RegexFilter for StringEntry {
+    fn regex(&mut self, pattern: &str, options: &str) {
+        *self = StringEntry::Regex(RegexEntry::new(pattern, options));
+    }
+}
+
+impl ElementFilter for StringEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = StringEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = StringEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = StringEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for StringEntry {
+    fn not(&mut self) {
+        *self = StringEntry::Not(Box::new(std::mem::take(self)));
     }
 }
 
@@ -421,6 +716,8 @@ impl BuildInnerFilter for StringEntry {
         match self {
             StringEntry::Value(value) => Filter::with_field(field, bson::Bson::String(value)),
             StringEntry::Comparison(comparison) => comparison.build_filter(field),
+            StringEntry::Regex(regex) => regex.build_filter(field),
+            StringEntry::Not(entry) => !entry.build_filter(field),
             StringEntry::Element(element) => element.build_filter(field),
             StringEntry::Empty => Filter::empty(),
         }
@@ -454,10 +751,37 @@ where
 {
     Value(K),
     Comparison(Comparison<String>),
+    Not(Box<EnumEntry<K>>),
     Element(Element),
     Empty,
 }
 
+impl<K> ElementFilter for EnumEntry<K>
+where
+    K: HuusKey,
+{
+    fn exists(&mut self, exists: bool) {
+        *self = EnumEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = EnumEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = EnumEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl<K> NegationFilter for EnumEntry<K>
+where
+    K: HuusKey,
+{
+    fn not(&mut self) {
+        *self = EnumEntry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl<K> BuildInnerFilter for EnumEntry<K>
 where
     K: HuusKey,
@@ -468,6 +792,7 @@ where
                 Filter::with_field(field, bson::Bson::String(value.to_str().to_string()))
             }
             EnumEntry::Comparison(comparison) => comparison.build_filter(field),
+            EnumEntry::Not(entry) => !entry.build_filter(field),
             EnumEntry::Element(element) => element.build_filter(field),
             EnumEntry::Empty => Filter::empty(),
         }
@@ -512,6 +837,34 @@ where
     }
 }
 
+impl<F, B> ElementFilter for ObjectEntry<F, B>
+where
+    F: BuildInnerFilter,
+    B: HuusIntoBson,
+{
+    fn exists(&mut self, exists: bool) {
+        *self = ObjectEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = ObjectEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = ObjectEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl<F, B> NegationFilter for ObjectEntry<F, B>
+where
+    F: BuildInnerFilter,
+    B: HuusIntoBson,
+{
+    fn not(&mut self) {
+        *self = ObjectEntry::Logical(Box::new(Logical::Not(std::mem::take(self))));
+    }
+}
+
 impl<F, B> BuildInnerFilter for ObjectEntry<F, B>
 where
     F: BuildInnerFilter,
@@ -552,6 +905,34 @@ where
     Empty,
 }
 
+impl<K, B> ElementFilter for BTreeMapEntry<K, B>
+where
+    K: HuusKey,
+    B: HuusIntoBson,
+{
+    fn exists(&mut self, exists: bool) {
+        *self = BTreeMapEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = BTreeMapEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = BTreeMapEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl<K, B> NegationFilter for BTreeMapEntry<K, B>
+where
+    K: HuusKey,
+    B: HuusIntoBson,
+{
+    fn not(&mut self) {
+        *self = BTreeMapEntry::Logical(Box::new(Logical::Not(std::mem::take(self))));
+    }
+}
+
 impl<K, B> BuildInnerFilter for BTreeMapEntry<K, B>
 where
     K: HuusKey,
@@ -591,6 +972,34 @@ where
     Empty,
 }
 
+impl<K, B> ElementFilter for HashMapEntry<K, B>
+where
+    K: HuusKey,
+    B: HuusIntoBson,
+{
+    fn exists(&mut self, exists: bool) {
+        *self = HashMapEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = HashMapEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = HashMapEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl<K, B> NegationFilter for HashMapEntry<K, B>
+where
+    K: HuusKey,
+    B: HuusIntoBson,
+{
+    fn not(&mut self) {
+        *self = HashMapEntry::Logical(Box::new(Logical::Not(std::mem::take(self))));
+    }
+}
+
 impl<K, B> BuildInnerFilter for HashMapEntry<K, B>
 where
     K: HuusKey,
@@ -626,8 +1035,9 @@ where
 {
     Value(B),
     Dot(F),
-    Array(Array<B>),
+    Array(Array<F, B>),
     Comparison(Comparison<B>),
+    Not(Box<ArrayEntry<F, B>>),
     Element(Element),
     Empty,
 }
@@ -646,7 +1056,7 @@ where
     }
 }
 
-impl<F, B> ArrayFilter<B> for ArrayEntry<F, B>
+impl<F, B> ArrayFilter<F, B> for ArrayEntry<F, B>
 where
     F: BuildInnerFilter,
     B: HuusIntoBson,
@@ -655,13 +1065,17 @@ where
         *self = ArrayEntry::Array(Array::All(array));
     }
 
-    fn elem_match(&mut self, array: Vec<B>) {
-        *self = ArrayEntry::Array(Array::ElemMatch(array));
+    fn elem_match(&mut self, filter: F) {
+        *self = ArrayEntry::Array(Array::ElemMatch(filter));
     }
 
     fn size(&mut self, size: usize) {
         *self = ArrayEntry::Array(Array::Size(size));
     }
+
+    fn size_between(&mut self, min: usize, max: usize) {
+        *self = ArrayEntry::Array(Array::SizeBetween(min, max));
+    }
 }
 
 impl<F, B> ComparisonFilter<B> for ArrayEntry<F, B>
@@ -681,8 +1095,13 @@ where
         *self = ArrayEntry::Comparison(Comparison::Gte(value));
     }
 
-    fn r#in(&mut self, value: Vec<B>) {
-        *self = ArrayEntry::Comparison(Comparison::In(value));
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<B>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = ArrayEntry::Comparison(Comparison::In(values));
     }
 
     fn lt(&mut self, value: B) {
@@ -697,8 +1116,41 @@ where
         *self = ArrayEntry::Comparison(Comparison::Ne(value));
     }
 
-    fn nin(&mut self, value: Vec<B>) {
-        *self = ArrayEntry::Comparison(Comparison::Nin(value));
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<B>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = ArrayEntry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl<F, B> ElementFilter for ArrayEntry<F, B>
+where
+    F: BuildInnerFilter,
+    B: HuusIntoBson,
+{
+    fn exists(&mut self, exists: bool) {
+        *self = ArrayEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = ArrayEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = ArrayEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl<F, B> NegationFilter for ArrayEntry<F, B>
+where
+    F: BuildInnerFilter,
+    B: HuusIntoBson,
+{
+    fn not(&mut self) {
+        *self = ArrayEntry::Not(Box::new(std::mem::take(self)));
     }
 }
 
@@ -713,6 +1165,7 @@ where
             ArrayEntry::Dot(value) => value.build_filter(field),
             ArrayEntry::Array(array) => array.build_filter(field),
             ArrayEntry::Comparison(value) => value.build_filter(field),
+            ArrayEntry::Not(entry) => !entry.build_filter(field),
             ArrayEntry::Element(element) => element.build_filter(field),
             ArrayEntry::Empty => Filter::empty(),
         }
@@ -744,14 +1197,82 @@ where
 #[derive(Clone, Debug)]
 pub enum ObjectIdEntry {
     Value(types::ObjectId),
+    Comparison(Comparison<types::ObjectId>),
+    Not(Box<ObjectIdEntry>),
     Element(Element),
     Empty,
 }
 
+impl ComparisonFilter<types::ObjectId> for ObjectIdEntry {
+    fn eq(&mut self, value: types::ObjectId) {
+        *self = ObjectIdEntry::Comparison(Comparison::Eq(value));
+    }
+
+    fn gt(&mut self, value: types::ObjectId) {
+        *self = ObjectIdEntry::Comparison(Comparison::Gt(value));
+    }
+
+    fn gte(&mut self, value: types::ObjectId) {
+        *self = ObjectIdEntry::Comparison(Comparison::Gte(value));
+    }
+
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::ObjectId>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = ObjectIdEntry::Comparison(Comparison::In(values));
+    }
+
+    fn lt(&mut self, value: types::ObjectId) {
+        *self = ObjectIdEntry::Comparison(Comparison::Lt(value));
+    }
+
+    fn lte(&mut self, value: types::ObjectId) {
+        *self = ObjectIdEntry::Comparison(Comparison::Lte(value));
+    }
+
+    fn ne(&mut self, value: types::ObjectId) {
+        *self = ObjectIdEntry::Comparison(Comparison::Ne(value));
+    }
+
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::ObjectId>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = ObjectIdEntry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl ElementFilter for ObjectIdEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = ObjectIdEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = ObjectIdEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = ObjectIdEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for ObjectIdEntry {
+    fn not(&mut self) {
+        *self = ObjectIdEntry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for ObjectIdEntry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             ObjectIdEntry::Value(value) => Filter::with_field(field, bson::Bson::ObjectId(value)),
+            ObjectIdEntry::Comparison(comparison) => comparison.build_filter(field),
+            ObjectIdEntry::Not(entry) => !entry.build_filter(field),
             ObjectIdEntry::Element(element) => element.build_filter(field),
             ObjectIdEntry::Empty => Filter::empty(),
         }
@@ -775,6 +1296,7 @@ impl std::convert::From<types::ObjectId> for ObjectIdEntry {
 #[derive(Clone, Debug)]
 pub enum BooleanEntry {
     Value(bool),
+    Not(Box<BooleanEntry>),
     Element(Element),
     Empty,
 }
@@ -787,12 +1309,23 @@ impl ElementFilter for BooleanEntry {
     fn with_type(&mut self, bson_type: types::Type) {
         *self = BooleanEntry::Element(Element::Type(bson_type));
     }
+
+    fn is_null_or_missing(&mut self) {
+        *self = BooleanEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for BooleanEntry {
+    fn not(&mut self) {
+        *self = BooleanEntry::Not(Box::new(std::mem::take(self)));
+    }
 }
 
 impl BuildInnerFilter for BooleanEntry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             BooleanEntry::Value(value) => Filter::with_field(field, bson::Bson::Boolean(value)),
+            BooleanEntry::Not(entry) => !entry.build_filter(field),
             BooleanEntry::Element(element) => element.build_filter(field),
             BooleanEntry::Empty => Filter::empty(),
         }
@@ -817,6 +1350,7 @@ impl std::convert::From<bool> for BooleanEntry {
 pub enum DateEntry {
     Value(types::Date),
     Comparison(Comparison<chrono::DateTime<chrono::Utc>>),
+    Not(Box<DateEntry>),
     Element(Element),
     Empty,
 }
@@ -834,8 +1368,13 @@ impl ComparisonFilter<types::Date> for DateEntry {
         *self = DateEntry::Comparison(Comparison::Gte(value));
     }
 
-    fn r#in(&mut self, value: Vec<types::Date>) {
-        *self = DateEntry::Comparison(Comparison::In(value));
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::Date>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = DateEntry::Comparison(Comparison::In(values));
     }
 
     fn lt(&mut self, value: types::Date) {
@@ -850,8 +1389,33 @@ impl ComparisonFilter<types::Date> for DateEntry {
         *self = DateEntry::Comparison(Comparison::Ne(value));
     }
 
-    fn nin(&mut self, value: Vec<types::Date>) {
-        *self = DateEntry::Comparison(Comparison::Nin(value));
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::Date>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = DateEntry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl ElementFilter for DateEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = DateEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = DateEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = DateEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for DateEntry {
+    fn not(&mut self) {
+        *self = DateEntry::Not(Box::new(std::mem::take(self)));
     }
 }
 
@@ -860,6 +1424,7 @@ impl BuildInnerFilter for DateEntry {
         match self {
             DateEntry::Value(value) => Filter::with_field(field, bson::Bson::UtcDatetime(value)),
             DateEntry::Comparison(comparison) => comparison.build_filter(field),
+            DateEntry::Not(entry) => !entry.build_filter(field),
             DateEntry::Element(element) => element.build_filter(field),
             DateEntry::Empty => Filter::empty(),
         }
@@ -881,13 +1446,35 @@ impl std::convert::From<types::Date> for DateEntry {
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
 pub enum NullEntry {
+    Not(Box<NullEntry>),
     Element(Element),
     Empty,
 }
 
+impl ElementFilter for NullEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = NullEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = NullEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = NullEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for NullEntry {
+    fn not(&mut self) {
+        *self = NullEntry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for NullEntry {
     fn build_filter(self, field: String) -> Filter {
         match self {
+            NullEntry::Not(entry) => !entry.build_filter(field),
             NullEntry::Element(element) => element.build_filter(field),
             NullEntry::Empty => Filter::empty(),
         }
@@ -906,15 +1493,95 @@ impl Default for NullEntry {
 pub enum I32Entry {
     Value(i32),
     Comparison(Comparison<i32>),
+    Modulo(Modulo),
+    Bitwise(Bitwise),
+    Not(Box<I32Entry>),
     Element(Element),
     Empty,
 }
 
+impl ComparisonFilter<i32> for I32Entry {
+    fn eq(&mut self, value: i32) {
+        *self = I32Entry::Comparison(Comparison::Eq(value));
+    }
+
+    fn gt(&mut self, value: i32) {
+        *self = I32Entry::Comparison(Comparison::Gt(value));
+    }
+
+    fn gte(&mut self, value: i32) {
+        *self = I32Entry::Comparison(Comparison::Gte(value));
+    }
+
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<i32>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = I32Entry::Comparison(Comparison::In(values));
+    }
+
+    fn lt(&mut self, value: i32) {
+        *self = I32Entry::Comparison(Comparison::Lt(value));
+    }
+
+    fn lte(&mut self, value: i32) {
+        *self = I32Entry::Comparison(Comparison::Lte(value));
+    }
+
+    fn ne(&mut self, value: i32) {
+        *self = I32Entry::Comparison(Comparison::Ne(value));
+    }
+
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<i32>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = I32Entry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl ModuloFilter for I32Entry {
+    fn r#mod(&mut self, divisor: i64, remainder: i64) {
+        *self = I32Entry::Modulo(Modulo::Mod(divisor, remainder));
+    }
+}
+
+impl BitwiseFilter for I32Entry {
+    fn bits_all_set(&mut self, mask: u64) {
+        *self = I32Entry::Bitwise(Bitwise::BitsAllSet(mask));
+    }
+
+    fn bits_any_set(&mut self, mask: u64) {
+        *self = I32Entry::Bitwise(Bitwise::BitsAnySet(mask));
+    }
+
+    fn bits_all_clear(&mut self, mask: u64) {
+        *self = I32Entry::Bitwise(Bitwise::BitsAllClear(mask));
+    }
+
+    fn bits_any_clear(&mut self, mask: u64) {
+        *self = I32Entry::Bitwise(Bitwise::BitsAnyClear(mask));
+    }
+}
+
+impl NegationFilter for I32Entry {
+    fn not(&mut self) {
+        *self = I32Entry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for I32Entry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             I32Entry::Value(value) => Filter::with_field(field, bson::Bson::I32(value)),
             I32Entry::Comparison(comparison) => comparison.build_filter(field),
+            I32Entry::Modulo(modulo) => modulo.build_filter(field),
+            I32Entry::Bitwise(bitwise) => bitwise.build_filter(field),
+            I32Entry::Not(entry) => !entry.build_filter(field),
             I32Entry::Element(element) => element.build_filter(field),
             I32Entry::Empty => Filter::empty(),
         }
@@ -935,6 +1602,10 @@ impl ElementFilter for I32Entry {
     fn with_type(&mut self, bson_type: types::Type) {
         *self = I32Entry::Element(Element::Type(bson_type));
     }
+
+    fn is_null_or_missing(&mut self) {
+        *self = I32Entry::Element(Element::NullOrMissing);
+    }
 }
 
 impl std::convert::From<i32> for I32Entry {
@@ -949,15 +1620,81 @@ impl std::convert::From<i32> for I32Entry {
 pub enum TimeStampEntry {
     Value(types::TimeStamp),
     Comparison(Comparison<types::TimeStamp>),
+    Not(Box<TimeStampEntry>),
     Element(Element),
     Empty,
 }
 
+impl ComparisonFilter<types::TimeStamp> for TimeStampEntry {
+    fn eq(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Comparison(Comparison::Eq(value));
+    }
+
+    fn gt(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Comparison(Comparison::Gt(value));
+    }
+
+    fn gte(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Comparison(Comparison::Gte(value));
+    }
+
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::TimeStamp>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = TimeStampEntry::Comparison(Comparison::In(values));
+    }
+
+    fn lt(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Comparison(Comparison::Lt(value));
+    }
+
+    fn lte(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Comparison(Comparison::Lte(value));
+    }
+
+    fn ne(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Comparison(Comparison::Ne(value));
+    }
+
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<types::TimeStamp>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = TimeStampEntry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl ElementFilter for TimeStampEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = TimeStampEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = TimeStampEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = TimeStampEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for TimeStampEntry {
+    fn not(&mut self) {
+        *self = TimeStampEntry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for TimeStampEntry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             TimeStampEntry::Value(value) => Filter::with_field(field, value.huus_into_bson()),
             TimeStampEntry::Comparison(comparison) => comparison.build_filter(field),
+            TimeStampEntry::Not(entry) => !entry.build_filter(field),
             TimeStampEntry::Element(element) => element.build_filter(field),
             TimeStampEntry::Empty => Filter::empty(),
         }
@@ -976,15 +1713,109 @@ impl Default for TimeStampEntry {
 pub enum I64Entry {
     Value(i64),
     Comparison(Comparison<i64>),
+    Modulo(Modulo),
+    Bitwise(Bitwise),
+    Not(Box<I64Entry>),
     Element(Element),
     Empty,
 }
 
+impl ComparisonFilter<i64> for I64Entry {
+    fn eq(&mut self, value: i64) {
+        *self = I64Entry::Comparison(Comparison::Eq(value));
+    }
+
+    fn gt(&mut self, value: i64) {
+        *self = I64Entry::Comparison(Comparison::Gt(value));
+    }
+
+    fn gte(&mut self, value: i64) {
+        *self = I64Entry::Comparison(Comparison::Gte(value));
+    }
+
+    fn r#in<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<i64>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = I64Entry::Comparison(Comparison::In(values));
+    }
+
+    fn lt(&mut self, value: i64) {
+        *self = I64Entry::Comparison(Comparison::Lt(value));
+    }
+
+    fn lte(&mut self, value: i64) {
+        *self = I64Entry::Comparison(Comparison::Lte(value));
+    }
+
+    fn ne(&mut self, value: i64) {
+        *self = I64Entry::Comparison(Comparison::Ne(value));
+    }
+
+    fn nin<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<i64>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        *self = I64Entry::Comparison(Comparison::Nin(values));
+    }
+}
+
+impl ElementFilter for I64Entry {
+    fn exists(&mut self, exists: bool) {
+        *self = I64Entry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = I64Entry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = I64Entry::Element(Element::NullOrMissing);
+    }
+}
+
+impl ModuloFilter for I64Entry {
+    fn r#mod(&mut self, divisor: i64, remainder: i64) {
+        *self = I64Entry::Modulo(Modulo::Mod(divisor, remainder));
+    }
+}
+
+impl BitwiseFilter for I64Entry {
+    fn bits_all_set(&mut self, mask: u64) {
+        *self = I64Entry::Bitwise(Bitwise::BitsAllSet(mask));
+    }
+
+    fn bits_any_set(&mut self, mask: u64) {
+        *self = I64Entry::Bitwise(Bitwise::BitsAnySet(mask));
+    }
+
+    fn bits_all_clear(&mut self, mask: u64) {
+        *self = I64Entry::Bitwise(Bitwise::BitsAllClear(mask));
+    }
+
+    fn bits_any_clear(&mut self, mask: u64) {
+        *self = I64Entry::Bitwise(Bitwise::BitsAnyClear(mask));
+    }
+}
+
+impl NegationFilter for I64Entry {
+    fn not(&mut self) {
+        *self = I64Entry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for I64Entry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             I64Entry::Value(value) => Filter::with_field(field, bson::Bson::I64(value)),
             I64Entry::Comparison(comparison) => comparison.build_filter(field),
+            I64Entry::Modulo(modulo) => modulo.build_filter(field),
+            I64Entry::Bitwise(bitwise) => bitwise.build_filter(field),
+            I64Entry::Not(entry) => !entry.build_filter(field),
             I64Entry::Element(element) => element.build_filter(field),
             I64Entry::Empty => Filter::empty(),
         }
@@ -1005,17 +1836,159 @@ impl std::convert::From<i64> for I64Entry {
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
+pub trait GeoFilter {
+    fn near(&mut self, point: types::GeoPoint, max_distance: Option<f64>);
+    fn within_polygon(&mut self, points: Vec<types::GeoPoint>);
+    fn intersects(&mut self, geometry: bson::Document);
+}
+
+#[derive(Clone, Debug)]
+pub enum GeoEntry {
+    Near(types::GeoPoint, Option<f64>),
+    WithinPolygon(Vec<types::GeoPoint>),
+    Intersects(bson::Document),
+}
+
+impl GeoEntry {
+    /// Matches points ordered by the distance from the given point, optionally limited by the
+    /// maximal distance in meters. Requires a `2dsphere` index on the field.
+    pub fn near(point: types::GeoPoint, max_distance: Option<f64>) -> Self {
+        GeoEntry::Near(point, max_distance)
+    }
+
+    /// Matches points inside the polygon. The ring is closed if the last point differs from the
+    /// first one.
+    pub fn within_polygon(points: Vec<types::GeoPoint>) -> Self {
+        GeoEntry::WithinPolygon(points)
+    }
+
+    /// Matches geometries intersecting with the given GeoJSON geometry.
+    pub fn intersects(geometry: bson::Document) -> Self {
+        GeoEntry::Intersects(geometry)
+    }
+}
+
+impl BuildInnerFilter for GeoEntry {
+    fn build_filter(self, field: String) -> Filter {
+        match self {
+            GeoEntry::Near(point, max_distance) => {
+                let mut near = doc! { "$geometry": point.into_doc() };
+                if let Some(max_distance) = max_distance {
+                    near.insert("$maxDistance", max_distance);
+                }
+                Filter::with_field(field, bson!({ "$near": near }))
+            }
+            GeoEntry::WithinPolygon(mut points) => {
+                if let (Some(first), Some(last)) = (points.first(), points.last()) {
+                    if first != last {
+                        points.push(*first);
+                    }
+                }
+                let ring: bson::Array = points
+                    .into_iter()
+                    .map(|point| bson!([point.longitude, point.latitude]))
+                    .collect();
+                let polygon = bson!({ "type": "Polygon", "coordinates": [ring] });
+                Filter::with_field(field, bson!({ "$geoWithin": { "$geometry": polygon } }))
+            }
+            GeoEntry::Intersects(geometry) => {
+                Filter::with_field(field, bson!({ "$geoIntersects": { "$geometry": geometry } }))
+            }
+        }
+    }
+}
+
+/// Filter on a geospatial point. It does not implement `NegationFilter` as the database rejects
+/// geospatial operators like `$near` inside `$not` and `$nor`.
+#[derive(Clone, Debug, Default)]
+pub enum GeoPointEntry {
+    Value(types::GeoPoint),
+    Geo(GeoEntry),
+    Element(Element),
+    #[default]
+    Empty,
+}
+
+impl GeoFilter for GeoPointEntry {
+    fn near(&mut self, point: types::GeoPoint, max_distance: Option<f64>) {
+        *self = GeoPointEntry::Geo(GeoEntry::near(point, max_distance));
+    }
+
+    fn within_polygon(&mut self, points: Vec<types::GeoPoint>) {
+        *self = GeoPointEntry::Geo(GeoEntry::within_polygon(points));
+    }
+
+    fn intersects(&mut self, geometry: bson::Document) {
+        *self = GeoPointEntry::Geo(GeoEntry::intersects(geometry));
+    }
+}
+
+impl ElementFilter for GeoPointEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = GeoPointEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = GeoPointEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = GeoPointEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl BuildInnerFilter for GeoPointEntry {
+    fn build_filter(self, field: String) -> Filter {
+        match self {
+            GeoPointEntry::Value(value) => Filter::with_field(field, value.huus_into_bson()),
+            GeoPointEntry::Geo(geo) => geo.build_filter(field),
+            GeoPointEntry::Element(element) => element.build_filter(field),
+            GeoPointEntry::Empty => Filter::empty(),
+        }
+    }
+}
+
+impl std::convert::From<types::GeoPoint> for GeoPointEntry {
+    fn from(value: types::GeoPoint) -> GeoPointEntry {
+        GeoPointEntry::Value(value)
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
 #[derive(Clone, Debug)]
 pub enum BsonEntry {
     Value(bson::Document),
+    Not(Box<BsonEntry>),
     Element(Element),
     Empty,
 }
 
+impl ElementFilter for BsonEntry {
+    fn exists(&mut self, exists: bool) {
+        *self = BsonEntry::Element(Element::Exists(exists));
+    }
+
+    fn with_type(&mut self, bson_type: types::Type) {
+        *self = BsonEntry::Element(Element::Type(bson_type));
+    }
+
+    fn is_null_or_missing(&mut self) {
+        *self = BsonEntry::Element(Element::NullOrMissing);
+    }
+}
+
+impl NegationFilter for BsonEntry {
+    fn not(&mut self) {
+        *self = BsonEntry::Not(Box::new(std::mem::take(self)));
+    }
+}
+
 impl BuildInnerFilter for BsonEntry {
     fn build_filter(self, field: String) -> Filter {
         match self {
             BsonEntry::Value(value) => Filter::with_field(field, bson::Bson::Document(value)),
+            BsonEntry::Not(entry) => !entry.build_filter(field),
             BsonEntry::Element(element) => element.build_filter(field),
             BsonEntry::Empty => Filter::empty(),
         }
@@ -1034,9 +2007,83 @@ impl std::convert::From<bson::Document> for BsonEntry {
     }
 }
 
+fn are_operators(conditions: &bson::Document) -> bool {
+    !conditions.is_empty() && conditions.keys().all(|key| key.starts_with('$'))
+}
+
 // -------------------------------------------------------------------------------------------------
 
+/// Builds a `$text` filter searching the text index of the collection.
+#[derive(Clone, Debug)]
+pub struct TextSearch {
+    pub(crate) search: String,
+    pub(crate) language: Option<String>,
+    pub(crate) case_sensitive: Option<bool>,
+    pub(crate) diacritic_sensitive: Option<bool>,
+}
+
+impl TextSearch {
+    pub fn new(search: String) -> Self {
+        Self { search, language: None, case_sensitive: None, diacritic_sensitive: None }
+    }
+
+    /// Sets the language determining the stop words, the stemmer and the tokenizer.
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = Some(case_sensitive);
+        self
+    }
+
+    pub fn with_diacritic_sensitive(mut self, diacritic_sensitive: bool) -> Self {
+        self.diacritic_sensitive = Some(diacritic_sensitive);
+        self
+    }
+
+    pub fn get_search(&self) -> &str {
+        &self.search
+    }
+
+    pub fn get_language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn get_case_sensitive(&self) -> Option<bool> {
+        self.case_sensitive
+    }
+
+    pub fn get_diacritic_sensitive(&self) -> Option<bool> {
+        self.diacritic_sensitive
+    }
+}
+
+impl BuildFilter for TextSearch {
+    fn build_filter(self) -> Filter {
+        let mut text = doc! { "$search": self.search };
+        if let Some(language) = self.language {
+            text.insert("$language", language);
+        }
+        if let Some(case_sensitive) = self.case_sensitive {
+            text.insert("$caseSensitive", case_sensitive);
+        }
+        if let Some(diacritic_sensitive) = self.diacritic_sensitive {
+            text.insert("$diacriticSensitive", diacritic_sensitive);
+        }
+        Filter::with_field("$text".to_string(), bson::Bson::Document(text))
+    }
+}
+
+impl IntoDoc for TextSearch {
+    fn into_doc(self) -> bson::Document {
+        self.build_filter().into_doc()
+    }
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Filter {
     doc: bson::Document,
 }
@@ -1060,9 +2107,113 @@ impl Filter {
         bson::Bson::Document(self.doc)
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.doc.is_empty()
+    }
+
+    /// Merges conditions of the other filter into this one. Conditions on a field already present
+    /// in this filter are merged with the existing operators if they do not overlap and moved to
+    /// `$and` otherwise, so that no condition is silently overwritten.
     pub fn incorporate(&mut self, filter: Filter) {
         for (key, value) in filter.doc {
-            self.doc.insert_bson(key, value);
+            match (self.doc.get_mut(&key), value) {
+                (None, value) => {
+                    self.doc.insert_bson(key, value);
+                }
+                (Some(existing), value) if *existing == value => {}
+                (Some(bson::Bson::Array(conditions)), bson::Bson::Array(mut other))
+                    if key == "$and" =>
+                {
+                    conditions.append(&mut other);
+                }
+                (Some(bson::Bson::Document(existing)), bson::Bson::Document(other))
+                    if are_operators(existing)
+                        && are_operators(&other)
+                        && other.keys().all(|operator| !existing.contains_key(operator)) =>
+                {
+                    for (operator, operand) in other {
+                        existing.insert_bson(operator, operand);
+                    }
+                }
+                (Some(_), value) => {
+                    let mut condition = bson::Document::new();
+                    condition.insert_bson(key, value);
+                    self.push_and(bson::Bson::Document(condition));
+                }
+            }
+        }
+    }
+
+    /// Returns a filter matching documents matched by both filters.
+    pub fn and(self, other: Filter) -> Filter {
+        self.combine("$and", other)
+    }
+
+    /// Returns a filter matching documents matched by any of the filters.
+    pub fn or(self, other: Filter) -> Filter {
+        self.combine("$or", other)
+    }
+
+    /// Returns a filter matching documents matched by none of the filters.
+    pub fn nor(self, other: Filter) -> Filter {
+        self.combine("$nor", other)
+    }
+
+    fn push_and(&mut self, condition: bson::Bson) {
+        match self.doc.get_mut("$and") {
+            Some(bson::Bson::Array(conditions)) => conditions.push(condition),
+            _ => {
+                self.doc.insert_bson("$and".to_string(), bson::Bson::Array(vec![condition]));
+            }
         }
     }
+
+    fn combine(self, operator: &str, other: Filter) -> Filter {
+        let mut conditions = self.into_operands(operator);
+        conditions.append(&mut other.into_operands(operator));
+        Filter::with_field(operator.to_string(), bson::Bson::Array(conditions))
+    }
+
+    // Operands of `$and` and `$or` are flattened so that chained combinations produce a single array.
+    // Operands of `$nor` can not be flattened without changing the meaning of the filter.
+    fn into_operands(self, operator: &str) -> bson::Array {
+        if operator != "$nor" && self.doc.len() == 1 {
+            if let Some(bson::Bson::Array(conditions)) = self.doc.get(operator) {
+                return conditions.clone();
+            }
+        }
+        vec![self.into_bson()]
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    /// Returns a filter matching documents not matched by this filter. Conditions on a single field
+    /// are negated with the field-scoped `$not` (or `$ne` for equality), other filters with `$nor`.
+    /// An empty filter stands for no condition and is returned unchanged.
+    fn not(mut self) -> Filter {
+        if self.is_empty() {
+            return self;
+        }
+        let field = match self.doc.keys().next() {
+            Some(key) if self.doc.len() == 1 && !key.starts_with('$') => key.clone(),
+            _ => {
+                let filters = bson::Bson::Array(vec![self.into_bson()]);
+                return Filter::with_field("$nor".to_string(), filters);
+            }
+        };
+        match self.doc.remove(&field).expect("Filter contains the field") {
+            bson::Bson::Document(conditions) if are_operators(&conditions) => {
+                Filter::with_field(field, bson!({ "$not": conditions }))
+            }
+            value => Filter::with_field(field, bson!({ "$ne": value })),
+        }
+    }
+}
+
+impl std::convert::From<bson::Document> for Filter {
+    fn from(doc: bson::Document) -> Filter {
+        Filter { doc }
+    }
 }
@@ -0,0 +1,169 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Serialization of documents to MongoDB Extended JSON.
+
+use std::fmt::Write;
+
+/// The last millisecond of year 9999. Later dates cannot be written as ISO-8601 strings.
+const MAX_RELAXED_DATE: i64 = 253_402_300_799_999;
+
+/// Format of the Extended JSON.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsonMode {
+    /// Preserves the type of every value, e.g. `{"$numberInt": "1"}`.
+    Canonical,
+
+    /// Writes numbers and dates in their natural JSON representation where it does not lose
+    /// information, e.g. `1` or `{"$date": "2020-01-01T00:00:00.000Z"}`.
+    Relaxed,
+}
+
+/// Serializes the document to a single line of Extended JSON.
+pub fn to_extended_json(document: &bson::Document, mode: JsonMode) -> String {
+    let mut output = String::new();
+    write_document(&mut output, document, mode);
+    output
+}
+
+/// Serializes the value to a single line of Extended JSON.
+pub fn value_to_extended_json(value: &bson::Bson, mode: JsonMode) -> String {
+    let mut output = String::new();
+    write_value(&mut output, value, mode);
+    output
+}
+
+fn write_document(output: &mut String, document: &bson::Document, mode: JsonMode) {
+    output.push('{');
+    for (index, (key, value)) in document.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write_string(output, key);
+        output.push(':');
+        write_value(output, value, mode);
+    }
+    output.push('}');
+}
+
+fn write_value(output: &mut String, value: &bson::Bson, mode: JsonMode) {
+    use bson::Bson;
+
+    match value {
+        Bson::FloatingPoint(number) => write_double(output, *number, mode),
+        Bson::String(string) => write_string(output, string),
+        Bson::Array(array) => {
+            output.push('[');
+            for (index, element) in array.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_value(output, element, mode);
+            }
+            output.push(']');
+        }
+        Bson::Document(document) => write_document(output, document, mode),
+        Bson::Boolean(boolean) => output.push_str(if *boolean { "true" } else { "false" }),
+        Bson::Null => output.push_str("null"),
+        Bson::RegExp(pattern, options) => {
+            output.push_str("{\"$regularExpression\":{\"pattern\":");
+            write_string(output, pattern);
+            output.push_str(",\"options\":");
+            write_string(output, options);
+            output.push_str("}}");
+        }
+        Bson::JavaScriptCode(code) => {
+            output.push_str("{\"$code\":");
+            write_string(output, code);
+            output.push('}');
+        }
+        Bson::JavaScriptCodeWithScope(code, scope) => {
+            output.push_str("{\"$code\":");
+            write_string(output, code);
+            output.push_str(",\"$scope\":");
+            write_document(output, scope, mode);
+            output.push('}');
+        }
+        Bson::I32(number) => match mode {
+            JsonMode::Canonical => write!(output, "{{\"$numberInt\":\"{}\"}}", number).unwrap(),
+            JsonMode::Relaxed => write!(output, "{}", number).unwrap(),
+        },
+        Bson::I64(number) => match mode {
+            JsonMode::Canonical => write!(output, "{{\"$numberLong\":\"{}\"}}", number).unwrap(),
+            JsonMode::Relaxed => write!(output, "{}", number).unwrap(),
+        },
+        Bson::TimeStamp(timestamp) => {
+            let time = (*timestamp as u64) >> 32;
+            let increment = (*timestamp as u64) & 0xffff_ffff;
+            write!(output, "{{\"$timestamp\":{{\"t\":{},\"i\":{}}}}}", time, increment).unwrap();
+        }
+        Bson::Binary(subtype, bytes) => {
+            let subtype: u8 = (*subtype).into();
+            output.push_str("{\"$binary\":{\"base64\":\"");
+            write_base64(output, bytes);
+            write!(output, "\",\"subType\":\"{:02x}\"}}}}", subtype).unwrap();
+        }
+        Bson::ObjectId(id) => write!(output, "{{\"$oid\":\"{}\"}}", id.to_hex()).unwrap(),
+        Bson::UtcDatetime(date) => {
+            let millis = date.timestamp_millis();
+            if mode == JsonMode::Relaxed && (0..=MAX_RELAXED_DATE).contains(&millis) {
+                let date = date.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+                write!(output, "{{\"$date\":\"{}\"}}", date).unwrap();
+            } else {
+                write!(output, "{{\"$date\":{{\"$numberLong\":\"{}\"}}}}", millis).unwrap();
+            }
+        }
+        Bson::Symbol(symbol) => {
+            output.push_str("{\"$symbol\":");
+            write_string(output, symbol);
+            output.push('}');
+        }
+    }
+}
+
+fn write_double(output: &mut String, number: f64, mode: JsonMode) {
+    let string = if number.is_nan() {
+        "NaN".to_string()
+    } else if number.is_infinite() {
+        if number > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        format!("{:?}", number)
+    };
+    if mode == JsonMode::Relaxed && number.is_finite() {
+        output.push_str(&string);
+    } else {
+        write!(output, "{{\"$numberDouble\":\"{}\"}}", string).unwrap();
+    }
+}
+
+pub(crate) fn write_string(output: &mut String, string: &str) {
+    output.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+pub(crate) fn write_base64(output: &mut String, bytes: &[u8]) {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for chunk in bytes.chunks(3) {
+        let byte = |index: usize| u32::from(*chunk.get(index).unwrap_or(&0));
+        let triple = byte(0) << 16 | byte(1) << 8 | byte(2);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (triple >> (18 - 6 * index)) & 0x3f;
+                output.push(ALPHABET[sextet as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+}
@@ -4,15 +4,30 @@
 //! This crate provides a way to define `mongo` database structure as `rust` data structures. This
 //! approach helps to find problems with database queries (like wrong field names or wrong query
 //! structure) in compilation-time.
+//!
+//! Commands are executed with the official `mongodb` driver: synchronously when the `sync-driver`
+//! feature (enabled by default) is on, and asynchronously when the `async-driver` feature is on
+//! (see the `exec` module). Without them the commands only describe the database operations.
 
 // TODO: Provide documentation of whole crate.
 // #![warn(missing_docs)]
 
+pub mod changes;
 pub mod commands;
 pub mod conversions;
+pub mod cursor;
+#[cfg(any(feature = "sync-driver", feature = "async-driver"))]
+pub mod driver;
 pub mod errors;
+#[cfg(feature = "async-driver")]
+pub mod exec;
+pub mod execution;
 pub mod filters;
+pub mod json;
+pub mod pagination;
 pub mod query;
+pub mod shell;
+pub mod testing;
 pub mod types;
 pub mod updates;
 pub mod values;
@@ -27,12 +42,16 @@ pub mod models {
 }
 
 pub mod prelude {
+    pub use crate::commands::Command;
     pub use crate::conversions::{FromDoc, HuusKey, IntoDoc};
-    pub use crate::filters::{ArrayFilter, ComparisonFilter, ElementFilter, ObjectFilter};
-    pub use crate::query::Query;
+    pub use crate::filters::{
+        ArrayFilter, BitwiseFilter, ComparisonFilter, ElementFilter, GeoFilter, ModuloFilter,
+        NegationFilter, ObjectFilter, RegexFilter,
+    };
+    pub use crate::query::{Query, ReadQuery, Schema, UpdateQuery, WriteQuery};
     pub use crate::updates::{
-        ArrayUpdate, DateUpdate, ElementUpdate, FieldUpdate, NumericalUpdate, ObjectUpdate,
-        Operator,
+        AddToSetUpdate, BitwiseUpdate, DateUpdate, ElementUpdate, FieldUpdate, NumericalUpdate,
+        ObjectUpdate, PopUpdate, PullUpdate, PushUpdate,
     };
     pub use crate::values::{PullValue, PushValue};
 }
@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides paginated fetching of documents. Pages are always ordered by the document ID so the
+//! order stays stable between requests.
+
+use std::marker::PhantomData;
+
+use bson::{bson, doc};
+
+use crate::commands::{impl_display, Command, FindCommand, FindOptions};
+use crate::conversions::FromDoc;
+use crate::errors::{ConversionError, HuusError, PaginationError};
+
+/// Describes which page of the results should be fetched.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pagination {
+    /// Fetches documents following the document with the given ID (or from the beginning if no ID
+    /// is given). Stays correct when documents are inserted or removed between requests.
+    Cursor { after: Option<bson::oid::ObjectId>, page_size: u32 },
+
+    /// Fetches the page with the given number (counted from zero).
+    Offset { page: u32, page_size: u32 },
+}
+
+impl Pagination {
+    /// Constructs a cursor-based pagination starting from the first document.
+    pub fn first(page_size: u32) -> Result<Self, PaginationError> {
+        Pagination::Cursor { after: None, page_size }.validated()
+    }
+
+    /// Constructs a cursor-based pagination starting after the document with the given ID.
+    pub fn after(id: bson::oid::ObjectId, page_size: u32) -> Result<Self, PaginationError> {
+        Pagination::Cursor { after: Some(id), page_size }.validated()
+    }
+
+    /// Constructs an offset-based pagination. Fails if the number of the documents preceding the
+    /// page does not fit into `u32`.
+    pub fn offset(page: u32, page_size: u32) -> Result<Self, PaginationError> {
+        Pagination::Offset { page, page_size }.validated()
+    }
+
+    pub fn get_page_size(&self) -> u32 {
+        match self {
+            Pagination::Cursor { page_size, .. } => *page_size,
+            Pagination::Offset { page_size, .. } => *page_size,
+        }
+    }
+
+    /// Returns the number of documents preceding the page. Always zero for cursor-based
+    /// pagination.
+    pub fn get_skip(&self) -> Result<u32, PaginationError> {
+        match self {
+            Pagination::Cursor { .. } => Ok(0),
+            Pagination::Offset { page, page_size } => page
+                .checked_mul(*page_size)
+                .ok_or(PaginationError::Overflow { page: *page, page_size: *page_size }),
+        }
+    }
+
+    /// Checks that the page size is not zero and the page is within range.
+    fn validated(self) -> Result<Self, PaginationError> {
+        if self.get_page_size() == 0 {
+            return Err(PaginationError::ZeroPageSize);
+        }
+        self.get_skip()?;
+        Ok(self)
+    }
+}
+
+/// Single page of documents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<Data> {
+    /// Documents on the page.
+    pub items: Vec<Data>,
+
+    /// Pagination fetching the next page. `None` if this is the last page.
+    pub next: Option<Pagination>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PageCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub(crate) collection_name: String,
+    pub(crate) filter: bson::Document,
+    pub(crate) pagination: Pagination,
+    pub(crate) phantom: PhantomData<Data>,
+}
+
+impl<Data> PageCommand<Data>
+where
+    Data: FromDoc,
+{
+    pub fn new(collection_name: String, filter: bson::Document, pagination: Pagination) -> Self {
+        Self { collection_name, filter, pagination, phantom: PhantomData }
+    }
+
+    pub fn get_pagination(&self) -> &Pagination {
+        &self.pagination
+    }
+
+    /// Returns the command fetching raw documents of the page. One document more than the page
+    /// size is requested to find out if there is a next page. Offsets out of range, which are
+    /// rejected by the constructors of `Pagination`, skip all the documents.
+    pub fn get_find_command(&self) -> FindCommand<bson::Document> {
+        let limit = self.pagination.get_page_size().saturating_add(1);
+        let options = FindOptions::new().with_sort(doc! { "_id": 1 }).with_limit(limit);
+        match &self.pagination {
+            Pagination::Cursor { after: None, .. } => FindCommand::new_with_options(
+                self.collection_name.clone(),
+                self.filter.clone(),
+                options,
+            ),
+            Pagination::Cursor { after: Some(after), .. } => {
+                let after = doc! { "_id": { "$gt": after.clone() } };
+                let filter = if self.filter.is_empty() {
+                    after
+                } else {
+                    doc! { "$and": [self.filter.clone(), after] }
+                };
+                FindCommand::new_with_options(self.collection_name.clone(), filter, options)
+            }
+            Pagination::Offset { page, page_size } => FindCommand::new_with_options(
+                self.collection_name.clone(),
+                self.filter.clone(),
+                options.with_skip(page.saturating_mul(*page_size)),
+            ),
+        }
+    }
+
+    /// Converts the documents fetched by the command returned from `get_find_command` to a page.
+    /// Fails if the page size is zero or the next page would be out of range.
+    pub fn build_page(&self, mut documents: Vec<bson::Document>) -> Result<Page<Data>, HuusError> {
+        let page_size = self.pagination.get_page_size();
+        if page_size == 0 {
+            return Err(PaginationError::ZeroPageSize.into());
+        }
+        let has_next = documents.len() > page_size as usize;
+        documents.truncate(page_size as usize);
+
+        let next = match (has_next, &self.pagination) {
+            (false, _) => None,
+            (true, Pagination::Cursor { .. }) => {
+                let last = documents.last().map(get_id).transpose()?;
+                Some(Pagination::Cursor { after: last, page_size })
+            }
+            (true, Pagination::Offset { page, .. }) => {
+                let overflow = PaginationError::Overflow { page: *page, page_size };
+                Some(Pagination::offset(page.checked_add(1).ok_or(overflow)?, page_size)?)
+            }
+        };
+
+        let mut items = Vec::with_capacity(documents.len());
+        for document in documents {
+            items.push(Data::from_doc(document)?);
+        }
+        Ok(Page { items, next })
+    }
+
+    #[cfg(feature = "sync-driver")]
+    pub fn execute(&self, db: &mongodb::sync::Database) -> Result<Page<Data>, HuusError> {
+        self.build_page(self.get_find_command().execute(db)?)
+    }
+}
+
+impl<Data> Command for PageCommand<Data>
+where
+    Data: FromDoc,
+{
+    /// Returns the `find` database command fetching the page.
+    fn get_command(&self) -> bson::Document {
+        self.get_find_command().get_command()
+    }
+
+    fn to_shell(&self) -> String {
+        self.get_find_command().to_shell()
+    }
+}
+
+impl_display!(PageCommand<Data: FromDoc>);
+
+/// Reads the ID of the document.
+fn get_id(document: &bson::Document) -> Result<bson::oid::ObjectId, ConversionError> {
+    match document.get("_id") {
+        Some(bson::Bson::ObjectId(id)) => Ok(id.clone()),
+        Some(_) => Err(ConversionError::wrong_type("_id".to_string())),
+        None => Err(ConversionError::missing_key("_id".to_string())),
+    }
+}
@@ -1,10 +1,12 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
 // the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
 
-//! Contains a trait representing all possible operations that may be performed on database.
+//! Contains traits representing all possible operations that may be performed on database.
 
 use crate::conversions::IntoDoc;
-use crate::{commands, conversions, filters};
+use crate::filters::BuildFilter;
+use crate::updates::BuildArrayFilters;
+use crate::{commands, conversions, filters, pagination, updates};
 
 pub mod filter {
     use bson::{bson, doc};
@@ -18,30 +20,97 @@ pub mod filter {
     }
 }
 
-pub trait Query: Sized {
+/// Describes the collection: its name, the type of its documents and the declared indexes. Provides
+/// the commands managing the collection itself.
+pub trait Schema: Sized {
     type Data: conversions::FromDoc + conversions::IntoDoc;
-    type Insert: conversions::IntoDoc;
-    type Filter: conversions::IntoDoc;
-    type Update: conversions::IntoDoc;
 
     fn get_collection_name() -> &'static str;
     fn get_indexed_fields() -> Vec<&'static str>;
 
+    /// Returns the paths of all the fields of the documents, including the ones nested in
+    /// structures (e.g. `data.int`), with their BSON types.
+    fn get_field_types() -> Vec<(&'static str, &'static str)>;
+
+    /// Returns the validator enforcing the schema on the documents in the collection.
+    fn get_validator() -> Option<bson::Document> {
+        None
+    }
+
+    /// Creates the collection without validating the documents.
     fn create_collection() -> commands::CreateCollectionCommand {
         commands::CreateCollectionCommand::new(Self::get_collection_name().to_string())
     }
 
+    /// Creates the collection validating the documents against the schema.
+    fn create_collection_validated() -> commands::CreateCollectionCommand {
+        let command = Self::create_collection();
+        match Self::get_validator() {
+            Some(validator) => command.with_validator(validator),
+            None => command,
+        }
+    }
+
     fn drop_collection() -> commands::DropCollectionCommand {
         commands::DropCollectionCommand::new(Self::get_collection_name().to_string())
     }
 
+    /// Returns the indexes declared in the schema: a text index over the indexed fields named
+    /// after the collection.
+    fn get_indexes() -> Vec<commands::IndexModel> {
+        let fields = Self::get_indexed_fields();
+        if fields.is_empty() {
+            return Vec::new();
+        }
+        let name = Self::get_collection_name().to_string();
+        let fields = fields.iter().map(|f| f.to_string()).collect();
+        let options = commands::IndexOptions::new().with_name(name);
+        vec![commands::IndexModel::text(fields).with_options(options)]
+    }
+
     fn create_indexes() -> commands::CreateIndexesCommand {
-        commands::CreateIndexesCommand::new(
+        commands::CreateIndexesCommand::new_models(
             Self::get_collection_name().to_string(),
-            Self::get_indexed_fields().iter().map(|f| f.to_string()).collect(),
+            Self::get_indexes(),
         )
     }
 
+    fn list_indexes() -> commands::ListIndexesCommand {
+        commands::ListIndexesCommand::new(Self::get_collection_name().to_string())
+    }
+
+    /// Compares the indexes returned by `list_indexes` with the ones declared in the schema and
+    /// returns the operations needed to converge. Indexes not declared in the schema are kept unless
+    /// `IndexSync::with_drop_undeclared` is used.
+    fn sync_indexes(existing: &[commands::IndexInfo]) -> commands::IndexSync {
+        commands::IndexSync::new(
+            Self::get_collection_name().to_string(),
+            Self::get_indexes(),
+            existing,
+        )
+    }
+
+    /// Checks if the hint refers to an index declared in the schema or the default index on
+    /// `_id`.
+    fn is_index_declared(hint: &commands::Hint) -> bool {
+        let indexes = Self::get_indexes();
+        match hint {
+            commands::Hint::Name(hint) => {
+                hint == "_id_" || indexes.iter().any(|index| index.get_name() == *hint)
+            }
+            commands::Hint::Keys(keys) => {
+                let is_id = keys.len() == 1 && keys.contains_key("_id");
+                is_id || indexes.iter().any(|index| index.get_keys() == keys)
+            }
+        }
+    }
+}
+
+/// Commands reading the documents selected by the filter type of the collection.
+pub trait ReadQuery: Schema {
+    type Filter: conversions::IntoDoc;
+    type Projection: conversions::IntoDoc;
+
     fn fetch_all() -> commands::FindCommand<Self::Data> {
         commands::FindCommand::new(Self::get_collection_name().to_string(), filter::all(), None)
     }
@@ -54,6 +123,41 @@ pub trait Query: Sized {
         commands::FindCommand::new(Self::get_collection_name().to_string(), filter.into_doc(), None)
     }
 
+    /// Finds the documents matching the filter applying the given sorting, pagination and other
+    /// options. A projection passed in the options has to keep the fields required by the data.
+    fn find_with(
+        filter: Self::Filter,
+        options: commands::FindOptions,
+    ) -> commands::FindCommand<Self::Data> {
+        commands::FindCommand::new_with_options(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            options,
+        )
+    }
+
+    /// Fetches a single page of the documents matching the filter.
+    fn paginate(
+        filter: Self::Filter,
+        pagination: pagination::Pagination,
+    ) -> pagination::PageCommand<Self::Data> {
+        pagination::PageCommand::new(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            pagination,
+        )
+    }
+
+    /// Returns only the projected fields. As the results may not contain all the required fields
+    /// they are returned as raw documents.
+    fn find_projected(
+        filter: Self::Filter,
+        projection: Self::Projection,
+    ) -> commands::FindCommand<bson::Document> {
+        commands::FindCommand::new(Self::get_collection_name().to_string(), filter.into_doc(), None)
+            .with_projection(projection.into_doc())
+    }
+
     // TODO: Provide a better way for defining logical oprations
     fn find_logical(filters: filters::Filters<Self::Filter>) -> commands::FindCommand<Self::Data> {
         commands::FindCommand::new(
@@ -71,6 +175,38 @@ pub trait Query: Sized {
         )
     }
 
+    /// Finds the documents matching both the text search and the filter.
+    fn text_search_with(
+        search: filters::TextSearch,
+        filter: Self::Filter,
+    ) -> commands::FindCommand<Self::Data> {
+        let filter = search.build_filter().and(filters::Filter::from(filter.into_doc()));
+        commands::FindCommand::new(Self::get_collection_name().to_string(), filter.into_doc(), None)
+    }
+
+    fn count(filter: Self::Filter) -> commands::CountDocumentsCommand {
+        commands::CountDocumentsCommand::new(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+        )
+    }
+
+    /// Returns the number of documents in the collection basing on the collection metadata. The
+    /// result may be inaccurate but does not require scanning the collection.
+    fn estimated_count() -> commands::EstimatedCountCommand {
+        commands::EstimatedCountCommand::new(Self::get_collection_name().to_string())
+    }
+
+    /// Observes changes of the documents in the collection.
+    fn watch() -> commands::WatchCommand<Self::Data> {
+        commands::WatchCommand::new(Self::get_collection_name().to_string())
+    }
+}
+
+/// Commands inserting new documents into the collection.
+pub trait WriteQuery: Schema {
+    type Insert: conversions::IntoDoc;
+
     fn insert(data: Self::Insert) -> commands::InsertCommand {
         commands::InsertCommand::new(Self::get_collection_name().to_string(), data.into_doc())
     }
@@ -79,37 +215,209 @@ pub trait Query: Sized {
         commands::InsertCommand::new(Self::get_collection_name().to_string(), data.into_doc())
     }
 
+    fn insert_many(data: Vec<Self::Data>) -> commands::InsertManyCommand {
+        commands::InsertManyCommand::new(
+            Self::get_collection_name().to_string(),
+            data.into_iter().map(|data| data.into_doc()).collect(),
+        )
+    }
+}
+
+/// Commands modifying or removing the documents selected by the filter type of the collection.
+pub trait UpdateQuery: ReadQuery + WriteQuery {
+    type Update: conversions::IntoDoc + updates::BuildArrayFilters;
+
     fn update(filter: Self::Filter, update: Self::Update) -> commands::UpdateCommand {
+        let array_filters = update.build_array_filters();
         commands::UpdateCommand::new(
             Self::get_collection_name().to_string(),
             filter.into_doc(),
             update.into_doc(),
             commands::UpdateOptions::UpdateOne,
         )
+        .with_array_filters(array_filters)
     }
 
     fn update_many(filter: Self::Filter, update: Self::Update) -> commands::UpdateCommand {
+        let array_filters = update.build_array_filters();
         commands::UpdateCommand::new(
             Self::get_collection_name().to_string(),
             filter.into_doc(),
             update.into_doc(),
             commands::UpdateOptions::UpdateMany,
         )
+        .with_array_filters(array_filters)
+    }
+
+    /// Replaces the first document matching the filter with the given data.
+    fn replace(filter: Self::Filter, data: Self::Data) -> commands::ReplaceOneCommand {
+        commands::ReplaceOneCommand::new(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            data.into_doc(),
+            false,
+        )
+    }
+
+    /// Atomically updates the first document matching the filter and returns it as it was before
+    /// the update. Use `with_return_document` to get the updated version instead.
+    fn find_one_and_update(
+        filter: Self::Filter,
+        update: Self::Update,
+    ) -> commands::FindAndModifyCommand<Self::Data> {
+        let array_filters = update.build_array_filters();
+        commands::FindAndModifyCommand::new(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            commands::FindAndModifyAction::Update(update.into_doc()),
+        )
+        .with_array_filters(array_filters)
+    }
+
+    /// Atomically replaces the first document matching the filter with the given data and returns
+    /// the document as it was before the replacement.
+    fn find_one_and_replace(
+        filter: Self::Filter,
+        data: Self::Data,
+    ) -> commands::FindAndModifyCommand<Self::Data> {
+        commands::FindAndModifyCommand::new(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            commands::FindAndModifyAction::Replace(data.into_doc()),
+        )
+    }
+
+    /// Atomically deletes the first document matching the filter and returns it.
+    fn find_one_and_delete(filter: Self::Filter) -> commands::FindAndModifyCommand<Self::Data> {
+        commands::FindAndModifyCommand::new(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            commands::FindAndModifyAction::Delete,
+        )
+    }
+
+    /// Updates the first document matching the filter or inserts a new one built from the filter
+    /// and the update if none matches.
+    fn upsert(filter: Self::Filter, update: Self::Update) -> commands::UpdateCommand {
+        Self::update(filter, update).with_upsert(true)
+    }
+
+    fn update_pipeline(
+        filter: Self::Filter,
+        pipeline: updates::PipelineUpdate,
+    ) -> commands::UpdateCommand {
+        commands::UpdateCommand::new_pipeline(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            pipeline,
+            commands::UpdateOptions::UpdateOne,
+        )
+    }
+
+    fn update_many_pipeline(
+        filter: Self::Filter,
+        pipeline: updates::PipelineUpdate,
+    ) -> commands::UpdateCommand {
+        commands::UpdateCommand::new_pipeline(
+            Self::get_collection_name().to_string(),
+            filter.into_doc(),
+            pipeline,
+            commands::UpdateOptions::UpdateMany,
+        )
     }
 
-    fn remove_one(filter: Self::Filter) -> commands::RemoveCommand {
-        commands::RemoveCommand::new(
+    fn delete_one(filter: Self::Filter) -> commands::DeleteCommand {
+        commands::DeleteCommand::new(
             Self::get_collection_name().to_string(),
             filter.into_doc(),
-            commands::RemoveOptions::RemoveOne,
+            commands::DeleteOptions::DeleteOne,
         )
     }
 
-    fn remove(filter: Self::Filter) -> commands::RemoveCommand {
-        commands::RemoveCommand::new(
+    fn delete_many(filter: Self::Filter) -> commands::DeleteCommand {
+        commands::DeleteCommand::new(
             Self::get_collection_name().to_string(),
             filter.into_doc(),
-            commands::RemoveOptions::RemoveMany,
+            commands::DeleteOptions::DeleteMany,
+        )
+    }
+
+    #[deprecated(note = "use `delete_one` instead")]
+    fn remove_one(filter: Self::Filter) -> commands::DeleteCommand {
+        Self::delete_one(filter)
+    }
+
+    #[deprecated(note = "use `delete_many` instead")]
+    fn remove(filter: Self::Filter) -> commands::DeleteCommand {
+        Self::delete_many(filter)
+    }
+
+    /// Executes many operations in a single round trip.
+    fn bulk_write(operations: Vec<BulkOperation<Self>>) -> commands::BulkWriteCommand {
+        commands::BulkWriteCommand::new(
+            Self::get_collection_name().to_string(),
+            operations.into_iter().map(BulkOperation::into_write_operation).collect(),
         )
     }
 }
+
+/// All the operations that may be performed on the collection. Implemented for every type
+/// implementing the read, write and update capabilities. Prefer the narrower traits as bounds
+/// where only some of the capabilities are needed.
+///
+/// It cannot be implemented directly. Types implementing it by hand have to implement `Schema`,
+/// `ReadQuery`, `WriteQuery` and `UpdateQuery` instead (see `CHANGELOG.md`).
+pub trait Query: UpdateQuery {}
+
+impl<T: UpdateQuery> Query for T {}
+
+/// Single operation of a bulk write on the collection described by `Q`.
+pub enum BulkOperation<Q: UpdateQuery> {
+    Insert(Q::Insert),
+    Update(Q::Filter, Q::Update),
+    UpdateMany(Q::Filter, Q::Update),
+    Upsert(Q::Filter, Q::Update),
+    Replace(Q::Filter, Q::Data),
+    Delete(Q::Filter),
+    DeleteMany(Q::Filter),
+}
+
+impl<Q: UpdateQuery> BulkOperation<Q> {
+    fn into_write_operation(self) -> commands::WriteOperation {
+        use commands::WriteOperation;
+        match self {
+            BulkOperation::Insert(data) => WriteOperation::Insert {
+                document: commands::InsertCommand::new(String::new(), data.into_doc()).document,
+            },
+            BulkOperation::Update(filter, update) => Self::update(filter, update, false, false),
+            BulkOperation::UpdateMany(filter, update) => Self::update(filter, update, true, false),
+            BulkOperation::Upsert(filter, update) => Self::update(filter, update, false, true),
+            BulkOperation::Replace(filter, data) => WriteOperation::Replace {
+                filter: filter.into_doc(),
+                replacement: data.into_doc(),
+                upsert: false,
+            },
+            BulkOperation::Delete(filter) => {
+                WriteOperation::Delete { filter: filter.into_doc(), multi: false }
+            }
+            BulkOperation::DeleteMany(filter) => {
+                WriteOperation::Delete { filter: filter.into_doc(), multi: true }
+            }
+        }
+    }
+
+    fn update(
+        filter: Q::Filter,
+        update: Q::Update,
+        multi: bool,
+        upsert: bool,
+    ) -> commands::WriteOperation {
+        commands::WriteOperation::Update {
+            filter: filter.into_doc(),
+            array_filters: update.build_array_filters(),
+            update: update.into_doc(),
+            multi,
+            upsert,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Rendering of commands as `mongosh` snippets which can be pasted to the shell.
+
+use std::fmt::Write;
+
+use crate::json::{write_base64, write_string};
+
+/// Renders the document in the shell syntax, e.g. `{ name: "a", count: NumberLong("2") }`.
+pub fn document_to_shell(document: &bson::Document) -> String {
+    let mut output = String::new();
+    write_document(&mut output, document);
+    output
+}
+
+/// Renders the value in the shell syntax.
+pub fn value_to_shell(value: &bson::Bson) -> String {
+    let mut output = String::new();
+    write_value(&mut output, value);
+    output
+}
+
+/// Returns the expression referring to the collection in the current database.
+pub(crate) fn collection(name: &str) -> String {
+    if is_identifier(name) {
+        format!("db.{}", name)
+    } else {
+        let mut output = "db.getCollection(".to_string();
+        write_string(&mut output, name);
+        output.push(')');
+        output
+    }
+}
+
+/// Returns a method call with the given arguments, e.g. `.find({ a: 1 })`. The options are
+/// passed as the last argument unless they are empty.
+pub(crate) fn call(method: &str, arguments: Vec<bson::Bson>, options: bson::Document) -> String {
+    let mut output = format!(".{}(", method);
+    for (index, argument) in arguments.iter().enumerate() {
+        if index > 0 {
+            output.push_str(", ");
+        }
+        write_value(&mut output, argument);
+    }
+    if !options.is_empty() {
+        if !arguments.is_empty() {
+            output.push_str(", ");
+        }
+        write_document(&mut output, &options);
+    }
+    output.push(')');
+    output
+}
+
+fn write_document(output: &mut String, document: &bson::Document) {
+    if document.is_empty() {
+        output.push_str("{}");
+        return;
+    }
+    output.push_str("{ ");
+    for (index, (key, value)) in document.iter().enumerate() {
+        if index > 0 {
+            output.push_str(", ");
+        }
+        if is_identifier(key) {
+            output.push_str(key);
+        } else {
+            write_string(output, key);
+        }
+        output.push_str(": ");
+        write_value(output, value);
+    }
+    output.push_str(" }");
+}
+
+fn write_value(output: &mut String, value: &bson::Bson) {
+    use bson::Bson;
+
+    match value {
+        Bson::FloatingPoint(number) => {
+            if number.is_nan() {
+                output.push_str("NaN");
+            } else if number.is_infinite() {
+                output.push_str(if *number > 0.0 { "Infinity" } else { "-Infinity" });
+            } else {
+                write!(output, "{:?}", number).unwrap();
+            }
+        }
+        Bson::String(string) => write_string(output, string),
+        Bson::Array(array) => {
+            if array.is_empty() {
+                output.push_str("[]");
+                return;
+            }
+            output.push_str("[ ");
+            for (index, element) in array.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_value(output, element);
+            }
+            output.push_str(" ]");
+        }
+        Bson::Document(document) => write_document(output, document),
+        Bson::Boolean(boolean) => output.push_str(if *boolean { "true" } else { "false" }),
+        Bson::Null => output.push_str("null"),
+        Bson::RegExp(pattern, options) => write_regex(output, pattern, options),
+        Bson::JavaScriptCode(code) => {
+            output.push_str("Code(");
+            write_string(output, code);
+            output.push(')');
+        }
+        Bson::JavaScriptCodeWithScope(code, scope) => {
+            output.push_str("Code(");
+            write_string(output, code);
+            output.push_str(", ");
+            write_document(output, scope);
+            output.push(')');
+        }
+        Bson::I32(number) => write!(output, "{}", number).unwrap(),
+        Bson::I64(number) => write!(output, "NumberLong(\"{}\")", number).unwrap(),
+        Bson::TimeStamp(timestamp) => {
+            let time = (*timestamp as u64) >> 32;
+            let increment = (*timestamp as u64) & 0xffff_ffff;
+            write!(output, "Timestamp({{ t: {}, i: {} }})", time, increment).unwrap();
+        }
+        Bson::Binary(subtype, bytes) => {
+            let subtype: u8 = (*subtype).into();
+            write!(output, "BinData({}, \"", subtype).unwrap();
+            write_base64(output, bytes);
+            output.push_str("\")");
+        }
+        Bson::ObjectId(id) => write!(output, "ObjectId(\"{}\")", id.to_hex()).unwrap(),
+        Bson::UtcDatetime(date) => {
+            let date = date.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            write!(output, "ISODate(\"{}\")", date).unwrap();
+        }
+        Bson::Symbol(symbol) => {
+            output.push_str("BSONSymbol(");
+            write_string(output, symbol);
+            output.push(')');
+        }
+    }
+}
+
+/// Writes the regular expression as a literal, escaping the slashes which would end it.
+fn write_regex(output: &mut String, pattern: &str, options: &str) {
+    output.push('/');
+    let mut escaped = false;
+    for character in pattern.chars() {
+        match character {
+            '/' if !escaped => output.push_str("\\/"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            c => output.push(c),
+        }
+        escaped = !escaped && character == '\\';
+    }
+    output.push('/');
+    output.push_str(options);
+}
+
+/// Checks if the key can be written without quotes.
+fn is_identifier(key: &str) -> bool {
+    let mut characters = key.chars();
+    match characters.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+            characters.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,1120 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Provides an in-memory collection executing commands without a database so that the code using
+//! them can be unit tested.
+//!
+//! Only a subset of the operators is evaluated: comparisons, `$in`, `$nin`, `$exists`, `$type`,
+//! `$not`, `$size`, `$all`, `$elemMatch`, `$mod`, `$regex`, the bitwise and the logical operators
+//! in filters; the field, arithmetic and array operators in updates. Commands using other
+//! operators, positional operators or update pipelines cause a panic. Regular expressions use the
+//! syntax of the `regex` crate, so patterns with look-arounds or back-references cause a panic too.
+//! Projections and collations are ignored.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+use crate::commands::{
+    CountDocumentsCommand, DeleteCommand, DeleteOptions, DeleteResult, FindCommand, FindOneCommand,
+    InsertCommand, InsertManyCommand, InsertManyResult, InsertResult, UpdateCommand, UpdateOptions,
+    UpdateResult, DUPLICATE_KEY_ERROR,
+};
+use crate::conversions::{FromDoc, IntoDoc};
+use crate::errors::{HuusError, UpdateError, WriteError};
+
+/// Code of the error reported when an update operator gets an argument it cannot use.
+const BAD_VALUE_ERROR: i32 = 2;
+
+/// Code of the error reported when an update operator is applied to a value of a wrong type.
+const TYPE_MISMATCH_ERROR: i32 = 14;
+
+/// Code of the error reported when a path of an update goes through a non-container value.
+const PATH_NOT_VIABLE_ERROR: i32 = 28;
+
+/// Code of the error reported when an update changes `_id`.
+const IMMUTABLE_FIELD_ERROR: i32 = 66;
+
+/// Collection of documents kept in memory. The data is stored as documents so that filters and
+/// updates are evaluated on the same representation as in the database.
+#[derive(Debug)]
+pub struct MemoryCollection<Data> {
+    documents: Vec<bson::Document>,
+    phantom: PhantomData<Data>,
+}
+
+impl<Data> MemoryCollection<Data>
+where
+    Data: FromDoc + IntoDoc,
+{
+    pub fn new() -> Self {
+        Self { documents: Vec::new(), phantom: PhantomData }
+    }
+
+    /// Constructs a collection containing the given data. Documents without `_id` get a new one.
+    pub fn with_data(data: Vec<Data>) -> Self {
+        let documents = data
+            .into_iter()
+            .map(|data| InsertCommand::new(String::new(), data.into_doc()).document)
+            .collect();
+        Self { documents, phantom: PhantomData }
+    }
+
+    /// Returns the stored documents in the order of insertion.
+    pub fn get_documents(&self) -> &Vec<bson::Document> {
+        &self.documents
+    }
+
+    /// Returns the stored documents converted to the data.
+    pub fn get_data(&self) -> Result<Vec<Data>, HuusError> {
+        self.documents.iter().map(|document| Ok(Data::from_doc(document.clone())?)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Finds the documents matching the filter applying the sorting, skip and limit.
+    pub fn find(&self, command: &FindCommand<Data>) -> Result<Vec<Data>, HuusError> {
+        let options = &command.options;
+        let mut documents: Vec<&bson::Document> =
+            self.documents.iter().filter(|document| matches(document, &command.filter)).collect();
+        if let Some(sort) = &options.sort {
+            documents.sort_by(|a, b| compare_by_sort(a, b, sort));
+        }
+        let skip = options.skip.unwrap_or(0) as usize;
+        let limit = match options.limit {
+            Some(0) | None => usize::MAX,
+            Some(limit) => limit as usize,
+        };
+        documents
+            .into_iter()
+            .skip(skip)
+            .take(limit)
+            .map(|document| Ok(Data::from_doc(document.clone())?))
+            .collect()
+    }
+
+    /// Finds the first document matching the filter in the order given by the sorting.
+    pub fn find_one(&self, command: &FindOneCommand<Data>) -> Result<Option<Data>, HuusError> {
+        let mut documents =
+            self.documents.iter().filter(|document| matches(document, &command.filter));
+        let found = match &command.sort {
+            Some(sort) => documents.min_by(|a, b| compare_by_sort(a, b, sort)),
+            None => documents.next(),
+        };
+        match found {
+            Some(document) => Ok(Some(Data::from_doc(document.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn count(&self, command: &CountDocumentsCommand) -> u64 {
+        self.documents.iter().filter(|document| matches(document, &command.filter)).count() as u64
+    }
+
+    pub fn insert(&mut self, command: &InsertCommand) -> Result<InsertResult, HuusError> {
+        self.insert_document(command.document.clone())?;
+        Ok(InsertResult { inserted_id: command.id.clone() })
+    }
+
+    /// Inserts the documents. Documents with a duplicate `_id` are reported as rejected. An ordered
+    /// insertion stops at the first of them.
+    pub fn insert_many(
+        &mut self,
+        command: &InsertManyCommand,
+    ) -> Result<InsertManyResult, HuusError> {
+        let mut result = InsertManyResult { inserted_ids: Vec::new(), duplicate_ids: Vec::new() };
+        for (document, id) in command.documents.iter().zip(command.ids.iter()) {
+            if self.insert_document(document.clone()).is_ok() {
+                result.inserted_ids.push(id.clone());
+            } else {
+                result.duplicate_ids.push(id.clone());
+                if command.ordered {
+                    break;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Applies the update to the matching documents. If none matches an upsert, inserts a new
+    /// document built from the equality conditions of the filter and the update.
+    pub fn update(&mut self, command: &UpdateCommand) -> Result<UpdateResult, HuusError> {
+        let update = match &command.update {
+            bson::Bson::Document(update) => update,
+            _ => panic!("Update pipelines are not supported by MemoryCollection"),
+        };
+        if command.is_empty() {
+            return Err(UpdateError::Empty.into());
+        }
+        if !command.array_filters.is_empty() {
+            panic!("Array filters are not supported by MemoryCollection");
+        }
+
+        let mut result = UpdateResult { matched: 0, modified: 0, upserted_id: None };
+        for document in self.documents.iter_mut() {
+            if !matches(document, &command.filter) {
+                continue;
+            }
+            let updated = apply_update(document, update, false)?;
+            result.matched += 1;
+            if updated != *document {
+                *document = updated;
+                result.modified += 1;
+            }
+            if command.options == UpdateOptions::UpdateOne {
+                break;
+            }
+        }
+
+        if result.matched == 0 && command.upsert {
+            let document = apply_update(&get_upserted(&command.filter)?, update, true)?;
+            let document = InsertCommand::new(String::new(), document);
+            self.insert_document(document.document)?;
+            result.upserted_id = Some(document.id);
+        }
+        Ok(result)
+    }
+
+    pub fn delete(&mut self, command: &DeleteCommand) -> Result<DeleteResult, HuusError> {
+        let is_many = command.options == DeleteOptions::DeleteMany;
+        let mut deleted = 0;
+        self.documents.retain(|document| {
+            if (is_many || deleted == 0) && matches(document, &command.filter) {
+                deleted += 1;
+                false
+            } else {
+                true
+            }
+        });
+        Ok(DeleteResult { deleted })
+    }
+
+    /// Stores the document unless one with the same `_id` is already stored.
+    fn insert_document(&mut self, document: bson::Document) -> Result<(), WriteError> {
+        let id = document.get("_id");
+        if self.documents.iter().any(|stored| stored.get("_id") == id) {
+            let message = format!("Duplicate key: {:?}", id);
+            return Err(WriteError { code: DUPLICATE_KEY_ERROR, message });
+        }
+        self.documents.push(document);
+        Ok(())
+    }
+}
+
+impl<Data> Default for MemoryCollection<Data>
+where
+    Data: FromDoc + IntoDoc,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Filters
+
+/// Checks if the document matches the filter.
+fn matches(document: &bson::Document, filter: &bson::Document) -> bool {
+    filter.iter().all(|(key, condition)| match key.as_str() {
+        "$and" => get_clauses(key, condition).iter().all(|clause| matches(document, clause)),
+        "$or" => get_clauses(key, condition).iter().any(|clause| matches(document, clause)),
+        "$nor" => !get_clauses(key, condition).iter().any(|clause| matches(document, clause)),
+        key if key.starts_with('$') => {
+            panic!("Operator '{}' is not supported by MemoryCollection", key)
+        }
+        path => matches_condition(&lookup(document, path), condition),
+    })
+}
+
+/// Returns the clauses of a logical operator.
+fn get_clauses<'a>(operator: &str, condition: &'a bson::Bson) -> Vec<&'a bson::Document> {
+    let clauses = match condition {
+        bson::Bson::Array(clauses) => clauses,
+        _ => panic!("Operator '{}' requires an array", operator),
+    };
+    clauses
+        .iter()
+        .map(|clause| match clause {
+            bson::Bson::Document(clause) => clause,
+            _ => panic!("Operator '{}' requires an array of documents", operator),
+        })
+        .collect()
+}
+
+/// Returns the values found under the dotted path. Arrays met on the way are traversed element by
+/// element unless the next part of the path is an index.
+fn lookup<'a>(document: &'a bson::Document, path: &str) -> Vec<&'a bson::Bson> {
+    let parts: Vec<&str> = path.split('.').collect();
+    let mut values = Vec::new();
+    lookup_in_document(document, &parts, &mut values);
+    values
+}
+
+fn lookup_in_document<'a>(
+    document: &'a bson::Document,
+    parts: &[&str],
+    values: &mut Vec<&'a bson::Bson>,
+) {
+    if let Some(value) = document.get(parts[0]) {
+        lookup_in_value(value, &parts[1..], values);
+    }
+}
+
+fn lookup_in_value<'a>(value: &'a bson::Bson, parts: &[&str], values: &mut Vec<&'a bson::Bson>) {
+    if parts.is_empty() {
+        values.push(value);
+        return;
+    }
+    match value {
+        bson::Bson::Document(document) => lookup_in_document(document, parts, values),
+        bson::Bson::Array(array) => {
+            if let Some(element) = parts[0].parse::<usize>().ok().and_then(|i| array.get(i)) {
+                lookup_in_value(element, &parts[1..], values);
+            }
+            for element in array {
+                if let bson::Bson::Document(document) = element {
+                    lookup_in_document(document, parts, values);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Checks if a document is an operator expression like `{ "$gt": 1 }` rather than a value.
+fn is_expression(document: &bson::Document) -> bool {
+    document.keys().next().is_some_and(|key| key.starts_with('$'))
+}
+
+/// Checks if the values found under a path satisfy the condition being either an operator
+/// expression or a value to compare with.
+fn matches_condition(values: &[&bson::Bson], condition: &bson::Bson) -> bool {
+    match condition {
+        bson::Bson::Document(expression) if is_expression(expression) => {
+            matches_expression(values, expression)
+        }
+        bson::Bson::RegExp(pattern, options) => matches_regex(values, pattern, options),
+        _ => matches_equality(values, condition),
+    }
+}
+
+/// Checks if the values satisfy all operators of the expression. `$options` is read together with
+/// `$regex`.
+fn matches_expression(values: &[&bson::Bson], expression: &bson::Document) -> bool {
+    expression.iter().all(|(operator, argument)| match (operator.as_str(), argument) {
+        ("$regex", bson::Bson::String(pattern)) => {
+            let options = match expression.get("$options") {
+                Some(bson::Bson::String(options)) => options.as_str(),
+                Some(_) => panic!("Operator '$options' requires a string"),
+                None => "",
+            };
+            matches_regex(values, pattern, options)
+        }
+        ("$regex", bson::Bson::RegExp(pattern, options)) => match expression.get("$options") {
+            Some(bson::Bson::String(extra)) => matches_regex(values, pattern, extra),
+            _ => matches_regex(values, pattern, options),
+        },
+        ("$regex", _) => panic!("Operator '$regex' requires a string or a regular expression"),
+        ("$options", _) if expression.contains_key("$regex") => true,
+        (operator, argument) => matches_operator(values, operator, argument),
+    })
+}
+
+fn matches_operator(values: &[&bson::Bson], operator: &str, argument: &bson::Bson) -> bool {
+    match operator {
+        "$eq" => matches_equality(values, argument),
+        "$ne" => !matches_equality(values, argument),
+        "$gt" => matches_comparison(values, argument, |ordering| ordering == Ordering::Greater),
+        "$gte" => matches_comparison(values, argument, |ordering| ordering != Ordering::Less),
+        "$lt" => matches_comparison(values, argument, |ordering| ordering == Ordering::Less),
+        "$lte" => matches_comparison(values, argument, |ordering| ordering != Ordering::Greater),
+        "$in" => get_array(operator, argument).iter().any(|value| matches_in(values, value)),
+        "$nin" => !get_array(operator, argument).iter().any(|value| matches_in(values, value)),
+        "$exists" => is_truthy(argument) != values.is_empty(),
+        "$type" => {
+            let types = match argument {
+                bson::Bson::Array(types) => types.iter().collect(),
+                argument => vec![argument],
+            };
+            any_element(values, |value| types.iter().any(|kind| is_of_type(value, kind)))
+        }
+        "$not" => !matches_condition(values, argument),
+        "$size" => values.iter().any(|value| match *value {
+            bson::Bson::Array(array) => to_i64(argument) == Some(array.len() as i64),
+            _ => false,
+        }),
+        "$all" => {
+            let expected = get_array(operator, argument);
+            !expected.is_empty() && expected.iter().all(|value| matches_equality(values, value))
+        }
+        "$elemMatch" => {
+            let condition = match argument {
+                bson::Bson::Document(condition) => condition,
+                _ => panic!("Operator '$elemMatch' requires a document"),
+            };
+            values.iter().any(|value| match *value {
+                bson::Bson::Array(array) => {
+                    array.iter().any(|element| matches_element(element, condition))
+                }
+                _ => false,
+            })
+        }
+        "$mod" => {
+            let arguments = get_array(operator, argument);
+            let (divisor, remainder) = match (arguments.first(), arguments.get(1)) {
+                (Some(divisor), Some(remainder)) => (to_f64(divisor), to_f64(remainder)),
+                _ => panic!("Operator '$mod' requires a divisor and a remainder"),
+            };
+            let (divisor, remainder) = match (divisor, remainder) {
+                (Some(divisor), Some(remainder)) => (divisor as i64, remainder as i64),
+                _ => panic!("Operator '$mod' requires numbers"),
+            };
+            // The database rejects a zero divisor; nothing matches here instead.
+            any_element(values, |value| {
+                to_f64(value)
+                    .and_then(|value| (value as i64).checked_rem(divisor))
+                    .is_some_and(|value| value == remainder)
+            })
+        }
+        "$bitsAllSet" | "$bitsAnySet" | "$bitsAllClear" | "$bitsAnyClear" => {
+            let positions = get_bit_positions(argument);
+            let expected = operator.ends_with("Set");
+            any_element(values, |value| {
+                let mut bits = positions.iter().map(|position| get_bit(value, *position));
+                if operator.starts_with("$bitsAll") {
+                    bits.all(|bit| bit == Some(expected))
+                } else {
+                    bits.any(|bit| bit == Some(expected))
+                }
+            })
+        }
+        _ => panic!("Operator '{}' is not supported by MemoryCollection", operator),
+    }
+}
+
+/// Checks if any of the values or of their elements is equal to the expected value. A missing
+/// field is equal to `null`.
+fn matches_equality(values: &[&bson::Bson], expected: &bson::Bson) -> bool {
+    if values.is_empty() {
+        return *expected == bson::Bson::Null;
+    }
+    any_element(values, |value| is_equal(value, expected))
+}
+
+fn matches_comparison<F>(values: &[&bson::Bson], argument: &bson::Bson, accept: F) -> bool
+where
+    F: Fn(Ordering) -> bool,
+{
+    any_element(values, |value| compare_values(value, argument).is_some_and(&accept))
+}
+
+/// Checks if any of the values or of their elements is equal to the expected value or, if it is a
+/// regular expression, is a string matching it.
+fn matches_in(values: &[&bson::Bson], expected: &bson::Bson) -> bool {
+    match expected {
+        bson::Bson::RegExp(pattern, options) => matches_regex(values, pattern, options),
+        expected => matches_equality(values, expected),
+    }
+}
+
+/// Checks if any of the values or of their elements is a string matching the regular expression.
+fn matches_regex(values: &[&bson::Bson], pattern: &str, options: &str) -> bool {
+    let mut builder = regex::RegexBuilder::new(pattern);
+    for option in options.chars() {
+        match option {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            's' => builder.dot_matches_new_line(true),
+            'x' => builder.ignore_whitespace(true),
+            _ => &mut builder,
+        };
+    }
+    let regex = builder.build().unwrap_or_else(|error| {
+        panic!("Regular expression '{}' is not supported by MemoryCollection: {}", pattern, error)
+    });
+    any_element(values, |value| match value {
+        bson::Bson::String(string) | bson::Bson::Symbol(string) => regex.is_match(string),
+        _ => false,
+    })
+}
+
+/// Checks if the value has the type given by its number or alias.
+fn is_of_type(value: &bson::Bson, kind: &bson::Bson) -> bool {
+    let (number, alias) = match value {
+        bson::Bson::FloatingPoint(_) => (1, "double"),
+        bson::Bson::String(_) => (2, "string"),
+        bson::Bson::Document(_) => (3, "object"),
+        bson::Bson::Array(_) => (4, "array"),
+        bson::Bson::Binary(..) => (5, "binData"),
+        bson::Bson::ObjectId(_) => (7, "objectId"),
+        bson::Bson::Boolean(_) => (8, "bool"),
+        bson::Bson::UtcDatetime(_) => (9, "date"),
+        bson::Bson::Null => (10, "null"),
+        bson::Bson::RegExp(..) => (11, "regex"),
+        bson::Bson::JavaScriptCode(_) => (13, "javascript"),
+        bson::Bson::Symbol(_) => (14, "symbol"),
+        bson::Bson::JavaScriptCodeWithScope(..) => (15, "javascriptWithScope"),
+        bson::Bson::I32(_) => (16, "int"),
+        bson::Bson::TimeStamp(_) => (17, "timestamp"),
+        bson::Bson::I64(_) => (18, "long"),
+    };
+    match kind {
+        bson::Bson::String(kind) if kind == "number" => to_f64(value).is_some(),
+        bson::Bson::String(kind) => kind == alias,
+        kind => match to_f64(kind) {
+            Some(kind) => kind == f64::from(number),
+            None => panic!("Operator '$type' requires a type number or alias"),
+        },
+    }
+}
+
+/// Returns the positions of the bits checked by a bitwise operator given as a bit mask or as an
+/// array of positions.
+fn get_bit_positions(argument: &bson::Bson) -> Vec<usize> {
+    match argument {
+        bson::Bson::Array(positions) => positions
+            .iter()
+            .map(|position| match to_i64(position).map(usize::try_from) {
+                Some(Ok(position)) => position,
+                _ => panic!("Bitwise operators require non-negative bit positions"),
+            })
+            .collect(),
+        bson::Bson::Binary(_, bytes) => (0..bytes.len() * 8)
+            .filter(|position| bytes[position / 8] & (1 << (position % 8)) != 0)
+            .collect(),
+        argument => match to_i64(argument) {
+            Some(mask) if mask >= 0 => {
+                (0..64).filter(|position| mask & (1 << position) != 0).collect()
+            }
+            _ => panic!("Bitwise operators require a non-negative bit mask"),
+        },
+    }
+}
+
+/// Returns the bit of an integral number or of binary data. Numbers are sign extended. `None` is
+/// returned for values of other types.
+fn get_bit(value: &bson::Bson, position: usize) -> Option<bool> {
+    let number = match value {
+        bson::Bson::Binary(_, bytes) => {
+            let byte = bytes.get(position / 8).copied().unwrap_or(0);
+            return Some(byte & (1 << (position % 8)) != 0);
+        }
+        bson::Bson::FloatingPoint(number) if number.fract() == 0.0 => *number as i64,
+        value => to_i64(value)?,
+    };
+    Some(if position < 64 { number & (1 << position) != 0 } else { number < 0 })
+}
+
+/// Checks if the element of an array matches the condition of `$elemMatch`: an operator
+/// expression applied to the element itself or a filter applied to the element being a document.
+fn matches_element(element: &bson::Bson, condition: &bson::Document) -> bool {
+    let is_logical = condition.keys().any(|key| key == "$and" || key == "$or" || key == "$nor");
+    if is_expression(condition) && !is_logical {
+        matches_expression(&[element], condition)
+    } else {
+        match element {
+            bson::Bson::Document(element) => matches(element, condition),
+            _ => false,
+        }
+    }
+}
+
+/// Checks if any of the values or, in case of arrays, any of their elements satisfies the
+/// predicate.
+fn any_element<F>(values: &[&bson::Bson], predicate: F) -> bool
+where
+    F: Fn(&bson::Bson) -> bool,
+{
+    values.iter().any(|value| {
+        predicate(value)
+            || match *value {
+                bson::Bson::Array(array) => array.iter().any(&predicate),
+                _ => false,
+            }
+    })
+}
+
+fn get_array<'a>(operator: &str, argument: &'a bson::Bson) -> &'a Vec<bson::Bson> {
+    match argument {
+        bson::Bson::Array(array) => array,
+        _ => panic!("Operator '{}' requires an array", operator),
+    }
+}
+
+fn is_truthy(value: &bson::Bson) -> bool {
+    match value {
+        bson::Bson::Boolean(value) => *value,
+        bson::Bson::Null => false,
+        value => to_f64(value) != Some(0.0),
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Comparisons
+
+fn to_i64(value: &bson::Bson) -> Option<i64> {
+    match value {
+        bson::Bson::I32(number) => Some(i64::from(*number)),
+        bson::Bson::I64(number) => Some(*number),
+        _ => None,
+    }
+}
+
+fn to_f64(value: &bson::Bson) -> Option<f64> {
+    match value {
+        bson::Bson::I32(number) => Some(f64::from(*number)),
+        bson::Bson::I64(number) => Some(*number as f64),
+        bson::Bson::FloatingPoint(number) => Some(*number),
+        _ => None,
+    }
+}
+
+/// Returns the position of the type of the value in the order in which the database sorts values
+/// of different types. Numbers of all types share the same position.
+fn get_type_rank(value: &bson::Bson) -> u8 {
+    match value {
+        bson::Bson::Null => 1,
+        bson::Bson::I32(_) | bson::Bson::I64(_) | bson::Bson::FloatingPoint(_) => 2,
+        bson::Bson::String(_) | bson::Bson::Symbol(_) => 3,
+        bson::Bson::Document(_) => 4,
+        bson::Bson::Array(_) => 5,
+        bson::Bson::Binary(..) => 6,
+        bson::Bson::ObjectId(_) => 7,
+        bson::Bson::Boolean(_) => 8,
+        bson::Bson::UtcDatetime(_) => 9,
+        bson::Bson::TimeStamp(_) => 10,
+        bson::Bson::RegExp(..) => 11,
+        bson::Bson::JavaScriptCode(_) | bson::Bson::JavaScriptCodeWithScope(..) => 12,
+    }
+}
+
+/// Compares values of any types in the order used by the database for sorting.
+fn compare(a: &bson::Bson, b: &bson::Bson) -> Ordering {
+    if let (Some(a), Some(b)) = (to_i64(a), to_i64(b)) {
+        return a.cmp(&b);
+    }
+    if let (Some(a), Some(b)) = (to_f64(a), to_f64(b)) {
+        return a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    }
+    match (a, b) {
+        (bson::Bson::String(a), bson::Bson::String(b)) => a.cmp(b),
+        (bson::Bson::Document(a), bson::Bson::Document(b)) => {
+            for ((a_key, a_value), (b_key, b_value)) in a.iter().zip(b.iter()) {
+                let ordering = a_key.cmp(b_key).then_with(|| compare(a_value, b_value));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            a.len().cmp(&b.len())
+        }
+        (bson::Bson::Array(a), bson::Bson::Array(b)) => {
+            for (a, b) in a.iter().zip(b.iter()) {
+                let ordering = compare(a, b);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            a.len().cmp(&b.len())
+        }
+        (bson::Bson::Binary(_, a), bson::Bson::Binary(_, b)) => {
+            a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+        }
+        (bson::Bson::ObjectId(a), bson::Bson::ObjectId(b)) => a.bytes().cmp(&b.bytes()),
+        (bson::Bson::Boolean(a), bson::Bson::Boolean(b)) => a.cmp(b),
+        (bson::Bson::UtcDatetime(a), bson::Bson::UtcDatetime(b)) => a.cmp(b),
+        (bson::Bson::TimeStamp(a), bson::Bson::TimeStamp(b)) => a.cmp(b),
+        (bson::Bson::RegExp(a, a_options), bson::Bson::RegExp(b, b_options)) => {
+            a.cmp(b).then_with(|| a_options.cmp(b_options))
+        }
+        _ => get_type_rank(a).cmp(&get_type_rank(b)),
+    }
+}
+
+/// Compares values of the same type. Query operators do not match values of different types.
+fn compare_values(a: &bson::Bson, b: &bson::Bson) -> Option<Ordering> {
+    if get_type_rank(a) == get_type_rank(b) {
+        Some(compare(a, b))
+    } else {
+        None
+    }
+}
+
+fn is_equal(a: &bson::Bson, b: &bson::Bson) -> bool {
+    compare_values(a, b) == Some(Ordering::Equal)
+}
+
+/// Compares the documents according to the sort specification. Missing fields are sorted as
+/// `null`.
+fn compare_by_sort(a: &bson::Document, b: &bson::Document, sort: &bson::Document) -> Ordering {
+    let null = bson::Bson::Null;
+    for (path, direction) in sort.iter() {
+        let a_values = lookup(a, path);
+        let b_values = lookup(b, path);
+        let a_value = a_values.first().cloned().unwrap_or(&null);
+        let b_value = b_values.first().cloned().unwrap_or(&null);
+        let ordering = compare(a_value, b_value);
+        let ordering = if to_f64(direction).is_some_and(|direction| direction < 0.0) {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+// -------------------------------------------------------------------------------------------------
+// Updates
+
+fn write_error(code: i32, message: String) -> WriteError {
+    WriteError { code, message }
+}
+
+/// Returns the document modified by the update operators or replaced by the replacement document.
+/// Operators prefixed with `$setOnInsert` are applied only when inserting an upserted document.
+fn apply_update(
+    document: &bson::Document,
+    update: &bson::Document,
+    is_upsert: bool,
+) -> Result<bson::Document, WriteError> {
+    let result = if is_expression(update) {
+        let mut root = bson::Bson::Document(document.clone());
+        for (operator, fields) in update.iter() {
+            let fields = match fields {
+                bson::Bson::Document(fields) => fields,
+                _ => {
+                    let message = format!("Operator '{}' requires a document", operator);
+                    return Err(write_error(BAD_VALUE_ERROR, message));
+                }
+            };
+            for (path, argument) in fields.iter() {
+                apply_operator(&mut root, operator, path, argument, is_upsert)?;
+            }
+        }
+        match root {
+            bson::Bson::Document(result) => result,
+            _ => unreachable!("The root of the document is always a document"),
+        }
+    } else {
+        let mut result = bson::Document::new();
+        if let Some(id) = document.get("_id") {
+            result.insert_bson("_id".to_string(), id.clone());
+        }
+        for (key, value) in update.iter() {
+            if key != "_id" || !result.contains_key("_id") {
+                result.insert_bson(key.clone(), value.clone());
+            }
+        }
+        if let (Some(id), Some(replaced_id)) = (document.get("_id"), update.get("_id")) {
+            if id != replaced_id {
+                return Err(immutable_id_error());
+            }
+        }
+        result
+    };
+
+    if document.get("_id").is_some() && document.get("_id") != result.get("_id") {
+        return Err(immutable_id_error());
+    }
+    Ok(result)
+}
+
+fn immutable_id_error() -> WriteError {
+    write_error(IMMUTABLE_FIELD_ERROR, "The update would modify the immutable field '_id'".into())
+}
+
+/// Builds the document inserted by an upsert from the equality conditions of the filter.
+fn get_upserted(filter: &bson::Document) -> Result<bson::Document, WriteError> {
+    let mut root = bson::Bson::Document(bson::Document::new());
+    add_equalities(&mut root, filter)?;
+    match root {
+        bson::Bson::Document(document) => Ok(document),
+        _ => unreachable!("The root of the document is always a document"),
+    }
+}
+
+fn add_equalities(root: &mut bson::Bson, filter: &bson::Document) -> Result<(), WriteError> {
+    for (key, condition) in filter.iter() {
+        if key == "$and" {
+            for clause in get_clauses(key, condition) {
+                add_equalities(root, clause)?;
+            }
+            continue;
+        }
+        if key.starts_with('$') {
+            continue;
+        }
+        let value = match condition {
+            bson::Bson::Document(expression) if is_expression(expression) => {
+                match expression.get("$eq") {
+                    Some(value) => value.clone(),
+                    None => continue,
+                }
+            }
+            value => value.clone(),
+        };
+        set(root, &key.split('.').collect::<Vec<_>>(), value)?;
+    }
+    Ok(())
+}
+
+fn apply_operator(
+    root: &mut bson::Bson,
+    operator: &str,
+    path: &str,
+    argument: &bson::Bson,
+    is_upsert: bool,
+) -> Result<(), WriteError> {
+    let parts: Vec<&str> = path.split('.').collect();
+    if parts.iter().any(|part| part.starts_with('$')) {
+        panic!("Positional operators are not supported by MemoryCollection");
+    }
+    let current = get_mut(root, &parts).cloned();
+    match operator {
+        "$set" => set(root, &parts, argument.clone()),
+        "$setOnInsert" if is_upsert => set(root, &parts, argument.clone()),
+        "$setOnInsert" => Ok(()),
+        "$unset" => {
+            unset(root, &parts);
+            Ok(())
+        }
+        "$inc" | "$mul" => {
+            // A missing field is treated as zero of the type of the argument.
+            let current = current.unwrap_or(bson::Bson::I32(0));
+            set(root, &parts, calculate(operator, path, &current, argument)?)
+        }
+        "$min" | "$max" => {
+            let expected = if operator == "$min" { Ordering::Less } else { Ordering::Greater };
+            match current {
+                Some(current) if compare(argument, &current) != expected => Ok(()),
+                _ => set(root, &parts, argument.clone()),
+            }
+        }
+        "$rename" => {
+            let target = match argument {
+                bson::Bson::String(target) => target,
+                _ => {
+                    let message = "Operator '$rename' requires a string".to_string();
+                    return Err(write_error(BAD_VALUE_ERROR, message));
+                }
+            };
+            if let Some(value) = current {
+                unset(root, &parts);
+                set(root, &target.split('.').collect::<Vec<_>>(), value)?;
+            }
+            Ok(())
+        }
+        "$currentDate" => {
+            let now = chrono::Utc::now();
+            let value = match argument {
+                bson::Bson::Document(kind) if kind.get_str("$type") == Ok("timestamp") => {
+                    bson::Bson::TimeStamp(now.timestamp() << 32)
+                }
+                _ => bson::Bson::UtcDatetime(now),
+            };
+            set(root, &parts, value)
+        }
+        "$push" => {
+            let mut array = to_array(operator, path, current)?;
+            push(&mut array, argument)?;
+            set(root, &parts, bson::Bson::Array(array))
+        }
+        "$addToSet" => {
+            let mut array = to_array(operator, path, current)?;
+            let values = match argument {
+                bson::Bson::Document(modifiers) if modifiers.contains_key("$each") => {
+                    get_each(modifiers)?.clone()
+                }
+                value => vec![value.clone()],
+            };
+            for value in values {
+                if !array.iter().any(|element| is_equal(element, &value)) {
+                    array.push(value);
+                }
+            }
+            set(root, &parts, bson::Bson::Array(array))
+        }
+        "$pop" | "$pull" | "$pullAll" => {
+            if current.is_none() {
+                return Ok(());
+            }
+            let mut array = to_array(operator, path, current)?;
+            match operator {
+                "$pop" if to_f64(argument).is_some_and(|first| first < 0.0) => {
+                    if !array.is_empty() {
+                        array.remove(0);
+                    }
+                }
+                "$pop" => {
+                    array.pop();
+                }
+                "$pull" => array.retain(|element| !matches_pulled(element, argument)),
+                _ => {
+                    let values = match argument {
+                        bson::Bson::Array(values) => values,
+                        _ => {
+                            let message = "Operator '$pullAll' requires an array".to_string();
+                            return Err(write_error(BAD_VALUE_ERROR, message));
+                        }
+                    };
+                    array.retain(|element| !values.iter().any(|value| is_equal(element, value)));
+                }
+            }
+            set(root, &parts, bson::Bson::Array(array))
+        }
+        "$bit" => {
+            let current = current.unwrap_or(bson::Bson::I32(0));
+            set(root, &parts, calculate_bitwise(path, &current, argument)?)
+        }
+        _ => panic!("Update operator '{}' is not supported by MemoryCollection", operator),
+    }
+}
+
+/// Applies the bitwise operations of `$bit` to an integer. The result is a 64-bit integer if either
+/// of the operands is one.
+fn calculate_bitwise(
+    path: &str,
+    current: &bson::Bson,
+    argument: &bson::Bson,
+) -> Result<bson::Bson, WriteError> {
+    let mismatch = || {
+        let message = format!("Operator '$bit' cannot be applied to the field '{}'", path);
+        write_error(TYPE_MISMATCH_ERROR, message)
+    };
+    let operations = match argument {
+        bson::Bson::Document(operations) => operations,
+        _ => return Err(mismatch()),
+    };
+    let mut result = current.clone();
+    for (operation, mask) in operations.iter() {
+        let (a, b) = match (to_i64(&result), to_i64(mask)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Err(mismatch()),
+        };
+        let value = match operation.as_str() {
+            "and" => a & b,
+            "or" => a | b,
+            "xor" => a ^ b,
+            _ => {
+                let message = format!("Unknown bitwise operation '{}'", operation);
+                return Err(write_error(BAD_VALUE_ERROR, message));
+            }
+        };
+        result = match (&result, mask) {
+            (bson::Bson::I32(_), bson::Bson::I32(_)) => bson::Bson::I32(value as i32),
+            _ => bson::Bson::I64(value),
+        };
+    }
+    Ok(result)
+}
+
+/// Adds or multiplies the numbers. Integers stay integers unless one of the operands is a floating
+/// point number. 32-bit integers are widened if the result does not fit.
+fn calculate(
+    operator: &str,
+    path: &str,
+    current: &bson::Bson,
+    argument: &bson::Bson,
+) -> Result<bson::Bson, WriteError> {
+    if let (Some(a), Some(b)) = (to_i64(current), to_i64(argument)) {
+        let result = if operator == "$inc" { a.checked_add(b) } else { a.checked_mul(b) };
+        let result = match result {
+            Some(result) => result,
+            None => {
+                let message = format!("Operator '{}' overflows the field '{}'", operator, path);
+                return Err(write_error(BAD_VALUE_ERROR, message));
+            }
+        };
+        return Ok(match (current, argument, i32::try_from(result)) {
+            (bson::Bson::I32(_), bson::Bson::I32(_), Ok(result)) => bson::Bson::I32(result),
+            _ => bson::Bson::I64(result),
+        });
+    }
+    match (to_f64(current), to_f64(argument)) {
+        (Some(a), Some(b)) => {
+            Ok(bson::Bson::FloatingPoint(if operator == "$inc" { a + b } else { a * b }))
+        }
+        _ => {
+            let message =
+                format!("Operator '{}' cannot be applied to the field '{}'", operator, path);
+            Err(write_error(TYPE_MISMATCH_ERROR, message))
+        }
+    }
+}
+
+/// Returns the array stored in the field or an empty one if the field does not exist.
+fn to_array(
+    operator: &str,
+    path: &str,
+    current: Option<bson::Bson>,
+) -> Result<Vec<bson::Bson>, WriteError> {
+    match current {
+        None => Ok(Vec::new()),
+        Some(bson::Bson::Array(array)) => Ok(array),
+        Some(_) => {
+            let message =
+                format!("Operator '{}' requires the field '{}' to be an array", operator, path);
+            Err(write_error(BAD_VALUE_ERROR, message))
+        }
+    }
+}
+
+fn get_each(modifiers: &bson::Document) -> Result<&Vec<bson::Bson>, WriteError> {
+    match modifiers.get("$each") {
+        Some(bson::Bson::Array(each)) => Ok(each),
+        _ => Err(write_error(BAD_VALUE_ERROR, "Modifier '$each' requires an array".into())),
+    }
+}
+
+/// Appends the value to the array applying the `$each`, `$position`, `$sort` and `$slice`
+/// modifiers.
+fn push(array: &mut Vec<bson::Bson>, argument: &bson::Bson) -> Result<(), WriteError> {
+    let modifiers = match argument {
+        bson::Bson::Document(modifiers) if modifiers.contains_key("$each") => modifiers,
+        value => {
+            array.push(value.clone());
+            return Ok(());
+        }
+    };
+
+    let each = get_each(modifiers)?;
+    let length = array.len() as i64;
+    let position = match modifiers.get("$position").map(to_i64) {
+        None => length,
+        Some(Some(position)) if position < 0 => (length + position).max(0),
+        Some(Some(position)) => position.min(length),
+        Some(None) => {
+            let message = "Modifier '$position' requires an integer".to_string();
+            return Err(write_error(BAD_VALUE_ERROR, message));
+        }
+    };
+    let tail = array.split_off(position as usize);
+    array.extend(each.iter().cloned());
+    array.extend(tail);
+
+    match modifiers.get("$sort") {
+        Some(bson::Bson::Document(sort)) => array.sort_by(|a, b| match (a, b) {
+            (bson::Bson::Document(a), bson::Bson::Document(b)) => compare_by_sort(a, b, sort),
+            _ => compare(a, b),
+        }),
+        Some(direction) if to_f64(direction).is_some_and(|direction| direction < 0.0) => {
+            array.sort_by(|a, b| compare(a, b).reverse())
+        }
+        Some(_) => array.sort_by(compare),
+        None => {}
+    }
+
+    match modifiers.get("$slice").map(to_i64) {
+        None => {}
+        Some(Some(slice)) if slice < 0 => {
+            let keep = slice.unsigned_abs() as usize;
+            if array.len() > keep {
+                array.drain(0..array.len() - keep);
+            }
+        }
+        Some(Some(slice)) => array.truncate(slice as usize),
+        Some(None) => {
+            let message = "Modifier '$slice' requires an integer".to_string();
+            return Err(write_error(BAD_VALUE_ERROR, message));
+        }
+    }
+    Ok(())
+}
+
+/// Checks if the element of an array matches the condition of `$pull`: a value, an operator
+/// expression or a filter applied to the element being a document.
+fn matches_pulled(element: &bson::Bson, condition: &bson::Bson) -> bool {
+    match (element, condition) {
+        (bson::Bson::Document(element), bson::Bson::Document(filter)) if !is_expression(filter) => {
+            matches(element, filter)
+        }
+        (_, bson::Bson::Document(filter)) if !is_expression(filter) => false,
+        _ => matches_condition(&[element], condition),
+    }
+}
+
+/// Returns the value under the dotted path or `None` if it does not exist.
+fn get_mut<'a>(value: &'a mut bson::Bson, parts: &[&str]) -> Option<&'a mut bson::Bson> {
+    if parts.is_empty() {
+        return Some(value);
+    }
+    let child = match value {
+        bson::Bson::Document(document) => document.get_mut(parts[0]),
+        bson::Bson::Array(array) => {
+            parts[0].parse::<usize>().ok().and_then(move |i| array.get_mut(i))
+        }
+        _ => None,
+    }?;
+    get_mut(child, &parts[1..])
+}
+
+/// Sets the value under the dotted path creating the missing intermediate documents. Arrays are
+/// padded with `null` when setting an element past their end.
+fn set(value: &mut bson::Bson, parts: &[&str], new_value: bson::Bson) -> Result<(), WriteError> {
+    let (part, rest) = (parts[0], &parts[1..]);
+    match value {
+        bson::Bson::Document(document) => {
+            if rest.is_empty() {
+                match document.get_mut(part) {
+                    Some(old_value) => *old_value = new_value,
+                    None => {
+                        document.insert_bson(part.to_string(), new_value);
+                    }
+                }
+                return Ok(());
+            }
+            if !document.contains_key(part) {
+                let child = bson::Bson::Document(bson::Document::new());
+                document.insert_bson(part.to_string(), child);
+            }
+            set(document.get_mut(part).expect("Inserted above"), rest, new_value)
+        }
+        bson::Bson::Array(array) => {
+            let index = match part.parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => return Err(path_not_viable_error(part)),
+            };
+            while array.len() <= index {
+                array.push(bson::Bson::Null);
+            }
+            if rest.is_empty() {
+                array[index] = new_value;
+                return Ok(());
+            }
+            if array[index] == bson::Bson::Null {
+                array[index] = bson::Bson::Document(bson::Document::new());
+            }
+            set(&mut array[index], rest, new_value)
+        }
+        _ => Err(path_not_viable_error(part)),
+    }
+}
+
+fn path_not_viable_error(part: &str) -> WriteError {
+    let message = format!("Cannot create the field '{}' in a value which is not a container", part);
+    write_error(PATH_NOT_VIABLE_ERROR, message)
+}
+
+/// Removes the value under the dotted path. Elements of arrays are replaced with `null`.
+fn unset(value: &mut bson::Bson, parts: &[&str]) {
+    let (last, parents) = parts.split_last().expect("Path is not empty");
+    match get_mut(value, parents) {
+        Some(bson::Bson::Document(document)) => {
+            document.remove(last);
+        }
+        Some(bson::Bson::Array(array)) => {
+            if let Some(element) = last.parse::<usize>().ok().and_then(|i| array.get_mut(i)) {
+                *element = bson::Bson::Null;
+            }
+        }
+        _ => {}
+    }
+}
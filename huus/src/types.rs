@@ -35,3 +35,28 @@ pub type Date = chrono::DateTime<chrono::Utc>;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct TimeStamp(pub i64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeoPoint {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(longitude: f64, latitude: f64) -> Self {
+        Self { longitude, latitude }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joined<Data, Foreign> {
+    pub data: Data,
+    pub joined: Vec<Foreign>,
+}
+
+impl<Data, Foreign> Joined<Data, Foreign> {
+    pub fn new(data: Data, joined: Vec<Foreign>) -> Self {
+        Self { data, joined }
+    }
+}
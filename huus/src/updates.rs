@@ -5,7 +5,11 @@
 
 use std::collections::{BTreeMap, HashMap};
 
+use bson::{bson, doc};
+
 use crate::conversions::{HuusIntoBson, HuusKey};
+use crate::errors::UpdateError;
+use crate::query::Schema;
 use crate::values::BuildValue;
 use crate::{types, values};
 
@@ -19,6 +23,13 @@ pub trait BuildInnerUpdate {
     fn build_update(self, field: String) -> Update;
 }
 
+/// Provides filters for identified positional operators (`$[<identifier>]`) used in an update.
+pub trait BuildArrayFilters {
+    fn build_array_filters(&self) -> Vec<bson::Document> {
+        Vec::new()
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 #[derive(Clone, Debug)]
@@ -36,22 +47,9 @@ impl PopOption {
     }
 }
 
-// -------------------------------------------------------------------------------------------------
-
-#[derive(Clone, Debug)]
-pub enum Operator {
-    None,
-
-    /// https://docs.mongodb.com/manual/reference/operator/update/positional/
-    First,
-}
-
-impl Operator {
-    fn to_string(&self) -> &'static str {
-        match self {
-            Operator::None => "",
-            Operator::First => ".$",
-        }
+impl BuildInnerUpdate for PopOption {
+    fn build_update(self, field: String) -> Update {
+        Update::with_operator(UpdateOperator::Pop, field, self.as_number().build_value())
     }
 }
 
@@ -113,6 +111,46 @@ where
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
+pub trait BitwiseUpdate<V>
+where
+    V: BuildValue,
+{
+    fn bit_and(&mut self, mask: V);
+    fn bit_or(&mut self, mask: V);
+    fn bit_xor(&mut self, mask: V);
+}
+
+#[derive(Clone, Debug)]
+pub enum Bitwise<V>
+where
+    V: BuildValue,
+{
+    And(V),
+    Or(V),
+    Xor(V),
+}
+
+impl<V> BuildInnerUpdate for Bitwise<V>
+where
+    V: BuildValue,
+{
+    fn build_update(self, field: String) -> Update {
+        let (operation, mask) = match self {
+            Bitwise::And(mask) => ("and", mask),
+            Bitwise::Or(mask) => ("or", mask),
+            Bitwise::Xor(mask) => ("xor", mask),
+        };
+        let mut value = bson::Document::new();
+        value.insert(operation, mask.build_value().into_bson());
+        let value = values::Value::new(bson::Bson::Document(value));
+        Update::with_operator(UpdateOperator::Bit, field, value)
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
+/// Sets the field to the current date using `$currentDate`. Date fields are set to a date and
+/// timestamp fields to a timestamp.
 pub trait DateUpdate {
     fn current_date(&mut self);
 }
@@ -169,6 +207,8 @@ where
     fn set_element(&mut self, value: V);
     fn at(&mut self, index: usize, update: U);
     fn at_selected(&mut self, update: U);
+    fn at_all(&mut self, update: U);
+    fn at_identified(&mut self, identifier: String, update: U);
 }
 
 #[derive(Clone, Debug)]
@@ -180,6 +220,8 @@ where
     Set(V),
     Indexed(usize, U),
     Selected(U),
+    All(U),
+    Identified(String, U),
 }
 
 impl<U, V> BuildInnerUpdate for Element<U, V>
@@ -198,21 +240,50 @@ where
                 operation.build_update(format!("{}.{}", field, index))
             }
             Element::Selected(operation) => operation.build_update(format!("{}.$", field)),
+            Element::All(operation) => operation.build_update(format!("{}.$[]", field)),
+            Element::Identified(identifier, operation) => {
+                operation.build_update(format!("{}.$[{}]", field, identifier))
+            }
         }
     }
 }
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
-pub trait ArrayUpdate<V>
+/// Appends values to the array. Arrays stored in array elements are updated by passing an update
+/// of the element to `ElementUpdate`, so a positional operator cannot be applied to arrays of
+/// scalars.
+pub trait PushUpdate<V>
+where
+    V: BuildValue,
+{
+    fn push(&mut self, value: values::PushValue<V>);
+
+    /// Pushes all the values returning the modifiers of the `$push` operator for adjustment.
+    fn push_each(&mut self, values: Vec<V>) -> &mut values::Each<V>;
+}
+
+/// Adds values to the array unless they are already present.
+pub trait AddToSetUpdate<V>
+where
+    V: BuildValue,
+{
+    fn add_to_set(&mut self, value: values::PushValue<V>);
+    fn add_to_set_each(&mut self, values: Vec<V>);
+}
+
+/// Removes the values matching a condition or equal to one of the given values.
+pub trait PullUpdate<V>
 where
     V: BuildValue,
 {
-    fn add_to_set(&mut self, value: values::PushValue<V>, operator: Operator);
-    fn pop(&mut self, option: PopOption, operator: Operator);
-    fn pull(&mut self, value: values::PullValue<V>, operator: Operator);
-    fn push(&mut self, value: values::PushValue<V>, operator: Operator);
-    fn pull_all(&mut self, value: V, operator: Operator);
+    fn pull(&mut self, value: values::PullValue<V>);
+    fn pull_all(&mut self, value: V);
+}
+
+/// Removes the first or the last element of the array.
+pub trait PopUpdate {
+    fn pop(&mut self, option: PopOption);
 }
 
 #[derive(Clone, Debug)]
@@ -221,7 +292,7 @@ where
     V: BuildValue,
 {
     AddToSet(values::PushValue<V>),
-    Pop(PopOption),
+    AddToSetEach(Vec<V>),
     Pull(values::PullValue<V>),
     Push(values::PushValue<V>),
     PullAll(V),
@@ -236,8 +307,9 @@ where
             Array::AddToSet(value) => {
                 Update::with_operator(UpdateOperator::AddToSet, field, value.build_value())
             }
-            Array::Pop(option) => {
-                Update::with_operator(UpdateOperator::Pop, field, option.as_number().build_value())
+            Array::AddToSetEach(values) => {
+                let value = values::PushValue::Each(values::Each::new(values)).build_value();
+                Update::with_operator(UpdateOperator::AddToSet, field, value)
             }
             Array::Pull(value) => {
                 Update::with_operator(UpdateOperator::Pull, field, value.build_value())
@@ -262,6 +334,42 @@ pub enum F64Entry {
     Empty,
 }
 
+impl NumericalUpdate<types::Double> for F64Entry {
+    fn inc(&mut self, value: types::Double) {
+        *self = F64Entry::Numerical(Numerical::Inc(value));
+    }
+
+    fn min(&mut self, value: types::Double) {
+        *self = F64Entry::Numerical(Numerical::Min(value));
+    }
+
+    fn max(&mut self, value: types::Double) {
+        *self = F64Entry::Numerical(Numerical::Max(value));
+    }
+
+    fn mul(&mut self, value: types::Double) {
+        *self = F64Entry::Numerical(Numerical::Mul(value));
+    }
+}
+
+impl FieldUpdate<types::Double> for F64Entry {
+    fn rename(&mut self, new_name: String) {
+        *self = F64Entry::Field(Field::Rename(new_name));
+    }
+
+    fn set(&mut self, value: types::Double) {
+        *self = F64Entry::Field(Field::Set(value));
+    }
+
+    fn set_on_insert(&mut self, value: types::Double) {
+        *self = F64Entry::Field(Field::SetOnInsert(value));
+    }
+
+    fn unset(&mut self) {
+        *self = F64Entry::Field(Field::Unset);
+    }
+}
+
 impl BuildInnerUpdate for F64Entry {
     fn build_update(self, field: String) -> Update {
         match self {
@@ -613,36 +721,58 @@ where
     U: BuildInnerUpdate,
     V: BuildValue,
 {
-    Array(Array<V>, Operator),
+    Array(Array<V>),
+    Pop(PopOption),
     Element(Element<U, V>),
     Numerical(Numerical<V>),
     Field(Field<Vec<V>>),
     Empty,
 }
 
-impl<U, V> ArrayUpdate<V> for ArrayEntry<U, V>
+impl<U, V> PushUpdate<V> for ArrayEntry<U, V>
 where
     U: BuildInnerUpdate,
     V: BuildValue,
 {
-    fn add_to_set(&mut self, value: values::PushValue<V>, operator: Operator) {
-        *self = ArrayEntry::Array(Array::AddToSet(value), operator);
+    fn push(&mut self, value: values::PushValue<V>) {
+        *self = ArrayEntry::Array(Array::Push(value));
+    }
+
+    fn push_each(&mut self, values: Vec<V>) -> &mut values::Each<V> {
+        let each = values::PushValue::Each(values::Each::new(values));
+        *self = ArrayEntry::Array(Array::Push(each));
+        match self {
+            ArrayEntry::Array(Array::Push(values::PushValue::Each(each))) => each,
+            _ => unreachable!("The entry was just set to push"),
+        }
     }
+}
 
-    fn pop(&mut self, option: PopOption, operator: Operator) {
-        *self = ArrayEntry::Array(Array::Pop(option), operator);
+impl<U, V> AddToSetUpdate<V> for ArrayEntry<U, V>
+where
+    U: BuildInnerUpdate,
+    V: BuildValue,
+{
+    fn add_to_set(&mut self, value: values::PushValue<V>) {
+        *self = ArrayEntry::Array(Array::AddToSet(value));
     }
 
-    fn pull(&mut self, value: values::PullValue<V>, operator: Operator) {
-        *self = ArrayEntry::Array(Array::Pull(value), operator);
+    fn add_to_set_each(&mut self, values: Vec<V>) {
+        *self = ArrayEntry::Array(Array::AddToSetEach(values));
     }
+}
 
-    fn push(&mut self, value: values::PushValue<V>, operator: Operator) {
-        *self = ArrayEntry::Array(Array::Push(value), operator);
+impl<U, V> PullUpdate<V> for ArrayEntry<U, V>
+where
+    U: BuildInnerUpdate,
+    V: BuildValue,
+{
+    fn pull(&mut self, value: values::PullValue<V>) {
+        *self = ArrayEntry::Array(Array::Pull(value));
     }
 
-    fn pull_all(&mut self, value: V, operator: Operator) {
-        *self = ArrayEntry::Array(Array::PullAll(value), operator);
+    fn pull_all(&mut self, value: V) {
+        *self = ArrayEntry::Array(Array::PullAll(value));
     }
 }
 
@@ -662,6 +792,14 @@ where
     fn at_selected(&mut self, update: U) {
         *self = ArrayEntry::Element(Element::Selected(update));
     }
+
+    fn at_all(&mut self, update: U) {
+        *self = ArrayEntry::Element(Element::All(update));
+    }
+
+    fn at_identified(&mut self, identifier: String, update: U) {
+        *self = ArrayEntry::Element(Element::Identified(identifier, update));
+    }
 }
 
 impl<U, V> FieldUpdate<Vec<V>> for ArrayEntry<U, V>
@@ -686,6 +824,16 @@ where
     }
 }
 
+impl<U, V> PopUpdate for ArrayEntry<U, V>
+where
+    U: BuildInnerUpdate,
+    V: BuildValue,
+{
+    fn pop(&mut self, option: PopOption) {
+        *self = ArrayEntry::Pop(option);
+    }
+}
+
 impl<U, V> BuildInnerUpdate for ArrayEntry<U, V>
 where
     U: BuildInnerUpdate,
@@ -693,9 +841,8 @@ where
 {
     fn build_update(self, field: String) -> Update {
         match self {
-            ArrayEntry::Array(operation, operator) => {
-                operation.build_update(field + operator.to_string())
-            }
+            ArrayEntry::Array(operation) => operation.build_update(field),
+            ArrayEntry::Pop(option) => option.build_update(field),
             ArrayEntry::Element(operation) => operation.build_update(field),
             ArrayEntry::Numerical(operation) => operation.build_update(field),
             ArrayEntry::Field(operation) => operation.build_update(field),
@@ -851,7 +998,7 @@ impl BuildInnerUpdate for DateEntry {
         match self {
             DateEntry::Value(value) => Update::with_field(field, bson::Bson::UtcDatetime(value)),
             DateEntry::CurrentDate => {
-                let value = "date".to_string().build_value();
+                let value = values::Value::new(bson::Bson::Document(doc! { "$type": "date" }));
                 Update::with_operator(UpdateOperator::CurrentDate, field, value)
             }
             DateEntry::Field(value) => value.build_update(field),
@@ -878,6 +1025,7 @@ impl std::convert::From<types::Date> for DateEntry {
 pub enum I32Entry {
     Value(i32),
     Numerical(Numerical<i32>),
+    Bitwise(Bitwise<i32>),
     Field(Field<i32>),
     Empty,
 }
@@ -900,11 +1048,44 @@ impl NumericalUpdate<i32> for I32Entry {
     }
 }
 
+impl BitwiseUpdate<i32> for I32Entry {
+    fn bit_and(&mut self, mask: i32) {
+        *self = I32Entry::Bitwise(Bitwise::And(mask));
+    }
+
+    fn bit_or(&mut self, mask: i32) {
+        *self = I32Entry::Bitwise(Bitwise::Or(mask));
+    }
+
+    fn bit_xor(&mut self, mask: i32) {
+        *self = I32Entry::Bitwise(Bitwise::Xor(mask));
+    }
+}
+
+impl FieldUpdate<i32> for I32Entry {
+    fn rename(&mut self, new_name: String) {
+        *self = I32Entry::Field(Field::Rename(new_name));
+    }
+
+    fn set(&mut self, value: i32) {
+        *self = I32Entry::Field(Field::Set(value));
+    }
+
+    fn set_on_insert(&mut self, value: i32) {
+        *self = I32Entry::Field(Field::SetOnInsert(value));
+    }
+
+    fn unset(&mut self) {
+        *self = I32Entry::Field(Field::Unset);
+    }
+}
+
 impl BuildInnerUpdate for I32Entry {
     fn build_update(self, field: String) -> Update {
         match self {
             I32Entry::Value(value) => Update::with_field(field, bson::Bson::I32(value)),
             I32Entry::Numerical(value) => value.build_update(field),
+            I32Entry::Bitwise(value) => value.build_update(field),
             I32Entry::Field(value) => value.build_update(field),
             I32Entry::Empty => Update::empty(),
         }
@@ -933,12 +1114,36 @@ pub enum TimeStampEntry {
     Empty,
 }
 
+impl DateUpdate for TimeStampEntry {
+    fn current_date(&mut self) {
+        *self = TimeStampEntry::CurrentDate
+    }
+}
+
+impl FieldUpdate<types::TimeStamp> for TimeStampEntry {
+    fn rename(&mut self, new_name: String) {
+        *self = TimeStampEntry::Field(Field::Rename(new_name));
+    }
+
+    fn set(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Field(Field::Set(value));
+    }
+
+    fn set_on_insert(&mut self, value: types::TimeStamp) {
+        *self = TimeStampEntry::Field(Field::SetOnInsert(value));
+    }
+
+    fn unset(&mut self) {
+        *self = TimeStampEntry::Field(Field::Unset);
+    }
+}
+
 impl BuildInnerUpdate for TimeStampEntry {
     fn build_update(self, field: String) -> Update {
         match self {
             TimeStampEntry::Value(value) => Update::with_field(field, value.huus_into_bson()),
             TimeStampEntry::CurrentDate => {
-                let value = "timestamp".to_string().build_value();
+                let value = values::Value::new(bson::Bson::Document(doc! { "$type": "timestamp" }));
                 Update::with_operator(UpdateOperator::CurrentDate, field, value)
             }
             TimeStampEntry::Field(value) => value.build_update(field),
@@ -959,15 +1164,67 @@ impl Default for TimeStampEntry {
 pub enum I64Entry {
     Value(i64),
     Numerical(Numerical<i64>),
+    Bitwise(Bitwise<i64>),
     Field(Field<i64>),
     Empty,
 }
 
+impl NumericalUpdate<i64> for I64Entry {
+    fn inc(&mut self, value: i64) {
+        *self = I64Entry::Numerical(Numerical::Inc(value));
+    }
+
+    fn min(&mut self, value: i64) {
+        *self = I64Entry::Numerical(Numerical::Min(value));
+    }
+
+    fn max(&mut self, value: i64) {
+        *self = I64Entry::Numerical(Numerical::Max(value));
+    }
+
+    fn mul(&mut self, value: i64) {
+        *self = I64Entry::Numerical(Numerical::Mul(value));
+    }
+}
+
+impl BitwiseUpdate<i64> for I64Entry {
+    fn bit_and(&mut self, mask: i64) {
+        *self = I64Entry::Bitwise(Bitwise::And(mask));
+    }
+
+    fn bit_or(&mut self, mask: i64) {
+        *self = I64Entry::Bitwise(Bitwise::Or(mask));
+    }
+
+    fn bit_xor(&mut self, mask: i64) {
+        *self = I64Entry::Bitwise(Bitwise::Xor(mask));
+    }
+}
+
+impl FieldUpdate<i64> for I64Entry {
+    fn rename(&mut self, new_name: String) {
+        *self = I64Entry::Field(Field::Rename(new_name));
+    }
+
+    fn set(&mut self, value: i64) {
+        *self = I64Entry::Field(Field::Set(value));
+    }
+
+    fn set_on_insert(&mut self, value: i64) {
+        *self = I64Entry::Field(Field::SetOnInsert(value));
+    }
+
+    fn unset(&mut self) {
+        *self = I64Entry::Field(Field::Unset);
+    }
+}
+
 impl BuildInnerUpdate for I64Entry {
     fn build_update(self, field: String) -> Update {
         match self {
             I64Entry::Value(value) => Update::with_field(field, bson::Bson::I64(value)),
             I64Entry::Numerical(value) => value.build_update(field),
+            I64Entry::Bitwise(value) => value.build_update(field),
             I64Entry::Field(value) => value.build_update(field),
             I64Entry::Empty => Update::empty(),
         }
@@ -988,6 +1245,50 @@ impl std::convert::From<i64> for I64Entry {
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
+#[derive(Clone, Debug, Default)]
+pub enum GeoPointEntry {
+    Value(types::GeoPoint),
+    Field(Field<types::GeoPoint>),
+    #[default]
+    Empty,
+}
+
+impl FieldUpdate<types::GeoPoint> for GeoPointEntry {
+    fn rename(&mut self, new_name: String) {
+        *self = GeoPointEntry::Field(Field::Rename(new_name));
+    }
+
+    fn set(&mut self, value: types::GeoPoint) {
+        *self = GeoPointEntry::Field(Field::Set(value));
+    }
+
+    fn set_on_insert(&mut self, value: types::GeoPoint) {
+        *self = GeoPointEntry::Field(Field::SetOnInsert(value));
+    }
+
+    fn unset(&mut self) {
+        *self = GeoPointEntry::Field(Field::Unset);
+    }
+}
+
+impl BuildInnerUpdate for GeoPointEntry {
+    fn build_update(self, field: String) -> Update {
+        match self {
+            GeoPointEntry::Value(value) => Update::with_field(field, value.huus_into_bson()),
+            GeoPointEntry::Field(value) => value.build_update(field),
+            GeoPointEntry::Empty => Update::empty(),
+        }
+    }
+}
+
+impl std::convert::From<types::GeoPoint> for GeoPointEntry {
+    fn from(value: types::GeoPoint) -> GeoPointEntry {
+        GeoPointEntry::Value(value)
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
 #[derive(Clone, Debug)]
 pub enum BsonEntry {
     Value(bson::Document),
@@ -1052,6 +1353,7 @@ enum UpdateOperator {
     Push,
     PullAll,
     CurrentDate,
+    Bit,
 }
 
 impl UpdateOperator {
@@ -1071,6 +1373,7 @@ impl UpdateOperator {
             UpdateOperator::Push => "$push",
             UpdateOperator::PullAll => "$pullAll",
             UpdateOperator::CurrentDate => "$currentDate",
+            UpdateOperator::Bit => "$bit",
         }
     }
 }
@@ -1078,6 +1381,7 @@ impl UpdateOperator {
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct UpdateInstruction {
     path: Vec<String>,
     value: values::Value,
@@ -1087,11 +1391,16 @@ impl UpdateInstruction {
     fn new(field: String, value: values::Value) -> Self {
         Self { path: vec![field], value: value }
     }
+
+    fn get_path_string(&self) -> String {
+        self.path.join(".")
+    }
 }
 
 // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Update {
     doc: bson::Document,
     inc_instructions: Vec<UpdateInstruction>,
@@ -1108,6 +1417,8 @@ pub struct Update {
     push_instructions: Vec<UpdateInstruction>,
     pull_all_instructions: Vec<UpdateInstruction>,
     current_date_instructions: Vec<UpdateInstruction>,
+    bit_instructions: Vec<UpdateInstruction>,
+    array_filters: Vec<bson::Document>,
 }
 
 impl Update {
@@ -1128,6 +1439,8 @@ impl Update {
             push_instructions: Vec::new(),
             pull_all_instructions: Vec::new(),
             current_date_instructions: Vec::new(),
+            bit_instructions: Vec::new(),
+            array_filters: Vec::new(),
         }
     }
 
@@ -1137,6 +1450,95 @@ impl Update {
         update
     }
 
+    /// Adds a filter selecting array elements for an identified positional operator
+    /// (`Element::Identified`) used in the update, e.g. `{ "el.value": { "$gt": 5 } }` for `el`.
+    pub fn with_array_filter(mut self, filter: bson::Document) -> Self {
+        self.array_filters.push(filter);
+        self
+    }
+
+    pub fn get_array_filters(&self) -> &Vec<bson::Document> {
+        &self.array_filters
+    }
+
+    /// Builds an update renaming `from` to `to`. Both have to be distinct fields of the schema,
+    /// possibly nested in structures (e.g. `data.int`), of the same BSON type.
+    pub fn rename_to<S: Schema>(from: &str, to: &str) -> Result<Self, UpdateError> {
+        if from == to {
+            return Err(UpdateError::RenameToSelf { path: from.to_string() });
+        }
+        let types = S::get_field_types();
+        let get_type = |path: &str| types.iter().find(|(p, _)| *p == path).map(|(_, t)| *t);
+        match (get_type(from), get_type(to)) {
+            (None, _) => Err(UpdateError::UnknownField { path: from.to_string() }),
+            (_, None) => Err(UpdateError::UnknownField { path: to.to_string() }),
+            (Some(source), Some(target)) if source != target => {
+                let (path, other_path) = (from.to_string(), to.to_string());
+                Err(UpdateError::IncompatibleTypes { path, other_path })
+            }
+            _ => Ok(Field::<bool>::Rename(to.to_string()).build_update(from.to_string())),
+        }
+    }
+
+    /// Builds an update unsetting all the given fields.
+    pub fn unset_fields<I>(paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut update = Update::empty();
+        for path in paths {
+            let value = true.build_value();
+            update.incorporate(Update::with_operator(UpdateOperator::Unset, path.into(), value));
+        }
+        update
+    }
+
+    /// Builds an update changing the `old` document into the `new` one. Changed and added fields
+    /// are set and removed fields are unset. Subdocuments are compared field by field while other
+    /// values, including arrays, are replaced as a whole. The top-level `_id` is skipped as it
+    /// cannot be modified.
+    pub fn diff(old: &bson::Document, new: &bson::Document) -> Self {
+        fn diff(update: &mut Update, prefix: &str, old: &bson::Document, new: &bson::Document) {
+            for (key, new_value) in new.iter() {
+                if prefix.is_empty() && key == "_id" {
+                    continue;
+                }
+                let path = prefix.to_string() + key;
+                match (old.get(key), new_value) {
+                    (Some(old_value), _) if old_value == new_value => {}
+                    (Some(bson::Bson::Document(old_doc)), bson::Bson::Document(new_doc)) => {
+                        diff(update, &(path + "."), old_doc, new_doc);
+                    }
+                    _ => {
+                        let value = values::Value::new(new_value.clone());
+                        update.incorporate(Update::with_operator(UpdateOperator::Set, path, value));
+                    }
+                }
+            }
+            for key in old.keys() {
+                if prefix.is_empty() && key == "_id" {
+                    continue;
+                }
+                if !new.contains_key(key) {
+                    let path = prefix.to_string() + key;
+                    let value = true.build_value();
+                    update.incorporate(Update::with_operator(UpdateOperator::Unset, path, value));
+                }
+            }
+        }
+
+        let mut update = Update::empty();
+        diff(&mut update, "", old, new);
+        update
+    }
+
+    /// Checks if the update contains neither fields nor operator instructions.
+    pub fn is_empty(&self) -> bool {
+        self.doc.is_empty()
+            && self.get_instructions().iter().all(|(_, instructions)| instructions.is_empty())
+    }
+
     fn with_operator(operator: UpdateOperator, field: String, value: values::Value) -> Self {
         let mut update = Update::empty();
         let instruction = UpdateInstruction::new(field, value);
@@ -1155,6 +1557,7 @@ impl Update {
             UpdateOperator::Push => update.push_instructions.push(instruction),
             UpdateOperator::PullAll => update.pull_all_instructions.push(instruction),
             UpdateOperator::CurrentDate => update.current_date_instructions.push(instruction),
+            UpdateOperator::Bit => update.bit_instructions.push(instruction),
         }
         update
     }
@@ -1184,6 +1587,121 @@ impl Update {
         incorporate(update.push_instructions, &mut self.push_instructions);
         incorporate(update.pull_all_instructions, &mut self.pull_all_instructions);
         incorporate(update.current_date_instructions, &mut self.current_date_instructions);
+        incorporate(update.bit_instructions, &mut self.bit_instructions);
+
+        for filter in update.array_filters {
+            if !self.array_filters.contains(&filter) {
+                self.array_filters.push(filter);
+            }
+        }
+    }
+
+    /// Checks that no two operators modify the same path or overlapping paths (like `a` and `a.b`),
+    /// which the database would reject.
+    pub fn validate(&self) -> Result<(), UpdateError> {
+        fn is_overlapping(path: &str, other: &str) -> bool {
+            let (shorter, longer) =
+                if path.len() <= other.len() { (path, other) } else { (other, path) };
+            longer.starts_with(shorter)
+                && (longer.len() == shorter.len() || longer[shorter.len()..].starts_with('.'))
+        }
+
+        let mut paths = Vec::new();
+        for (operator, instructions) in self.get_instructions() {
+            for instruction in instructions {
+                paths.push((operator.to_string(), instruction.get_path_string()));
+                if let UpdateOperator::Rename = operator {
+                    if let bson::Bson::String(target) = instruction.value.clone().into_bson() {
+                        paths.push((operator.to_string(), target));
+                    }
+                }
+            }
+        }
+
+        for (i, (operator, path)) in paths.iter().enumerate() {
+            for (other_operator, other_path) in paths.iter().skip(i + 1) {
+                // Repeated instructions of one operator overwrite each other.
+                if operator == other_operator && path == other_path {
+                    continue;
+                }
+                if is_overlapping(path, other_path) {
+                    return Err(UpdateError::conflict(
+                        operator.to_string(),
+                        path.clone(),
+                        other_operator.to_string(),
+                        other_path.clone(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the update document after checking it with `validate`.
+    pub fn try_into_doc(self) -> Result<bson::Document, UpdateError> {
+        self.validate()?;
+        Ok(self.into_doc())
+    }
+
+    fn get_instructions(&self) -> Vec<(UpdateOperator, &Vec<UpdateInstruction>)> {
+        vec![
+            (UpdateOperator::Inc, &self.inc_instructions),
+            (UpdateOperator::Min, &self.min_instructions),
+            (UpdateOperator::Max, &self.max_instructions),
+            (UpdateOperator::Mul, &self.mul_instructions),
+            (UpdateOperator::Rename, &self.rename_instructions),
+            (UpdateOperator::Set, &self.set_instructions),
+            (UpdateOperator::SetOnInsert, &self.set_on_insert_instructions),
+            (UpdateOperator::Unset, &self.unset_instructions),
+            (UpdateOperator::AddToSet, &self.add_to_set_instructions),
+            (UpdateOperator::Pop, &self.pop_instructions),
+            (UpdateOperator::Pull, &self.pull_instructions),
+            (UpdateOperator::Push, &self.push_instructions),
+            (UpdateOperator::PullAll, &self.pull_all_instructions),
+            (UpdateOperator::CurrentDate, &self.current_date_instructions),
+            (UpdateOperator::Bit, &self.bit_instructions),
+        ]
+    }
+
+    fn get_instructions_mut(&mut self) -> Vec<(UpdateOperator, &mut Vec<UpdateInstruction>)> {
+        vec![
+            (UpdateOperator::Inc, &mut self.inc_instructions),
+            (UpdateOperator::Min, &mut self.min_instructions),
+            (UpdateOperator::Max, &mut self.max_instructions),
+            (UpdateOperator::Mul, &mut self.mul_instructions),
+            (UpdateOperator::Rename, &mut self.rename_instructions),
+            (UpdateOperator::Set, &mut self.set_instructions),
+            (UpdateOperator::SetOnInsert, &mut self.set_on_insert_instructions),
+            (UpdateOperator::Unset, &mut self.unset_instructions),
+            (UpdateOperator::AddToSet, &mut self.add_to_set_instructions),
+            (UpdateOperator::Pop, &mut self.pop_instructions),
+            (UpdateOperator::Pull, &mut self.pull_instructions),
+            (UpdateOperator::Push, &mut self.push_instructions),
+            (UpdateOperator::PullAll, &mut self.pull_all_instructions),
+            (UpdateOperator::CurrentDate, &mut self.current_date_instructions),
+            (UpdateOperator::Bit, &mut self.bit_instructions),
+        ]
+    }
+
+    /// Incorporates an update of an inner structure with all its paths (including the targets of
+    /// `$rename`) placed under the given prefix.
+    pub fn incorporate_at(&mut self, prefix: &str, mut update: Update) {
+        let doc = std::mem::replace(&mut update.doc, bson::Document::new());
+        for (key, value) in doc {
+            update.doc.insert_bson(format!("{}.{}", prefix, key), value);
+        }
+        for (operator, instructions) in update.get_instructions_mut() {
+            for instruction in instructions.iter_mut() {
+                instruction.path.insert(0, prefix.to_string());
+                if let UpdateOperator::Rename = operator {
+                    if let bson::Bson::String(target) = instruction.value.clone().into_bson() {
+                        let target = bson::Bson::String(format!("{}.{}", prefix, target));
+                        instruction.value = values::Value::new(target);
+                    }
+                }
+            }
+        }
+        self.incorporate(update);
     }
 
     pub fn into_doc(self) -> bson::Document {
@@ -1195,13 +1713,10 @@ impl Update {
             if !instructions.is_empty() {
                 let mut bson = bson::Document::new();
                 for instruction in instructions.iter().rev() {
-                    let mut path = String::new();
-                    for field in &instruction.path {
-                        path += &field;
-                        path += ".";
-                    }
-                    path.pop();
-                    bson.insert(path, instruction.value.clone().into_bson());
+                    bson.insert(
+                        instruction.get_path_string(),
+                        instruction.value.clone().into_bson(),
+                    );
                 }
                 result.insert(operator, bson);
             }
@@ -1222,12 +1737,187 @@ impl Update {
         build(&mut res, UpdateOperator::Push.to_string(), self.push_instructions);
         build(&mut res, UpdateOperator::PullAll.to_string(), self.pull_all_instructions);
         build(&mut res, UpdateOperator::CurrentDate.to_string(), self.current_date_instructions);
+        build(&mut res, UpdateOperator::Bit.to_string(), self.bit_instructions);
         res
     }
 }
 
+impl BuildArrayFilters for Update {
+    fn build_array_filters(&self) -> Vec<bson::Document> {
+        self.array_filters.clone()
+    }
+}
+
 impl From<Update> for bson::Bson {
     fn from(update: Update) -> bson::Bson {
         bson::Bson::Document(update.into_doc())
     }
 }
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
+/// Builds an `Update` from paths given at runtime, for cases where the fields to update are not
+/// known upfront. The values are built in the same way as in the generated update structures.
+#[derive(Debug)]
+pub struct UpdateBuilder {
+    update: Update,
+}
+
+impl UpdateBuilder {
+    pub fn new() -> Self {
+        Self { update: Update::empty() }
+    }
+
+    pub fn set<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Field::Set(value))
+    }
+
+    pub fn set_on_insert<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Field::SetOnInsert(value))
+    }
+
+    pub fn unset(self, path: &str) -> Self {
+        self.with(path, Field::<bool>::Unset)
+    }
+
+    pub fn rename(self, path: &str, new_name: &str) -> Self {
+        self.with(path, Field::<bool>::Rename(new_name.to_string()))
+    }
+
+    pub fn inc<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Numerical::Inc(value))
+    }
+
+    pub fn mul<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Numerical::Mul(value))
+    }
+
+    pub fn min<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Numerical::Min(value))
+    }
+
+    pub fn max<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Numerical::Max(value))
+    }
+
+    pub fn current_date(self, path: &str) -> Self {
+        self.with(path, DateEntry::CurrentDate)
+    }
+
+    pub fn push<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Array::Push(values::PushValue::Value(value)))
+    }
+
+    pub fn push_each<V: BuildValue>(self, path: &str, values: Vec<V>) -> Self {
+        self.with(path, Array::Push(values::PushValue::Each(values::Each::new(values))))
+    }
+
+    pub fn add_to_set<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Array::AddToSet(values::PushValue::Value(value)))
+    }
+
+    pub fn add_to_set_each<V: BuildValue>(self, path: &str, values: Vec<V>) -> Self {
+        self.with(path, Array::AddToSetEach(values))
+    }
+
+    pub fn pull<V: BuildValue>(self, path: &str, value: V) -> Self {
+        self.with(path, Array::Pull(values::PullValue::Value(value)))
+    }
+
+    pub fn pop(self, path: &str, option: PopOption) -> Self {
+        self.with(path, option)
+    }
+
+    pub fn array_filter(mut self, filter: bson::Document) -> Self {
+        self.update = self.update.with_array_filter(filter);
+        self
+    }
+
+    pub fn build(self) -> Update {
+        self.update
+    }
+
+    fn with<U: BuildInnerUpdate>(mut self, path: &str, update: U) -> Self {
+        self.update.incorporate(update.build_update(path.to_string()));
+        self
+    }
+}
+
+impl Default for UpdateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+
+/// Represents an update expressed as an aggregation pipeline. Requires MongoDB 4.2 or newer.
+///
+/// https://docs.mongodb.com/manual/tutorial/update-documents-with-aggregation-pipeline/
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineUpdate {
+    stages: Vec<bson::Document>,
+}
+
+impl PipelineUpdate {
+    pub fn new(stages: Vec<bson::Document>) -> Self {
+        Self { stages }
+    }
+
+    pub fn from_stages(stages: Vec<UpdateStage>) -> Self {
+        Self { stages: stages.into_iter().map(UpdateStage::into_doc).collect() }
+    }
+
+    pub fn with_stage(mut self, stage: UpdateStage) -> Self {
+        self.stages.push(stage.into_doc());
+        self
+    }
+
+    pub fn get_stages(&self) -> &Vec<bson::Document> {
+        &self.stages
+    }
+
+    pub fn into_bson(self) -> bson::Bson {
+        bson::Bson::Array(self.stages.into_iter().map(bson::Bson::Document).collect())
+    }
+}
+
+impl From<PipelineUpdate> for bson::Bson {
+    fn from(update: PipelineUpdate) -> bson::Bson {
+        update.into_bson()
+    }
+}
+
+/// A stage of an update pipeline. The values may contain aggregation expressions, e.g. `"$field"`.
+#[derive(Clone, Debug)]
+pub enum UpdateStage {
+    /// Adds new fields or replaces the existing ones.
+    Set(Vec<(String, values::Value)>),
+
+    /// Removes the fields.
+    Unset(Vec<String>),
+
+    /// Replaces the whole document with the given one.
+    ReplaceRoot(values::Value),
+}
+
+impl UpdateStage {
+    pub fn into_doc(self) -> bson::Document {
+        match self {
+            UpdateStage::Set(fields) => {
+                let mut set = bson::Document::new();
+                for (field, value) in fields {
+                    set.insert(field, value.into_bson());
+                }
+                doc! { "$set": set }
+            }
+            UpdateStage::Unset(fields) => {
+                let fields = fields.into_iter().map(bson::Bson::String).collect();
+                doc! { "$unset": bson::Bson::Array(fields) }
+            }
+            UpdateStage::ReplaceRoot(root) => {
+                doc! { "$replaceRoot": { "newRoot": root.into_bson() } }
+            }
+        }
+    }
+}
@@ -70,6 +70,12 @@ impl BuildValue for i64 {
     }
 }
 
+impl BuildValue for types::GeoPoint {
+    fn build_value(self) -> Value {
+        Value::new(self.huus_into_bson())
+    }
+}
+
 impl<V> BuildValue for Vec<V>
 where
     V: BuildValue,
@@ -146,6 +152,7 @@ where
 // -------------------------------------------------------------------------------------------------
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Value {
     value: bson::Bson,
 }
@@ -276,14 +283,34 @@ where
 
 // -------------------------------------------------------------------------------------------------
 
+#[derive(Clone, Debug)]
+pub enum Sort {
+    Ascending,
+    Descending,
+    Fields(bson::Document),
+}
+
+impl Sort {
+    pub fn into_bson(self) -> bson::Bson {
+        match self {
+            Sort::Ascending => bson::Bson::I32(1),
+            Sort::Descending => bson::Bson::I32(-1),
+            Sort::Fields(fields) => bson::Bson::Document(fields),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 #[derive(Clone, Debug)]
 pub struct Each<V>
 where
     V: BuildValue,
 {
     pub each: Vec<V>,
-    pub position: Option<usize>,
-    pub slice: Option<usize>,
+    pub position: Option<i64>,
+    pub slice: Option<i64>,
+    pub sort: Option<Sort>,
 }
 
 impl<V> Each<V>
@@ -291,7 +318,22 @@ where
     V: BuildValue,
 {
     pub fn new(each: Vec<V>) -> Self {
-        Self { each: each, position: None, slice: None }
+        Self { each, position: None, slice: None, sort: None }
+    }
+
+    pub fn position(&mut self, position: i64) -> &mut Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn slice(&mut self, slice: i64) -> &mut Self {
+        self.slice = Some(slice);
+        self
+    }
+
+    pub fn sort_by(&mut self, sort: Sort) -> &mut Self {
+        self.sort = Some(sort);
+        self
     }
 }
 
@@ -317,10 +359,13 @@ where
                 let mut result = bson::Document::new();
                 result.insert("$each", vec_into_array(each.each));
                 if let Some(position) = each.position {
-                    result.insert("$position", position as i64);
+                    result.insert("$position", position);
                 }
                 if let Some(slice) = each.slice {
-                    result.insert("$slice", slice as i64);
+                    result.insert("$slice", slice);
+                }
+                if let Some(sort) = each.sort {
+                    result.insert("$sort", sort.into_bson());
                 }
                 Value::new(bson::Bson::Document(result))
             }
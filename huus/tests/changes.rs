@@ -0,0 +1,62 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `changes` module.
+
+use bson::{bson, doc};
+
+use huus::changes::{ChangeEvent, ChangeStream};
+use huus::errors::HuusError;
+
+/// Change events should be converted according to their operation type.
+#[test]
+fn change_stream_converts_events() {
+    let events: Vec<Result<bson::Document, HuusError>> = vec![
+        Ok(doc! {
+            "_id": { "_data": "1" },
+            "operationType": "insert",
+            "documentKey": { "_id": 1 },
+            "fullDocument": { "_id": 1, "a": 1 },
+        }),
+        Ok(doc! {
+            "_id": { "_data": "2" },
+            "operationType": "update",
+            "documentKey": { "_id": 1 },
+            "updateDescription": { "updatedFields": { "a": 2 }, "removedFields": ["b"] },
+        }),
+        Ok(doc! {
+            "_id": { "_data": "3" },
+            "operationType": "replace",
+            "documentKey": { "_id": 1 },
+        }),
+        Ok(doc! {
+            "_id": { "_data": "4" },
+            "operationType": "delete",
+            "documentKey": { "_id": 1 },
+        }),
+        Ok(doc! { "_id": { "_data": "5" }, "operationType": "drop" }),
+    ];
+
+    let mut stream: ChangeStream<bson::Document, _> = ChangeStream::new(events.into_iter());
+    assert_eq!(
+        stream.next().unwrap().unwrap(),
+        ChangeEvent::Insert { id: bson::Bson::I32(1), document: doc! { "_id": 1, "a": 1 } }
+    );
+    assert_eq!(
+        stream.next().unwrap().unwrap(),
+        ChangeEvent::Update {
+            id: bson::Bson::I32(1),
+            updated_fields: doc! { "a": 2 },
+            removed_fields: vec!["b".to_string()],
+            document: None,
+        }
+    );
+    assert!(stream.next().unwrap().is_err());
+    assert_eq!(stream.next().unwrap().unwrap(), ChangeEvent::Delete { id: bson::Bson::I32(1) });
+    assert_eq!(
+        stream.next().unwrap().unwrap(),
+        ChangeEvent::Other { operation_type: "drop".to_string() }
+    );
+    assert!(stream.next().is_none());
+    assert_eq!(stream.get_resume_token(), Some(&doc! { "_data": "5" }));
+}
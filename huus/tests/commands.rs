@@ -23,7 +23,58 @@ fn create_indexes_command() {
             },
         }],
     };
-    assert_eq!(*command.get_command().unwrap(), expected);
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Index options should be passed in the index specification.
+#[test]
+fn create_indexes_command_with_options() {
+    let unique = IndexModel::new(doc! { "a": 1, "b": -1 })
+        .with_options(IndexOptions::new().with_unique(true).with_sparse(true));
+    let ttl = IndexModel::new(doc! { "created": 1 }).with_options(
+        IndexOptions::new()
+            .with_name("ttl".to_string())
+            .with_expire_after_seconds(3600)
+            .with_partial_filter(doc! { "c": { "$exists": true } }),
+    );
+    let command =
+        CreateIndexesCommand::new_models("coll".to_string(), vec![unique]).with_index(ttl);
+    let expected = doc! {
+        "createIndexes": "coll",
+        "indexes": [{
+            "name": "a_1_b_-1",
+            "key": { "a": 1, "b": -1 },
+            "unique": true,
+            "sparse": true,
+        }, {
+            "name": "ttl",
+            "key": { "created": 1 },
+            "expireAfterSeconds": 3600i64,
+            "partialFilterExpression": { "c": { "$exists": true } },
+        }],
+    };
+    assert_eq!(command.get_command(), expected);
+    assert!(!command.is_empty());
+
+    let command = CreateIndexesCommand::new_models("coll".to_string(), Vec::new());
+    assert!(command.is_empty());
+    assert_eq!(command.get_command(), doc! { "createIndexes": "coll", "indexes": [] });
+}
+
+/// Counting documents should be done with an aggregation matching the filter.
+#[test]
+fn create_count_documents_command() {
+    let collection = "collection".to_string();
+    let command = CountDocumentsCommand::new(collection.clone(), doc! { "a": 1 });
+    let expected = doc! {
+        "aggregate": collection.clone(),
+        "pipeline": [
+            { "$match": { "a": 1 } },
+            { "$group": { "_id": 1, "n": { "$sum": 1 } } },
+        ],
+        "cursor": {},
+    };
+    assert_eq!(command.get_command(), expected);
 }
 
 /// When document passed to insert command contains `_id` the document should not be changed.
@@ -46,3 +97,651 @@ fn create_insert_command_without_id() {
     assert_eq!(*command.get_document().get("a").unwrap(), bson::Bson::I32(1));
     assert_eq!(*command.get_document().get("b").unwrap(), bson::Bson::I32(2));
 }
+
+/// Documents passed to insert many command without `_id` should get random `_id` added.
+#[test]
+fn create_insert_many_command() {
+    let collection = "collection".to_string();
+    let docs = vec![doc! { "_id": 0, "a": 1 }, doc! { "a": 2 }];
+    let command = InsertManyCommand::new(collection.clone(), docs);
+
+    let documents = command.get_documents();
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[0], doc! { "_id": 0, "a": 1 });
+    assert!(documents[1].get("_id").is_some());
+    assert_eq!(*documents[1].get("a").unwrap(), bson::Bson::I32(2));
+}
+
+/// Documents rejected due to duplicate keys should be reported separately from the inserted ones.
+#[test]
+fn insert_many_command_duplicate_keys() {
+    let docs = vec![doc! { "_id": 0 }, doc! { "_id": 1 }, doc! { "_id": 2 }];
+    let reply = doc! { "writeErrors": [{ "index": 1, "code": 11000, "errmsg": "duplicate" }] };
+
+    let command = InsertManyCommand::new("collection".to_string(), docs.clone());
+    let expected = InsertManyResult {
+        inserted_ids: vec![bson::Bson::I32(0)],
+        duplicate_ids: vec![bson::Bson::I32(1)],
+    };
+    assert_eq!(command.get_result(&reply), Some(expected));
+
+    let command = InsertManyCommand::new("collection".to_string(), docs).with_ordered(false);
+    let expected = InsertManyResult {
+        inserted_ids: vec![bson::Bson::I32(0), bson::Bson::I32(2)],
+        duplicate_ids: vec![bson::Bson::I32(1)],
+    };
+    assert_eq!(command.get_result(&reply), Some(expected));
+
+    let reply = doc! { "writeErrors": [{ "index": 1, "code": 121, "errmsg": "validation" }] };
+    assert_eq!(command.get_result(&reply), None);
+}
+
+/// Array filters should be passed along with the update statement.
+#[test]
+fn create_update_command_with_array_filters() {
+    let collection = "collection".to_string();
+    let filter = doc! { "a": 1 };
+    let update = doc! { "$set": { "b.$[el].c": 2 } };
+    let command = UpdateCommand::new(collection.clone(), filter, update, UpdateOptions::UpdateMany)
+        .with_array_filters(vec![doc! { "el.c": { "$gt": 1 } }]);
+    let expected = doc! {
+        "update": collection.clone(),
+        "updates": [{
+            "q": { "a": 1 },
+            "u": { "$set": { "b.$[el].c": 2 } },
+            "multi": true,
+            "arrayFilters": [{ "el.c": { "$gt": 1 } }],
+        }],
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Upsert flag should be passed along with the update statement.
+#[test]
+fn create_update_command_with_upsert() {
+    let update = doc! { "$set": { "b": 2 } };
+    let command = UpdateCommand::new(
+        "collection".to_string(),
+        doc! { "a": 1 },
+        update,
+        UpdateOptions::UpdateOne,
+    )
+    .with_upsert(true);
+    let expected = doc! {
+        "update": "collection",
+        "updates": [{
+            "q": { "a": 1 },
+            "u": { "$set": { "b": 2 } },
+            "multi": false,
+            "upsert": true,
+        }],
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Pipeline updates should be serialized as an array of stages.
+#[test]
+fn create_update_command_with_pipeline() {
+    let collection = "collection".to_string();
+    let filter = doc! { "a": 1 };
+    let pipeline = huus::updates::PipelineUpdate::new(vec![
+        doc! { "$set": { "b": "$c" } },
+        doc! { "$unset": ["c"] },
+    ]);
+    let command =
+        UpdateCommand::new_pipeline(collection.clone(), filter, pipeline, UpdateOptions::UpdateOne);
+    let expected = doc! {
+        "update": collection.clone(),
+        "updates": [{
+            "q": { "a": 1 },
+            "u": [{ "$set": { "b": "$c" } }, { "$unset": ["c"] }],
+            "multi": false,
+        }],
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Pipeline updates can be built from typed stages.
+#[test]
+fn create_update_command_with_pipeline_stages() {
+    use huus::updates::{PipelineUpdate, UpdateStage};
+    use huus::values::BuildValue;
+
+    let pipeline = PipelineUpdate::from_stages(vec![
+        UpdateStage::Set(vec![
+            ("b".to_string(), "$c".to_string().build_value()),
+            ("d".to_string(), 3i64.build_value()),
+        ]),
+        UpdateStage::Unset(vec!["c".to_string()]),
+    ])
+    .with_stage(UpdateStage::ReplaceRoot("$b".to_string().build_value()));
+    let expected = PipelineUpdate::new(vec![
+        doc! { "$set": { "b": "$c", "d": 3i64 } },
+        doc! { "$unset": ["c"] },
+        doc! { "$replaceRoot": { "newRoot": "$b" } },
+    ]);
+    assert_eq!(pipeline, expected);
+}
+
+/// Delete command should limit the number of deleted documents only when deleting one.
+#[test]
+fn create_delete_command() {
+    let collection = "collection".to_string();
+    let filter = doc! { "a": 1 };
+    let command = DeleteCommand::new(collection.clone(), filter.clone(), DeleteOptions::DeleteOne);
+    let expected = doc! {
+        "delete": collection.clone(),
+        "deletes": [{ "q": { "a": 1 }, "limit": 1 }],
+    };
+    assert_eq!(command.get_command(), expected);
+
+    let command = DeleteCommand::new(collection.clone(), filter, DeleteOptions::DeleteMany);
+    let expected = doc! {
+        "delete": collection.clone(),
+        "deletes": [{ "q": { "a": 1 }, "limit": 0 }],
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Deprecated remove command should build the same command as the delete command.
+#[test]
+#[allow(deprecated)]
+fn create_remove_command() {
+    let collection = "collection".to_string();
+    let filter = doc! { "a": 1 };
+    let command = RemoveCommand::new(collection.clone(), filter.clone(), RemoveOptions::RemoveOne);
+    let expected = DeleteCommand::new(collection, filter, DeleteOptions::DeleteOne);
+    assert_eq!(command, expected);
+}
+
+/// Aggregate command should wrap its stages in a pipeline.
+#[test]
+fn create_aggregate_command() {
+    let stages = vec![doc! { "$match": { "a": 1 } }, doc! { "$unwind": "$b" }];
+    let command = AggregateCommand::new("collection".to_string(), stages);
+    let expected = doc! {
+        "pipeline": [{ "$match": { "a": 1 } }, { "$unwind": "$b" }],
+    };
+    assert_eq!(command.get_pipeline(), expected);
+    assert_eq!(command.get_options(), None);
+
+    let command = command.with_allow_disk_use(true).with_batch_size(100);
+    assert_eq!(command.get_options(), Some(doc! { "allowDiskUse": true, "batchSize": 100i64 }));
+
+    let command = command.with_max_time_ms(500);
+    let expected = doc! {
+        "aggregate": "collection",
+        "pipeline": [{ "$match": { "a": 1 } }, { "$unwind": "$b" }],
+        "cursor": { "batchSize": 100i64 },
+        "allowDiskUse": true,
+        "maxTimeMS": 500i64,
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Batch size and time limit should be passed to the `find` command and the time limit also as a
+/// query modifier.
+#[test]
+fn create_find_command_with_batch_size_and_time_limit() {
+    let command: FindCommand<bson::Document> =
+        FindCommand::new("collection".to_string(), doc! { "a": 1 }, None)
+            .with_batch_size(50)
+            .with_max_time_ms(1000);
+    let expected = doc! {
+        "find": "collection",
+        "filter": { "a": 1 },
+        "batchSize": 50i64,
+        "maxTimeMS": 1000i64,
+    };
+    assert_eq!(command.get_command(), expected);
+    assert_eq!(command.get_query(), doc! { "$query": { "a": 1 }, "$maxTimeMS": 1000i64 });
+    assert_eq!(command.to_shell(), "db.collection.find({ a: 1 }).batchSize(50).maxTimeMS(1000)");
+}
+
+/// Sorting of the find one command should be passed to the `find` command and as a query modifier.
+#[test]
+fn create_find_one_command_with_sort() {
+    let command: FindOneCommand<bson::Document> =
+        FindOneCommand::new("collection".to_string(), doc! { "a": 1 }).with_sort(doc! { "b": -1 });
+    let expected = doc! {
+        "find": "collection",
+        "filter": { "a": 1 },
+        "limit": 1i64,
+        "singleBatch": true,
+        "sort": { "b": -1 },
+    };
+    assert_eq!(command.get_command(), expected);
+    assert_eq!(command.get_query(), doc! { "$query": { "a": 1 }, "$orderby": { "b": -1 } });
+    assert_eq!(command.to_shell(), "db.collection.findOne({ a: 1 }, {}, { sort: { b: -1 } })");
+}
+
+/// Replacement should be sent as a whole document updating at most one document.
+#[test]
+fn create_replace_one_command() {
+    let command = ReplaceOneCommand::new(
+        "collection".to_string(),
+        doc! { "a": 1 },
+        doc! { "a": 2, "b": 3 },
+        true,
+    );
+    let expected = doc! {
+        "update": "collection",
+        "updates": [{ "q": { "a": 1 }, "u": { "a": 2, "b": 3 }, "upsert": true, "multi": false }],
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Collation should be passed with the filter of each command supporting it.
+#[test]
+fn create_commands_with_collation() {
+    let collection = "collection".to_string();
+    let collation = Collation::new("en".to_string()).with_strength(2).with_numeric_ordering(true);
+    let expected_collation = doc! { "locale": "en", "strength": 2, "numericOrdering": true };
+    assert_eq!(collation.to_doc(), expected_collation);
+
+    let options = FindOptions::new().with_collation(collation.clone());
+    let command: FindCommand<bson::Document> =
+        FindCommand::new_with_options(collection.clone(), doc! { "a": 1 }, options);
+    let expected = doc! { "$query": { "a": 1 }, "$collation": expected_collation.clone() };
+    assert_eq!(command.get_query(), expected);
+
+    let command = CountDocumentsCommand::new(collection.clone(), doc! { "a": 1 })
+        .with_collation(collation.clone());
+    assert_eq!(command.get_command().get_document("collation"), Ok(&expected_collation));
+
+    let update = doc! { "$set": { "b": 2 } };
+    let command =
+        UpdateCommand::new(collection.clone(), doc! { "a": 1 }, update, UpdateOptions::UpdateOne)
+            .with_collation(collation.clone());
+    let expected = doc! {
+        "update": collection.clone(),
+        "updates": [{
+            "q": { "a": 1 },
+            "u": { "$set": { "b": 2 } },
+            "multi": false,
+            "collation": expected_collation.clone(),
+        }],
+    };
+    assert_eq!(command.get_command(), expected);
+
+    let command = DeleteCommand::new(collection.clone(), doc! { "a": 1 }, DeleteOptions::DeleteOne)
+        .with_collation(collation);
+    let expected = doc! {
+        "delete": collection.clone(),
+        "deletes": [{ "q": { "a": 1 }, "limit": 1, "collation": expected_collation }],
+    };
+    assert_eq!(command.get_command(), expected);
+}
+
+/// Read and write concerns should be passed along with the commands.
+#[test]
+fn create_commands_with_concerns() {
+    let collection = "collection".to_string();
+    let write_concern = WriteConcern::new().with_majority().with_journal(true).with_timeout_ms(100);
+    let expected_write_concern = doc! { "w": "majority", "j": true, "wtimeout": 100i64 };
+    assert_eq!(write_concern.to_doc(), expected_write_concern);
+    assert_eq!(WriteConcern::new().with_nodes(0).to_doc(), doc! { "w": 0 });
+    assert_eq!(ReadConcern::Majority.to_doc(), doc! { "level": "majority" });
+
+    let command = CountDocumentsCommand::new(collection.clone(), doc! { "a": 1 })
+        .with_read_concern(ReadConcern::Local)
+        .with_read_preference(ReadPreference::SecondaryPreferred);
+    let read_concern = command.get_command().get_document("readConcern").cloned();
+    assert_eq!(read_concern, Ok(doc! { "level": "local" }));
+
+    let options = FindOptions::new().with_read_concern(ReadConcern::Available);
+    let command: FindCommand<bson::Document> =
+        FindCommand::new_with_options(collection.clone(), doc! { "a": 1 }, options);
+    let expected = doc! { "$query": { "a": 1 }, "$readConcern": { "level": "available" } };
+    assert_eq!(command.get_query(), expected);
+
+    let command = DeleteCommand::new(collection.clone(), doc! { "a": 1 }, DeleteOptions::DeleteOne)
+        .with_write_concern(write_concern.clone());
+    let expected = doc! {
+        "delete": collection.clone(),
+        "deletes": [{ "q": { "a": 1 }, "limit": 1 }],
+        "writeConcern": expected_write_concern.clone(),
+    };
+    assert_eq!(command.get_command(), expected);
+
+    let command = ReplaceOneCommand::new(collection.clone(), doc! { "a": 1 }, doc! {}, false)
+        .with_write_concern(write_concern);
+    let written = command.get_command().get_document("writeConcern").cloned();
+    assert_eq!(written, Ok(expected_write_concern));
+}
+
+/// Consecutive bulk operations of the same kind should be sent in a single database command.
+#[test]
+fn create_bulk_write_commands() {
+    let operations = vec![
+        WriteOperation::Insert { document: doc! { "_id": 1 } },
+        WriteOperation::Insert { document: doc! { "_id": 2 } },
+        WriteOperation::Update {
+            filter: doc! { "_id": 1 },
+            update: doc! { "$set": { "a": 1 } },
+            array_filters: Vec::new(),
+            multi: false,
+            upsert: false,
+        },
+        WriteOperation::Update {
+            filter: doc! { "_id": 2 },
+            update: doc! { "$set": { "b.$[el]": 1 } },
+            array_filters: vec![doc! { "el": { "$gt": 2 } }],
+            multi: true,
+            upsert: false,
+        },
+        WriteOperation::Replace { filter: doc! { "_id": 2 }, replacement: doc! {}, upsert: true },
+        WriteOperation::Delete { filter: doc! { "_id": 1 }, multi: true },
+    ];
+    let command = BulkWriteCommand::new("collection".to_string(), operations)
+        .with_ordered(false)
+        .with_write_concern(WriteConcern::new().with_nodes(1));
+    let expected = vec![
+        doc! {
+            "insert": "collection",
+            "documents": [{ "_id": 1 }, { "_id": 2 }],
+            "ordered": false,
+            "writeConcern": { "w": 1 },
+        },
+        doc! {
+            "update": "collection",
+            "updates": [
+                { "q": { "_id": 1 }, "u": { "$set": { "a": 1 } }, "multi": false, "upsert": false },
+                {
+                    "q": { "_id": 2 },
+                    "u": { "$set": { "b.$[el]": 1 } },
+                    "multi": true,
+                    "upsert": false,
+                    "arrayFilters": [{ "el": { "$gt": 2 } }],
+                },
+                { "q": { "_id": 2 }, "u": {}, "multi": false, "upsert": true },
+            ],
+            "ordered": false,
+            "writeConcern": { "w": 1 },
+        },
+        doc! {
+            "delete": "collection",
+            "deletes": [{ "q": { "_id": 1 }, "limit": 0 }],
+            "ordered": false,
+            "writeConcern": { "w": 1 },
+        },
+    ];
+    assert_eq!(command.get_commands(), expected);
+}
+
+/// Index hint should be passed as a query modifier.
+#[test]
+fn create_find_command_with_hint() {
+    let command: FindCommand<bson::Document> =
+        FindCommand::new("collection".to_string(), doc! { "a": 1 }, None)
+            .with_hint(Hint::Keys(doc! { "a": 1 }));
+    let expected = doc! { "$query": { "a": 1 }, "$hint": { "a": 1 } };
+    assert_eq!(command.get_query(), expected);
+
+    let options = FindOptions::new().with_hint(Hint::Name("a_1".to_string()));
+    let command: FindCommand<bson::Document> =
+        FindCommand::new_with_options("collection".to_string(), doc! { "a": 1 }, options);
+    let expected = doc! { "$query": { "a": 1 }, "$hint": "a_1" };
+    assert_eq!(command.get_query(), expected);
+}
+
+/// Explain command should wrap the equivalent database command of the explained query.
+#[test]
+fn create_explain_command() {
+    let options = FindOptions::new().with_sort(doc! { "a": 1 }).with_limit(5);
+    let command: FindCommand<bson::Document> =
+        FindCommand::new_with_options("collection".to_string(), doc! { "a": 1 }, options);
+    let expected = doc! {
+        "explain": {
+            "find": "collection",
+            "filter": { "a": 1 },
+            "sort": { "a": 1 },
+            "limit": 5i64,
+        },
+        "verbosity": "executionStats",
+    };
+    assert_eq!(command.explain(Verbosity::ExecutionStats).get_command(), expected);
+
+    let command = AggregateCommand::new("collection".to_string(), vec![doc! { "$match": {} }])
+        .with_batch_size(10);
+    let expected = doc! {
+        "explain": {
+            "aggregate": "collection",
+            "pipeline": [{ "$match": {} }],
+            "cursor": { "batchSize": 10i64 },
+        },
+        "verbosity": "queryPlanner",
+    };
+    assert_eq!(command.explain(Verbosity::QueryPlanner).get_command(), expected);
+}
+
+/// Explain result should list the stages and indexes of the winning plan.
+#[test]
+fn read_explain_result() {
+    let plan = doc! {
+        "stage": "FETCH",
+        "inputStage": {
+            "stage": "OR",
+            "inputStages": [
+                { "stage": "IXSCAN", "indexName": "a_1" },
+                { "stage": "IXSCAN", "indexName": "b_1" },
+            ],
+        },
+    };
+    let reply = doc! {
+        "queryPlanner": { "winningPlan": plan.clone(), "rejectedPlans": [] },
+        "executionStats": { "nReturned": 2 },
+    };
+    let result = ExplainResult::from_reply(reply).unwrap();
+    assert_eq!(result.winning_plan, plan);
+    assert_eq!(result.stages, vec!["FETCH", "OR", "IXSCAN", "IXSCAN"]);
+    assert_eq!(result.indexes, vec!["a_1", "b_1"]);
+    assert_eq!(result.execution_stats, Some(doc! { "nReturned": 2 }));
+    assert!(result.uses_index("a_1"));
+    assert!(!result.is_collection_scan());
+
+    let reply = doc! {
+        "stages": [
+            { "$cursor": { "queryPlanner": { "winningPlan": { "stage": "COLLSCAN" } } } },
+            { "$group": {} },
+        ],
+    };
+    let result = ExplainResult::from_reply(reply).unwrap();
+    assert_eq!(result.stages, vec!["COLLSCAN"]);
+    assert!(result.is_collection_scan());
+
+    assert!(ExplainResult::from_reply(doc! { "ok": 1 }).is_err());
+}
+
+/// Watching should open a change stream followed by the given stages.
+#[test]
+fn create_watch_command() {
+    let command: WatchCommand<bson::Document> = WatchCommand::new("collection".to_string())
+        .with_stages(vec![doc! { "$match": { "operationType": "insert" } }])
+        .with_full_document(true)
+        .with_resume_after(doc! { "_data": "1" });
+    let expected = doc! {
+        "pipeline": [
+            {
+                "$changeStream": {
+                    "fullDocument": "updateLookup",
+                    "resumeAfter": { "_data": "1" },
+                },
+            },
+            { "$match": { "operationType": "insert" } },
+        ],
+    };
+    assert_eq!(command.get_pipeline(), expected);
+}
+
+/// Update result should distinguish matched documents from the upserted one.
+#[test]
+fn read_update_result() {
+    let reply = doc! { "n": 3, "nModified": 2, "ok": 1.0 };
+    let expected = UpdateResult { matched: 3, modified: 2, upserted_id: None };
+    assert_eq!(UpdateResult::from_reply(&reply).unwrap(), expected);
+
+    let reply = doc! { "n": 1, "nModified": 0, "upserted": [{ "index": 0, "_id": 7 }], "ok": 1.0 };
+    let expected = UpdateResult { matched: 0, modified: 0, upserted_id: Some(bson::Bson::I32(7)) };
+    assert_eq!(UpdateResult::from_reply(&reply).unwrap(), expected);
+
+    assert!(UpdateResult::from_reply(&doc! { "ok": 1.0 }).is_err());
+}
+
+/// Commands should be serialized to Extended JSON as the database commands they send.
+#[test]
+fn commands_to_extended_json() {
+    use huus::json::JsonMode;
+
+    let command = DeleteCommand::new("coll".to_string(), doc! { "a": 1 }, DeleteOptions::DeleteOne);
+    assert_eq!(
+        command.to_extended_json(JsonMode::Relaxed),
+        r#"{"delete":"coll","deletes":[{"q":{"a":1},"limit":1}]}"#
+    );
+    assert_eq!(
+        command.to_extended_json(JsonMode::Canonical),
+        r#"{"delete":"coll","deletes":[{"q":{"a":{"$numberInt":"1"}},"limit":{"$numberInt":"1"}}]}"#
+    );
+
+    let command = CreateIndexesCommand::new("coll".to_string(), Vec::new());
+    assert_eq!(
+        command.to_extended_json(JsonMode::Relaxed),
+        r#"{"createIndexes":"coll","indexes":[]}"#
+    );
+}
+
+/// Commands should be rendered as `mongosh` snippets.
+#[test]
+fn commands_to_shell() {
+    let options = FindOptions::new()
+        .with_sort(doc! { "a": -1 })
+        .with_limit(10)
+        .with_read_preference(ReadPreference::Secondary);
+    let command: FindCommand<bson::Document> =
+        FindCommand::new_with_options("coll_3".to_string(), doc! { "a": { "$gt": 1 } }, options);
+    assert_eq!(
+        command.to_string(),
+        r#"db.coll_3.find({ a: { $gt: 1 } }).sort({ a: -1 }).limit(10).readPref("secondary")"#
+    );
+
+    let command = UpdateCommand::new(
+        "my-coll".to_string(),
+        doc! { "a": 1 },
+        doc! { "$set": { "b": 2 } },
+        UpdateOptions::UpdateMany,
+    )
+    .with_upsert(true);
+    assert_eq!(
+        command.to_shell(),
+        r#"db.getCollection("my-coll").updateMany({ a: 1 }, { $set: { b: 2 } }, { upsert: true })"#
+    );
+
+    let operations = vec![
+        WriteOperation::Insert { document: doc! { "a": 1 } },
+        WriteOperation::Delete { filter: doc! { "a": 2 }, multi: true },
+    ];
+    let command = BulkWriteCommand::new("coll".to_string(), operations).with_ordered(false);
+    assert_eq!(
+        command.to_shell(),
+        concat!(
+            r#"db.coll.bulkWrite([ { insertOne: { document: { a: 1 } } }, "#,
+            r#"{ deleteMany: { filter: { a: 2 } } } ], { ordered: false })"#,
+        )
+    );
+
+    let command = CountDocumentsCommand::new("coll".to_string(), doc! {})
+        .with_read_concern(ReadConcern::Majority);
+    assert_eq!(
+        command.to_shell(),
+        r#"db.coll.countDocuments({}, { readConcern: { level: "majority" } })"#
+    );
+}
+
+/// Collection options should be passed in the `create` command.
+#[test]
+fn create_collection_command_with_options() {
+    let validator = doc! { "$jsonSchema": { "bsonType": "object", "required": ["a"] } };
+    let command = CreateCollectionCommand::new("coll".to_string())
+        .with_capped(4096)
+        .with_max_documents(100)
+        .with_validator(validator.clone())
+        .with_collation(Collation::new("pl".to_string()));
+    let expected = doc! {
+        "create": "coll",
+        "capped": true,
+        "size": 4096i64,
+        "max": 100i64,
+        "validator": validator,
+        "collation": { "locale": "pl" },
+    };
+    assert_eq!(command.get_command(), expected);
+    assert_eq!(
+        command.to_shell(),
+        concat!(
+            r#"db.createCollection("coll", { capped: true, size: NumberLong("4096"), "#,
+            r#"max: NumberLong("100"), validator: { $jsonSchema: { bsonType: "object", "#,
+            r#"required: [ "a" ] } }, collation: { locale: "pl" } })"#,
+        )
+    );
+
+    let command = CreateCollectionCommand::new("coll".to_string()).with_max_documents(100);
+    assert_eq!(command.get_command(), doc! { "create": "coll" });
+}
+
+/// Synchronization should keep matching indexes and replace the other ones.
+#[test]
+fn synchronize_indexes() {
+    let id = IndexInfo::from_doc(&doc! { "v": 2, "key": { "_id": 1 }, "name": "_id_" }).unwrap();
+    let text = IndexInfo::from_doc(&doc! {
+        "v": 2,
+        "key": { "_fts": "text", "_ftsx": 1 },
+        "name": "coll",
+        "weights": { "a": 1, "b.c": 1 },
+    })
+    .unwrap();
+    let other = IndexInfo::from_doc(&doc! { "key": { "d": 1 }, "name": "d_1" }).unwrap();
+    assert_eq!(text.get_text_fields(), Some(vec!["a".to_string(), "b.c".to_string()]));
+    assert_eq!(other.get_text_fields(), None);
+
+    let text_model = |fields: Vec<String>| {
+        IndexModel::text(fields).with_options(IndexOptions::new().with_name("coll".to_string()))
+    };
+    let fields = vec!["b.c".to_string(), "a".to_string()];
+    let existing = vec![id.clone(), text.clone(), other.clone()];
+    let sync = IndexSync::new("coll".to_string(), vec![text_model(fields)], &existing);
+    let drop_other = || DropIndexesCommand::new("coll".to_string(), "d_1".into());
+    assert_eq!(sync.get_create(), None);
+    assert!(sync.get_drop().is_empty());
+    assert_eq!(sync.get_undeclared(), &vec![drop_other()]);
+    assert!(sync.is_empty());
+    let sync = sync.with_drop_undeclared(true);
+    assert_eq!(sync.get_drop(), vec![&drop_other()]);
+    assert!(!sync.is_empty());
+
+    let declared = vec![IndexModel::new(doc! { "d": 1 })];
+    let sync = IndexSync::new("coll".to_string(), declared, &existing).with_drop_undeclared(true);
+    assert_eq!(sync.get_create(), None);
+    assert_eq!(sync.get_drop(), vec![&DropIndexesCommand::new("coll".to_string(), "coll".into())]);
+
+    let declared =
+        vec![IndexModel::new(doc! { "d": 1 }).with_options(IndexOptions::new().with_unique(true))];
+    let sync = IndexSync::new("coll".to_string(), declared.clone(), &existing[2..3]);
+    assert_eq!(
+        sync.get_create(),
+        Some(&CreateIndexesCommand::new_models("coll".to_string(), declared))
+    );
+    assert_eq!(sync.get_drop(), vec![&DropIndexesCommand::new("coll".to_string(), "d_1".into())]);
+
+    let fields = vec!["a".to_string()];
+    let sync =
+        IndexSync::new("coll".to_string(), vec![text_model(fields.clone())], &existing[0..2]);
+    assert_eq!(sync.get_create(), Some(&CreateIndexesCommand::new("coll".to_string(), fields)));
+    assert_eq!(sync.get_drop(), vec![&DropIndexesCommand::new("coll".to_string(), "coll".into())]);
+
+    let sync = IndexSync::new("coll".to_string(), Vec::new(), &existing[0..1]);
+    assert!(sync.is_empty());
+
+    let command = DropIndexesCommand::all("coll".to_string());
+    assert_eq!(command.get_command(), doc! { "dropIndexes": "coll", "index": "*" });
+    assert_eq!(ListIndexesCommand::new("coll".to_string()).to_shell(), "db.coll.getIndexes()");
+}
@@ -67,3 +67,34 @@ fn test_conversion_from_doc_to_hash() {
     assert_eq!(result.get(&TestEnum::Abc).unwrap(), "cba");
     assert_eq!(result.get(&TestEnum::Def).unwrap(), "fed");
 }
+
+#[test]
+fn test_spread() {
+    let mut document = doc! { "a": 1, "b": 2 };
+    huus::conversions::spread(&mut document, doc! { "b": 3, "c.d": 4 }, &["a", "b", "c"]);
+    assert_eq!(document, doc! { "a": 1, "b": 3, "c.d": 4 });
+}
+
+#[test]
+#[should_panic]
+fn test_spread_with_unknown_field() {
+    let mut document = doc! { "a": 1 };
+    huus::conversions::spread(&mut document, doc! { "b": 2 }, &["a"]);
+}
+
+#[test]
+fn test_conversion_of_geo_point() {
+    use huus::conversions::{FromDoc, IntoDoc};
+    use huus::types::GeoPoint;
+
+    let document = doc! {
+        "type": "Point",
+        "coordinates": [13.4, 52.5],
+    };
+
+    let point = GeoPoint::new(13.4, 52.5);
+
+    assert_eq!(GeoPoint::from_doc(document.clone()).unwrap(), point);
+    assert_eq!(point.into_doc(), document);
+    assert!(GeoPoint::from_doc(doc! { "type": "Polygon", "coordinates": [] }).is_err());
+}
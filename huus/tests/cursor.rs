@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `cursor` module.
+
+use bson::{bson, doc};
+
+use huus::cursor::TypedCursor;
+use huus::errors::HuusError;
+use huus::types::GeoPoint;
+
+/// Documents failing to convert should be reported without stopping the iteration.
+#[test]
+fn typed_cursor_reports_conversion_errors() {
+    let documents: Vec<Result<bson::Document, HuusError>> = vec![
+        Ok(doc! { "type": "Point", "coordinates": [13.4, 52.5] }),
+        Ok(doc! { "type": "Polygon", "coordinates": [] }),
+        Ok(doc! { "type": "Point", "coordinates": [21.0, 52.2] }),
+    ];
+
+    let results: Vec<Result<GeoPoint, HuusError>> =
+        TypedCursor::new(documents.into_iter()).collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(*results[0].as_ref().unwrap(), GeoPoint::new(13.4, 52.5));
+    assert!(results[1].is_err());
+    assert_eq!(*results[2].as_ref().unwrap(), GeoPoint::new(21.0, 52.2));
+}
@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `driver` module.
+
+#![cfg(any(feature = "sync-driver", feature = "async-driver"))]
+
+use bson::{bson, doc};
+use mongodb::bson::Bson as DriverBson;
+
+use huus::driver::{from_driver, to_driver};
+
+/// Checks if the value is converted to the expected value of the driver and back without changes.
+fn check_round_trip(value: bson::Bson, expected: DriverBson) {
+    let document = doc! { "value": value };
+    let converted = to_driver(&document).unwrap();
+    assert_eq!(converted, mongodb::bson::doc! { "value": expected });
+    assert_eq!(from_driver(&converted).unwrap(), document);
+}
+
+/// Every `bson` value should keep its value and type when passed to the driver and back.
+#[test]
+fn value_round_trip() {
+    check_round_trip(bson::Bson::FloatingPoint(1.5), DriverBson::Double(1.5));
+    check_round_trip(bson::Bson::String("abc".into()), DriverBson::String("abc".into()));
+    check_round_trip(
+        bson::Bson::Array(vec![bson::Bson::I32(1), bson::Bson::String("a".into())]),
+        DriverBson::Array(vec![DriverBson::Int32(1), DriverBson::String("a".into())]),
+    );
+    check_round_trip(
+        bson::Bson::Document(doc! { "a": { "b": 1 } }),
+        DriverBson::Document(mongodb::bson::doc! { "a": { "b": 1 } }),
+    );
+    check_round_trip(bson::Bson::Boolean(true), DriverBson::Boolean(true));
+    check_round_trip(bson::Bson::Null, DriverBson::Null);
+    check_round_trip(
+        bson::Bson::RegExp("^a.c$".into(), "im".into()),
+        DriverBson::RegularExpression(mongodb::bson::Regex {
+            pattern: "^a.c$".into(),
+            options: "im".into(),
+        }),
+    );
+    check_round_trip(
+        bson::Bson::JavaScriptCode("return 1;".into()),
+        DriverBson::JavaScriptCode("return 1;".into()),
+    );
+    check_round_trip(
+        bson::Bson::JavaScriptCodeWithScope("return x;".into(), doc! { "x": 1 }),
+        DriverBson::JavaScriptCodeWithScope(mongodb::bson::JavaScriptCodeWithScope {
+            code: "return x;".into(),
+            scope: mongodb::bson::doc! { "x": 1 },
+        }),
+    );
+    check_round_trip(bson::Bson::I32(-3), DriverBson::Int32(-3));
+    check_round_trip(bson::Bson::I64(1 << 40), DriverBson::Int64(1 << 40));
+    check_round_trip(
+        bson::Bson::TimeStamp((1_577_836_800 << 32) + 7),
+        DriverBson::Timestamp(mongodb::bson::Timestamp { time: 1_577_836_800, increment: 7 }),
+    );
+    check_round_trip(
+        bson::Bson::Binary(bson::spec::BinarySubtype::Generic, vec![1, 2, 3]),
+        DriverBson::Binary(mongodb::bson::Binary {
+            subtype: mongodb::bson::spec::BinarySubtype::Generic,
+            bytes: vec![1, 2, 3],
+        }),
+    );
+    check_round_trip(
+        bson::Bson::Binary(bson::spec::BinarySubtype::Uuid, vec![7; 16]),
+        DriverBson::Binary(mongodb::bson::Binary {
+            subtype: mongodb::bson::spec::BinarySubtype::Uuid,
+            bytes: vec![7; 16],
+        }),
+    );
+    check_round_trip(
+        bson::Bson::ObjectId(bson::oid::ObjectId::with_string("0123456789abcdef01234567").unwrap()),
+        DriverBson::ObjectId(
+            mongodb::bson::oid::ObjectId::parse_str("0123456789abcdef01234567").unwrap(),
+        ),
+    );
+    let date = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00.123Z").unwrap();
+    check_round_trip(
+        bson::Bson::UtcDatetime(date.into()),
+        DriverBson::DateTime(mongodb::bson::DateTime::from_millis(1_577_836_800_123)),
+    );
+    check_round_trip(bson::Bson::Symbol("sym".into()), DriverBson::Symbol("sym".into()));
+}
+
+/// Dates before the Unix epoch should keep their value when passed to the driver and back.
+#[test]
+fn date_before_epoch_round_trip() {
+    let date = chrono::DateTime::parse_from_rfc3339("1969-12-31T23:59:59Z").unwrap();
+    check_round_trip(
+        bson::Bson::UtcDatetime(date.into()),
+        DriverBson::DateTime(mongodb::bson::DateTime::from_millis(-1000)),
+    );
+}
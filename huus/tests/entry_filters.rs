@@ -89,7 +89,7 @@ fn test_logical_filter() {
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
     let entry = Not(Eq(3.14));
-    let expected = doc! { "$not": { KEY: { "$eq": 3.14 } } };
+    let expected = doc! { KEY: { "$not": { "$eq": 3.14 } } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
     let entry = Nor(vec![Eq(3.14), Eq(2.718)]);
@@ -114,21 +114,276 @@ fn test_element_filter() {
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 }
 
+#[test]
+fn test_null_filter_methods() {
+    let mut entry = StringEntry::Empty;
+    entry.is_null();
+    let expected = doc! { KEY: { "$type": 10 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I32Entry::Empty;
+    entry.is_missing();
+    let expected = doc! { KEY: { "$exists": false } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ObjectEntry::<f64, f64>::Empty;
+    entry.is_null_or_missing();
+    let expected = doc! { KEY: bson::Bson::Null };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+#[test]
+fn test_comparison_filter_methods() {
+    let mut entry = F64Entry::Empty;
+    entry.lt(3.14);
+    let expected = doc! { KEY: { "$lt": 3.14 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I32Entry::Empty;
+    entry.gt(5);
+    let expected = doc! { KEY: { "$gt": 5 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.r#in(vec![1, 2]);
+    let expected = doc! { KEY: { "$in": [1i64, 2i64] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = TimeStampEntry::Empty;
+    entry.gte(types::TimeStamp(3));
+    let expected = doc! { KEY: { "$gte": bson::Bson::TimeStamp(3) } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = StringEntry::Empty;
+    entry.r#in(["abc", "def"]);
+    let expected = doc! { KEY: { "$in": ["abc", "def"] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let names: &[&str] = &["abc", "def", "ghi"];
+    let mut entry = StringEntry::Empty;
+    entry.nin(names.iter().copied().filter(|name| name.starts_with('d')));
+    let expected = doc! { KEY: { "$nin": ["def"] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let oid = bson::oid::ObjectId::with_string("11223344556677889900aabb").unwrap();
+    let mut entry = ObjectIdEntry::Empty;
+    entry.nin(vec![oid.clone()]);
+    let expected = doc! { KEY: { "$nin": [bson::Bson::ObjectId(oid)] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+#[test]
+fn test_element_filter_methods() {
+    let exists = doc! { KEY: { "$exists": true } };
+    let not_exists = doc! { KEY: { "$exists": false } };
+
+    let mut entry = StringEntry::Empty;
+    entry.exists(true);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), exists);
+
+    let mut entry = F64Entry::Empty;
+    entry.exists(false);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), not_exists);
+
+    let mut entry = I64Entry::Empty;
+    entry.with_type(types::Type::I64);
+    let expected = doc! { KEY: { "$type": 18 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ObjectIdEntry::Empty;
+    entry.exists(true);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), exists);
+
+    let mut entry = DateEntry::Empty;
+    entry.exists(true);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), exists);
+
+    let mut entry = TimeStampEntry::Empty;
+    entry.exists(true);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), exists);
+
+    let mut entry = BsonEntry::Empty;
+    entry.exists(false);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), not_exists);
+
+    let mut entry = ObjectEntry::<f64, f64>::Empty;
+    entry.exists(true);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), exists);
+
+    let mut entry = ArrayEntry::<f64, f64>::Empty;
+    entry.with_type(types::Type::Array);
+    let expected = doc! { KEY: { "$type": 4 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = HashMapEntry::<String, i32>::Empty;
+    entry.exists(true);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), exists);
+
+    let mut entry = BTreeMapEntry::<String, i32>::Empty;
+    entry.exists(false);
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), not_exists);
+}
+
+#[test]
+fn test_bitwise_filter() {
+    use huus::filters::Bitwise::{BitsAllClear, BitsAllSet, BitsAnyClear, BitsAnySet};
+
+    let entry = BitsAllSet(0b101);
+    let expected = doc! { KEY: { "$bitsAllSet": 5i64 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let entry = BitsAnySet(0b11);
+    let expected = doc! { KEY: { "$bitsAnySet": 3i64 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let entry = BitsAllClear(u64::max_value());
+    let expected = doc! { KEY: { "$bitsAllClear": -1i64 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let entry = BitsAnyClear(1);
+    let expected = doc! { KEY: { "$bitsAnyClear": 1i64 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I32Entry::Empty;
+    entry.bits_any_set(0b110);
+    let expected = doc! { KEY: { "$bitsAnySet": 6i64 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.bits_all_clear(0b1);
+    let expected = doc! { KEY: { "$bitsAllClear": 1i64 } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+#[test]
+fn test_modulo_filter() {
+    let entry = Modulo::Mod(4, 1);
+    let expected = doc! { KEY : { "$mod": [4i64, 1i64] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+#[test]
+fn test_modulo_filter_methods() {
+    let mut entry = I32Entry::Empty;
+    entry.r#mod(4, 1);
+    let expected = doc! { KEY: { "$mod": [4i64, 1i64] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.r#mod(3, 0);
+    let expected = doc! { KEY: { "$mod": [3i64, 0i64] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+#[test]
+fn test_negation_filter_methods() {
+    let mut entry = I32Entry::Empty;
+    entry.gt(5);
+    entry.not();
+    let expected = doc! { KEY: { "$not": { "$gt": 5 } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = StringEntry::from("abc");
+    entry.not();
+    let expected = doc! { KEY: { "$ne": "abc" } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = StringEntry::Empty;
+    entry.regex("^a", "");
+    entry.not();
+    let expected = doc! { KEY: { "$not": { "$regex": "^a", "$options": "" } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ArrayEntry::<f64, f64>::Empty;
+    entry.size(2);
+    entry.not();
+    let expected = doc! { KEY: { "$not": { "$size": 2 } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = BooleanEntry::from(true);
+    entry.not();
+    let expected = doc! { KEY: { "$ne": true } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = EnumEntry::Value("abc".to_string());
+    entry.not();
+    let expected = doc! { KEY: { "$ne": "abc" } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = NullEntry::Empty;
+    entry.exists(true);
+    entry.not();
+    let expected = doc! { KEY: { "$not": { "$exists": true } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = BsonEntry::from(doc! { "a": 1 });
+    entry.not();
+    let expected = doc! { KEY: { "$ne": { "a": 1 } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I32Entry::Empty;
+    entry.not();
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), doc! {});
+}
+
+#[test]
+fn test_regex_filter() {
+    let entry = RegexEntry::new("^ab.*c$", "im");
+    let expected = doc! { KEY: { "$regex": "^ab.*c$", "$options": "im" } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
 #[test]
 fn test_array_filter() {
-    use huus::filters::Array::{All, ElemMatch, Size};
+    use huus::filters::Array::{All, ElemMatch, Size, SizeBetween};
 
-    let entry = All(vec![3.14, 2.718]);
+    let entry = All::<f64, f64>(vec![3.14, 2.718]);
     let expected = doc! { KEY: { "$all": [3.14, 2.718] } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
-    let entry = ElemMatch(vec![3.14, 2.718]);
-    let expected = doc! { KEY: { "$elemMatch": [3.14, 2.718] } };
+    let entry = ElemMatch::<_, f64>(F64Entry::Comparison(Comparison::Gt(2.718)));
+    let expected = doc! { KEY: { "$elemMatch": { "$gt": 2.718 } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let entry = ElemMatch::<_, f64>(F64Entry::Value(3.14));
+    let expected = doc! { KEY: { "$elemMatch": { "$eq": 3.14 } } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
-    let entry = Size::<f32>(5);
+    let entry = Size::<f32, f32>(5);
     let expected = doc! { KEY: { "$size": 5 } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let entry = SizeBetween::<f32, f32>(2, 4);
+    let expected = doc! {
+        "$expr": { "$and": [
+            { "$isArray": "$xxx" },
+            { "$gte": [{ "$size": "$xxx" }, 2i64] },
+            { "$lte": [{ "$size": "$xxx" }, 4i64] },
+        ] },
+    };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+/// Inverted size range should match nothing.
+#[test]
+fn test_array_size_between_inverted() {
+    let mut entry = ArrayEntry::<f64, f64>::Empty;
+    entry.size_between(3, 2);
+    let expected = doc! { KEY: { "$in": [] } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+/// Size range should be applicable to the elements of a nested array.
+#[test]
+fn test_array_size_between_in_elem_match() {
+    let mut inner = ArrayEntry::<f64, f64>::Empty;
+    inner.size_between(1, 2);
+    let mut entry = ArrayEntry::<ArrayEntry<f64, f64>, Vec<f64>>::Empty;
+    entry.elem_match(inner);
+    let expected = doc! {
+        KEY: { "$elemMatch": { "0": { "$exists": true }, "2": { "$exists": false } } },
+    };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 }
 
 #[test]
@@ -159,6 +414,11 @@ fn test_string_entry_filter() {
     let entry = StringEntry::Element(Element::Exists(true));
     let expected = doc! { KEY: { "$exists": true } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = StringEntry::Empty;
+    entry.regex("^abc", "i");
+    let expected = doc! { KEY: { "$regex": "^abc", "$options": "i" } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 }
 
 #[test]
@@ -171,7 +431,7 @@ fn test_array_entry_filter() {
     let expected = doc! { KEY: 3.14 };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
-    let entry = ArrayEntry::Array::<f64, f64>(Array::All(vec![3.14, 2.718]));
+    let entry = ArrayEntry::<f64, f64>::Array(Array::All(vec![3.14, 2.718]));
     let expected = doc! { KEY: { "$all": [3.14, 2.718] } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
@@ -188,6 +448,10 @@ fn test_objectid_entry_filter() {
     let expected = doc! { KEY: bson::Bson::ObjectId(oid.clone()) };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 
+    let entry = ObjectIdEntry::Comparison(Comparison::Gt(oid.clone()));
+    let expected = doc! { KEY: { "$gt": bson::Bson::ObjectId(oid.clone()) } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
     let entry = ObjectIdEntry::Element(Element::Exists(true));
     let expected = doc! { KEY: { "$exists": true } };
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
@@ -274,6 +538,40 @@ fn test_i64_entry_filter() {
     assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
 }
 
+#[test]
+fn test_geo_entry_filter() {
+    let point = types::GeoPoint::new(20.0, 50.0);
+    let geometry = doc! { "type": "Point", "coordinates": [20.0, 50.0] };
+
+    let entry = GeoEntry::near(point, Some(100.0));
+    let expected =
+        doc! { KEY: { "$near": { "$geometry": geometry.clone(), "$maxDistance": 100.0 } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let entry = GeoEntry::near(point, None);
+    let expected = doc! { KEY: { "$near": { "$geometry": geometry.clone() } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let points = vec![point, types::GeoPoint::new(21.0, 50.0), types::GeoPoint::new(21.0, 51.0)];
+    let entry = GeoEntry::within_polygon(points);
+    let expected = doc! {
+        KEY: {
+            "$geoWithin": {
+                "$geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[20.0, 50.0], [21.0, 50.0], [21.0, 51.0], [20.0, 50.0]]],
+                },
+            },
+        },
+    };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = GeoPointEntry::Empty;
+    entry.intersects(geometry.clone());
+    let expected = doc! { KEY: { "$geoIntersects": { "$geometry": geometry } } };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
 #[test]
 fn test_bson_entry_filter() {
     let entry = BsonEntry::Value(doc! { "a": 1, "b": 2 });
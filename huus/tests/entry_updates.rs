@@ -47,6 +47,61 @@ fn test_numerical_update() {
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
 }
 
+#[test]
+fn test_numerical_update_methods() {
+    let mut entry = I32Entry::Empty;
+    entry.inc(3);
+    let expected = doc! { "$inc": { KEY: 3 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.inc(3);
+    let expected = doc! { "$inc": { KEY: 3i64 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.max(7);
+    let expected = doc! { "$max": { KEY: 7i64 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = F64Entry::Empty;
+    entry.mul(1.5);
+    let expected = doc! { "$mul": { KEY: 1.5 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = F64Entry::Empty;
+    entry.min(0.5);
+    let expected = doc! { "$min": { KEY: 0.5 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+}
+
+#[test]
+fn test_bitwise_update() {
+    use huus::updates::Bitwise::{And, Or, Xor};
+
+    let operation = And(0b1010);
+    let expected = doc! { "$bit": { KEY: { "and": 10 } } };
+    assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
+
+    let operation = Or(0b0101i64);
+    let expected = doc! { "$bit": { KEY: { "or": 5i64 } } };
+    assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
+
+    let operation = Xor(1);
+    let expected = doc! { "$bit": { KEY: { "xor": 1 } } };
+    assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I32Entry::Empty;
+    entry.bit_or(4);
+    let expected = doc! { "$bit": { KEY: { "or": 4 } } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.bit_and(6);
+    let expected = doc! { "$bit": { KEY: { "and": 6i64 } } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+}
+
 #[test]
 fn test_field_update() {
     use huus::updates::Field::{Rename, Set, SetOnInsert, Unset};
@@ -68,10 +123,43 @@ fn test_field_update() {
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
 }
 
+#[test]
+fn test_field_update_methods() {
+    let mut entry = I32Entry::Empty;
+    entry.set(3);
+    let expected = doc! { "$set": { KEY: 3 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = I64Entry::Empty;
+    entry.set_on_insert(3);
+    let expected = doc! { "$setOnInsert": { KEY: 3i64 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = F64Entry::Empty;
+    entry.rename("new_name".to_string());
+    let expected = doc! { "$rename": { KEY: "new_name" } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = TimeStampEntry::Empty;
+    entry.unset();
+    let expected = doc! { "$unset": { KEY: true } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ArrayEntry::<F64Entry, f64>::Empty;
+    entry.set(vec![3.14]);
+    let expected = doc! { "$set": { KEY: [3.14] } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = BsonEntry::Empty;
+    entry.unset();
+    let expected = doc! { "$unset": { KEY: true } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+}
+
 #[test]
 fn test_array_update() {
-    use huus::updates::Array::{AddToSet, Pop, Pull, PullAll, Push};
-    use huus::values::{Each, PushValue};
+    use huus::updates::Array::{AddToSet, AddToSetEach, Pull, PullAll, Push};
+    use huus::values::{Each, PushValue, Sort};
 
     let operation = AddToSet(PushValue::Value(3.14));
     let expected = doc! { "$addToSet": { KEY: 3.14 } };
@@ -81,11 +169,15 @@ fn test_array_update() {
     let expected = doc! { "$addToSet": { KEY: { "$each": ["abc", "def"] } } };
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
 
-    let operation = Pop::<i32>(PopOption::First);
+    let operation = AddToSetEach(vec![1, 2]);
+    let expected = doc! { "$addToSet": { KEY: { "$each": [1, 2] } } };
+    assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
+
+    let operation = PopOption::First;
     let expected = doc! { "$pop": { KEY: -1 } };
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
 
-    let operation = Pop::<i32>(PopOption::Last);
+    let operation = PopOption::Last;
     let expected = doc! { "$pop": { KEY: 1 } };
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
 
@@ -105,6 +197,14 @@ fn test_array_update() {
     let expected = doc! { "$push": { KEY: { "$each": ["abc", "def"] } } };
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
 
+    let mut each = Each::new(vec![3, 4]);
+    each.position(0).slice(-5).sort_by(Sort::Descending);
+    let operation = Push(PushValue::Each(each));
+    let expected = doc! {
+        "$push": { KEY: { "$each": [3, 4], "$position": 0i64, "$slice": -5i64, "$sort": -1 } }
+    };
+    assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
+
     let operation = PullAll(3.14);
     let expected = doc! { "$pullAll": { KEY: 3.14 } };
     assert_eq!(operation.build_update(KEY.to_string()).into_doc(), expected);
@@ -140,16 +240,34 @@ fn test_string_entry_update() {
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 }
 
-#[test]
-fn test_array_entry_update() {
-    let entry = ArrayEntry::Array::<I32Entry, i32>(Array::Pop(PopOption::First), Operator::None);
-    let expected = doc! { "$pop": { KEY: -1 } };
-    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+type NestedArrayEntry = ArrayEntry<ArrayEntry<I32Entry, i32>, Vec<i32>>;
 
-    let entry = ArrayEntry::Array::<I32Entry, i32>(Array::Pop(PopOption::Last), Operator::First);
-    let expected = doc! { "$pop": { KEY.to_string() + ".$": 1 } };
-    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+/// Returns entries of an array of arrays applying `update` to the nested arrays with each form of
+/// positional operator, together with the expected path of the update.
+fn at_each_position(update: ArrayEntry<I32Entry, i32>) -> Vec<(String, NestedArrayEntry)> {
+    let mut result = Vec::new();
+
+    let mut entry = NestedArrayEntry::Empty;
+    entry.at(2, update.clone());
+    result.push((KEY.to_string() + ".2", entry));
+
+    let mut entry = NestedArrayEntry::Empty;
+    entry.at_selected(update.clone());
+    result.push((KEY.to_string() + ".$", entry));
 
+    let mut entry = NestedArrayEntry::Empty;
+    entry.at_all(update.clone());
+    result.push((KEY.to_string() + ".$[]", entry));
+
+    let mut entry = NestedArrayEntry::Empty;
+    entry.at_identified("el".to_string(), update);
+    result.push((KEY.to_string() + ".$[el]", entry));
+
+    result
+}
+
+#[test]
+fn test_array_entry_update() {
     let entry = ArrayEntry::Element::<F64Entry, f64>(Element::Set(3.14));
     let expected = doc! { "$set": { KEY.to_string() + ".$": 3.14 } };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
@@ -162,11 +280,88 @@ fn test_array_entry_update() {
     let expected = doc! { KEY.to_string() + ".$": 3.14 };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 
+    let entry = ArrayEntry::Element::<F64Entry, f64>(Element::All(F64Entry::Value(3.14)));
+    let expected = doc! { KEY.to_string() + ".$[]": 3.14 };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let element = Element::Identified("el".to_string(), F64Entry::Value(3.14));
+    let entry = ArrayEntry::Element::<F64Entry, f64>(element);
+    let expected = doc! { KEY.to_string() + ".$[el]": 3.14 };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
     let entry = ArrayEntry::Field::<F64Entry, f64>(Field::Set(vec![3.14, 2.72]));
     let expected = doc! { "$set": { KEY: [3.14, 2.72] } };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 }
 
+#[test]
+fn test_array_push_update() {
+    let entry = ArrayEntry::Array::<I32Entry, i32>(Array::Push(3.into()));
+    let expected = doc! { "$push": { KEY: 3 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ArrayEntry::<I32Entry, i32>::Empty;
+    entry.push_each(vec![3, 4]).slice(-5);
+    let expected = doc! { "$push": { KEY: { "$each": [3, 4], "$slice": -5i64 } } };
+    assert_eq!(entry.clone().build_update(KEY.to_string()).into_doc(), expected);
+
+    for (path, nested) in at_each_position(entry) {
+        let expected = doc! { "$push": { path: { "$each": [3, 4], "$slice": -5i64 } } };
+        assert_eq!(nested.build_update(KEY.to_string()).into_doc(), expected);
+    }
+}
+
+#[test]
+fn test_array_add_to_set_update() {
+    let entry = ArrayEntry::Array::<I32Entry, i32>(Array::AddToSetEach(vec![3, 4]));
+    let expected = doc! { "$addToSet": { KEY: { "$each": [3, 4] } } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ArrayEntry::<I32Entry, i32>::Empty;
+    entry.add_to_set(3.into());
+    let expected = doc! { "$addToSet": { KEY: 3 } };
+    assert_eq!(entry.clone().build_update(KEY.to_string()).into_doc(), expected);
+
+    for (path, nested) in at_each_position(entry) {
+        let expected = doc! { "$addToSet": { path: 3 } };
+        assert_eq!(nested.build_update(KEY.to_string()).into_doc(), expected);
+    }
+}
+
+#[test]
+fn test_array_pull_update() {
+    let entry = ArrayEntry::Array::<I32Entry, i32>(Array::PullAll(3));
+    let expected = doc! { "$pullAll": { KEY: 3 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ArrayEntry::<I32Entry, i32>::Empty;
+    entry.pull(3.into());
+    let expected = doc! { "$pull": { KEY: 3 } };
+    assert_eq!(entry.clone().build_update(KEY.to_string()).into_doc(), expected);
+
+    for (path, nested) in at_each_position(entry) {
+        let expected = doc! { "$pull": { path: 3 } };
+        assert_eq!(nested.build_update(KEY.to_string()).into_doc(), expected);
+    }
+}
+
+#[test]
+fn test_array_pop_update() {
+    let entry = ArrayEntry::Pop::<I32Entry, i32>(PopOption::First);
+    let expected = doc! { "$pop": { KEY: -1 } };
+    assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
+
+    let mut entry = ArrayEntry::<I32Entry, i32>::Empty;
+    entry.pop(PopOption::Last);
+    let expected = doc! { "$pop": { KEY: 1 } };
+    assert_eq!(entry.clone().build_update(KEY.to_string()).into_doc(), expected);
+
+    for (path, nested) in at_each_position(entry) {
+        let expected = doc! { "$pop": { path: 1 } };
+        assert_eq!(nested.build_update(KEY.to_string()).into_doc(), expected);
+    }
+}
+
 #[test]
 fn test_objectid_entry_update() {
     let oid = bson::oid::ObjectId::with_string("11223344556677889900aabb").unwrap();
@@ -200,8 +395,9 @@ fn test_date_entry_update() {
     let expected = doc! { KEY: datetime };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 
-    let entry = DateEntry::CurrentDate;
-    let expected = doc! { "$currentDate": { KEY: "date" } };
+    let mut entry = DateEntry::Empty;
+    entry.current_date();
+    let expected = doc! { "$currentDate": { KEY: { "$type": "date" } } };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 
     let entry = DateEntry::Field(Field::Set(datetime));
@@ -230,8 +426,9 @@ fn test_timestamp_entry_update() {
     let expected = doc! { KEY: bson::Bson::TimeStamp(3) };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 
-    let entry = TimeStampEntry::CurrentDate;
-    let expected = doc! { "$currentDate": { KEY: "timestamp" } };
+    let mut entry = TimeStampEntry::Empty;
+    entry.current_date();
+    let expected = doc! { "$currentDate": { KEY: { "$type": "timestamp" } } };
     assert_eq!(entry.build_update(KEY.to_string()).into_doc(), expected);
 
     let entry = TimeStampEntry::Field(Field::Set(types::TimeStamp(3)));
@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `exec` module.
+
+#![cfg(feature = "async-driver")]
+
+use bson::{bson, doc};
+
+use huus::commands::{FindCommand, InsertCommand, UpdateCommand, UpdateOptions, WatchCommand};
+use huus::exec::{from_driver, to_driver, Database};
+
+/// Documents should keep their values and types when passed to the driver and back.
+#[test]
+fn driver_document_round_trip() {
+    let date = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap();
+    let document = doc! {
+        "int": 1,
+        "long": 2i64,
+        "double": 1.5,
+        "string": "abc",
+        "date": bson::Bson::UtcDatetime(date.into()),
+        "id": bson::oid::ObjectId::with_string("0123456789abcdef01234567").unwrap(),
+        "array": [true, bson::Bson::Null, { "a": "b" }],
+    };
+
+    let converted = to_driver(&document).unwrap();
+    let expected = mongodb::bson::doc! {
+        "int": 1,
+        "long": 2i64,
+        "double": 1.5,
+        "string": "abc",
+        "date": mongodb::bson::DateTime::from_millis(1577836800000),
+        "id": mongodb::bson::oid::ObjectId::parse_str("0123456789abcdef01234567").unwrap(),
+        "array": [true, mongodb::bson::Bson::Null, { "a": "b" }],
+    };
+    assert_eq!(converted, expected);
+    assert_eq!(from_driver(&converted).unwrap(), document);
+}
+
+/// Futures returned by the commands should be usable in multi-threaded runtimes.
+#[allow(dead_code)]
+fn futures_are_send(db: &Database) {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let command = FindCommand::<bson::Document>::new("coll".to_string(), doc! {}, None);
+    assert_send(&command.execute_async(db));
+    let command = InsertCommand::new("coll".to_string(), doc! { "a": 1 });
+    assert_send(&command.execute_async(db));
+    let update = doc! { "$set": { "a": 1 } };
+    let command = UpdateCommand::new("coll".to_string(), doc! {}, update, UpdateOptions::UpdateOne);
+    assert_send(&command.execute_async(db));
+    let command = WatchCommand::<bson::Document>::new("coll".to_string());
+    assert_send(&command.iterate_async(db));
+}
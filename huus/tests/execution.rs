@@ -0,0 +1,229 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `execution` module.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake};
+use std::time::Duration;
+
+use huus::errors::{ConversionError, HuusError, WriteError};
+use huus::execution::{CommandObserver, Executor, RetryPolicy};
+
+fn write_error(code: i32) -> HuusError {
+    HuusError::Write(WriteError { code, message: "failed".to_string() })
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Runs the future to completion. The futures used in the tests never wait for external events.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Arc::new(NoopWaker).into();
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Backoff should double with each retry up to the maximum.
+#[test]
+fn retry_policy_backoff() {
+    let policy =
+        RetryPolicy::new().with_backoff(Duration::from_millis(10), Duration::from_millis(50));
+    assert_eq!(policy.get_backoff(1), Duration::from_millis(10));
+    assert_eq!(policy.get_backoff(2), Duration::from_millis(20));
+    assert_eq!(policy.get_backoff(3), Duration::from_millis(40));
+    assert_eq!(policy.get_backoff(4), Duration::from_millis(50));
+    assert_eq!(policy.get_backoff(100), Duration::from_millis(50));
+}
+
+/// Transient errors should be retried until the attempts run out.
+#[test]
+fn retry_policy_retries_transient_errors() {
+    let policy = RetryPolicy::new()
+        .with_max_attempts(4)
+        .with_backoff(Duration::from_millis(0), Duration::from_millis(0));
+
+    let mut attempts = 0;
+    let result = policy.execute(|| {
+        attempts += 1;
+        if attempts < 3 {
+            Err(write_error(112))
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(result.unwrap(), 3);
+
+    let mut attempts = 0;
+    let result: Result<(), HuusError> = policy.execute(|| {
+        attempts += 1;
+        Err(write_error(189))
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 4);
+}
+
+/// Asynchronous execution should retry like the synchronous one awaiting the given delays.
+#[test]
+fn retry_policy_retries_asynchronously() {
+    let policy =
+        RetryPolicy::new().with_backoff(Duration::from_millis(10), Duration::from_millis(15));
+
+    let attempts = RefCell::new(0);
+    let delays = RefCell::new(Vec::new());
+    let result = block_on(policy.execute_async(
+        || async {
+            *attempts.borrow_mut() += 1;
+            if *attempts.borrow() < 3 {
+                Err(write_error(112))
+            } else {
+                Ok(*attempts.borrow())
+            }
+        },
+        |delay| {
+            delays.borrow_mut().push(delay);
+            std::future::ready(())
+        },
+    ));
+    assert_eq!(result.unwrap(), 3);
+    assert_eq!(*delays.borrow(), vec![Duration::from_millis(10), Duration::from_millis(15)]);
+
+    let result: Result<(), HuusError> = block_on(policy.execute_async(
+        || async { Err(ConversionError::missing_key("a".to_string()).into()) },
+        |_| std::future::ready(()),
+    ));
+    assert!(result.is_err());
+    assert_eq!(delays.borrow().len(), 2);
+}
+
+/// Network errors should be retried only on request, as they may hide an applied write.
+#[test]
+fn retry_policy_network_errors() {
+    assert!(!RetryPolicy::new().get_network_errors());
+    assert!(RetryPolicy::new().with_network_errors(true).get_network_errors());
+}
+
+/// Errors which are not transient should be returned immediately.
+#[test]
+fn retry_policy_does_not_retry_other_errors() {
+    let policy = RetryPolicy::new().with_error_codes(vec![1234]);
+    assert!(policy.is_retryable(&write_error(1234)));
+    assert!(!policy.is_retryable(&write_error(112)));
+
+    let mut attempts = 0;
+    let result: Result<(), HuusError> = policy.execute(|| {
+        attempts += 1;
+        Err(ConversionError::missing_key("a".to_string()).into())
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+}
+
+/// Records the notifications of the observer.
+struct RecordingObserver {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl CommandObserver for RecordingObserver {
+    fn on_start(&self, command: &dyn std::fmt::Display) {
+        self.events.lock().unwrap().push(format!("start {}", command));
+    }
+
+    fn on_success(&self, command: &dyn std::fmt::Display, _duration: Duration) {
+        self.events.lock().unwrap().push(format!("success {}", command));
+    }
+
+    fn on_error(&self, command: &dyn std::fmt::Display, error: &HuusError, _duration: Duration) {
+        self.events.lock().unwrap().push(format!("error {} {}", command, error));
+    }
+}
+
+/// Observers should be notified about every attempt of the command.
+#[test]
+fn executor_notifies_observers() {
+    use bson::{bson, doc};
+    use huus::commands::CountDocumentsCommand;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let policy =
+        RetryPolicy::new().with_backoff(Duration::from_millis(0), Duration::from_millis(0));
+    let executor = Executor::new()
+        .with_observer(RecordingObserver { events: events.clone() })
+        .with_retry_policy(policy);
+
+    let command = CountDocumentsCommand::new("coll".to_string(), doc! { "a": 1 });
+    let mut attempts = 0;
+    let result = executor.execute(&command, |_| {
+        attempts += 1;
+        if attempts < 2 {
+            Err(write_error(112))
+        } else {
+            Ok(5)
+        }
+    });
+    assert_eq!(result.unwrap(), 5);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "start db.coll.countDocuments({ a: 1 })".to_string(),
+            "error db.coll.countDocuments({ a: 1 }) MongoDB: Write failed with code 112: 'failed'"
+                .to_string(),
+            "start db.coll.countDocuments({ a: 1 })".to_string(),
+            "success db.coll.countDocuments({ a: 1 })".to_string(),
+        ]
+    );
+}
+
+/// Observers should be notified about every attempt of an asynchronously executed command.
+#[test]
+fn executor_notifies_observers_asynchronously() {
+    use bson::{bson, doc};
+    use huus::commands::CountDocumentsCommand;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let policy =
+        RetryPolicy::new().with_backoff(Duration::from_millis(0), Duration::from_millis(0));
+    let executor = Executor::new()
+        .with_observer(RecordingObserver { events: events.clone() })
+        .with_retry_policy(policy);
+
+    let command = CountDocumentsCommand::new("coll".to_string(), doc! { "a": 1 });
+    let attempts = Mutex::new(0);
+    let future = executor.execute_async(
+        &command,
+        |_| async {
+            let mut attempts = attempts.lock().unwrap();
+            *attempts += 1;
+            if *attempts < 2 {
+                Err(write_error(112))
+            } else {
+                Ok(5)
+            }
+        },
+        |_| std::future::ready(()),
+    );
+    fn assert_send<T: Send>(_: &T) {}
+    assert_send(&future);
+
+    assert_eq!(block_on(future).unwrap(), 5);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "start db.coll.countDocuments({ a: 1 })".to_string(),
+            "error db.coll.countDocuments({ a: 1 }) MongoDB: Write failed with code 112: 'failed'"
+                .to_string(),
+            "start db.coll.countDocuments({ a: 1 })".to_string(),
+            "success db.coll.countDocuments({ a: 1 })".to_string(),
+        ]
+    );
+}
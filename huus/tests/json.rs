@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `json` module.
+
+use bson::{bson, doc};
+
+use huus::json::{to_extended_json, JsonMode};
+
+/// Canonical mode should preserve the types of all values.
+#[test]
+fn canonical_extended_json() {
+    let date = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap();
+    let document = doc! {
+        "int": 1,
+        "long": 2i64,
+        "double": 1.5,
+        "date": bson::Bson::UtcDatetime(date.into()),
+        "id": bson::oid::ObjectId::with_string("0123456789abcdef01234567").unwrap(),
+        "array": [true, bson::Bson::Null, "a\"b"],
+    };
+    let expected = concat!(
+        r#"{"int":{"$numberInt":"1"},"long":{"$numberLong":"2"},"#,
+        r#""double":{"$numberDouble":"1.5"},"date":{"$date":{"$numberLong":"1577836800000"}},"#,
+        r#""id":{"$oid":"0123456789abcdef01234567"},"array":[true,null,"a\"b"]}"#,
+    );
+    assert_eq!(to_extended_json(&document, JsonMode::Canonical), expected);
+}
+
+/// Relaxed mode should write numbers and dates in their natural form.
+#[test]
+fn relaxed_extended_json() {
+    let date = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap();
+    let document = doc! {
+        "int": 1,
+        "long": 2i64,
+        "double": 1.0,
+        "infinity": std::f64::INFINITY,
+        "date": bson::Bson::UtcDatetime(date.into()),
+        "regex": bson::Bson::RegExp("^a".to_string(), "i".to_string()),
+        "binary": bson::Bson::Binary(bson::spec::BinarySubtype::Generic, b"huus".to_vec()),
+    };
+    let expected = concat!(
+        r#"{"int":1,"long":2,"double":1.0,"infinity":{"$numberDouble":"Infinity"},"#,
+        r#""date":{"$date":"2020-01-01T00:00:00.000Z"},"#,
+        r#""regex":{"$regularExpression":{"pattern":"^a","options":"i"}},"#,
+        r#""binary":{"$binary":{"base64":"aHV1cw==","subType":"00"}}}"#,
+    );
+    assert_eq!(to_extended_json(&document, JsonMode::Relaxed), expected);
+}
@@ -105,3 +105,115 @@ fn test_outer_object_entry_filter() {
     assert_eq!(filter1.build_filter().into_doc(), expected1);
     assert_eq!(filter2.build_filter().into_doc(), expected2);
 }
+
+#[test]
+fn test_filter_combinators() {
+    let filter1 = Filter::with_field("a".to_string(), bson!(1));
+    let filter2 = Filter::with_field("b".to_string(), bson!(2));
+    let filter3 = Filter::with_field("c".to_string(), bson!(3));
+
+    let filter = filter1.clone().and(filter2.clone()).and(filter3.clone());
+    let expected = doc! { "$and": [{ "a": 1 }, { "b": 2 }, { "c": 3 }] };
+    assert_eq!(filter.into_doc(), expected);
+
+    let filter = filter1.clone().or(filter2.clone().or(filter3.clone()));
+    let expected = doc! { "$or": [{ "a": 1 }, { "b": 2 }, { "c": 3 }] };
+    assert_eq!(filter.into_doc(), expected);
+
+    let filter = filter1.clone().nor(filter2.clone()).nor(filter3);
+    let expected = doc! { "$nor": [{ "$nor": [{ "a": 1 }, { "b": 2 }] }, { "c": 3 }] };
+    assert_eq!(filter.into_doc(), expected);
+
+    let filter = filter1.and(filter2.clone().or(filter2));
+    let expected = doc! { "$and": [{ "a": 1 }, { "$or": [{ "b": 2 }, { "b": 2 }] }] };
+    assert_eq!(filter.into_doc(), expected);
+}
+
+#[test]
+fn test_filter_incorporation_of_colliding_keys() {
+    let mut filter = Filter::with_field("a".to_string(), bson!({ "$gt": 1 }));
+    filter.incorporate(Filter::with_field("a".to_string(), bson!({ "$lt": 5 })));
+    filter.incorporate(Filter::with_field("a".to_string(), bson!({ "$gt": 2 })));
+    filter.incorporate(Filter::with_field("b".to_string(), bson!(1)));
+    filter.incorporate(Filter::with_field("b".to_string(), bson!(1)));
+    filter.incorporate(Filter::with_field("b".to_string(), bson!({ "$ne": 2 })));
+    filter.incorporate(Filter::with_field("$or".to_string(), bson!([{ "b": 1 }, { "c": 1 }])));
+    filter.incorporate(Filter::with_field("$or".to_string(), bson!([{ "b": 2 }, { "c": 2 }])));
+    filter.incorporate(Filter::with_field("$and".to_string(), bson!([{ "d": 1 }])));
+
+    let expected = doc! {
+        "a": { "$gt": 1, "$lt": 5 },
+        "$and": [
+            { "a": { "$gt": 2 } },
+            { "b": { "$ne": 2 } },
+            { "$or": [{ "b": 2 }, { "c": 2 }] },
+            { "d": 1 },
+        ],
+        "b": 1,
+        "$or": [{ "b": 1 }, { "c": 1 }],
+    };
+    assert_eq!(filter.into_doc(), expected);
+}
+
+#[test]
+fn test_object_entry_filter_with_colliding_conditions() {
+    let data = DataFilter1 { int: I32Entry::Value(3), string: StringEntry::Empty };
+    let entry1 = ObjectEntry::Dot::<DataFilter1, Data1>(data);
+    let data = DataFilter1 {
+        int: I32Entry::Comparison(Comparison::Gt(1)),
+        string: StringEntry::Value("abc".to_string()),
+    };
+    let entry2 = ObjectEntry::Dot::<DataFilter1, Data1>(data);
+
+    let mut filter = entry1.build_filter(KEY.to_string());
+    filter.incorporate(entry2.build_filter(KEY.to_string()));
+
+    let expected = doc! {
+        KEY.to_string() + ".int": 3,
+        "$and": [{ KEY.to_string() + ".int": { "$gt": 1 } }],
+        KEY.to_string() + ".string": "abc",
+    };
+    assert_eq!(filter.into_doc(), expected);
+}
+
+#[test]
+fn test_filter_negation() {
+    let filter = !Filter::with_field("a".to_string(), bson!({ "$gt": 1, "$lt": 5 }));
+    let expected = doc! { "a": { "$not": { "$gt": 1, "$lt": 5 } } };
+    assert_eq!(filter.into_doc(), expected);
+
+    let filter = !Filter::with_field("a".to_string(), bson!({ "b": 1 }));
+    let expected = doc! { "a": { "$ne": { "b": 1 } } };
+    assert_eq!(filter.into_doc(), expected);
+
+    let mut filter = Filter::with_field("a".to_string(), bson!(1));
+    filter.incorporate(Filter::with_field("b".to_string(), bson!(2)));
+    let expected = doc! { "$nor": [{ "a": 1, "b": 2 }] };
+    assert_eq!((!filter).into_doc(), expected);
+
+    let entry = ObjectEntry::Dot::<DataFilter1, Data1>(DataFilter1 {
+        int: I32Entry::Value(3),
+        string: StringEntry::Value("def".to_string()),
+    });
+    let entry = ObjectEntry::Logical(Box::new(Logical::Not(entry)));
+    let expected =
+        doc! { "$nor": [{ KEY.to_string() + ".int": 3, KEY.to_string() + ".string": "def" }] };
+    assert_eq!(entry.build_filter(KEY.to_string()).into_doc(), expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_filter_serialization() {
+    let filter = DataFilter2 {
+        data: ObjectEntry::Dot(DataFilter1 { int: I32Entry::Value(3), string: StringEntry::Empty }),
+        array: ArrayEntry::Array(Array::All(vec![5, 6])),
+    };
+    let expected = doc! {
+        "data.int": 3,
+        "array": { "$all": [5, 6] },
+    };
+
+    let serialized = bson::to_bson(&filter.build_filter()).unwrap();
+    let filter: Filter = bson::from_bson(serialized).unwrap();
+    assert_eq!(filter.into_doc(), expected);
+}
@@ -5,7 +5,7 @@
 
 use bson::{bson, doc};
 
-use huus::updates::{BuildInnerUpdate, BuildUpdate, Element, Operator};
+use huus::updates::{BuildInnerUpdate, BuildUpdate, Element};
 use huus::{updates, values};
 
 const KEY: &'static str = "xxx";
@@ -88,10 +88,7 @@ fn test_object_entry_update_nested_with_dot() {
             int: updates::I32Entry::Value(2),
             string: updates::StringEntry::Value("abc".to_string()),
         }),
-        array: updates::ArrayEntry::Array(
-            updates::Array::Pop(updates::PopOption::First),
-            Operator::None,
-        ),
+        array: updates::ArrayEntry::Pop(updates::PopOption::First),
     };
     let expected = doc! {
         "data.int": 2,
@@ -106,13 +103,9 @@ fn test_object_entry_update_nested_with_dot() {
 fn test_object_entry_update_nested_with_value() {
     let object = DataUpdate2 {
         data: updates::ObjectEntry::Value(DataValue1 { int: 2, string: "abc".to_string() }),
-        array: updates::ArrayEntry::Array(
-            updates::Array::Pull(values::PullValue::Value(DataValue1 {
-                int: 3,
-                string: "def".to_string(),
-            })),
-            Operator::None,
-        ),
+        array: updates::ArrayEntry::Array(updates::Array::Pull(values::PullValue::Value(
+            DataValue1 { int: 3, string: "def".to_string() },
+        ))),
     };
     let expected = doc! {
         "data": { "int": 2, "string": "abc" },
@@ -156,3 +149,155 @@ fn test_object_entry_update_nested_indexed() {
     assert_eq!(object1.build_update().into_doc(), expected1);
     assert_eq!(object2.build_update().into_doc(), expected2);
 }
+
+#[test]
+fn test_update_with_array_filters() {
+    use huus::commands::{UpdateCommand, UpdateOptions};
+
+    let element = Element::Identified(
+        "el".to_string(),
+        DataUpdate1 { int: updates::I32Entry::Value(2), string: updates::StringEntry::Empty },
+    );
+    let entry = updates::ArrayEntry::Element::<DataUpdate1, DataValue1>(element);
+    let mut update =
+        entry.build_update("array".to_string()).with_array_filter(doc! { "el.int": { "$gt": 1 } });
+    update.incorporate(updates::Update::empty().with_array_filter(doc! { "el.int": { "$gt": 1 } }));
+    update.incorporate(updates::Update::empty().with_array_filter(doc! { "el.string": "abc" }));
+
+    let array_filters = vec![doc! { "el.int": { "$gt": 1 } }, doc! { "el.string": "abc" }];
+    assert_eq!(*update.get_array_filters(), array_filters);
+
+    let command =
+        UpdateCommand::new_update("coll".to_string(), doc! {}, update, UpdateOptions::UpdateMany);
+    let expected = UpdateCommand::new(
+        "coll".to_string(),
+        doc! {},
+        doc! { "array.$[el].int": 2 },
+        UpdateOptions::UpdateMany,
+    )
+    .with_array_filters(array_filters);
+    assert_eq!(command, expected);
+}
+
+#[test]
+fn test_update_validation() {
+    use huus::errors::UpdateError;
+    use huus::updates::{Field, NumericalUpdate};
+
+    let mut update = Field::Set(1).build_update("a.b".to_string());
+    update.incorporate(Field::Set(2).build_update("a.b".to_string()));
+    update.incorporate(Field::<i32>::Unset.build_update("ab".to_string()));
+    let mut entry = updates::I32Entry::Empty;
+    entry.inc(1);
+    update.incorporate(entry.build_update("c".to_string()));
+    assert_eq!(update.validate(), Ok(()));
+
+    update.incorporate(Field::<i32>::Unset.build_update("a".to_string()));
+    let expected = UpdateError::conflict(
+        "$set".to_string(),
+        "a.b".to_string(),
+        "$unset".to_string(),
+        "a".to_string(),
+    );
+    assert_eq!(update.try_into_doc(), Err(expected));
+
+    let mut update = Field::<i32>::Rename("d".to_string()).build_update("c".to_string());
+    update.incorporate(Field::Set(1).build_update("d".to_string()));
+    let expected = UpdateError::conflict(
+        "$rename".to_string(),
+        "d".to_string(),
+        "$set".to_string(),
+        "d".to_string(),
+    );
+    assert_eq!(update.validate(), Err(expected));
+}
+
+#[test]
+fn test_update_incorporate_at() {
+    use huus::updates::Field;
+
+    let inner = DataUpdate2 {
+        data: updates::ObjectEntry::Dot(DataUpdate1 {
+            int: updates::I32Entry::Value(2),
+            string: updates::StringEntry::Field(Field::Rename("name".to_string())),
+        }),
+        array: updates::ArrayEntry::Pop(updates::PopOption::First),
+    };
+    let mut update = Field::Set(1).build_update("count".to_string());
+    update.incorporate_at(KEY, inner.build_update());
+
+    let expected = doc! {
+        "xxx.data.int": 2,
+        "$rename": { "xxx.data.string": "xxx.name" },
+        "$set": { "count": 1 },
+        "$pop": { "xxx.array": -1 },
+    };
+    assert_eq!(update.into_doc(), expected);
+}
+
+#[test]
+fn test_update_unset_fields() {
+    let update = updates::Update::unset_fields(vec!["a", "b.c"]);
+    let expected = doc! { "$unset": { "b.c": true, "a": true } };
+    assert_eq!(update.into_doc(), expected);
+}
+
+#[test]
+fn test_update_builder() {
+    use huus::updates::{PopOption, UpdateBuilder};
+
+    let fields = vec!["a", "b"];
+    let mut builder = UpdateBuilder::new().inc("count", 1).pop("array", PopOption::Last);
+    for field in fields {
+        builder = builder.set(&format!("data.{}", field), field.to_string());
+    }
+    let update = builder
+        .unset("old")
+        .push_each("list.$[el]", vec![1i64, 2i64])
+        .array_filter(doc! { "el": { "$gt": 0 } })
+        .build();
+
+    assert_eq!(*update.get_array_filters(), vec![doc! { "el": { "$gt": 0 } }]);
+    let expected = doc! {
+        "$inc": { "count": 1 },
+        "$set": { "data.b": "b", "data.a": "a" },
+        "$unset": { "old": true },
+        "$pop": { "array": 1 },
+        "$push": { "list.$[el]": { "$each": [1i64, 2i64] } },
+    };
+    assert_eq!(update.into_doc(), expected);
+}
+
+#[test]
+fn test_update_is_empty() {
+    let update = DataUpdate1 { int: updates::I32Entry::Empty, string: updates::StringEntry::Empty };
+    let update = update.build_update(KEY.to_string()).with_array_filter(doc! { "el": 1 });
+    assert!(update.is_empty());
+
+    let update = DataUpdate1 {
+        int: updates::I32Entry::Empty,
+        string: updates::StringEntry::Field(updates::Field::Unset),
+    };
+    assert!(!update.build_update(KEY.to_string()).is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_update_serialization() {
+    let object = DataUpdate2 {
+        data: updates::ObjectEntry::Dot(DataUpdate1 {
+            int: updates::I32Entry::Value(2),
+            string: updates::StringEntry::Value("abc".to_string()),
+        }),
+        array: updates::ArrayEntry::Pop(updates::PopOption::First),
+    };
+    let expected = doc! {
+        "data.int": 2,
+        "data.string": "abc",
+        "$pop": { "array": -1 },
+    };
+
+    let serialized = bson::to_bson(&object.build_update()).unwrap();
+    let update: updates::Update = bson::from_bson(serialized).unwrap();
+    assert_eq!(update.into_doc(), expected);
+}
@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `pagination` module.
+
+use bson::{bson, doc};
+
+use huus::commands::FindOptions;
+use huus::errors::{HuusError, PaginationError};
+use huus::pagination::{PageCommand, Pagination};
+
+fn oid(string: &str) -> bson::oid::ObjectId {
+    bson::oid::ObjectId::with_string(string).unwrap()
+}
+
+/// Cursor-based pagination should continue after the last document of the previous page.
+#[test]
+fn cursor_pagination() {
+    let first = PageCommand::<bson::Document>::new(
+        "collection".to_string(),
+        doc! { "a": 1 },
+        Pagination::first(2).unwrap(),
+    );
+    let find = first.get_find_command();
+    assert_eq!(find.get_filter(), &doc! { "a": 1 });
+    assert_eq!(find.get_options(), &FindOptions::new().with_sort(doc! { "_id": 1 }).with_limit(3));
+
+    let documents = vec![
+        doc! { "_id": oid("000000000000000000000001") },
+        doc! { "_id": oid("000000000000000000000002") },
+        doc! { "_id": oid("000000000000000000000003") },
+    ];
+    let page = first.build_page(documents).unwrap();
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.next, Some(Pagination::after(oid("000000000000000000000002"), 2).unwrap()));
+
+    let second = PageCommand::<bson::Document>::new(
+        "collection".to_string(),
+        doc! { "a": 1 },
+        page.next.unwrap(),
+    );
+    let expected = doc! {
+        "$and": [{ "a": 1 }, { "_id": { "$gt": oid("000000000000000000000002") } }],
+    };
+    assert_eq!(second.get_find_command().get_filter(), &expected);
+
+    let page = second.build_page(vec![doc! { "_id": oid("000000000000000000000003") }]).unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.next, None);
+}
+
+/// Offset pagination should skip the preceding pages.
+#[test]
+fn offset_pagination() {
+    let command = PageCommand::<bson::Document>::new(
+        "collection".to_string(),
+        doc! {},
+        Pagination::offset(2, 10).unwrap(),
+    );
+    let options = FindOptions::new().with_sort(doc! { "_id": 1 }).with_limit(11).with_skip(20);
+    assert_eq!(command.get_find_command().get_options(), &options);
+
+    let documents = (0..11).map(|i| doc! { "i": i }).collect();
+    let page = command.build_page(documents).unwrap();
+    assert_eq!(page.items.len(), 10);
+    assert_eq!(page.next, Some(Pagination::offset(3, 10).unwrap()));
+}
+
+/// Page sizes of zero and offsets out of range should be rejected.
+#[test]
+fn invalid_pagination() {
+    assert_eq!(Pagination::first(0), Err(PaginationError::ZeroPageSize));
+    assert_eq!(Pagination::offset(0, 0), Err(PaginationError::ZeroPageSize));
+    let overflow = PaginationError::Overflow { page: 2, page_size: u32::MAX };
+    assert_eq!(Pagination::offset(2, u32::MAX), Err(overflow));
+
+    let pagination = Pagination::first(u32::MAX).unwrap();
+    let command = PageCommand::<bson::Document>::new("collection".to_string(), doc! {}, pagination);
+    assert_eq!(command.get_find_command().get_options().get_limit(), Some(u32::MAX));
+
+    let pagination = Pagination::Cursor { after: None, page_size: 0 };
+    let command = PageCommand::<bson::Document>::new("collection".to_string(), doc! {}, pagination);
+    let result = command.build_page(vec![doc! { "_id": oid("000000000000000000000001") }]);
+    assert!(matches!(result, Err(HuusError::Pagination(PaginationError::ZeroPageSize))));
+
+    let pagination = Pagination::offset(u32::MAX / 2, 2).unwrap();
+    let command = PageCommand::<bson::Document>::new("collection".to_string(), doc! {}, pagination);
+    let documents = (0..3).map(|i| doc! { "i": i }).collect();
+    let result = command.build_page(documents);
+    assert!(matches!(result, Err(HuusError::Pagination(PaginationError::Overflow { .. }))));
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `shell` module.
+
+use bson::{bson, doc};
+
+use huus::shell::document_to_shell;
+
+/// Values should be written with the shell constructors of their types.
+#[test]
+fn document_to_shell_syntax() {
+    let date = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap();
+    let document = doc! {
+        "int": 1,
+        "long": 2i64,
+        "double": 1.5,
+        "date": bson::Bson::UtcDatetime(date.into()),
+        "id": bson::oid::ObjectId::with_string("0123456789abcdef01234567").unwrap(),
+        "a.b": { "$in": ["x\"y", bson::Bson::Null] },
+        "regex": bson::Bson::RegExp("^a/b".to_string(), "i".to_string()),
+        "binary": bson::Bson::Binary(bson::spec::BinarySubtype::Generic, b"huus".to_vec()),
+        "empty": {},
+    };
+    let expected = concat!(
+        r#"{ int: 1, long: NumberLong("2"), double: 1.5, "#,
+        r#"date: ISODate("2020-01-01T00:00:00.000Z"), "#,
+        r#"id: ObjectId("0123456789abcdef01234567"), "a.b": { $in: [ "x\"y", null ] }, "#,
+        r#"regex: /^a\/b/i, binary: BinData(0, "aHV1cw=="), empty: {} }"#,
+    );
+    assert_eq!(document_to_shell(&document), expected);
+}
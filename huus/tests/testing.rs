@@ -0,0 +1,219 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests of `testing` module.
+
+use bson::{bson, doc};
+
+use huus::commands::*;
+use huus::testing::MemoryCollection;
+
+fn make_collection() -> MemoryCollection<bson::Document> {
+    MemoryCollection::with_data(vec![
+        doc! { "_id": 1, "name": "a", "count": 5, "tags": ["x", "y"], "sub": { "n": 1 } },
+        doc! { "_id": 2, "name": "b", "count": 10i64, "tags": ["y"], "sub": { "n": 2 } },
+        doc! { "_id": 3, "name": "c", "count": 7.5, "items": [{ "k": 1 }, { "k": 2 }] },
+    ])
+}
+
+fn find_ids(collection: &MemoryCollection<bson::Document>, filter: bson::Document) -> Vec<i32> {
+    let command = FindCommand::new("coll".to_string(), filter, None);
+    let documents = collection.find(&command).unwrap();
+    documents.iter().map(|document| document.get_i32("_id").unwrap()).collect()
+}
+
+/// Filters should be evaluated like by the database.
+#[test]
+fn memory_collection_find() {
+    let collection = make_collection();
+    assert_eq!(find_ids(&collection, doc! {}), vec![1, 2, 3]);
+    assert_eq!(find_ids(&collection, doc! { "name": "b" }), vec![2]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$gt": 5, "$lt": 10 } }), vec![3]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$gte": 10.0 } }), vec![2]);
+    assert_eq!(find_ids(&collection, doc! { "tags": "y" }), vec![1, 2]);
+    assert_eq!(find_ids(&collection, doc! { "tags": { "$all": ["x", "y"] } }), vec![1]);
+    assert_eq!(find_ids(&collection, doc! { "tags": { "$size": 1 } }), vec![2]);
+    assert_eq!(find_ids(&collection, doc! { "sub.n": { "$in": [2, 3] } }), vec![2]);
+    assert_eq!(find_ids(&collection, doc! { "sub": { "$exists": false } }), vec![3]);
+    assert_eq!(find_ids(&collection, doc! { "sub.n": { "$ne": 1 } }), vec![2, 3]);
+    assert_eq!(find_ids(&collection, doc! { "items.k": 2 }), vec![3]);
+    assert_eq!(find_ids(&collection, doc! { "items": { "$elemMatch": { "k": 1 } } }), vec![3]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$not": { "$gt": 5 } } }), vec![1]);
+    assert_eq!(
+        find_ids(&collection, doc! { "$or": [{ "name": "a" }, { "sub.n": 2 }] }),
+        vec![1, 2]
+    );
+
+    let command = FindCommand::new("coll".to_string(), doc! {}, None)
+        .with_sort(doc! { "count": -1 })
+        .with_skip(1)
+        .with_limit(1);
+    let documents: Vec<bson::Document> = collection.find(&command).unwrap();
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0].get_i32("_id"), Ok(3));
+
+    let command = FindOneCommand::new("coll".to_string(), doc! { "name": "z" });
+    assert_eq!(collection.find_one(&command).unwrap(), None);
+    let command = FindOneCommand::new("coll".to_string(), doc! { "count": { "$gte": 5 } })
+        .with_sort(doc! { "count": -1 });
+    let document: Option<bson::Document> = collection.find_one(&command).unwrap();
+    assert_eq!(document.unwrap().get_i32("_id"), Ok(2));
+    assert_eq!(collection.count(&CountDocumentsCommand::new("coll".to_string(), doc! {})), 3);
+}
+
+/// Type, regular expression and bitwise operators should be evaluated like by the database.
+#[test]
+fn memory_collection_find_by_type_pattern_and_bits() {
+    let collection = make_collection();
+    assert_eq!(find_ids(&collection, doc! { "count": { "$type": "int" } }), vec![1]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$type": [18, "double"] } }), vec![2, 3]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$type": "number" } }), vec![1, 2, 3]);
+    assert_eq!(find_ids(&collection, doc! { "tags": { "$type": "array" } }), vec![1, 2]);
+    assert_eq!(find_ids(&collection, doc! { "tags": { "$type": "string" } }), vec![1, 2]);
+
+    let pattern = bson::Bson::RegExp("^[AB]$".to_string(), "i".to_string());
+    assert_eq!(find_ids(&collection, doc! { "name": pattern.clone() }), vec![1, 2]);
+    assert_eq!(find_ids(&collection, doc! { "name": { "$not": pattern.clone() } }), vec![3]);
+    assert_eq!(find_ids(&collection, doc! { "name": { "$in": [pattern, "c"] } }), vec![1, 2, 3]);
+    assert_eq!(find_ids(&collection, doc! { "tags": { "$regex": "x|z" } }), vec![1]);
+    assert_eq!(
+        find_ids(&collection, doc! { "name": { "$regex": "^B", "$options": "i" } }),
+        vec![2]
+    );
+    assert_eq!(find_ids(&collection, doc! { "name": { "$not": { "$regex": "a|b" } } }), vec![3]);
+
+    assert_eq!(find_ids(&collection, doc! { "count": { "$bitsAllSet": 5 } }), vec![1]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$bitsAnySet": [1, 3] } }), vec![2]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$bitsAllClear": [1] } }), vec![1]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$bitsAnyClear": 10 } }), vec![1]);
+
+    assert_eq!(find_ids(&collection, doc! { "count": { "$mod": [5, 0] } }), vec![1, 2]);
+    assert_eq!(find_ids(&collection, doc! { "count": { "$mod": [0, 0] } }), Vec::<i32>::new());
+}
+
+/// Update operators should modify the matching documents and report the counts.
+#[test]
+fn memory_collection_update() {
+    let mut collection = make_collection();
+    let command = UpdateCommand::new(
+        "coll".to_string(),
+        doc! { "tags": "y" },
+        doc! {
+            "$set": { "sub.m": "new" },
+            "$inc": { "count": 1 },
+            "$push": { "tags": { "$each": ["z"], "$slice": -2 } },
+        },
+        UpdateOptions::UpdateMany,
+    );
+    let expected = UpdateResult { matched: 2, modified: 2, upserted_id: None };
+    assert_eq!(collection.update(&command).unwrap(), expected);
+
+    let documents = collection.get_documents();
+    assert_eq!(
+        documents[0],
+        doc! {
+            "_id": 1,
+            "name": "a",
+            "count": 6,
+            "tags": ["y", "z"],
+            "sub": { "n": 1, "m": "new" },
+        }
+    );
+    assert_eq!(
+        documents[1],
+        doc! {
+            "_id": 2,
+            "name": "b",
+            "count": 11i64,
+            "tags": ["y", "z"],
+            "sub": { "n": 2, "m": "new" },
+        }
+    );
+
+    let command = UpdateCommand::new(
+        "coll".to_string(),
+        doc! { "_id": 3 },
+        doc! { "$pull": { "items": { "k": 1 } }, "$unset": { "count": "" } },
+        UpdateOptions::UpdateOne,
+    );
+    collection.update(&command).unwrap();
+    assert_eq!(
+        collection.get_documents()[2],
+        doc! { "_id": 3, "name": "c", "items": [{ "k": 2 }] }
+    );
+
+    let command = UpdateCommand::new(
+        "coll".to_string(),
+        doc! { "_id": 3 },
+        doc! { "$set": { "name": "c" } },
+        UpdateOptions::UpdateOne,
+    );
+    let expected = UpdateResult { matched: 1, modified: 0, upserted_id: None };
+    assert_eq!(collection.update(&command).unwrap(), expected);
+
+    let command = UpdateCommand::new(
+        "coll".to_string(),
+        doc! { "_id": { "$lte": 2 } },
+        doc! { "$bit": { "count": { "and": 3, "or": 8 } } },
+        UpdateOptions::UpdateMany,
+    );
+    collection.update(&command).unwrap();
+    assert_eq!(collection.get_documents()[0].get_i32("count"), Ok(10));
+    assert_eq!(collection.get_documents()[1].get_i64("count"), Ok(11));
+
+    let command = UpdateCommand::new(
+        "coll".to_string(),
+        doc! { "_id": 1 },
+        doc! { "$inc": { "name": 1 } },
+        UpdateOptions::UpdateOne,
+    );
+    assert!(collection.update(&command).is_err());
+
+    let command =
+        UpdateCommand::new("coll".to_string(), doc! {}, doc! {}, UpdateOptions::UpdateMany);
+    match collection.update(&command) {
+        Err(huus::errors::HuusError::Update(huus::errors::UpdateError::Empty)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+/// Upserts should insert a document built from the filter and the update if none matches.
+#[test]
+fn memory_collection_upsert() {
+    let mut collection = make_collection();
+    let command = UpdateCommand::new(
+        "coll".to_string(),
+        doc! { "_id": 4, "name": { "$eq": "d" }, "count": { "$gt": 1 } },
+        doc! { "$set": { "count": 2 }, "$setOnInsert": { "new": true } },
+        UpdateOptions::UpdateOne,
+    )
+    .with_upsert(true);
+    let expected = UpdateResult { matched: 0, modified: 0, upserted_id: Some(bson!(4)) };
+    assert_eq!(collection.update(&command).unwrap(), expected);
+    assert_eq!(
+        collection.get_documents()[3],
+        doc! { "_id": 4, "name": "d", "count": 2, "new": true }
+    );
+}
+
+/// Inserts should reject duplicate IDs and deletes should remove the matching documents.
+#[test]
+fn memory_collection_insert_and_delete() {
+    let mut collection = make_collection();
+    let command = InsertCommand::new("coll".to_string(), doc! { "_id": 1 });
+    assert!(collection.insert(&command).is_err());
+
+    let documents = vec![doc! { "_id": 4 }, doc! { "_id": 2 }, doc! { "_id": 5 }];
+    let command = InsertManyCommand::new("coll".to_string(), documents).with_ordered(false);
+    let expected =
+        InsertManyResult { inserted_ids: vec![bson!(4), bson!(5)], duplicate_ids: vec![bson!(2)] };
+    assert_eq!(collection.insert_many(&command).unwrap(), expected);
+    assert_eq!(collection.len(), 5);
+
+    let filter = doc! { "_id": { "$gte": 3 } };
+    let command = DeleteCommand::new("coll".to_string(), filter.clone(), DeleteOptions::DeleteOne);
+    assert_eq!(collection.delete(&command).unwrap(), DeleteResult { deleted: 1 });
+    let command = DeleteCommand::new("coll".to_string(), filter, DeleteOptions::DeleteMany);
+    assert_eq!(collection.delete(&command).unwrap(), DeleteResult { deleted: 2 });
+    assert_eq!(find_ids(&collection, doc! {}), vec![1, 2]);
+}
@@ -34,3 +34,8 @@ pub struct Doc3 in "coll_3" {
     bson: Bson,
 }
 
+pub struct Doc4 in "coll_4" {
+    number: f64,
+    location: GeoPoint?,
+    area: Bson?,
+}
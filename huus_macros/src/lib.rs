@@ -62,6 +62,17 @@ pub fn data(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     "bson::Document::new()".parse().expect("Parse into TokenStream")
 }
 
+#[proc_macro]
+pub fn data_many(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_data_many() {
+            return generator.generate_data_many();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
 #[proc_macro]
 pub fn filter(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let formulation = Formulation::new(false);
@@ -84,6 +95,116 @@ pub fn update(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     "bson::Document::new()".parse().expect("Parse into TokenStream")
 }
 
+#[proc_macro]
+pub fn replace(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_replacement() {
+            return generator.generate_update();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn projection(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_projection() {
+            return generator.generate_projection();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn find(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_find() {
+            return generator.generate_find();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn find_and_modify(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_find_and_modify() {
+            return generator.generate_find_and_modify();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn lookup(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_lookup() {
+            return generator.generate_lookup();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn count(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_count() {
+            return generator.generate_count();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn delete(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_delete() {
+            return generator.generate_delete();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn distinct(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_distinct() {
+            return generator.generate_distinct();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn aggregate(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_aggregate() {
+            return generator.generate_aggregate();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
+#[proc_macro]
+pub fn sort(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(false);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Ok(generator) = interpreter.build().verify_sort() {
+            return generator.generate_sort();
+        }
+    }
+    "bson::Document::new()".parse().expect("Parse into TokenStream")
+}
+
 #[cfg(feature = "testing")]
 #[proc_macro]
 pub fn data_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -93,7 +214,19 @@ pub fn data_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream
             return verdict.format().parse().expect("Parse into TokenStream");
         }
     }
-    "Vec::<huus_macros_support::Problem>::new()".parse().expect("Parse into TokenStream")
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn data_many_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_data_many() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
 }
 
 #[cfg(feature = "testing")]
@@ -105,7 +238,7 @@ pub fn filter_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStrea
             return verdict.format().parse().expect("Parse into TokenStream");
         }
     }
-    "Vec::<huus_macros_support::Problem>::new()".parse().expect("Parse into TokenStream")
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
 }
 
 #[cfg(feature = "testing")]
@@ -117,5 +250,125 @@ pub fn update_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStrea
             return verdict.format().parse().expect("Parse into TokenStream");
         }
     }
-    "Vec::<huus_macros_support::Problem>::new()".parse().expect("Parse into TokenStream")
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn replace_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_replacement() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn projection_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_projection() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn sort_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_sort() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn find_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_find() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn count_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_count() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn delete_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_delete() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn distinct_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_distinct() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn find_and_modify_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_find_and_modify() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn lookup_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_lookup() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
+}
+
+#[cfg(feature = "testing")]
+#[proc_macro]
+pub fn aggregate_testing(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let formulation = Formulation::new(true);
+    if let Ok(interpreter) = formulation.parse(stream) {
+        if let Err(verdict) = interpreter.build().verify_aggregate() {
+            return verdict.format().parse().expect("Parse into TokenStream");
+        }
+    }
+    "Vec::<huus_macros_support::LocatedProblem>::new()".parse().expect("Parse into TokenStream")
 }
@@ -110,6 +110,86 @@ fn test_data_contents() {
     assert_eq!(data, Doc3Data::huus_from_bson(bson::Bson::Document(expected)).unwrap());
 }
 
+#[test]
+fn test_data_conversion_errors() {
+    use bson::{bson, doc};
+    use huus::conversions::FromDoc;
+    use huus::errors::{ConversionError, DecodeError};
+
+    let doc = doc! { "data": { "int": "abc", "str": "def" } };
+    let expected = DecodeError {
+        path: "data.int".to_string(),
+        expected: "int".to_string(),
+        found: "string".to_string(),
+    };
+    match Doc2Data::from_doc(doc) {
+        Err(ConversionError::Decode(error)) => assert_eq!(error, expected),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let doc = doc! { "data": { "int": 1 } };
+    match Doc2Data::from_doc(doc) {
+        Err(ConversionError::MissingKey { key }) => assert_eq!(key, "data.str"),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_data_strict_conversion() {
+    use bson::{bson, doc};
+    use huus::conversions::FromDoc;
+    use huus::errors::ConversionError;
+
+    let doc = doc! { "data": { "str": "abc", "legacy": 1 }, "string": "def" };
+    let expected = Doc2Data {
+        data: Some(Doc1Data { integer: None, string: "abc".to_string() }),
+        string: Some("def".to_string()),
+    };
+    assert_eq!(Doc2Data::from_doc(doc.clone()).unwrap(), expected);
+    match Doc2Data::from_doc_strict(doc) {
+        Err(ConversionError::UnexpectedKey { key }) => assert_eq!(key, "data.legacy"),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let doc = doc! { "data": { "str": "abc" }, "string": "def", "legacy": 1 };
+    match Doc2Data::from_doc_strict(doc) {
+        Err(ConversionError::UnexpectedKey { key }) => assert_eq!(key, "legacy"),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let doc = doc! { "_huus_variant": "choice_1", "str": "abc", "legacy": 1 };
+    match Union1Data::from_doc_strict(doc) {
+        Err(ConversionError::UnexpectedKey { key }) => assert_eq!(key, "legacy"),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    let doc = doc! { "_huus_variant": "choice_1", "str": "abc" };
+    let expected = Union1Data::Choice1(Doc1Data { integer: None, string: "abc".to_string() });
+    assert_eq!(Union1Data::from_doc_strict(doc).unwrap(), expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_data_serialization() {
+    let data = Doc2Data {
+        data: Some(Doc1Data { integer: None, string: "abc".to_string() }),
+        string: None,
+    };
+    let json = serde_json::json!({ "data": { "str": "abc" } });
+    assert_eq!(serde_json::to_value(&data).unwrap(), json);
+    assert_eq!(serde_json::from_value::<Doc2Data>(json).unwrap(), data);
+
+    let data = Union1Data::Choice2(Doc1Data { integer: Some(1), string: "def".to_string() });
+    let json = serde_json::json!({ "_huus_variant": "choice_2", "int": 1, "str": "def" });
+    assert_eq!(serde_json::to_value(&data).unwrap(), json);
+    assert_eq!(serde_json::from_value::<Union1Data>(json).unwrap(), data);
+
+    let data: std::collections::BTreeMap<Enum1Data, i32> =
+        maplit::btreemap! { Enum1Data::Choice1 => 1 };
+    let json = serde_json::json!({ "choice_1": 1 });
+    assert_eq!(serde_json::to_value(&data).unwrap(), json);
+}
+
 #[test]
 fn test_filter_contents_by_assign() {
     use bson::{bson, doc};
@@ -259,6 +339,30 @@ fn test_filter_contents_by_modification() {
     }
 }
 
+#[test]
+fn test_filter_elem_match() {
+    use bson::{bson, doc};
+    use huus::filters::{ArrayFilter, BuildFilter, ComparisonFilter};
+
+    let mut data = Doc1Filter::default();
+    data.integer.gt(3);
+    data.string = "abc".into();
+
+    let mut integer = huus::filters::I64Entry::default();
+    integer.lt(5);
+
+    let mut filter = Doc3Filter::default();
+    filter.array.elem_match(data);
+    filter.integers.elem_match(integer);
+
+    let expected = doc! {
+        "array": { "$elemMatch": { "int": { "$gt": 3 }, "str": "abc" } },
+        "integers": { "$elemMatch": { "$lt": 5i64 } },
+    };
+
+    assert_eq!(filter.build_filter().into_doc(), expected);
+}
+
 #[test]
 fn test_value_contents_by_assign() {
     use bson::{bson, doc};
@@ -371,13 +475,12 @@ fn test_update_contents_by_assign() {
             integer: huus::updates::I32Entry::Value(1),
             string: huus::updates::StringEntry::Value("abc".to_string()),
         }),
-        array: huus::updates::ArrayEntry::Array(
-            huus::updates::Array::Push(huus::values::PushValue::Value(Doc1Value {
+        array: huus::updates::ArrayEntry::Array(huus::updates::Array::Push(
+            huus::values::PushValue::Value(Doc1Value {
                 integer: 2.into(),
                 string: "def".to_string().into(),
-            })),
-            huus::updates::Operator::None,
-        ),
+            }),
+        )),
         simple_map: huus::updates::BTreeMapEntry::Value(maplit::btreemap! {
             "choice_1".to_string() => "one".to_string(),
             "choice_2".to_string() => "two".to_string(),
@@ -395,10 +498,9 @@ fn test_update_contents_by_assign() {
         boolean: huus::updates::BooleanEntry::Value(true),
         date: huus::updates::DateEntry::Value(date),
         indexed: huus::updates::StringEntry::Value("indexed".to_string()),
-        integers: huus::updates::ArrayEntry::Array(
-            huus::updates::Array::Push(huus::values::PushValue::Value(4)),
-            huus::updates::Operator::First,
-        ),
+        integers: huus::updates::ArrayEntry::Array(huus::updates::Array::Push(
+            huus::values::PushValue::Value(4),
+        )),
         choice: huus::updates::EnumEntry::Value(Enum1Value::Choice1),
         union: huus::updates::ObjectEntry::Dot(Union1Update::Choice1(Doc1Update {
             integer: huus::updates::I32Entry::Value(6),
@@ -414,12 +516,9 @@ fn test_update_contents_by_assign() {
             integer: 1.into(),
             string: "abc".into(),
         }),
-        array: huus::updates::ArrayEntry::Array(
-            huus::updates::Array::Push(
-                Doc1Value { integer: 2.into(), string: "def".to_string().into() }.into(),
-            ),
-            huus::updates::Operator::None,
-        ),
+        array: huus::updates::ArrayEntry::Array(huus::updates::Array::Push(
+            Doc1Value { integer: 2.into(), string: "def".to_string().into() }.into(),
+        )),
         simple_map: huus::updates::BTreeMapEntry::Value(maplit::btreemap! {
             "choice_1".to_string() => "one".to_string(),
             "choice_2".to_string() => "two".to_string(),
@@ -437,10 +536,7 @@ fn test_update_contents_by_assign() {
         boolean: true.into(),
         date: date.into(),
         indexed: "indexed".into(),
-        integers: huus::updates::ArrayEntry::Array(
-            huus::updates::Array::Push(4.into()),
-            huus::updates::Operator::First,
-        ),
+        integers: huus::updates::ArrayEntry::Array(huus::updates::Array::Push(4.into())),
         choice: huus::updates::EnumEntry::Value(Enum1Value::Choice1),
         union: huus::updates::ObjectEntry::Dot(Union1Update::Choice1(Doc1Update {
             integer: huus::updates::I32Entry::Value(6),
@@ -469,7 +565,7 @@ fn test_update_contents_by_assign() {
         "union._huus_variant": "choice_1",
         "bson": { "a": 1, "b": 2 },
         "$push": {
-            "integers.$": 4i64,
+            "integers": 4i64,
             "array": {
                 "int": 2i32,
                 "str": "def",
@@ -483,10 +579,11 @@ fn test_update_contents_by_assign() {
 #[test]
 fn test_update_contents_by_modification() {
     use bson::{bson, doc};
-    use huus::updates::Operator;
     use huus::updates::{
-        ArrayUpdate, BuildUpdate, ElementUpdate, FieldUpdate, NumericalUpdate, ObjectUpdate,
+        AddToSetUpdate, BuildUpdate, ElementUpdate, FieldUpdate, NumericalUpdate, ObjectUpdate,
+        PushUpdate,
     };
+    use huus::values::Sort;
 
     let update = Doc3Update::default();
     let expected = doc! {};
@@ -499,7 +596,7 @@ fn test_update_contents_by_modification() {
     let mut update = Doc3Update::default();
     update.data.dot(update1.clone());
     update.boolean.set(true);
-    update.integers.push(4.into(), Operator::First);
+    update.integers.push(4.into());
     update.array.at(6, update1);
 
     let expected = doc! {
@@ -507,10 +604,127 @@ fn test_update_contents_by_modification() {
         "array.6.str": "abc",
         "$max": { "array.6.int": 40, "data.int": 40i32 },
         "$set": { "boolean": true },
-        "$push": { "integers.$": 4i64 },
+        "$push": { "integers": 4i64 },
+    };
+
+    assert_eq!(update.build_update().into_doc(), expected);
+
+    let mut update = Doc3Update::default();
+    update.integers.push_each(vec![4, 7]).slice(-10).sort_by(Sort::Descending);
+
+    let expected = doc! {
+        "$push": { "integers": { "$each": [4i64, 7i64], "$slice": -10i64, "$sort": -1 } },
     };
 
     assert_eq!(update.build_update().into_doc(), expected);
+
+    let mut update = Doc3Update::default();
+    update.integers.add_to_set_each(vec![4, 7]);
+
+    let expected = doc! { "$addToSet": { "integers": { "$each": [4i64, 7i64] } } };
+
+    assert_eq!(update.build_update().into_doc(), expected);
+}
+
+#[test]
+fn test_update_rename_to() {
+    use bson::{bson, doc};
+    use huus::errors::UpdateError;
+    use huus::updates::Update;
+
+    let expected = doc! { "$rename": { "data.str": "indexed" } };
+    assert_eq!(Update::rename_to::<Coll3>("data.str", "indexed").unwrap().into_doc(), expected);
+
+    let expected = UpdateError::UnknownField { path: "legacy".to_string() };
+    assert_eq!(Update::rename_to::<Coll3>("legacy", "indexed").unwrap_err(), expected);
+    assert_eq!(Update::rename_to::<Coll3>("indexed", "legacy").unwrap_err(), expected);
+
+    let expected = UpdateError::UnknownField { path: "data.legacy".to_string() };
+    assert_eq!(Update::rename_to::<Coll3>("data.legacy", "indexed").unwrap_err(), expected);
+
+    let path = "boolean".to_string();
+    let expected = UpdateError::IncompatibleTypes { path, other_path: "indexed".to_string() };
+    assert_eq!(Update::rename_to::<Coll3>("boolean", "indexed").unwrap_err(), expected);
+
+    let path = "data.int".to_string();
+    let expected = UpdateError::IncompatibleTypes { path, other_path: "data.str".to_string() };
+    assert_eq!(Update::rename_to::<Coll3>("data.int", "data.str").unwrap_err(), expected);
+
+    let expected = doc! { "$rename": { "data.str": "union.str" } };
+    assert_eq!(Update::rename_to::<Coll3>("data.str", "union.str").unwrap().into_doc(), expected);
+
+    let expected = UpdateError::RenameToSelf { path: "indexed".to_string() };
+    assert_eq!(Update::rename_to::<Coll3>("indexed", "indexed").unwrap_err(), expected);
+}
+
+#[test]
+fn test_schema_field_types() {
+    use huus::query::Schema;
+
+    let expected =
+        vec![("data", "object"), ("data.int", "int"), ("data.str", "string"), ("string", "string")];
+    assert_eq!(Coll2::get_field_types(), expected);
+    assert!(Coll3::get_field_types().contains(&("union.int", "int")));
+    assert!(!Coll3::get_field_types().contains(&("array.int", "int")));
+}
+
+#[test]
+fn test_update_unset() {
+    use bson::{bson, doc};
+    use huus::errors::UpdateError;
+
+    let expected = doc! { "$unset": { "indexed": true, "boolean": true } };
+    assert_eq!(Doc3Update::unset(&["boolean", "indexed"]).unwrap().into_doc(), expected);
+
+    let expected = doc! { "$unset": { "data.int": true } };
+    assert_eq!(Doc3Update::unset(&["data.int"]).unwrap().into_doc(), expected);
+
+    let expected = UpdateError::ImmutableField { path: "_id".to_string() };
+    assert_eq!(Doc3Update::unset(&["boolean", "_id"]).unwrap_err(), expected);
+
+    let expected = UpdateError::UnknownField { path: "data.legacy".to_string() };
+    assert_eq!(Doc3Update::unset(&["data.legacy"]).unwrap_err(), expected);
+}
+
+#[test]
+fn test_update_at_prefix() {
+    use bson::{bson, doc};
+
+    let update = Doc2Update { string: "abc".into(), ..Default::default() };
+    let expected = doc! { "archive.string": "abc" };
+    assert_eq!(update.build_update_at("archive").into_doc(), expected);
+}
+
+#[test]
+fn test_update_diff() {
+    use bson::{bson, doc};
+
+    let old = Doc2Data {
+        data: Some(Doc1Data { integer: Some(1), string: "abc".to_string() }),
+        string: Some("def".to_string()),
+    };
+    let new = Doc2Data {
+        data: Some(Doc1Data { integer: None, string: "ghi".to_string() }),
+        string: None,
+    };
+
+    let expected = doc! {
+        "$set": { "data.str": "ghi" },
+        "$unset": { "string": true, "data.int": true },
+    };
+    assert_eq!(old.diff(&new).into_doc(), expected);
+
+    let expected = doc! {
+        "$set": { "string": "def", "data.str": "abc", "data.int": 1i32 },
+    };
+    assert_eq!(new.diff(&old).into_doc(), expected);
+    assert!(old.diff(&old).is_empty());
+
+    let old = doc! { "_id": 1, "a": 1, "b": { "_id": 2 } };
+    let new = doc! { "a": 2, "b": { "_id": 3 } };
+    let expected = doc! { "$set": { "b._id": 3, "a": 2 } };
+    assert_eq!(huus::updates::Update::diff(&old, &new).into_doc(), expected);
+    assert!(huus::updates::Update::diff(&new, &old).into_doc().get_document("$unset").is_err());
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -518,7 +732,7 @@ fn test_update_contents_by_modification() {
 
 #[test]
 fn test_create_indexes_query() {
-    use huus::query::Query;
+    use huus::query::Schema;
 
     let indexed = vec![
         "data.str".to_string(),
@@ -534,16 +748,34 @@ fn test_create_indexes_query() {
 #[test]
 fn test_fetch_all_query() {
     use bson::doc;
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     let command = huus::commands::FindCommand::new("coll_2".to_string(), doc!(), None);
     assert_eq!(Coll2::fetch_all(), command);
 }
 
+#[test]
+fn test_read_query_bound() {
+    use bson::doc;
+    use huus::query::{Query, ReadQuery};
+
+    fn fetch_all<Q: ReadQuery>() -> huus::commands::FindCommand<Q::Data> {
+        Q::fetch_all()
+    }
+
+    fn get_collection_name<Q: Query>() -> &'static str {
+        Q::get_collection_name()
+    }
+
+    let command = huus::commands::FindCommand::new("coll_2".to_string(), doc!(), None);
+    assert_eq!(fetch_all::<Coll2>(), command);
+    assert_eq!(get_collection_name::<Coll2>(), "coll_2");
+}
+
 #[test]
 fn test_find_one_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     {
         let filter = Doc2Filter {
@@ -579,7 +811,7 @@ fn test_find_one_query() {
 #[test]
 fn test_find_many_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     {
         let filter = Doc2Filter {
@@ -614,10 +846,26 @@ fn test_find_many_query() {
     }
 }
 
+#[test]
+fn test_find_projected_query() {
+    use bson::{bson, doc};
+    use huus::query::ReadQuery;
+
+    let filter = Doc2Filter { string: "def".into(), ..Default::default() };
+    let projection = Doc2Projection { data: true, ..Default::default() };
+    let command = huus::commands::FindCommand::<bson::Document>::new(
+        "coll_2".to_string(),
+        doc! { "string": "def" },
+        None,
+    )
+    .with_projection(doc! { "data": 1 });
+    assert_eq!(Coll2::find_projected(filter, projection), command);
+}
+
 #[test]
 fn test_text_search_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     let command = huus::commands::FindCommand::new(
         "coll_2".to_string(),
@@ -627,10 +875,40 @@ fn test_text_search_query() {
     assert_eq!(Coll2::text_search("my_pattern".to_string()), command);
 }
 
+#[test]
+fn test_text_search_with_filter_query() {
+    use bson::{bson, doc};
+    use huus::query::ReadQuery;
+
+    let search = huus::filters::TextSearch::new("my_pattern".to_string())
+        .with_language("en".to_string())
+        .with_case_sensitive(true)
+        .with_diacritic_sensitive(false);
+    let filter = Doc2Filter { string: "def".into(), ..Default::default() };
+    let command = huus::commands::FindCommand::new(
+        "coll_2".to_string(),
+        doc! {
+            "$and": [
+                {
+                    "$text": {
+                        "$search": "my_pattern",
+                        "$language": "en",
+                        "$caseSensitive": true,
+                        "$diacriticSensitive": false,
+                    },
+                },
+                { "string": "def" },
+            ],
+        },
+        None,
+    );
+    assert_eq!(Coll2::text_search_with(search, filter), command);
+}
+
 #[test]
 fn test_insert_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::WriteQuery;
 
     let data = Doc2Data {
         data: Some(Doc1Data { integer: Some(1), string: "abc".to_string() }),
@@ -646,7 +924,7 @@ fn test_insert_query() {
 #[test]
 fn test_update_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::UpdateQuery;
 
     let filter = Doc2Filter {
         data: huus::filters::ObjectEntry::Dot(Doc1Filter {
@@ -8,6 +8,7 @@
 use bson::{bson, doc};
 use chrono::offset::TimeZone;
 
+use huus::commands::Command;
 use huus::models::prelude::*;
 
 huus_macros::define_from!("test");
@@ -196,7 +197,7 @@ fn filter_formulation() {
 /// Check if `huus_macros::update` generates the code as expected in update mode.
 #[test]
 fn update_formulation() {
-    let update1 = huus_macros::update! { ("coll_3")
+    let update1 = huus_macros::update! { ("coll_3", allow(rename_overwrites))
         "$inc": { "data.int": 1 },
         "$set": {
             data.str: "abc",
@@ -223,7 +224,7 @@ fn update_formulation() {
         },
         "$currentDate": { "date": true },
         "$rename": {
-            "bson": "info",
+            "data.str": "indexed",
         },
         "$push": {
             "integers": 4,
@@ -250,7 +251,7 @@ fn update_formulation() {
         },
         "$currentDate": { "date": true },
         "$rename": {
-            "bson": "info",
+            "data.str": "indexed",
         },
         "$push": {
             "integers": 4i64,
@@ -364,6 +365,146 @@ fn replacement_formulation() {
     assert_eq!(update2.into_doc(), expectation2);
 }
 
+/// Check if `huus_macros::replace` generates the same code as `huus_macros::update` in
+/// replacement mode and if strict `huus_macros::update` still accepts operators.
+#[test]
+fn replace_formulation() {
+    let replacement = huus_macros::replace! { ("coll_2")
+        "data": { "int": 1, "str": "abc" },
+        "str": "def",
+    };
+    let expected = doc! { "data": { "int": 1, "str": "abc" }, "str": "def" };
+    assert_eq!(replacement.into_doc(), expected);
+
+    let update = huus_macros::update! { ("coll_2", strict) "$set": { "str": "def" } };
+    let expected = doc! { "$set": { "str": "def" } };
+    assert_eq!(update.into_doc(), expected);
+}
+
+/// Check if the `null` literal is generated properly for optional members.
+#[test]
+fn null_formulation() {
+    let data = huus_macros::data! { ("coll_2") "data": null, "str": null };
+    let expected = doc! { "data": bson::Bson::Null, "str": bson::Bson::Null };
+    assert_eq!(data.into_doc(), expected);
+
+    let filter = huus_macros::filter! { ("coll_3") "data.int": null };
+    let expected = doc! { "data.int": bson::Bson::Null };
+    assert_eq!(filter.into_doc(), expected);
+
+    let update = huus_macros::update! { ("coll_4") "$set": { "location": null } };
+    let expected = doc! { "$set": { "location": bson::Bson::Null } };
+    assert_eq!(update.into_doc(), expected);
+}
+
+/// Check if inline array literals are generated properly.
+#[test]
+fn array_literal_formulation() {
+    let data = huus_macros::data! { ("coll_2") "data": { "str": "abc", "array": ["d", "e"] } };
+    let expected = doc! { "data": { "str": "abc", "array": ["d", "e"] } };
+    assert_eq!(data.into_doc(), expected);
+
+    let value = 3;
+    let replacement = huus_macros::replace! { ("coll_3")
+        "integers": [1, -2, (value)],
+        "array": [{ "str": "abc" }, { "int": 4, "str": "def" }],
+    };
+    let expected = doc! {
+        "integers": [1i64, -2i64, 3i64],
+        "array": [{ "str": "abc" }, { "int": 4i32, "str": "def" }],
+    };
+    assert_eq!(replacement.into_doc(), expected);
+
+    let update =
+        huus_macros::update! { ("coll_3") "$set": { "integers": [], "data.array": ["a"] } };
+    let expected = doc! { "$set": { "integers": [], "data.array": ["a"] } };
+    assert_eq!(update.into_doc(), expected);
+}
+
+/// Check if enum literals are generated properly.
+#[test]
+fn enum_literal_formulation() {
+    let filter = huus_macros::filter! { ("coll_3") "choice": "choice_2" };
+    let expected = doc! { "choice": "choice_2" };
+    assert_eq!(filter.into_doc(), expected);
+
+    let choice = Enum1Data::Choice1;
+    let update = huus_macros::update! { ("coll_3") "$set": { "choice": (choice) } };
+    let expected = doc! { "$set": { "choice": "choice_1" } };
+    assert_eq!(update.into_doc(), expected);
+
+    let update = huus_macros::update! { ("coll_3") "$set": { "choice": "choice_2" } };
+    let expected = doc! { "$set": { "choice": "choice_2" } };
+    assert_eq!(update.into_doc(), expected);
+}
+
+/// Check if union object literals are generated properly.
+#[test]
+fn union_literal_formulation() {
+    let replacement = huus_macros::replace! { ("coll_3")
+        "union": { "_huus_variant": "choice_1", "int": 6, "str": "pqr" },
+    };
+    let expected = doc! { "union": { "int": 6i32, "str": "pqr", "_huus_variant": "choice_1" } };
+    assert_eq!(replacement.into_doc(), expected);
+
+    let filter = huus_macros::filter! { ("coll_3")
+        "union": { "str": "abc", "_huus_variant": "choice_2" },
+    };
+    let expected = doc! { "union": { "str": "abc", "_huus_variant": "choice_2" } };
+    assert_eq!(filter.into_doc(), expected);
+}
+
+/// Check if documents spread into objects are merged properly.
+#[test]
+fn spread_formulation() {
+    let defaults = doc! { "str": "default", "data": { "str": "abc" } };
+    let data = huus_macros::data! { ("coll_2") ...(defaults), "str": "override" };
+    let expected = doc! { "data": { "str": "abc" }, "str": "override" };
+    assert_eq!(data.into_doc(), expected);
+
+    let doc1 = Doc1Data { integer: Some(3), string: "hello".to_string(), array: None };
+    let data = huus_macros::data! { ("coll_2") "data": { ...(doc1), "int": 4 } };
+    let expected = doc! { "data": { "str": "hello", "int": 4i32 } };
+    assert_eq!(data.into_doc(), expected);
+
+    let filter = huus_macros::filter! { ("coll_3") ...(doc! { "data.int": 1 }), "boolean": true };
+    let expected = doc! { "data.int": 1, "boolean": true };
+    assert_eq!(filter.into_doc(), expected);
+}
+
+/// Check if spreading a document with fields not present in the structure panics.
+#[test]
+#[should_panic(expected = "Huus: Field 'abc' cannot be spread into this document")]
+fn spread_with_unknown_field() {
+    let _ = huus_macros::data! { ("coll_2") ...(doc! { "abc": 1 }) };
+}
+
+/// Check if `huus_macros::projection` generates the code properly.
+#[test]
+fn projection_formulation() {
+    let projection = huus_macros::projection! { ("coll_3") "data.int": 1, "boolean": true };
+    let expected = doc! { "data.int": 1i32, "boolean": true };
+    assert_eq!(projection.into_doc(), expected);
+
+    let show = false;
+    let projection = huus_macros::projection! { ("coll_3") "_id": 0, "array.str": (show) };
+    let expected = doc! { "_id": 0i32, "array.str": false };
+    assert_eq!(projection.into_doc(), expected);
+}
+
+/// Check if `huus_macros::sort` generates the code properly.
+#[test]
+fn sort_formulation() {
+    let sort = huus_macros::sort! { ("coll_3") "date": -1, "data.int": 1 };
+    let expected = doc! { "date": -1i32, "data.int": 1i32 };
+    assert_eq!(sort, expected);
+
+    let order = -1;
+    let sort = huus_macros::sort! { ("coll_3") "score": { "$meta": "textScore" }, "date": (order) };
+    let expected = doc! { "score": { "$meta": "textScore" }, "date": -1i32 };
+    assert_eq!(sort, expected);
+}
+
 // -------------------------------------------------------------------------------------------------
 // Operators
 
@@ -398,6 +539,10 @@ fn filter_operators() {
     let query = huus_macros::filter! { ("coll_3") { data.int: { "$in": (opt) } } };
     let expected = doc! { "data.int": { "$in": [3i32, 4i32] } };
     assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_3") { data.int: { "$mod": [4, 1] } } };
+    let expected = doc! { "data.int": { "$mod": [4i64, 1i64] } };
+    assert_eq!(query.into_doc(), expected);
 }
 
 /// Check if `huus_macros::filter` generates the code properly for arrays.
@@ -419,6 +564,177 @@ fn filter_types() {
     assert_eq!(query.into_doc(), expected);
 }
 
+/// Check if object ID literals are generated properly.
+#[test]
+fn object_id_literals() {
+    let oid = bson::oid::ObjectId::with_string("243423323458458728644937").unwrap();
+
+    let query = huus_macros::filter! { ("coll_3") "_id": oid"243423323458458728644937" };
+    let expected = doc! { "_id": oid.clone() };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_3") "_id": { "$ne": oid"243423323458458728644937" } };
+    let expected = doc! { "_id": { "$ne": oid.clone() } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") "_id": oid"243423323458458728644937" };
+    let expected = doc! { "_id": oid };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::filter` generates the code properly for relative dates.
+#[test]
+fn filter_relative_dates() {
+    let before = chrono::Utc::now();
+    let query = huus_macros::filter! { ("coll_3") "date": { "$gte": now, "$lt": "+2h" } };
+    let after = chrono::Utc::now();
+
+    let doc = query.into_doc();
+    let date = doc.get_document("date").unwrap();
+    let gte = *date.get_utc_datetime("$gte").unwrap();
+    let lt = *date.get_utc_datetime("$lt").unwrap();
+    assert!(before.timestamp() <= gte.timestamp() && gte.timestamp() <= after.timestamp());
+    assert!(lt.timestamp() - gte.timestamp() >= 2 * 60 * 60 - 1);
+    assert!(lt.timestamp() - gte.timestamp() <= 2 * 60 * 60 + 1);
+
+    let before = chrono::Utc::now();
+    let query = huus_macros::filter! { ("coll_3") "date": "today" };
+    let date = *query.into_doc().get_utc_datetime("date").unwrap();
+    assert_eq!(chrono::Timelike::num_seconds_from_midnight(&date), 0);
+    assert_eq!(chrono::Timelike::nanosecond(&date), 0);
+    assert!(before - chrono::Duration::days(1) < date && date <= chrono::Utc::now());
+
+    let query = huus_macros::filter! { ("coll_3") "date": { "$lt": "-1w" } };
+    let doc = query.into_doc();
+    let date = doc.get_document("date").unwrap().get_utc_datetime("$lt").unwrap();
+    assert!(*date < chrono::Utc::now() - chrono::Duration::days(6));
+}
+
+/// Check if `huus_macros::filter` generates the code properly for map keys.
+#[test]
+fn filter_map_keys() {
+    let key = "abc".to_string();
+    let query = huus_macros::filter! { ("coll_3") simple_map.(key.clone()): "one" };
+    let expected = doc! { "simple_map.abc": "one" };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_3") "simple_map.def": { "$ne": "two" } };
+    let expected = doc! { "simple_map.def": { "$ne": "two" } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_3")
+        nested_map.(Enum1Data::Choice2).int: { "$gt": 3 },
+        "nested_map.choice_1.str": "xyz",
+    };
+    let expected = doc! {
+        "nested_map.choice_2.int": { "$gt": 3i32 },
+        "nested_map.choice_1.str": "xyz",
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") "$set": { simple_map.(key): "three" } };
+    let expected = doc! { "$set": { "simple_map.abc": "three" } };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::filter` generates the code properly for logical operators.
+#[test]
+fn filter_logical_operators() {
+    let query = huus_macros::filter! { ("coll_3") {
+        "$or": [ { "boolean": true }, { "data.int": { "$gt": 5 } } ]
+    } };
+    let expected = doc! { "$or": [ { "boolean": true }, { "data.int": { "$gt": 5i32 } } ] };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_3") {
+        "indexed": "abc",
+        "$and": [ { "$nor": [ { "boolean": false } ] }, { "data.str": "def" } ],
+    } };
+    let expected = doc! {
+        "indexed": "abc",
+        "$and": [ { "$nor": [ { "boolean": false } ] }, { "data.str": "def" } ],
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_3") { "data.int": { "$not": { "$lt": 3 } } } };
+    let expected = doc! { "data.int": { "$not": { "$lt": 3i32 } } };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::filter` generates the code properly for text search.
+#[test]
+fn filter_text_search() {
+    let query = huus_macros::filter! { ("coll_3") {
+        "$text": { "$search": "pattern", "$language": "en" },
+        "boolean": true,
+    } };
+    let expected = doc! {
+        "$text": { "$search": "pattern", "$language": "en" },
+        "boolean": true,
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let pattern = "pattern";
+    let query = huus_macros::filter! { ("coll_2") {
+        "$text": { "$search": (pattern.to_string()), "$caseSensitive": true },
+    } };
+    let expected = doc! { "$text": { "$search": "pattern", "$caseSensitive": true } };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::filter` generates the code properly for aggregation expressions.
+#[test]
+fn filter_expression() {
+    let query = huus_macros::filter! { ("coll_3") {
+        "$expr": { "$gt": [ "$integers.0", { "$add": [ "$data.int", 1 ] } ] },
+    } };
+    let expected = doc! {
+        "$expr": { "$gt": [ "$integers.0", { "$add": [ "$data.int", 1i64 ] } ] },
+    };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::filter` generates the code properly for geospatial operators.
+#[test]
+fn filter_geospatial() {
+    let query = huus_macros::filter! { ("coll_4") {
+        "location": { "$near": { "$geometry": [13.4, 52.5], "$maxDistance": 500 } },
+    } };
+    let expected = doc! {
+        "location": {
+            "$near": {
+                "$geometry": { "type": "Point", "coordinates": [13.4, 52.5] },
+                "$maxDistance": 500.0,
+            }
+        },
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let point = huus::types::GeoPoint::new(13.4, 52.5);
+    let query = huus_macros::filter! { ("coll_4") {
+        "location": { "$near": { "$geometry": (point) } },
+    } };
+    let expected = doc! {
+        "location": { "$near": { "$geometry": { "type": "Point", "coordinates": [13.4, 52.5] } } },
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_4") {
+        "area": { "$geoWithin": { "$centerSphere": [[13.4, 52.5], 0.1] } },
+    } };
+    let expected = doc! {
+        "area": { "$geoWithin": { "$centerSphere": [[13.4, 52.5], 0.1] } },
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let polygon = doc! { "type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 1.0], [0.0, 0.0]]] };
+    let query = huus_macros::filter! { ("coll_4") {
+        "location": { "$geoIntersects": { "$geometry": (polygon.clone()) } },
+    } };
+    let expected = doc! { "location": { "$geoIntersects": { "$geometry": polygon } } };
+    assert_eq!(query.into_doc(), expected);
+}
+
 /// Check if `huus_macros::update` generates the code properly for all operators.
 #[test]
 fn update_operators() {
@@ -468,11 +784,30 @@ fn update_operators() {
     let expected = doc! { "$push": { "integers": 3i64 } };
     assert_eq!(query.into_doc(), expected);
 
+    let array = vec![3, 4];
+    let query = huus_macros::update! { ("coll_3") {
+        "$push": { "integers": { "$each": (array), "$slice": (-5), "$sort": 1 } }
+    } };
+    let expected = doc! {
+        "$push": { "integers": { "$each": [3i64, 4i64], "$slice": -5i64, "$sort": 1i32 } }
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") {
+        "$push": { "array": { "$each": [{ "str": "abc" }], "$position": 0, "$sort": { "int": 1 } } }
+    } };
+    let expected = doc! {
+        "$push": {
+            "array": { "$each": [{ "str": "abc" }], "$position": 0i64, "$sort": { "int": 1i32 } }
+        }
+    };
+    assert_eq!(query.into_doc(), expected);
+
     let query = huus_macros::update! { ("coll_3") { "$set": { "data.int": 3 } } };
     let expected = doc! { "$set": { "data.int": 3i32 } };
     assert_eq!(query.into_doc(), expected);
 
-    let query = huus_macros::update! { ("coll_3") { "$setOnInsert": { "data.int": 3 } } };
+    let query = huus_macros::update! { ("coll_3", upsert) { "$setOnInsert": { "data.int": 3 } } };
     let expected = doc! { "$setOnInsert": { "data.int": 3i32 } };
     assert_eq!(query.into_doc(), expected);
 }
@@ -493,6 +828,14 @@ fn update_types() {
     let expected = doc! { "$set": { "integers.2": 3i64 } };
     assert_eq!(query.into_doc(), expected);
 
+    let query = huus_macros::update! { ("coll_3") { "$inc": { "integers.$[]": 3 } } };
+    let expected = doc! { "$inc": { "integers.$[]": 3i64 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") { "$set": { "array.$[].str": "abc" } } };
+    let expected = doc! { "$set": { "array.$[].str": "abc" } };
+    assert_eq!(query.into_doc(), expected);
+
     let query = huus_macros::update! { ("coll_3") { "$push": { "array.array": "1" } } };
     let expected = doc! { "$push": { "array.array": "1" } };
     assert_eq!(query.into_doc(), expected);
@@ -515,13 +858,110 @@ fn update_types() {
     assert_eq!(query.into_doc(), expected);
 }
 
+/// Check if `huus_macros::update` handles signed, float and suffixed numeric literals.
+#[test]
+fn update_numeric_literals() {
+    let query = huus_macros::update! { ("coll_3") { "$inc": { "data.int": -1 } } };
+    let expected = doc! { "$inc": { "data.int": -1i32 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") { "$inc": { "data.int": +1_000i32 } } };
+    let expected = doc! { "$inc": { "data.int": 1000i32 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_4") { "$mul": { "number": -2.5e-3 } } };
+    let expected = doc! { "$mul": { "number": -0.0025f64 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_4") { "$set": { "number": 1E3f64 } } };
+    let expected = doc! { "$set": { "number": 1000.0f64 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") {
+        "$push": { "integers": { "$each": [1, -2], "$slice": -5 } }
+    } };
+    let expected = doc! { "$push": { "integers": { "$each": [1i64, -2i64], "$slice": -5i64 } } };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if integer literals are widened to the declared type of the member.
+#[test]
+fn numeric_literal_widening() {
+    let query = huus_macros::update! { ("coll_3") { "$inc": { "integers.0": 5i32 } } };
+    let expected = doc! { "$inc": { "integers.0": 5i64 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_4") { "$inc": { "number": 2 } } };
+    let expected = doc! { "$inc": { "number": 2.0f64 } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::filter! { ("coll_4") { "number": { "$gt": -10i32 } } };
+    let expected = doc! { "number": { "$gt": -10.0f64 } };
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::update` generates the code properly for identified positional operators.
+#[test]
+fn update_array_filters() {
+    use huus::updates::BuildArrayFilters;
+
+    let query = huus_macros::update! { ("coll_3")
+        "$set": { "array.$[el].str": "abc" },
+        "arrayFilters": [{ "el.int": { "$gt": 3 }, "el.str": "def" }],
+    };
+    let expected = doc! { "$set": { "array.$[el].str": "abc" } };
+    let filters = vec![doc! { "el.int": { "$gt": 3i32 }, "el.str": "def" }];
+    assert_eq!(query.build_array_filters(), filters);
+    assert_eq!(query.into_doc(), expected);
+
+    let limit = 5;
+    let query = huus_macros::update! { ("coll_3")
+        "$inc": { "integers.$[small]": 1 },
+        "$pull": { "array.$[el].array": "abc" },
+        "arrayFilters": [{ "small": { "$lt": (limit) } }, { "el.str": "def" }],
+    };
+    let expected = doc! {
+        "$inc": { "integers.$[small]": 1i64 },
+        "$pull": { "array.$[el].array": "abc" },
+    };
+    let filters = vec![doc! { "small": { "$lt": 5i64 } }, doc! { "el.str": "def" }];
+    assert_eq!(query.build_array_filters(), filters);
+    assert_eq!(query.into_doc(), expected);
+}
+
+/// Check if `huus_macros::update` generates the code properly for update pipelines.
+#[test]
+fn update_pipeline() {
+    let query = huus_macros::update! { ("coll_3") [
+        { "$set": {
+            "data.int": 3,
+            "indexed": "$data.str",
+            "boolean": { "$eq": ["$data.int", 3] },
+        } },
+        { "$addFields": { "data.str": ("abc".to_string()) } },
+        { "$unset": ["date", "bson"] },
+        { "$unset": "choice" },
+    ] };
+    let expected = huus::updates::PipelineUpdate::new(vec![
+        doc! { "$set": {
+            "data.int": 3i32,
+            "indexed": "$data.str",
+            "boolean": { "$eq": ["$data.int", 3i64] },
+        } },
+        doc! { "$addFields": { "data.str": "abc" } },
+        doc! { "$unset": ["date", "bson"] },
+        doc! { "$unset": "choice" },
+    ]);
+    assert_eq!(query, expected);
+}
+
 // -------------------------------------------------------------------------------------------------
 // Creating queries
 
 /// Verify index creation query.
 #[test]
 fn create_indexes_query() {
-    use huus::query::Query;
+    use huus::query::Schema;
 
     let indexed = vec![
         "data.str".to_string(),
@@ -534,11 +974,82 @@ fn create_indexes_query() {
     assert_eq!(Coll3::create_indexes(), command);
 }
 
+/// Verify index synchronization against the indexes declared in the schema.
+#[test]
+fn sync_indexes_query() {
+    use huus::commands::IndexInfo;
+    use huus::query::Schema;
+
+    let existing =
+        vec![IndexInfo::from_doc(&doc! { "key": { "_id": 1 }, "name": "_id_" }).unwrap()];
+    assert!(Coll4::sync_indexes(&existing).is_empty());
+
+    let sync = Coll3::sync_indexes(&existing);
+    assert_eq!(sync.get_create(), Some(&Coll3::create_indexes()));
+    assert!(sync.get_drop().is_empty());
+}
+
+/// Verify collection creation query validating the documents against the schema.
+#[test]
+fn create_collection_query() {
+    use huus::query::Schema;
+
+    let validator = doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["number"],
+            "properties": {
+                "number": { "bsonType": "double" },
+                "location": { "bsonType": ["object", "null"] },
+                "area": { "bsonType": ["object", "null"] },
+            },
+        },
+    };
+    let command = huus::commands::CreateCollectionCommand::new("coll_4".to_string());
+    assert_eq!(Coll4::create_collection(), command);
+    let command = command.with_validator(validator);
+    assert_eq!(Coll4::create_collection_validated(), command);
+
+    let validator = Coll3::create_collection_validated().get_validator().cloned().unwrap();
+    let schema = validator.get_document("$jsonSchema").unwrap();
+    let required = vec!["_id", "data", "boolean", "date", "indexed", "choice", "union", "bson"];
+    let required: Vec<bson::Bson> = required.into_iter().map(bson::Bson::from).collect();
+    assert_eq!(schema.get_array("required").unwrap(), &required);
+    let properties = schema.get_document("properties").unwrap();
+    assert_eq!(properties.get_document("_id").unwrap(), &doc! { "bsonType": "objectId" });
+    assert_eq!(properties.get_document("array").unwrap(), &doc! { "bsonType": "array" });
+    assert_eq!(properties.get_document("choice").unwrap(), &doc! { "bsonType": "string" });
+    let data = properties.get_document("data").unwrap().get_document("properties").unwrap();
+    assert_eq!(data.get_document("int").unwrap(), &doc! { "bsonType": ["int", "null"] });
+}
+
+/// Verify checking hints against the indexes declared in the schema.
+#[test]
+fn index_declared_query() {
+    use bson::{bson, doc};
+    use huus::commands::Hint;
+    use huus::query::Schema;
+
+    assert!(Coll3::is_index_declared(&Hint::Name("coll_3".to_string())));
+    assert!(Coll3::is_index_declared(&Hint::Name("_id_".to_string())));
+    assert!(Coll3::is_index_declared(&Hint::Keys(doc! { "_id": 1 })));
+    assert!(Coll3::is_index_declared(&Hint::Keys(doc! {
+        "data.str": "text",
+        "array.str": "text",
+        "nested_map.choice_1.str": "text",
+        "nested_map.choice_2.str": "text",
+        "indexed": "text",
+    })));
+    assert!(!Coll3::is_index_declared(&Hint::Name("coll_2".to_string())));
+    assert!(!Coll3::is_index_declared(&Hint::Keys(doc! { "indexed": 1 })));
+    assert!(!Coll3::is_index_declared(&Hint::Keys(doc! { "indexed": "text" })));
+}
+
 /// Verify query fetching all entries.
 #[test]
 fn fetch_all_query() {
     use bson::doc;
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     let command = huus::commands::FindCommand::new("coll_2".to_string(), doc!(), None);
     assert_eq!(Coll2::fetch_all(), command);
@@ -548,7 +1059,7 @@ fn fetch_all_query() {
 #[test]
 fn find_one_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     let filter = huus_macros::filter! { ("coll_2")
         "data.int": 1,
@@ -566,7 +1077,7 @@ fn find_one_query() {
 #[test]
 fn find_many_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     let filter = huus_macros::filter! { ("coll_2")
         "data.int": 1,
@@ -581,11 +1092,343 @@ fn find_many_query() {
     assert_eq!(Coll2::find(filter), command);
 }
 
+/// Verify find query with projection.
+#[test]
+fn find_projected_query() {
+    use bson::{bson, doc};
+    use huus::query::ReadQuery;
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "def" };
+    let projection = huus_macros::projection! { ("coll_2") "data.int": 1 };
+    let command = huus::commands::FindCommand::<bson::Document>::new(
+        "coll_2".to_string(),
+        doc! { "str": "def" },
+        None,
+    )
+    .with_projection(doc! { "data.int": 1 });
+    assert_eq!(Coll2::find_projected(filter, projection), command);
+}
+
+/// Verify find query with options given separately from the filter.
+#[test]
+fn find_with_query() {
+    use bson::{bson, doc};
+    use huus::commands::FindOptions;
+    use huus::query::ReadQuery;
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "def" };
+    let options = FindOptions::new()
+        .with_sort(doc! { "str": 1 })
+        .with_limit(10)
+        .with_batch_size(5)
+        .with_max_time_ms(1000);
+    let command = Coll2::find_with(filter, options.clone());
+    assert_eq!(command.get_options(), &options);
+    assert_eq!(
+        command.get_query(),
+        doc! {
+            "$query": { "str": "def" },
+            "$orderby": { "str": 1 },
+            "$maxTimeMS": 1000i64,
+        }
+    );
+}
+
+/// Verify find query with options.
+#[test]
+fn find_with_options_query() {
+    use bson::{bson, doc};
+
+    let query = huus_macros::find! { ("coll_3")
+        filter: { "data.int": { "$gt": 3 } },
+        sort: { "date": -1, "data.int": 1 },
+        limit: 20,
+        skip: 40,
+    };
+    let command = huus::commands::FindCommand::<Doc3Data>::new(
+        "coll_3".to_string(),
+        doc! { "data.int": { "$gt": 3i32 } },
+        Some(20),
+    )
+    .with_skip(40)
+    .with_sort(doc! { "date": -1i32, "data.int": 1i32 });
+    assert_eq!(query, command);
+    assert_eq!(
+        query.get_query(),
+        doc! {
+            "$query": { "data.int": { "$gt": 3i32 } },
+            "$orderby": { "date": -1i32, "data.int": 1i32 },
+        }
+    );
+
+    let page = 2;
+    let query = huus_macros::find! { ("coll_2")
+        projection: { "str": 1 },
+        skip: (page * 10),
+    };
+    let command =
+        huus::commands::FindCommand::<bson::Document>::new("coll_2".to_string(), doc! {}, None)
+            .with_skip(20)
+            .with_projection(doc! { "str": 1i32 });
+    assert_eq!(query, command);
+}
+
+/// Verify find and modify queries.
+#[test]
+fn find_and_modify_query() {
+    use bson::{bson, doc};
+    use huus::commands::{FindAndModifyAction, FindAndModifyCommand, ReturnDocument};
+
+    let query = huus_macros::find_and_modify! { ("coll_2")
+        filter: { "str": "abc" },
+        update: { "$set": { "data.int": 1 } },
+        sort: { "data.int": -1 },
+        returnDocument: "after",
+        upsert: true,
+    };
+    let command = FindAndModifyCommand::<Doc2Data>::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        FindAndModifyAction::Update(doc! { "$set": { "data.int": 1i32 } }),
+    )
+    .with_sort(doc! { "data.int": -1i32 })
+    .with_return_document(ReturnDocument::After)
+    .with_upsert(true);
+    assert_eq!(query, command);
+    assert_eq!(
+        query.get_command(),
+        doc! {
+            "findAndModify": "coll_2",
+            "query": { "str": "abc" },
+            "sort": { "data.int": -1i32 },
+            "update": { "$set": { "data.int": 1i32 } },
+            "new": true,
+            "upsert": true,
+        }
+    );
+
+    let query = huus_macros::find_and_modify! { ("coll_2")
+        filter: { "str": "abc" },
+        replace: { "str": "def" },
+    };
+    let command = FindAndModifyCommand::<Doc2Data>::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        FindAndModifyAction::Replace(doc! { "str": "def" }),
+    );
+    assert_eq!(query, command);
+
+    let query = huus_macros::find_and_modify! { ("coll_2") filter: { "str": "abc" }, delete: true };
+    let command = FindAndModifyCommand::<Doc2Data>::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        FindAndModifyAction::Delete,
+    );
+    assert_eq!(query, command);
+    assert_eq!(
+        query.get_command(),
+        doc! { "findAndModify": "coll_2", "query": { "str": "abc" }, "remove": true }
+    );
+}
+
+/// Verify atomic update of a single document.
+#[test]
+fn find_one_and_update_query() {
+    use bson::{bson, doc};
+    use huus::commands::ReturnDocument;
+    use huus::query::UpdateQuery;
+
+    let filter = huus_macros::filter! { ("coll_3") "boolean": true };
+    let update = huus_macros::update! { ("coll_3")
+        "$set": { "array.$[el].int": 1 },
+        "arrayFilters": [{ "el.str": "abc" }],
+    };
+    let command = Coll3::find_one_and_update(filter, update)
+        .with_return_document(ReturnDocument::After)
+        .with_projection(doc! { "array": 1 });
+    assert_eq!(
+        command.get_command(),
+        doc! {
+            "findAndModify": "coll_3",
+            "query": { "boolean": true },
+            "fields": { "array": 1 },
+            "update": { "$set": { "array.$[el].int": 1 } },
+            "new": true,
+            "upsert": false,
+            "arrayFilters": [{ "el.str": "abc" }],
+        }
+    );
+}
+
+/// Verify atomic replacement and deletion of a single document.
+#[test]
+fn find_one_and_replace_or_delete_query() {
+    use bson::{bson, doc};
+    use huus::commands::{FindAndModifyAction, FindAndModifyCommand};
+    use huus::query::UpdateQuery;
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "abc" };
+    let data = Doc2Data { data: None, string: Some("def".to_string()) };
+    let command = FindAndModifyCommand::<Doc2Data>::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        FindAndModifyAction::Replace(doc! { "str": "def" }),
+    );
+    assert_eq!(Coll2::find_one_and_replace(filter, data), command);
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "abc" };
+    let command = FindAndModifyCommand::<Doc2Data>::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        FindAndModifyAction::Delete,
+    );
+    assert_eq!(Coll2::find_one_and_delete(filter), command);
+}
+
+/// Verify lookup query.
+#[test]
+fn lookup_query() {
+    use bson::{bson, doc};
+
+    let query = huus_macros::lookup! { ("coll_2")
+        filter: { "str": "abc" },
+        from: "coll_3",
+        localField: "data.str",
+        foreignField: "indexed",
+        as: "matches",
+    };
+    let command = huus::commands::LookupCommand::<Doc2Data, Doc3Data>::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        "coll_3".to_string(),
+        "data.str".to_string(),
+        "indexed".to_string(),
+        "matches".to_string(),
+    );
+    assert_eq!(query, command);
+    assert_eq!(
+        query.get_pipeline(),
+        doc! {
+            "pipeline": [
+                { "$match": { "str": "abc" } },
+                {
+                    "$lookup": {
+                        "from": "coll_3",
+                        "localField": "data.str",
+                        "foreignField": "indexed",
+                        "as": "matches",
+                    }
+                },
+            ],
+        }
+    );
+}
+
+/// Verify aggregation pipeline.
+#[test]
+fn aggregate_query() {
+    use bson::{bson, doc};
+
+    let query = huus_macros::aggregate! { ("coll_3") [
+        { "$match": { "boolean": true } },
+        { "$unwind": "$integers" },
+        { "$group": {
+            "_id": "$data.str",
+            "total": { "$sum": "$integers" },
+            "count": { "$sum": 1 },
+            "dates": { "$push": "$date" },
+        } },
+        { "$sort": { "total": -1 } },
+        { "$limit": 10 },
+    ] };
+    let command = huus::commands::AggregateCommand::new(
+        "coll_3".to_string(),
+        vec![
+            doc! { "$match": { "boolean": true } },
+            doc! { "$unwind": "$integers" },
+            doc! { "$group": {
+                "_id": "$data.str",
+                "total": { "$sum": "$integers" },
+                "count": { "$sum": 1i64 },
+                "dates": { "$push": "$date" },
+            } },
+            doc! { "$sort": { "total": -1 } },
+            doc! { "$limit": 10i64 },
+        ],
+    );
+    assert_eq!(query, command);
+}
+
+/// Verify distinct query.
+#[test]
+fn distinct_query() {
+    use bson::{bson, doc};
+
+    let query = huus_macros::distinct! { ("coll_3") "data.str",
+        filter: { "data.int": { "$gt": 3 } },
+    };
+    let command = huus::commands::DistinctCommand::<String>::new(
+        "coll_3".to_string(),
+        "data.str".to_string(),
+        doc! { "data.int": { "$gt": 3i32 } },
+    );
+    assert_eq!(query, command);
+    assert_eq!(
+        query.get_command(),
+        doc! {
+            "distinct": "coll_3",
+            "key": "data.str",
+            "query": { "data.int": { "$gt": 3i32 } },
+        }
+    );
+
+    let query = huus_macros::distinct! { ("coll_3") "integers" };
+    let command = huus::commands::DistinctCommand::<i64>::new(
+        "coll_3".to_string(),
+        "integers".to_string(),
+        doc! {},
+    );
+    assert_eq!(query, command);
+
+    let query = huus_macros::distinct! { ("coll_3") "choice" };
+    let command = huus::commands::DistinctCommand::<Enum1Data>::new(
+        "coll_3".to_string(),
+        "choice".to_string(),
+        doc! {},
+    );
+    assert_eq!(query, command);
+}
+
+/// Verify count queries.
+#[test]
+fn count_query() {
+    use bson::{bson, doc};
+    use huus::query::ReadQuery;
+
+    let query = huus_macros::count! { ("coll_3") "data.int": { "$gt": 3 } };
+    let command = huus::commands::CountDocumentsCommand::new(
+        "coll_3".to_string(),
+        doc! { "data.int": { "$gt": 3i32 } },
+    );
+    assert_eq!(query, command);
+
+    let query = huus_macros::count! { ("coll_3") };
+    assert_eq!(query, huus::commands::CountDocumentsCommand::new("coll_3".to_string(), doc! {}));
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "abc" };
+    let command =
+        huus::commands::CountDocumentsCommand::new("coll_2".to_string(), doc! { "str": "abc" });
+    assert_eq!(Coll2::count(filter), command);
+
+    let command = huus::commands::EstimatedCountCommand::new("coll_2".to_string());
+    assert_eq!(Coll2::estimated_count(), command);
+}
+
 /// Verify text search query.
 #[test]
 fn text_search_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::ReadQuery;
 
     let command = huus::commands::FindCommand::new(
         "coll_2".to_string(),
@@ -599,7 +1442,7 @@ fn text_search_query() {
 #[test]
 fn insert_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::WriteQuery;
 
     let data = huus_macros::data! { ("coll_2")
         "data": {
@@ -615,11 +1458,31 @@ fn insert_query() {
     assert_eq!(*actual.get_str("str").unwrap(), "def".to_string());
 }
 
+/// Verify bulk insert query.
+#[test]
+fn data_many_query() {
+    use bson::{bson, doc};
+
+    let string = "def".to_string();
+    let command = huus_macros::data_many! { ("coll_2") [
+        { "str": "abc" },
+        { "data": { "str": (string) } },
+    ] };
+
+    let mut documents = command.get_documents().clone();
+    for document in documents.iter_mut() {
+        assert!(document.remove("_id").is_some());
+    }
+
+    let expected = vec![doc! { "str": "abc" }, doc! { "data": { "str": "def" } }];
+    assert_eq!(documents, expected);
+}
+
 /// Verify update query.
 #[test]
 fn update_query() {
     use bson::{bson, doc};
-    use huus::query::Query;
+    use huus::query::UpdateQuery;
 
     let filter = huus_macros::filter! { ("coll_2")
         "data.int": 1,
@@ -644,3 +1507,202 @@ fn update_query() {
 
     assert_eq!(Coll2::update(filter, update), command);
 }
+
+/// Verify update query with a replacement document.
+#[test]
+fn replace_query() {
+    use bson::{bson, doc};
+    use huus::query::UpdateQuery;
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "abc" };
+    let replacement = huus_macros::replace! { ("coll_2") "str": "def" };
+
+    let command = huus::commands::UpdateCommand::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        doc! { "str": "def" },
+        huus::commands::UpdateOptions::UpdateOne,
+    );
+
+    assert_eq!(Coll2::update(filter, replacement), command);
+}
+
+/// Verify update query expressed as a pipeline.
+#[test]
+fn update_pipeline_query() {
+    use bson::{bson, doc};
+    use huus::query::UpdateQuery;
+
+    let filter = huus_macros::filter! { ("coll_3") "boolean": true };
+    let update = huus_macros::update! { ("coll_3") [{ "$set": { "indexed": "$data.str" } }] };
+
+    let command = huus::commands::UpdateCommand::new_pipeline(
+        "coll_3".to_string(),
+        doc! { "boolean": true },
+        huus::updates::PipelineUpdate::new(vec![doc! { "$set": { "indexed": "$data.str" } }]),
+        huus::commands::UpdateOptions::UpdateMany,
+    );
+
+    assert_eq!(Coll3::update_many_pipeline(filter, update), command);
+}
+
+/// Verify update query with array filters.
+#[test]
+fn update_query_with_array_filters() {
+    use bson::{bson, doc};
+    use huus::query::UpdateQuery;
+
+    let filter = huus_macros::filter! { ("coll_3") "boolean": true };
+    let update = huus_macros::update! { ("coll_3")
+        "$set": { "array.$[el].int": 1 },
+        "arrayFilters": [{ "el.str": "abc" }],
+    };
+
+    let command = huus::commands::UpdateCommand::new(
+        "coll_3".to_string(),
+        doc! { "boolean": true },
+        doc! { "$set": { "array.$[el].int": 1 } },
+        huus::commands::UpdateOptions::UpdateMany,
+    )
+    .with_array_filters(vec![doc! { "el.str": "abc" }]);
+
+    assert_eq!(Coll3::update_many(filter, update), command);
+}
+
+/// Verify delete queries.
+#[test]
+fn delete_query() {
+    use bson::{bson, doc};
+    use huus::query::UpdateQuery;
+
+    let query = huus_macros::delete! { ("coll_3") "data.int": { "$lt": 3 } };
+    let command = huus::commands::DeleteCommand::new(
+        "coll_3".to_string(),
+        doc! { "data.int": { "$lt": 3i32 } },
+        huus::commands::DeleteOptions::DeleteMany,
+    );
+    assert_eq!(query, command);
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "abc" };
+    let command = huus::commands::DeleteCommand::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        huus::commands::DeleteOptions::DeleteOne,
+    );
+    assert_eq!(Coll2::delete_one(filter), command);
+}
+
+/// Verify replacing a document with data.
+#[test]
+fn replace_one_query() {
+    use bson::{bson, doc};
+    use huus::query::UpdateQuery;
+
+    let filter = huus_macros::filter! { ("coll_2") "str": "abc" };
+    let data = Doc2Data { data: None, string: Some("def".to_string()) };
+    let command = huus::commands::ReplaceOneCommand::new(
+        "coll_2".to_string(),
+        doc! { "str": "abc" },
+        doc! { "str": "def" },
+        false,
+    );
+    assert_eq!(Coll2::replace(filter, data), command);
+}
+
+/// Verify bulk writes.
+#[test]
+fn bulk_write_query() {
+    use bson::{bson, doc};
+    use huus::commands::{BulkWriteCommand, WriteOperation};
+    use huus::query::{BulkOperation, UpdateQuery};
+
+    let operations = vec![
+        BulkOperation::Upsert(
+            huus_macros::filter! { ("coll_2") "str": "abc" },
+            huus_macros::update! { ("coll_2") "$set": { "data.int": 1 } },
+        ),
+        BulkOperation::DeleteMany(huus_macros::filter! { ("coll_2") "data.int": 2 }),
+    ];
+    let command = BulkWriteCommand::new(
+        "coll_2".to_string(),
+        vec![
+            WriteOperation::Update {
+                filter: doc! { "str": "abc" },
+                update: doc! { "$set": { "data.int": 1i32 } },
+                array_filters: Vec::new(),
+                multi: false,
+                upsert: true,
+            },
+            WriteOperation::Delete { filter: doc! { "data.int": 2i32 }, multi: true },
+        ],
+    );
+    assert_eq!(Coll2::bulk_write(operations), command);
+
+    let operations = vec![BulkOperation::UpdateMany(
+        huus_macros::filter! { ("coll_3") "boolean": true },
+        huus_macros::update! { ("coll_3")
+            "$set": { "array.$[el].int": 1 },
+            "arrayFilters": [{ "el.str": "abc" }],
+        },
+    )];
+    let command = BulkWriteCommand::new(
+        "coll_3".to_string(),
+        vec![WriteOperation::Update {
+            filter: doc! { "boolean": true },
+            update: doc! { "$set": { "array.$[el].int": 1i32 } },
+            array_filters: vec![doc! { "el.str": "abc" }],
+            multi: true,
+            upsert: false,
+        }],
+    );
+    assert_eq!(Coll3::bulk_write(operations), command);
+}
+
+/// Verify watching changes of a collection.
+#[test]
+fn watch_query() {
+    use bson::{bson, doc};
+    use huus::query::ReadQuery;
+
+    let command = Coll3::watch().with_full_document(true);
+    let expected = doc! { "pipeline": [{ "$changeStream": { "fullDocument": "updateLookup" } }] };
+    assert_eq!(command.get_pipeline(), expected);
+}
+
+/// Verify queries given as extended JSON.
+#[test]
+fn json_formulation() {
+    let query = huus_macros::filter! { ("coll_3") json r#"{
+        "data.int": { "$gt": 3 },
+        "_id": { "$oid": "0123456789abcdef01234567" },
+        "date": { "$date": { "$numberLong": "1577836800000" } },
+        "integers.0": { "$numberLong": "5" }
+    }"# };
+    let expected = doc! {
+        "data.int": { "$gt": 3i32 },
+        "_id": bson::oid::ObjectId::with_string("0123456789abcdef01234567").unwrap(),
+        "date": bson::Bson::UtcDatetime("2020-01-01T00:00:00Z".parse().unwrap()),
+        "integers.0": 5i64,
+    };
+    assert_eq!(query.into_doc(), expected);
+
+    let query = huus_macros::update! { ("coll_3") json "{\"$set\": {\"data.str\": \"abc\"}}" };
+    let expected = doc! { "$set": { "data.str": "abc" } };
+    assert_eq!(query.into_doc(), expected);
+
+    let query =
+        huus_macros::update! { ("coll_3") json r#"[{ "$set": { "indexed": "$data.str" } }]"# };
+    let expected = vec![doc! { "$set": { "indexed": "$data.str" } }];
+    assert_eq!(query.get_stages(), &expected);
+}
+
+/// Verify that collections can be referred to by names of their main document structures.
+#[test]
+fn collection_given_by_structure() {
+    let query = huus_macros::filter! { (Doc3) "boolean": true };
+    let expected = huus_macros::filter! { ("coll_3") "boolean": true };
+    assert_eq!(query.into_doc(), expected.into_doc());
+
+    let query = huus_macros::update! { (Doc2) "$set": { "str": "abc" } };
+    assert_eq!(query.into_doc(), doc! { "$set": { "str": "abc" } });
+}
@@ -64,16 +64,26 @@ fn data_exp_code_comp() {
     assert_eq!(problems, vec![Problem::ExpCodeComp, Problem::FieldsMissing]);
 }
 
-/// Value was provided for enum member `choice` which can be provided only in code mode.
+/// Value other than string was provided for enum member `choice`.
 #[test]
 fn data_exp_code_enum() {
     let problems = huus_macros::data_testing! { ("coll_3")
         "data": { "str": "abc" },
-        "choice": "choice_1",
+        "choice": 1,
     };
     assert_eq!(problems, vec![Problem::ExpCodeEnum, Problem::FieldsMissing]);
 }
 
+/// String provided for enum member `choice` is not one of its choices.
+#[test]
+fn data_enum_choice_unknown() {
+    let problems = huus_macros::data_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "choice": "choice_3",
+    };
+    assert_eq!(problems, vec![Problem::EnumChoiceUnknown, Problem::FieldsMissing]);
+}
+
 /// Value was provided for a union member `union` which can be provided only in code mode.
 #[test]
 fn data_exp_code_union() {
@@ -134,6 +144,36 @@ fn data_exp_date() {
     assert_eq!(problems, vec![Problem::ExpDate, Problem::FieldsMissing]);
 }
 
+/// Variant key provided for union member `union` is not one of its choices.
+#[test]
+fn data_union_choice_unknown() {
+    let problems = huus_macros::data_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "union": { "_huus_variant": "choice_3", "str": "abc" },
+    };
+    assert_eq!(problems, vec![Problem::UnionChoiceUnknown, Problem::FieldsMissing]);
+}
+
+/// Fields of union member `union` are checked against the selected choice.
+#[test]
+fn data_union_field_not_found() {
+    let problems = huus_macros::data_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "union": { "_huus_variant": "choice_2", "int": 1 },
+    };
+    assert_eq!(problems, vec![Problem::FieldNotFound, Problem::FieldsMissing]);
+}
+
+/// Required fields of the selected choice of union member `union` must be present.
+#[test]
+fn data_union_fields_missing() {
+    let problems = huus_macros::data_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "union": { "_huus_variant": "choice_1", "int": 1 },
+    };
+    assert_eq!(problems, vec![Problem::FieldsMissing, Problem::FieldsMissing]);
+}
+
 /// Value of another type was provided for member `data.int` which expected `i32`.
 #[test]
 fn data_exp_i32() {
@@ -154,6 +194,62 @@ fn data_exp_bson() {
     assert_eq!(problems, vec![Problem::ExpBson, Problem::FieldsMissing]);
 }
 
+/// Elements of an inline array must match the element type of member `integers`.
+#[test]
+fn data_array_literal_exp_i64() {
+    let problems = huus_macros::data_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "integers": [1, "two", 3.5],
+    };
+    assert_eq!(problems, vec![Problem::ExpI64, Problem::ExpI64, Problem::FieldsMissing]);
+}
+
+/// Member `str` is required so it cannot be null.
+#[test]
+fn data_null_on_required() {
+    let problems = huus_macros::data_testing! { ("coll_2")
+        "data": { "int": null, "str": null },
+    };
+    assert_eq!(problems, vec![Problem::NullOnRequired]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `data_many` macro
+
+/// Control test without problems found.
+#[test]
+fn data_many_control() {
+    let problems = huus_macros::data_many_testing! { ("coll_2") [
+        { "str": "abc" },
+        { "data": { "str": "def" } },
+    ] };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Macro is empty.
+#[test]
+fn data_many_macro_empty() {
+    let problems = huus_macros::data_many_testing! { ("coll_2") [] };
+    assert_eq!(problems, vec![Problem::MacroEmpty]);
+}
+
+/// Documents have to be passed in an array.
+#[test]
+fn data_many_exp_array() {
+    let problems = huus_macros::data_many_testing! { ("coll_2") { "str": "abc" } };
+    assert_eq!(problems, vec![Problem::ExpArray]);
+}
+
+/// Each document is validated separately.
+#[test]
+fn data_many_documents_incorrect() {
+    let problems = huus_macros::data_many_testing! { ("coll_3") [
+        "abc",
+        { "data": { "str": "abc" }, "abc": 1 },
+    ] };
+    assert_eq!(problems, vec![Problem::ExpObject, Problem::FieldNotFound, Problem::FieldsMissing]);
+}
+
 // -------------------------------------------------------------------------------------------------
 // `filter` macro
 
@@ -235,16 +331,26 @@ fn filter_exp_code_comp() {
     assert_eq!(problems, vec![Problem::ExpCodeComp]);
 }
 
-/// Enum members like `choice` expect their values to be provided in code mode.
+/// Enum members like `choice` expect their values to be provided as strings or in code mode.
 #[test]
 fn filter_exp_code_enum() {
     let problems = huus_macros::filter_testing! { ("coll_3")
         "data": { "str": "abc" },
-        "choice": "choice_1",
+        "choice": true,
     };
     assert_eq!(problems, vec![Problem::ExpCodeEnum]);
 }
 
+/// String provided for enum member `choice` is not one of its choices.
+#[test]
+fn filter_enum_choice_unknown() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "choice": "Choice1",
+    };
+    assert_eq!(problems, vec![Problem::EnumChoiceUnknown]);
+}
+
 /// Union members like `simple_map` expect their values to be provided in code mode.
 #[test]
 fn filter_exp_code_union() {
@@ -305,6 +411,35 @@ fn filter_exp_bool() {
     assert_eq!(problems, vec![Problem::ExpBool]);
 }
 
+/// Object ID literal for member `_id` is too short.
+#[test]
+fn filter_oid_length() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "_id": oid"24342332345845872864493",
+    };
+    assert_eq!(problems, vec![Problem::OidLength]);
+}
+
+/// Object ID literal for member `_id` contains characters other than hexadecimal digits.
+#[test]
+fn filter_oid_not_hex() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "_id": { "$ne": oid"24342332345845872864493x" },
+    };
+    assert_eq!(problems, vec![Problem::OidNotHex]);
+}
+
+/// Object ID literal was provided for member `data.str` which expected a string.
+#[test]
+fn filter_oid_on_string() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "str": oid"243423323458458728644937" },
+    };
+    assert_eq!(problems, vec![Problem::ExpString]);
+}
+
 /// Value of another type was provided for member `date` which expected a date.
 #[test]
 fn filter_exp_date() {
@@ -315,6 +450,16 @@ fn filter_exp_date() {
     assert_eq!(problems, vec![Problem::ExpDate]);
 }
 
+/// Relative dates require a sign, a number and one of the `s`, `m`, `h`, `d` or `w` units.
+#[test]
+fn filter_exp_date_relative() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "str": "abc" },
+        "date": { "$gt": "2h", "$lt": "+2y" },
+    };
+    assert_eq!(problems, vec![Problem::ExpDate, Problem::ExpDate]);
+}
+
 /// Value of another type was provided for member `data.int` which expected `i32`.
 #[test]
 fn filter_exp_i32() {
@@ -325,6 +470,16 @@ fn filter_exp_i32() {
     assert_eq!(problems, vec![Problem::ExpI32]);
 }
 
+/// Negative float value was provided for member `data.int` which expected `i32`.
+#[test]
+fn filter_exp_i32_float() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "int": -1.5e2 },
+        "boolean": true,
+    };
+    assert_eq!(problems, vec![Problem::ExpI32]);
+}
+
 /// Value of another type was provided for member `integers.1` which expected `i64`.
 #[test]
 fn filter_exp_i64() {
@@ -345,275 +500,1496 @@ fn filter_exp_bson() {
     assert_eq!(problems, vec![Problem::ExpBson]);
 }
 
-// -------------------------------------------------------------------------------------------------
-// `update` macro
-
-/// Control test without problems found.
+/// Branches of logical operators are validated against the same structure.
 #[test]
-fn update_control() {
-    let problems = huus_macros::update_testing! { ("coll_2")
-        "$set": {
-            "data.int": 1,
-            "data.str": "abc",
-            "str": "def",
-        }
+fn filter_logical_field_not_found() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "$or": [ { "boolean": true }, { "data.abc": 3 } ],
     };
-    assert_eq!(problems.len(), 0);
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
 }
 
-/// Both operators and non-operator attributes used.
+/// Logical operator `$xor` is unknown.
 #[test]
-fn update_query_both_update_and_repl() {
-    let problems = huus_macros::update_testing! { ("coll_2")
-        "$set": {
-            "data.int": 1,
-            "data.str": "abc",
-        },
-        "str": "def",
+fn filter_logical_operator_unknown() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$xor": [ { "boolean": true } ],
     };
-    assert_eq!(problems, vec![Problem::QueryBothUpdateAndRepl]);
+    assert_eq!(problems, vec![Problem::OperatorUnknown]);
 }
 
-/// Macro is empty.
+/// Logical operators expect an array of branches.
 #[test]
-fn update_query_empty() {
-    let problems = huus_macros::update_testing! { ("coll_3") };
-    assert_eq!(problems, vec![Problem::QueryEmpty]);
+fn filter_logical_exp_array() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$and": { "boolean": true },
+    };
+    assert_eq!(problems, vec![Problem::ExpArray]);
 }
 
-/// Attributes in replacement mode cannot contain dots.
+/// Every branch of a logical operator has to be an object.
 #[test]
-fn update_attr_with_dots() {
-    let problems = huus_macros::update_testing! { ("coll_2")
-        "data.int": 1,
-        "data.str": "abc",
-        "str": "def",
+fn filter_logical_exp_object() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "$nor": [ { "boolean": true }, 5 ],
     };
-    assert_eq!(problems, vec![Problem::AttrWithDots, Problem::AttrWithDots]);
+    assert_eq!(problems, vec![Problem::ExpObject]);
 }
 
-/// Field `abc` does not exist in the schema.
+/// Logical operators expect at least one branch.
 #[test]
-fn update_field_not_found() {
-    let problems = huus_macros::update_testing! { ("coll_2")
-        "abc": 3,
-        "str": "def",
+fn filter_logical_query_empty() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$or": [],
     };
-    assert_eq!(problems, vec![Problem::FieldNotFound]);
+    assert_eq!(problems, vec![Problem::QueryEmpty]);
 }
 
-/// Field is specified on a member `choice` defined as an enum.
+/// The `$not` operator expects an object with operators.
 #[test]
-fn update_field_on_enum() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "choice.abc": 3,
-        }
+fn filter_not_exp_object() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data.int": { "$not": 3 },
     };
-    assert_eq!(problems, vec![Problem::FieldOnEnum]);
+    assert_eq!(problems, vec![Problem::ExpObject]);
 }
 
-/// Field is specified on a member `date` defined as a built-in type.
+/// Text search requires the `$search` field.
 #[test]
-fn update_field_on_plain() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "date.abc": 3,
-        }
+fn filter_text_fields_missing() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$text": { "$language": "en" },
     };
-    assert_eq!(problems, vec![Problem::FieldOnPlain]);
+    assert_eq!(problems, vec![Problem::FieldsMissing]);
 }
 
-/// Used operator `$unk` is unknown.
+/// Text search does not accept unknown parameters.
 #[test]
-fn update_operator_unknown() {
-    let problems = huus_macros::update_testing! { ("coll_2")
-        "$unk": {
-            "data.int": 1,
-            "data.str": "abc",
-        },
-        "$set": {
-            "str": "def",
-        }
+fn filter_text_operator_unknown() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "$text": { "$search": "abc", "$unk": "def" },
     };
     assert_eq!(problems, vec![Problem::OperatorUnknown]);
 }
 
-/// The used operator `$push` cannot be uses with the type of `data.int` which is `i32`.
+/// Text search parameter `$caseSensitive` expects a boolean.
 #[test]
-fn update_operator_incorrect() {
-    let problems = huus_macros::update_testing! { ("coll_2")
-        "$set": {
-            "str": "def",
-        },
-        "$push": {
-            "data.int": 1,
-        },
+fn filter_text_exp_bool() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "$text": { "$search": "abc", "$caseSensitive": "yes" },
     };
-    assert_eq!(problems, vec![Problem::OperatorIncorrect]);
+    assert_eq!(problems, vec![Problem::ExpBool]);
 }
 
-/// Composed values like arrays have to be provided in code mode.
+/// Text search parameters are passed as an object.
 #[test]
-fn update_exp_code() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$pull": {
-            "integers": { "$in": 1 },
-        },
-        "$set": {
-            "boolean": true,
-        }
+fn filter_text_exp_object() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$text": "abc",
     };
-    assert_eq!(problems, vec![Problem::ExpCode]);
+    assert_eq!(problems, vec![Problem::ExpObject]);
 }
 
-/// Enum members like `simple_map` expect their values to be provided in code mode.
+/// Text search cannot be used on collection `coll_4` which does not have any indexed fields.
 #[test]
-fn update_exp_code_enum() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "choice": "choice_1",
-        }
+fn filter_text_index_missing() {
+    let problems = huus_macros::filter_testing! { ("coll_4")
+        "number": 1.5,
+        "$text": { "$search": "abc" },
     };
-    assert_eq!(problems, vec![Problem::ExpCodeEnum]);
+    assert_eq!(problems, vec![Problem::TextIndexMissing]);
 }
 
-/// Union members like `simple_map` expect their values to be provided in code mode.
+/// The `$mod` operator cannot be used with non-numeric member `indexed`.
 #[test]
-fn update_exp_code_union() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "union": { "str": "abc" },
-        }
+fn filter_mod_operator_incorrect() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "indexed": { "$mod": [4, 1] },
     };
-    assert_eq!(problems, vec![Problem::ExpCodeUnion]);
+    assert_eq!(problems, vec![Problem::OperatorIncorrect]);
 }
 
-/// Value was provided for member `data` which expected an object.
+/// The `$mod` operator expects exactly two values: a divisor and a remainder.
 #[test]
-fn update_exp_object() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "boolean": true,
-            "data": 5,
-        }
+fn filter_mod_exp_mod_array() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "integers": { "$mod": [4] },
     };
-    assert_eq!(problems, vec![Problem::ExpObject]);
+    assert_eq!(problems, vec![Problem::ExpModArray]);
 }
 
-/// Index was specified in an attribute where not index is allowed according to the schema.
+/// Literals not fitting into members `data.int` and `integers` are reported as overflows.
 #[test]
-fn update_exp_key() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "array.1.2": 2,
-        }
+fn filter_numeric_overflow() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data.int": -2147483649,
+        "integers": { "$mod": [99999999999999999999, 1] },
+        "boolean": true,
     };
-    assert_eq!(problems, vec![Problem::ExpKey]);
+    assert_eq!(problems, vec![Problem::NumericOverflow, Problem::NumericOverflow]);
 }
 
-/// Value of another type was provided for member `indexed` which expected a string.
+/// The `$mod` operator expects integer parameters.
 #[test]
-fn update_exp_string() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "indexed": 2,
-        }
+fn filter_mod_exp_i64() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "data.int": { "$mod": [4, "1"] },
     };
-    assert_eq!(problems, vec![Problem::ExpString]);
+    assert_eq!(problems, vec![Problem::ExpI64]);
 }
 
-/// Value of another type was provided for member `_id` which expected an object ID.
+/// Field references inside expressions must exist in the schema.
 #[test]
-fn update_exp_oid() {
-    let problems = huus_macros::update_testing! { ("coll_3")
-        "$set": {
-            "data": { "str": "abc" },
-            "_id": "xyz",
-        }
+fn filter_expression_field_not_found() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "$expr": { "$eq": [ "$data.int", "$data.abc" ] },
     };
-    assert_eq!(problems, vec![Problem::ExpOid]);
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
 }
 
-/// Value of another type was provided for member `boolean` which expected a `bool`.
+/// Expression operator `$concat` is not supported.
 #[test]
-fn update_exp_bool() {
+fn filter_expression_operator_unknown() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$expr": { "$concat": [ "$indexed", "abc" ] },
+    };
+    assert_eq!(problems, vec![Problem::OperatorUnknown]);
+}
+
+/// Arithmetic expressions cannot reference non-numeric member `indexed`.
+#[test]
+fn filter_expression_operator_incorrect() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "$expr": { "$lt": [ { "$add": [ "$indexed", 1 ] }, 5 ] },
+    };
+    assert_eq!(problems, vec![Problem::OperatorIncorrect]);
+}
+
+/// Expressions must contain exactly one operator.
+#[test]
+fn filter_exp_expression() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$expr": { "$gt": [ "$data.int", 1 ], "$lt": [ "$data.int", 5 ] },
+    };
+    assert_eq!(problems, vec![Problem::ExpExpression]);
+}
+
+/// Comparison expressions expect exactly two arguments.
+#[test]
+fn filter_exp_expression_args() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "boolean": true,
+        "$expr": { "$gt": [ "$data.int" ] },
+    };
+    assert_eq!(problems, vec![Problem::ExpExpressionArgs]);
+}
+
+/// Geospatial operators cannot be used with non-geospatial member `number`.
+#[test]
+fn filter_geo_operator_incorrect() {
+    let problems = huus_macros::filter_testing! { ("coll_4")
+        "number": { "$near": { "$geometry": [13.4, 52.5] } },
+    };
+    assert_eq!(problems, vec![Problem::OperatorIncorrect]);
+}
+
+/// The `$maxDistance` parameter can be used only with the `$near` operator.
+#[test]
+fn filter_geo_max_distance_incorrect() {
+    let problems = huus_macros::filter_testing! { ("coll_4")
+        "location": { "$geoIntersects": { "$geometry": [13.4, 52.5], "$maxDistance": 5 } },
+    };
+    assert_eq!(problems, vec![Problem::OperatorIncorrect]);
+}
+
+/// Geospatial operators require a shape to be specified.
+#[test]
+fn filter_geo_fields_missing() {
+    let problems = huus_macros::filter_testing! { ("coll_4")
+        "number": 1.5,
+        "location": { "$near": { "$maxDistance": 5 } },
+    };
+    assert_eq!(problems, vec![Problem::FieldsMissing]);
+}
+
+/// Point latitude must lie within `[-90, 90]`.
+#[test]
+fn filter_exp_geo_point() {
+    let problems = huus_macros::filter_testing! { ("coll_4")
+        "number": 1.5,
+        "location": { "$near": { "$geometry": [13.4, 152.5] } },
+    };
+    assert_eq!(problems, vec![Problem::ExpGeoPoint]);
+}
+
+/// The `$maxDistance` parameter expects a number.
+#[test]
+fn filter_exp_distance() {
+    let problems = huus_macros::filter_testing! { ("coll_4")
+        "number": 1.5,
+        "location": { "$near": { "$geometry": [13.4, 52.5], "$maxDistance": "far" } },
+    };
+    assert_eq!(problems, vec![Problem::ExpDistance]);
+}
+
+/// Member `boolean` is required so it cannot be compared with null.
+#[test]
+fn filter_null_on_required() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data.int": null,
+        "boolean": null,
+    };
+    assert_eq!(problems, vec![Problem::NullOnRequired]);
+}
+
+/// Literal keys of enum-keyed map `nested_map` have to be one of the enum choices.
+#[test]
+fn filter_map_key_enum_choice_unknown() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "nested_map.choice_3.int": 1,
+        "boolean": true,
+    };
+    assert_eq!(problems, vec![Problem::EnumChoiceUnknown]);
+}
+
+/// Values of map `simple_map` are checked against the map value type.
+#[test]
+fn filter_map_key_exp_string() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        simple_map.("abc".to_string()): 1,
+        "boolean": true,
+    };
+    assert_eq!(problems, vec![Problem::ExpString]);
+}
+
+/// Positional operators cannot be used as map keys.
+#[test]
+fn filter_map_key_positional() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "simple_map.$": "abc",
+        "boolean": true,
+    };
+    assert_eq!(problems, vec![Problem::ExpKey]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `update` macro
+
+/// Control test without problems found.
+#[test]
+fn update_control() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "$set": {
+            "data.int": 1,
+            "data.str": "abc",
+            "str": "def",
+        }
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Both operators and non-operator attributes used.
+#[test]
+fn update_query_both_update_and_repl() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "$set": {
+            "data.int": 1,
+            "data.str": "abc",
+        },
+        "str": "def",
+    };
+    assert_eq!(problems, vec![Problem::QueryBothUpdateAndRepl]);
+}
+
+/// Macro is empty.
+#[test]
+fn update_query_empty() {
+    let problems = huus_macros::update_testing! { ("coll_3") };
+    assert_eq!(problems, vec![Problem::QueryEmpty]);
+}
+
+/// Attributes in replacement mode cannot contain dots.
+#[test]
+fn update_attr_with_dots() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "data.int": 1,
+        "data.str": "abc",
+        "str": "def",
+    };
+    assert_eq!(problems, vec![Problem::AttrWithDots, Problem::AttrWithDots]);
+}
+
+/// Field `abc` does not exist in the schema.
+#[test]
+fn update_field_not_found() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "abc": 3,
+        "str": "def",
+    };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+}
+
+/// Field is specified on a member `choice` defined as an enum.
+#[test]
+fn update_field_on_enum() {
     let problems = huus_macros::update_testing! { ("coll_3")
         "$set": {
             "data": { "str": "abc" },
-            "boolean": 1,
+            "choice.abc": 3,
         }
     };
-    assert_eq!(problems, vec![Problem::ExpBool]);
+    assert_eq!(problems, vec![Problem::FieldOnEnum]);
 }
 
-/// Value of another type was provided for member `date` which expected a date.
+/// Field is specified on a member `date` defined as a built-in type.
 #[test]
-fn update_exp_date() {
+fn update_field_on_plain() {
     let problems = huus_macros::update_testing! { ("coll_3")
         "$set": {
             "data": { "str": "abc" },
-            "date": "Tuesday",
+            "date.abc": 3,
         }
     };
-    assert_eq!(problems, vec![Problem::ExpDate]);
+    assert_eq!(problems, vec![Problem::FieldOnPlain]);
 }
 
-/// Value of another type was provided for member `data.int` which expected `i32`.
+/// Used operator `$unk` is unknown.
 #[test]
-fn update_exp_i32() {
+fn update_operator_unknown() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "$unk": {
+            "data.int": 1,
+            "data.str": "abc",
+        },
+        "$set": {
+            "str": "def",
+        }
+    };
+    assert_eq!(problems, vec![Problem::OperatorUnknown]);
+}
+
+/// The used operator `$push` cannot be uses with the type of `data.int` which is `i32`.
+#[test]
+fn update_operator_incorrect() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "$set": {
+            "str": "def",
+        },
+        "$push": {
+            "data.int": 1,
+        },
+    };
+    assert_eq!(problems, vec![Problem::OperatorIncorrect]);
+}
+
+/// Composed values like arrays have to be provided in code mode.
+#[test]
+fn update_exp_code() {
     let problems = huus_macros::update_testing! { ("coll_3")
+        "$pull": {
+            "integers": { "$in": 1 },
+        },
         "$set": {
-            "data": { "int": "abc", "str": "abc" },
             "boolean": true,
         }
     };
-    assert_eq!(problems, vec![Problem::ExpI32]);
+    assert_eq!(problems, vec![Problem::ExpCode]);
 }
 
-/// Value of another type was provided for member `integers.1` which expected `i64`.
+/// Enum members like `choice` expect their values to be provided as strings or in code mode.
 #[test]
-fn update_exp_i64() {
+fn update_exp_code_enum() {
     let problems = huus_macros::update_testing! { ("coll_3")
         "$set": {
             "data": { "str": "abc" },
-            "integers.1": "abc",
+            "choice": 1,
         }
     };
-    assert_eq!(problems, vec![Problem::ExpI64]);
+    assert_eq!(problems, vec![Problem::ExpCodeEnum]);
 }
 
-/// The value assigned to `date` is not formatted according to requirements of `$currentDate`
-/// operator.
+/// String provided for enum member `choice` is not one of its choices.
 #[test]
-fn update_exp_date_obj() {
+fn update_enum_choice_unknown() {
     let problems = huus_macros::update_testing! { ("coll_3")
         "$set": {
             "data": { "str": "abc" },
-        },
-        "$currentDate": {
-            "date": { "$unk": "abc" }
+            "choice": "choice_3",
         }
     };
-    assert_eq!(problems, vec![Problem::ExpDateObj]);
+    assert_eq!(problems, vec![Problem::EnumChoiceUnknown]);
 }
 
-/// `$unset` operator expects the values to be empty strings.
+/// Union members like `simple_map` expect their values to be provided in code mode.
 #[test]
-fn update_exp_empty_string() {
-    let problems = huus_macros::update_testing! { ("coll_2")
+fn update_exp_code_union() {
+    let problems = huus_macros::update_testing! { ("coll_3")
         "$set": {
             "data": { "str": "abc" },
-        },
-        "$unset": {
-            "str": ".",
+            "union": { "str": "abc" },
         }
     };
-    assert_eq!(problems, vec![Problem::ExpEmptyString]);
+    assert_eq!(problems, vec![Problem::ExpCodeUnion]);
+}
+
+/// Value was provided for member `data` which expected an object.
+#[test]
+fn update_exp_object() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "boolean": true,
+            "data": 5,
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpObject]);
+}
+
+/// Index was specified in an attribute where not index is allowed according to the schema.
+#[test]
+fn update_exp_key() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+            "array.1.2": 2,
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpKey]);
+}
+
+/// Value of another type was provided for member `indexed` which expected a string.
+#[test]
+fn update_exp_string() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+            "indexed": 2,
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpString]);
+}
+
+/// Value of another type was provided for member `_id` which expected an object ID.
+#[test]
+fn update_exp_oid() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+            "_id": "xyz",
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpOid]);
+}
+
+/// Value of another type was provided for member `boolean` which expected a `bool`.
+#[test]
+fn update_exp_bool() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+            "boolean": 1,
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpBool]);
+}
+
+/// Value of another type was provided for member `date` which expected a date.
+#[test]
+fn update_exp_date() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+            "date": "Tuesday",
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpDate]);
+}
+
+/// Value of another type was provided for member `data.int` which expected `i32`.
+#[test]
+fn update_exp_i32() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "int": "abc", "str": "abc" },
+            "boolean": true,
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpI32]);
+}
+
+/// Literals not fitting into the declared type are reported as overflows.
+#[test]
+fn update_numeric_overflow() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$inc": { "data.int": 3_000_000_000i64, "integers.0": 2.5 },
+    };
+    assert_eq!(problems, vec![Problem::NumericOverflow, Problem::ExpI64]);
+
+    let problems = huus_macros::update_testing! { ("coll_4") "$mul": { "number": 1e400 } };
+    assert_eq!(problems, vec![Problem::NumericOverflow]);
+}
+
+/// Value of another type was provided for member `integers.1` which expected `i64`.
+#[test]
+fn update_exp_i64() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+            "integers.1": "abc",
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpI64]);
+}
+
+/// The value assigned to `date` is not formatted according to requirements of `$currentDate`
+/// operator.
+#[test]
+fn update_exp_date_obj() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": {
+            "data": { "str": "abc" },
+        },
+        "$currentDate": {
+            "date": { "$unk": "abc" }
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpDateObj]);
+}
+
+/// `$unset` operator expects the values to be empty strings.
+#[test]
+fn update_exp_empty_string() {
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "$set": {
+            "data": { "str": "abc" },
+        },
+        "$unset": {
+            "str": ".",
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpEmptyString]);
+}
+
+/// Push modifiers require the `$each` modifier.
+#[test]
+fn update_push_fields_missing() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$push": {
+            "integers": { "$slice": 3 },
+        }
+    };
+    assert_eq!(problems, vec![Problem::FieldsMissing]);
+}
+
+/// Modifier `$limit` is not a valid push modifier.
+#[test]
+fn update_push_operator_unknown() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$push": {
+            "integers": { "$each": [1, 2], "$limit": 3 },
+        }
+    };
+    assert_eq!(problems, vec![Problem::OperatorUnknown]);
+}
+
+/// Sort by field `abc` which does not exist in the elements of member `array`.
+#[test]
+fn update_push_field_not_found() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$push": {
+            "array": { "$each": [{ "str": "abc" }], "$sort": { "abc": 1 } },
+        }
+    };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+}
+
+/// The sort order can be only `1` or `-1`.
+#[test]
+fn update_push_exp_sort() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$push": {
+            "integers": { "$each": [1, 2], "$sort": 2 },
+        }
+    };
+    assert_eq!(problems, vec![Problem::ExpSort]);
+}
+
+/// Identified positional operators cannot be used in filters.
+#[test]
+fn filter_positional_incorrect() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "array.$[el].str": "abc",
+        "boolean": true,
+    };
+    assert_eq!(problems, vec![Problem::PositionalIncorrect]);
+}
+
+/// All-positional operators cannot be used in filters.
+#[test]
+fn filter_all_positional_incorrect() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "integers.$[]": 3,
+        "boolean": true,
+    };
+    assert_eq!(problems, vec![Problem::PositionalIncorrect]);
+}
+
+/// Identifiers must start with a lowercase letter.
+#[test]
+fn update_identifier_incorrect() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "array.$[El].str": "abc", "boolean": true },
+        "arrayFilters": [{ "El.str": "def" }],
+    };
+    assert_eq!(problems, vec![Problem::IdentifierIncorrect, Problem::ArrayFilterIncorrect]);
+}
+
+/// Identifier `el` is used in the update but no array filter was given for it.
+#[test]
+fn update_array_filter_missing() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "array.$[el].str": "abc" },
+    };
+    assert_eq!(problems, vec![Problem::ArrayFilterMissing]);
+}
+
+/// Array filter refers to identifier `other` not used in the update and mixes two identifiers, so
+/// identifier `num` ends up without a filter.
+#[test]
+fn update_array_filter_incorrect() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "array.$[el].str": "abc", "integers.$[num]": 1 },
+        "arrayFilters": [{ "el.str": "def", "num": 2 }, { "other": 3 }],
+    };
+    let expected = vec![
+        Problem::ArrayFilterIncorrect,
+        Problem::ArrayFilterIncorrect,
+        Problem::ArrayFilterMissing,
+    ];
+    assert_eq!(problems, expected);
+}
+
+/// Array filters are validated against the type of array elements.
+#[test]
+fn update_array_filter_types() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "array.$[el].str": "abc", "integers.$[num]": 1 },
+        "arrayFilters": [{ "el.str": 1 }, { "el.abc": 1 }, { "num": "abc" }],
+    };
+    assert_eq!(problems, vec![Problem::ExpString, Problem::FieldNotFound, Problem::ExpI64]);
+}
+
+/// Array filters must be given as an array of objects.
+#[test]
+fn update_array_filter_exp_array() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "array.$[el].str": "abc" },
+        "arrayFilters": { "el.str": "def" },
+    };
+    assert_eq!(problems, vec![Problem::ExpArray, Problem::ArrayFilterMissing]);
+}
+
+/// Pipelines cannot be used in filters.
+#[test]
+fn filter_pipeline_exp_object() {
+    let problems = huus_macros::filter_testing! { ("coll_3") [{ "$set": { "boolean": true } }] };
+    assert_eq!(problems, vec![Problem::ExpObject]);
+}
+
+/// Each pipeline stage must be an object with exactly one operator.
+#[test]
+fn update_pipeline_exp_stage() {
+    let problems = huus_macros::update_testing! { ("coll_3") [
+        { "$set": { "boolean": true }, "$unset": "date" },
+        "date",
+        { "$set": { "boolean": true } },
+    ] };
+    assert_eq!(problems, vec![Problem::ExpPipelineStage, Problem::ExpPipelineStage]);
+}
+
+/// Only `$set`, `$addFields` and `$unset` stages are supported.
+#[test]
+fn update_pipeline_operator_unknown() {
+    let problems = huus_macros::update_testing! { ("coll_3") [
+        { "$push": { "integers": 1 } },
+        { "$set": { "boolean": true } },
+    ] };
+    assert_eq!(problems, vec![Problem::OperatorUnknown]);
+}
+
+/// Fields and field references in pipeline stages must exist in the schema.
+#[test]
+fn update_pipeline_field_not_found() {
+    let problems = huus_macros::update_testing! { ("coll_3") [
+        { "$set": { "abc": true, "indexed": "$def", "boolean": true } },
+        { "$unset": ["date", "ghi"] },
+    ] };
+    let expected = vec![Problem::FieldNotFound, Problem::FieldNotFound, Problem::FieldNotFound];
+    assert_eq!(problems, expected);
+}
+
+/// Literal values in pipeline stages are validated against the schema.
+#[test]
+fn update_pipeline_types() {
+    let problems = huus_macros::update_testing! { ("coll_3") [
+        { "$set": { "boolean": 1, "indexed": 2 } },
+        { "$unset": [3] },
+    ] };
+    assert_eq!(problems, vec![Problem::ExpBool, Problem::ExpString, Problem::ExpString]);
+}
+
+/// Pipeline macro is empty.
+#[test]
+fn update_pipeline_macro_empty() {
+    let problems = huus_macros::update_testing! { ("coll_3") [] };
+    assert_eq!(problems, vec![Problem::MacroEmpty]);
+}
+
+/// Strict update does not accept replacements.
+#[test]
+fn update_strict_replacement() {
+    let problems = huus_macros::update_testing! { ("coll_2", strict) "str": "def" };
+    assert_eq!(problems, vec![Problem::ReplacementInUpdate]);
+}
+
+/// Elements of an inline array must match the element type of member `array`.
+#[test]
+fn update_array_literal_exp_string() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "array": [{ "str": 1 }], "data.array": [true] },
+    };
+    assert_eq!(problems, vec![Problem::ExpString, Problem::ExpString]);
+}
+
+/// Member `boolean` is required so it cannot be set to null.
+#[test]
+fn update_null_on_required() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$set": { "data.int": null, "boolean": null },
+    };
+    assert_eq!(problems, vec![Problem::NullOnRequired]);
+}
+
+/// Null can only be used with operators assigning values.
+#[test]
+fn update_null_with_arithmetic() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$inc": { "data.int": null },
+        "$set": { "boolean": true },
+    };
+    assert_eq!(problems, vec![Problem::ExpI32]);
+}
+
+/// Documents cannot be spread into update documents.
+#[test]
+fn update_spread_incorrect() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        ...(bson::Document::new()),
+        "$set": { "boolean": true },
+    };
+    assert_eq!(problems, vec![Problem::SpreadIncorrect]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `replace` macro
+
+/// Control test without problems found.
+#[test]
+fn replace_control() {
+    let problems = huus_macros::replace_testing! { ("coll_2")
+        "data": {
+            "int": 1,
+            "str": "abc",
+        },
+        "str": "def",
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Macro is empty.
+#[test]
+fn replace_query_empty() {
+    let problems = huus_macros::replace_testing! { ("coll_2") };
+    assert_eq!(problems, vec![Problem::QueryEmpty]);
+}
+
+/// Replacements cannot contain update operators.
+#[test]
+fn replace_operator() {
+    let problems = huus_macros::replace_testing! { ("coll_2")
+        "$set": { "str": "abc" },
+        "str": "def",
+    };
+    assert_eq!(problems, vec![Problem::OperatorInReplacement]);
+}
+
+/// Attributes in replacements cannot contain dots.
+#[test]
+fn replace_attr_with_dots() {
+    let problems = huus_macros::replace_testing! { ("coll_2") "data.int": 1, "str": "def" };
+    assert_eq!(problems, vec![Problem::AttrWithDots]);
+}
+
+/// Replacements cannot be pipelines.
+#[test]
+fn replace_pipeline() {
+    let problems = huus_macros::replace_testing! { ("coll_2") [{ "$set": { "str": "def" } }] };
+    assert_eq!(problems, vec![Problem::ExpObject]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `projection` macro
+
+/// Control test without problems found.
+#[test]
+fn projection_control() {
+    let problems = huus_macros::projection_testing! { ("coll_3")
+        "_id": 0,
+        "data.int": 1,
+        "array.str": true,
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Field `abc` does not exist in the schema.
+#[test]
+fn projection_field_not_found() {
+    let problems = huus_macros::projection_testing! { ("coll_3") "abc": 1, "boolean": 1 };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+}
+
+/// Only `1`, `0`, `true` and `false` can be used as projection values.
+#[test]
+fn projection_exp_projection() {
+    let problems = huus_macros::projection_testing! { ("coll_3") "date": 2, "boolean": "1" };
+    assert_eq!(problems, vec![Problem::ExpProjection, Problem::ExpProjection, Problem::MacroEmpty]);
+}
+
+/// Fields other than `_id` cannot be both included and excluded.
+#[test]
+fn projection_mixed() {
+    let problems = huus_macros::projection_testing! { ("coll_3") "date": 1, "boolean": 0 };
+    assert_eq!(problems, vec![Problem::ProjectionMixed]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `sort` macro
+
+/// Control test without problems found.
+#[test]
+fn sort_control() {
+    let problems = huus_macros::sort_testing! { ("coll_3")
+        "score": { "$meta": "textScore" },
+        "date": -1,
+        "data.int": 1,
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Field `abc` does not exist in the schema.
+#[test]
+fn sort_field_not_found() {
+    let problems = huus_macros::sort_testing! { ("coll_3") "abc": 1, "date": 1 };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+}
+
+/// Only `1` and `-1` can be used as a sort order.
+#[test]
+fn sort_exp_sort() {
+    let problems = huus_macros::sort_testing! { ("coll_3")
+        "date": 2,
+        "boolean": "1",
+        "score": { "$meta": "indexKey" },
+        "data.int": 1,
+    };
+    assert_eq!(problems, vec![Problem::ExpSort, Problem::ExpSort, Problem::ExpSort]);
+}
+
+/// Sorting by text score requires a text index.
+#[test]
+fn sort_text_index_missing() {
+    let problems = huus_macros::sort_testing! { ("coll_4")
+        "score": { "$meta": "textScore" },
+        "number": 1,
+    };
+    assert_eq!(problems, vec![Problem::TextIndexMissing]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `find` macro
+
+/// Control test without problems found.
+#[test]
+fn find_control() {
+    let problems = huus_macros::find_testing! { ("coll_3")
+        filter: { "data.int": 1 },
+        sort: { "date": -1 },
+        projection: { "data": 1 },
+        limit: 10,
+        skip: 20,
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Macro is empty.
+#[test]
+fn find_macro_empty() {
+    let problems = huus_macros::find_testing! { ("coll_3") };
+    assert_eq!(problems, vec![Problem::MacroEmpty]);
+}
+
+/// Only `filter`, `sort`, `projection`, `limit` and `skip` are allowed.
+#[test]
+fn find_part_unknown() {
+    let problems = huus_macros::find_testing! { ("coll_3") filter: {}, offset: 10 };
+    assert_eq!(problems, vec![Problem::FindPartUnknown]);
+}
+
+/// Limit and skip must be non-negative integers, the other parts must be objects.
+#[test]
+fn find_exp_values() {
+    let problems = huus_macros::find_testing! { ("coll_3")
+        filter: "abc",
+        limit: -1,
+        skip: "10",
+    };
+    assert_eq!(problems, vec![Problem::ExpObject, Problem::ExpU32, Problem::ExpU32]);
+}
+
+/// Problems in the parts are reported.
+#[test]
+fn find_field_not_found() {
+    let problems = huus_macros::find_testing! { ("coll_3")
+        filter: { "abc": 1 },
+        sort: { "def": 1 },
+        projection: { "ghi": 1 },
+    };
+    let expected = vec![Problem::FieldNotFound, Problem::FieldNotFound, Problem::FieldNotFound];
+    assert_eq!(problems, expected);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `find_and_modify` macro
+
+/// Control test without problems found.
+#[test]
+fn find_and_modify_control() {
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        filter: { "str": "abc" },
+        update: { "$set": { "data.int": 1 } },
+        sort: { "data.int": -1 },
+        returnDocument: "after",
+        upsert: true,
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Exactly one modification has to be given.
+#[test]
+fn find_and_modify_modification_incorrect() {
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2") filter: { "str": "abc" } };
+    assert_eq!(problems, vec![Problem::ModificationIncorrect]);
+
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        update: { "$set": { "str": "abc" } },
+        delete: true,
+    };
+    assert_eq!(problems, vec![Problem::ModificationIncorrect]);
+}
+
+/// Unknown parts and incorrect options.
+#[test]
+fn find_and_modify_exp_values() {
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        update: { "$set": { "str": "abc" } },
+        limit: 1,
+        returnDocument: "new",
+        upsert: 1,
+    };
+    assert_eq!(
+        problems,
+        vec![Problem::FindAndModifyPartUnknown, Problem::ExpReturnDocument, Problem::ExpBool]
+    );
+}
+
+/// Modifications are validated.
+#[test]
+fn find_and_modify_modification_validated() {
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        update: { "$set": { "data.abc": 1 } },
+    };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        replace: { "$set": { "str": "abc" }, "str": "def" },
+    };
+    assert_eq!(problems, vec![Problem::OperatorInReplacement]);
+}
+
+/// Options of updates cannot be used with deletion.
+#[test]
+fn find_and_modify_option_with_delete() {
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        delete: true,
+        upsert: true,
+    };
+    assert_eq!(problems, vec![Problem::OptionWithDelete]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `lookup` macro
+
+/// Control test without problems found.
+#[test]
+fn lookup_control() {
+    let problems = huus_macros::lookup_testing! { ("coll_2")
+        filter: { "str": "abc" },
+        from: "coll_3",
+        localField: "data.str",
+        foreignField: "indexed",
+        as: "matches",
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Required parts are missing.
+#[test]
+fn lookup_part_missing() {
+    let problems = huus_macros::lookup_testing! { ("coll_2") from: "coll_3", as: "matches" };
+    assert_eq!(problems, vec![Problem::LookupPartMissing]);
+}
+
+/// Joined collection has to be defined in the schema.
+#[test]
+fn lookup_main_doc_not_defined() {
+    let problems = huus_macros::lookup_testing! { ("coll_2")
+        from: "coll_5",
+        localField: "str",
+        foreignField: "str",
+        as: "matches",
+    };
+    assert_eq!(problems, vec![Problem::MainDocNotDefined, Problem::LookupPartMissing]);
+}
+
+/// Fields have to exist in the respective collections.
+#[test]
+fn lookup_field_not_found() {
+    let problems = huus_macros::lookup_testing! { ("coll_2")
+        from: "coll_3",
+        localField: "indexed",
+        foreignField: "str",
+        as: "matches",
+    };
+    assert_eq!(problems, vec![Problem::FieldNotFound, Problem::FieldNotFound]);
+}
+
+/// Unknown parts and incorrect values.
+#[test]
+fn lookup_exp_values() {
+    let problems = huus_macros::lookup_testing! { ("coll_2")
+        from: "coll_3",
+        localField: 1,
+        foreignField: "indexed",
+        as: "a.b",
+        limit: 1,
+    };
+    assert_eq!(
+        problems,
+        vec![
+            Problem::ExpString,
+            Problem::AttrWithDots,
+            Problem::LookupPartUnknown,
+            Problem::LookupPartMissing,
+        ]
+    );
+}
+
+// -------------------------------------------------------------------------------------------------
+// `count` macro
+
+/// Control test without problems found.
+#[test]
+fn count_control() {
+    let problems = huus_macros::count_testing! { ("coll_3") "data.int": { "$gt": 3 } };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Empty filter counts all the documents.
+#[test]
+fn count_empty() {
+    let problems = huus_macros::count_testing! { ("coll_3") };
+    assert_eq!(problems.len(), 0);
+}
+
+/// The filter is validated.
+#[test]
+fn count_incorrect_filter() {
+    let problems = huus_macros::count_testing! { ("coll_3") "abc": 1, "boolean": "true" };
+    assert_eq!(problems, vec![Problem::FieldNotFound, Problem::ExpBool]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `delete` macro
+
+/// Control test without problems found.
+#[test]
+fn delete_control() {
+    let problems = huus_macros::delete_testing! { ("coll_3") "data.int": { "$lt": 3 } };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Empty filter is not accepted.
+#[test]
+fn delete_macro_empty() {
+    let problems = huus_macros::delete_testing! { ("coll_3") };
+    assert_eq!(problems, vec![Problem::MacroEmpty]);
+}
+
+/// The filter is validated.
+#[test]
+fn delete_incorrect_filter() {
+    let problems =
+        huus_macros::delete_testing! { ("coll_3") "abc": 1, "boolean": "true", "indexed": "a" };
+    assert_eq!(problems, vec![Problem::FieldNotFound, Problem::ExpBool]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `distinct` macro
+
+/// Control test without problems found.
+#[test]
+fn distinct_control() {
+    let problems = huus_macros::distinct_testing! { ("coll_3") "data.str",
+        filter: { "data.int": { "$gt": 3 } },
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Field path is missing.
+#[test]
+fn distinct_field_path_missing() {
+    let problems = huus_macros::distinct_testing! { ("coll_3") filter: { "data.int": 1 } };
+    assert_eq!(problems, vec![Problem::FieldPathMissing]);
+}
+
+/// Field path refers to an unknown field.
+#[test]
+fn distinct_field_not_found() {
+    let problems = huus_macros::distinct_testing! { ("coll_3") "data.abc" };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+}
+
+/// Field path contains a positional operator.
+#[test]
+fn distinct_positional_incorrect() {
+    let problems = huus_macros::distinct_testing! { ("coll_3") "array.$[].str" };
+    assert_eq!(problems, vec![Problem::PositionalIncorrect]);
+}
+
+/// Unknown parts and incorrect filter.
+#[test]
+fn distinct_part_unknown() {
+    let problems = huus_macros::distinct_testing! { ("coll_3") "boolean",
+        filter: 1,
+        limit: 2,
+    };
+    assert_eq!(problems, vec![Problem::ExpObject, Problem::DistinctPartUnknown]);
+}
+
+/// Field path is accepted only in `distinct` macro.
+#[test]
+fn filter_field_path_unexpected() {
+    let problems = huus_macros::filter_testing! { ("coll_3") "boolean", "data.int": 1 };
+    assert_eq!(problems, vec![Problem::FieldPathUnexpected]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// `aggregate` macro
+
+/// Control test without problems found.
+#[test]
+fn aggregate_control() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$match": { "data.int": { "$gt": 3 } } },
+        { "$unwind": { "path": "$array", "preserveNullAndEmptyArrays": true } },
+        { "$group": {
+            "_id": { "choice": "$choice", "str": "$array.str" },
+            "average": { "$avg": "$array.int" },
+            "latest": { "$max": "$date" },
+        } },
+        { "$sort": { "_id.choice": 1, "average": -1 } },
+        { "$skip": 5 },
+    ] };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Pipeline without stages.
+#[test]
+fn aggregate_macro_empty() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [] };
+    assert_eq!(problems, vec![Problem::MacroEmpty]);
+}
+
+/// Stages must be objects with a single known operator.
+#[test]
+fn aggregate_incorrect_stages() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$match": { "boolean": true }, "$limit": 1 },
+        { "$bucket": {} },
+        { "$limit": "abc" },
+        { "$match": { "abc": 1 } },
+    ] };
+    let expected = vec![
+        Problem::ExpAggregationStage,
+        Problem::OperatorUnknown,
+        Problem::ExpU32,
+        Problem::FieldNotFound,
+    ];
+    assert_eq!(problems, expected);
+}
+
+/// `$unwind` must refer to an array member which was not unwound yet.
+#[test]
+fn aggregate_unwind_not_array() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$unwind": "$boolean" },
+        { "$unwind": "$integers" },
+        { "$unwind": "$integers" },
+        { "$unwind": "$abc" },
+        { "$unwind": "integers" },
+        { "$unwind": { "preserveNullAndEmptyArrays": true } },
+    ] };
+    let expected = vec![
+        Problem::UnwindNotArray,
+        Problem::UnwindNotArray,
+        Problem::FieldNotFound,
+        Problem::ExpFieldRef,
+        Problem::ExpUnwind,
+    ];
+    assert_eq!(problems, expected);
+}
+
+/// `_id` of `$group` must be given and refer to existing fields.
+#[test]
+fn aggregate_group_id_incorrect() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$group": { "count": { "$sum": 1 } } },
+    ] };
+    assert_eq!(problems, vec![Problem::GroupIdMissing]);
+
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$group": { "_id": { "a": "$data.abc", "b": "data.str" } } },
+    ] };
+    assert_eq!(problems, vec![Problem::FieldNotFound, Problem::ExpFieldRef]);
+
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$group": { "_id": "$abc" } },
+    ] };
+    assert_eq!(problems, vec![Problem::FieldNotFound]);
+
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$group": { "_id": [1, 2] } },
+    ] };
+    assert_eq!(problems, vec![Problem::ExpGroupId]);
+}
+
+/// Accumulators must be known and refer to existing fields of correct type.
+#[test]
+fn aggregate_accumulator_incorrect() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$group": {
+            "_id": null,
+            "a": { "$sum": "$data.str" },
+            "b": { "$sum": "$integers" },
+            "c": { "$push": "$data.abc" },
+            "d": { "$median": "$data.int" },
+            "e": 1,
+            "f.g": { "$sum": 1 },
+            "h": { "$push": "$$ROOT" },
+        } },
+    ] };
+    let expected = vec![
+        Problem::OperatorIncorrect,
+        Problem::OperatorIncorrect,
+        Problem::FieldNotFound,
+        Problem::OperatorUnknown,
+        Problem::ExpAccumulator,
+        Problem::AttrWithDots,
+    ];
+    assert_eq!(problems, expected);
+}
+
+/// Stages after `$group` may refer only to the grouped fields.
+#[test]
+fn aggregate_stage_after_group() {
+    let problems = huus_macros::aggregate_testing! { ("coll_3") [
+        { "$group": { "_id": "$choice", "count": { "$sum": 1 } } },
+        { "$match": { "count": 1 } },
+        { "$sort": { "count": 1, "boolean": 1 } },
+    ] };
+    assert_eq!(problems, vec![Problem::StageAfterGroup, Problem::FieldNotFound]);
+}
+
+// -------------------------------------------------------------------------------------------------
+// Problem locations
+
+/// Problems are reported together with their position and the path of the validated field.
+#[test]
+fn problem_locations() {
+    let line = line!() as usize;
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "data": { "int": "abc" },
+        "abc": 1,
+    };
+    let expected = vec![
+        (Problem::ExpI32, line + 2, 26, "data.int".to_string()),
+        (Problem::FieldNotFound, line + 3, 9, "abc".to_string()),
+    ];
+    assert_eq!(problems, expected);
+
+    let line = line!() as usize;
+    let problems = huus_macros::delete_testing! { ("coll_3") };
+    assert_eq!(problems, vec![(Problem::MacroEmpty, line + 1, 20, String::new())]);
+    assert_eq!(problems[0].0.as_str(), "The macro seems to be empty");
+}
+
+// -------------------------------------------------------------------------------------------------
+// Warnings
+
+/// `$ne` on a field of documents stored in an array is reported unless an index is given.
+#[test]
+fn warning_ne_on_array() {
+    let problems = huus_macros::filter_testing! { ("coll_3")
+        "array.int": { "$ne": 1 },
+        "array.0.int": { "$ne": 1 },
+        "data.int": { "$ne": 1 },
+    };
+    assert_eq!(problems, vec![Problem::NeOnArray]);
+}
+
+/// `$rename` into a field of the schema is reported.
+#[test]
+fn warning_rename_overwrites() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$rename": { "data.str": "indexed" },
+    };
+    assert_eq!(problems, vec![Problem::RenameOverwrites]);
+}
+
+/// `$rename` into a field not present in the schema is an error.
+#[test]
+fn rename_unknown() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$rename": { "data.str": "legacy_str" },
+    };
+    assert_eq!(problems, vec![Problem::RenameUnknown]);
+
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$rename": { "data.str": "data.legacy_str" },
+    };
+    assert_eq!(problems, vec![Problem::RenameUnknown]);
+}
+
+/// `$rename` into a field of the schema with a different type is an error.
+#[test]
+fn rename_incompatible() {
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$rename": { "data.str": "boolean" },
+    };
+    assert_eq!(problems, vec![Problem::RenameIncompatible]);
+
+    let problems = huus_macros::update_testing! { ("coll_3")
+        "$rename": { "data.int": "integers" },
+    };
+    assert_eq!(problems, vec![Problem::RenameIncompatible]);
+}
+
+/// `$setOnInsert` without enabling upserts is reported.
+#[test]
+fn warning_set_on_insert_without_upsert() {
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        filter: { "str": "abc" },
+        update: { "$setOnInsert": { "data.int": 1 } },
+    };
+    assert_eq!(problems, vec![Problem::SetOnInsertWithoutUpsert]);
+
+    let problems = huus_macros::find_and_modify_testing! { ("coll_2")
+        filter: { "str": "abc" },
+        update: { "$setOnInsert": { "data.int": 1 } },
+        upsert: true,
+    };
+    assert_eq!(problems.len(), 0);
+
+    let problems = huus_macros::update_testing! { ("coll_2")
+        "$set": { "str": "abc" },
+        "$setOnInsert": { "data.int": 1 },
+    };
+    assert_eq!(problems, vec![Problem::SetOnInsertWithoutUpsert]);
+
+    let problems = huus_macros::update_testing! { ("coll_2", upsert)
+        "$setOnInsert": { "data.int": 1 },
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+/// Warnings can be suppressed per macro.
+#[test]
+fn warning_allowed() {
+    let problems = huus_macros::filter_testing! { ("coll_3", allow(ne_on_array))
+        "array.int": { "$ne": 1 },
+    };
+    assert_eq!(problems.len(), 0);
+
+    let problems = huus_macros::update_testing! { ("coll_3", strict, allow(rename_overwrites))
+        "$rename": { "data.str": "indexed" },
+    };
+    assert_eq!(problems.len(), 0);
+}
+
+// -------------------------------------------------------------------------------------------------
+// JSON mode
+
+/// Queries given as extended JSON are validated the same way as the regular ones.
+#[test]
+fn json_validation() {
+    let problems = huus_macros::filter_testing! { ("coll_3") json r#"{ "data.int": 3 }"# };
+    assert_eq!(problems.len(), 0);
+
+    let problems = huus_macros::filter_testing! { ("coll_3") json r#"{
+        "data.abc": 1,
+        "boolean": "true",
+        "_id": { "$oid": "xyz" },
+        "indexed": "a"
+    }"# };
+    let expected = vec![Problem::FieldNotFound, Problem::ExpBool, Problem::OidLength];
+    assert_eq!(problems, expected);
+}
+
+// -------------------------------------------------------------------------------------------------
+// Collection names
+
+/// Collections may be referred to by names of their main document structures.
+#[test]
+fn collection_given_by_structure() {
+    let problems = huus_macros::filter_testing! { (Doc3) "boolean": true };
+    assert_eq!(problems.len(), 0);
+
+    let problems = huus_macros::update_testing! { (Doc2) "$set": { "str": 1 } };
+    assert_eq!(problems, vec![Problem::ExpString]);
+}
+
+/// Collection name contains a typo.
+#[test]
+fn collection_name_mistyped() {
+    let problems = huus_macros::filter_testing! { ("col_3") "boolean": true };
+    assert_eq!(problems, vec![Problem::MainDocNotDefined]);
 }
@@ -0,0 +1,39 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Tests for `huus_macros` crate checking resolution of schema files given relatively to the file
+//! invoking `define_from` macro. The schema files lie outside of the configured directories.
+
+#![feature(proc_macro_hygiene)]
+
+use huus_macros_support::Problem;
+
+huus_macros::define_from!("./schemas/relative");
+huus_macros::define_from!("../tests/schemas/parent");
+
+/// Schema file starting with `./` should be known to the formulation macros.
+#[test]
+fn relative_formulation() {
+    let problems = huus_macros::data_testing! { ("coll_relative") "name": "abc", "count": 1 };
+    assert_eq!(problems.len(), 0);
+
+    let problems = huus_macros::data_testing! { ("coll_relative") "name": 1, "count": 1 };
+    assert_eq!(problems, vec![Problem::ExpString]);
+}
+
+/// Schema file starting with `../` should be known to the formulation macros.
+#[test]
+fn parent_formulation() {
+    let problems = huus_macros::data_testing! { ("coll_parent") "number": 3, "text": "abc" };
+    assert_eq!(problems.len(), 0);
+
+    let problems = huus_macros::data_testing! { ("coll_parent") "number": "abc", "text": "abc" };
+    assert_eq!(problems, vec![Problem::ExpI32]);
+}
+
+/// Schema files from the configured directory should still be known to the formulation macros.
+#[test]
+fn configured_formulation() {
+    let problems = huus_macros::data_testing! { ("coll_2") "str": "def" };
+    assert_eq!(problems.len(), 0);
+}
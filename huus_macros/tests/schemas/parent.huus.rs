@@ -0,0 +1,4 @@
+pub struct ParentDoc in "coll_parent" {
+    number: i32,
+    text: String,
+}
@@ -0,0 +1,4 @@
+pub struct RelativeDoc in "coll_relative" {
+    name: String,
+    count: i32,
+}
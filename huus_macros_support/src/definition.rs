@@ -3,6 +3,7 @@
 
 //! Definitions of database data types.
 
+pub mod config;
 pub(crate) mod data;
 pub mod generator;
 pub mod input;
@@ -0,0 +1,288 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy of
+// the MPL was not distributed with this file, You can obtain one at http://mozilla.org/MPL/2.0/
+
+//! Configuration of locations of the schema files.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_DIR: &str = "huus";
+const DEFAULT_EXTENSION: &str = "huus.rs";
+const CONFIG_FILE: &str = "huus.toml";
+const PATH_VARIABLE: &str = "HUUS_SCHEMA_PATH";
+const EXTENSION_VARIABLE: &str = "HUUS_SCHEMA_EXTENSION";
+
+/// Describes where the schema files are searched for.
+///
+/// The directories and the extension are read from `HUUS_SCHEMA_PATH` and `HUUS_SCHEMA_EXTENSION`
+/// environment variables, or from the `[schema]` table of `huus.toml` file placed next to the
+/// crate's `Cargo.toml`:
+///
+/// ```toml
+/// [schema]
+/// dirs = ["huus", "../common/huus"]
+/// extension = "huus.rs"
+/// ```
+///
+/// Relative directories are resolved against the crate's root. By default schema files are
+/// searched for in the `huus` directory and have the `huus.rs` extension.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Directories containing the schema files.
+    pub dirs: Vec<PathBuf>,
+
+    /// Extension of the schema files.
+    pub extension: String,
+}
+
+/// Describes a problem with the configuration file or the environment variables.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    /// Explains what is wrong with the configuration.
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Loads the configuration for the crate being compiled.
+    pub fn load() -> Result<Self, ConfigError> {
+        let root = PathBuf::from(
+            std::env::var("CARGO_MANIFEST_DIR").expect("Read CARGO_MANIFEST_DIR variable"),
+        );
+        let path_variable = std::env::var_os(PATH_VARIABLE);
+        let extension_variable = std::env::var(EXTENSION_VARIABLE).ok();
+        Self::from_sources(&root, path_variable, extension_variable)
+            .map_err(|message| ConfigError { message })
+    }
+
+    /// Constructs the configuration from the values of the environment variables and the
+    /// configuration file found in the crate's root.
+    fn from_sources(
+        root: &Path,
+        path_variable: Option<OsString>,
+        extension_variable: Option<String>,
+    ) -> Result<Self, String> {
+        let table = Self::read_config_file(&root.join(CONFIG_FILE))?;
+
+        let dirs = if let Some(paths) = path_variable {
+            std::env::split_paths(&paths).collect()
+        } else if let Some(dirs) = table.as_ref().and_then(|t| t.get("dirs")) {
+            let dirs = dirs
+                .as_array()
+                .ok_or(format!("`schema.dirs` in `{}` should be an array", CONFIG_FILE))?;
+            let mut result = Vec::new();
+            for dir in dirs {
+                match dir.as_str() {
+                    Some(dir) => result.push(PathBuf::from(dir)),
+                    None => {
+                        return Err(format!(
+                            "`schema.dirs` in `{}` should contain strings",
+                            CONFIG_FILE
+                        ))
+                    }
+                }
+            }
+            result
+        } else {
+            vec![PathBuf::from(DEFAULT_DIR)]
+        };
+
+        let extension = if let Some(extension) = extension_variable {
+            extension
+        } else if let Some(extension) = table.as_ref().and_then(|t| t.get("extension")) {
+            match extension.as_str() {
+                Some(extension) => extension.to_string(),
+                None => {
+                    return Err(format!(
+                        "`schema.extension` in `{}` should be a string",
+                        CONFIG_FILE
+                    ))
+                }
+            }
+        } else {
+            DEFAULT_EXTENSION.to_string()
+        };
+
+        let dirs = dirs.into_iter().map(|dir| root.join(dir)).collect();
+        Ok(Self { dirs, extension: extension.trim_start_matches('.').to_string() })
+    }
+
+    /// Returns paths to all the schema files found in the configured directories.
+    pub fn find_files(&self) -> Vec<PathBuf> {
+        let suffix = format!(".{}", self.extension);
+        let mut files = Vec::new();
+        for dir in self.dirs.iter().filter(|dir| dir.is_dir()) {
+            for entry in std::fs::read_dir(dir).expect("Failed to read a schema directory") {
+                let path = entry.expect("Failed to read a schema directory entry").path();
+                let string = path.to_str().expect("Path is not UTF-8");
+                if path.is_file() && string.ends_with(&suffix) {
+                    files.push(path);
+                }
+            }
+        }
+        files
+    }
+
+    /// Resolves the name of a schema file. Names starting with `./` or `../` are resolved
+    /// relatively to the directory of the file containing the macro invocation, other names are
+    /// searched for in the configured directories.
+    pub fn resolve(&self, name: &str, span: &proc_macro::Span) -> Option<PathBuf> {
+        let file = span.local_file();
+        self.resolve_from(name, file.as_ref().map(|file| file.parent().unwrap_or(Path::new(""))))
+    }
+
+    /// Resolves the name of a schema file given the directory of the file containing the macro
+    /// invocation, if known.
+    fn resolve_from(&self, name: &str, invoking_dir: Option<&Path>) -> Option<PathBuf> {
+        if self.is_relative(name) {
+            Some(self.with_extension(invoking_dir?.join(name))).filter(|path| path.is_file())
+        } else {
+            self.dirs.iter().map(|dir| self.with_extension(dir.join(name))).find(|p| p.is_file())
+        }
+    }
+
+    /// Checks if the name should be resolved relatively to the file containing the invocation.
+    pub fn is_relative(&self, name: &str) -> bool {
+        name.starts_with("./") || name.starts_with("../")
+    }
+
+    /// Appends the schema extension to the path.
+    fn with_extension(&self, mut path: PathBuf) -> PathBuf {
+        path.set_extension(&self.extension);
+        path
+    }
+
+    /// Reads in the `[schema]` table of the configuration file if the file exists.
+    fn read_config_file(path: &Path) -> Result<Option<toml::value::Table>, String> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read `{}`: {}", CONFIG_FILE, err))?;
+        let value = contents
+            .parse::<toml::Value>()
+            .map_err(|err| format!("Failed to parse `{}`: {}", CONFIG_FILE, err))?;
+        match value.get("schema") {
+            Some(toml::Value::Table(table)) => Ok(Some(table.clone())),
+            Some(_) => Err(format!("`schema` in `{}` should be a table", CONFIG_FILE)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::Config;
+
+    /// Creates an empty directory for a single test.
+    fn make_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("huus-config-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    /// Creates an empty file together with its parent directories.
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn test_default_config() {
+        let root = make_root("default");
+        let config = Config::from_sources(&root, None, None).unwrap();
+        assert_eq!(config.dirs, vec![root.join("huus")]);
+        assert_eq!(config.extension, "huus.rs");
+    }
+
+    #[test]
+    fn test_environment_variables() {
+        let root = make_root("environment");
+        let paths = std::env::join_paths(&["schemas", "../common"]).unwrap();
+        let config = Config::from_sources(&root, Some(paths), Some(".schema.rs".into())).unwrap();
+        assert_eq!(config.dirs, vec![root.join("schemas"), root.join("../common")]);
+        assert_eq!(config.extension, "schema.rs");
+    }
+
+    #[test]
+    fn test_config_file() {
+        let root = make_root("file");
+        let contents = "[schema]\ndirs = [\"schemas\", \"../common\"]\nextension = \"schema.rs\"\n";
+        std::fs::write(root.join("huus.toml"), contents).unwrap();
+
+        let config = Config::from_sources(&root, None, None).unwrap();
+        assert_eq!(config.dirs, vec![root.join("schemas"), root.join("../common")]);
+        assert_eq!(config.extension, "schema.rs");
+
+        let paths = std::env::join_paths(&["other"]).unwrap();
+        let config = Config::from_sources(&root, Some(paths), Some("other.rs".into())).unwrap();
+        assert_eq!(config.dirs, vec![root.join("other")]);
+        assert_eq!(config.extension, "other.rs");
+    }
+
+    #[test]
+    fn test_malformed_config_file() {
+        let root = make_root("malformed");
+        let config_file = root.join("huus.toml");
+
+        std::fs::write(&config_file, "[schema\n").unwrap();
+        assert!(Config::from_sources(&root, None, None).is_err());
+
+        std::fs::write(&config_file, "schema = 1\n").unwrap();
+        assert!(Config::from_sources(&root, None, None).is_err());
+
+        std::fs::write(&config_file, "[schema]\ndirs = \"huus\"\n").unwrap();
+        assert!(Config::from_sources(&root, None, None).is_err());
+
+        std::fs::write(&config_file, "[schema]\ndirs = [1]\n").unwrap();
+        assert!(Config::from_sources(&root, None, None).is_err());
+
+        std::fs::write(&config_file, "[schema]\nextension = 1\n").unwrap();
+        assert!(Config::from_sources(&root, None, None).is_err());
+    }
+
+    #[test]
+    fn test_resolve_in_dirs() {
+        let root = make_root("resolve_dirs");
+        let paths = std::env::join_paths(&["first", "second"]).unwrap();
+        let config = Config::from_sources(&root, Some(paths), None).unwrap();
+        touch(&root.join("first/a.huus.rs"));
+        touch(&root.join("second/a.huus.rs"));
+        touch(&root.join("second/b.huus.rs"));
+
+        assert_eq!(config.resolve_from("a", None), Some(root.join("first/a.huus.rs")));
+        assert_eq!(config.resolve_from("b", None), Some(root.join("second/b.huus.rs")));
+        assert_eq!(config.resolve_from("c", None), None);
+    }
+
+    #[test]
+    fn test_resolve_relative() {
+        let root = make_root("resolve_relative");
+        let config = Config::from_sources(&root, None, None).unwrap();
+        let invoking_dir = root.join("src/module");
+        touch(&root.join("huus/a.huus.rs"));
+        touch(&root.join("src/module/a.huus.rs"));
+        touch(&root.join("src/b.huus.rs"));
+
+        let expected = invoking_dir.join("./a.huus.rs");
+        assert_eq!(config.resolve_from("./a", Some(&invoking_dir)), Some(expected));
+        let expected = invoking_dir.join("../b.huus.rs");
+        assert_eq!(config.resolve_from("../b", Some(&invoking_dir)), Some(expected));
+        assert_eq!(config.resolve_from("./b", Some(&invoking_dir)), None);
+        assert_eq!(config.resolve_from("./a", None), None);
+    }
+}
@@ -3,30 +3,84 @@
 
 //! Definitions of database data types.
 
-use crate::definition::{interpreter::Interpreter, output::Schema};
+use std::{collections::BTreeSet, path::PathBuf, sync::Mutex, time::SystemTime};
+
+use crate::definition::{config::Config, interpreter::Interpreter, output::Schema};
+
+/// Parsed schema together with the files it was read from and their modification times.
+struct CachedSchema {
+    files: Vec<(PathBuf, SystemTime)>,
+    schema: &'static Schema,
+}
 
 lazy_static::lazy_static! {
-    pub static ref SCHEMA: Schema = {
-        let mut interpreter = Interpreter::new();
-
-        let mut dir = std::path::PathBuf::new();
-        dir.push(std::env::var("CARGO_MANIFEST_DIR").expect("Read CARGO_MANIFEST_DIR variable"));
-        dir.push("huus");
-
-        if dir.is_dir() {
-            for entry in std::fs::read_dir(dir).expect("Failed to read `huus` directory") {
-                let path = entry.expect("Failed to read a `huus` directory entry").path();
-                let string = path.to_str().expect("Path is not UTF-8");
-                if path.is_file() && string.ends_with(".huus.rs") {
-                    interpreter = interpreter.parse_file(path)
-                        .expect("Stopping `huus` compilation due to previous error");
-                }
-            }
+    static ref CACHE: Mutex<Option<CachedSchema>> = Mutex::new(None);
+    static ref RESOLVED_FILES: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+}
+
+/// Adds a schema file resolved by name (e.g. relatively to the invoking file) to the files the
+/// schema is read from, so that it is known to the formulation macros even if it lies outside of
+/// the configured directories.
+pub fn add_resolved_file(path: PathBuf) {
+    RESOLVED_FILES.lock().expect("Lock the resolved schema files").insert(path);
+}
+
+/// Returns the schema defined in the schema directories of the crate being compiled and in the
+/// schema files resolved by name so far. The files are parsed only once and parsed again only if
+/// any of them was added, removed or modified since.
+///
+/// Outdated schemas are never freed as the validators borrow them for the whole macro invocation.
+pub fn schema() -> &'static Schema {
+    let files = find_schema_files();
+    let mut cache = CACHE.lock().expect("Lock the schema cache");
+    match &*cache {
+        Some(cached) if cached.files == files => cached.schema,
+        _ => {
+            let schema: &'static Schema = Box::leak(Box::new(parse_schema(&files)));
+            *cache = Some(CachedSchema { files, schema });
+            schema
         }
+    }
+}
+
+/// Lists schema files in the configured directories and the resolved schema files together with
+/// their modification times. Each file is listed once even if it was found in many ways.
+fn find_schema_files() -> Vec<(PathBuf, SystemTime)> {
+    let mut paths: BTreeSet<PathBuf> =
+        RESOLVED_FILES.lock().expect("Lock the resolved schema files").clone();
+    match Config::load() {
+        Ok(config) => paths.extend(config.find_files()),
+        Err(error) => proc_macro::Span::call_site().error(error.message).emit(),
+    }
+
+    let mut files: Vec<(PathBuf, SystemTime)> = paths
+        .into_iter()
+        .map(|path| std::fs::canonicalize(&path).unwrap_or(path))
+        .collect::<BTreeSet<PathBuf>>()
+        .into_iter()
+        .map(|path| {
+            let modified = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .expect("Failed to read modification time of a schema file");
+            (path, modified)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parses and validates the given schema files.
+fn parse_schema(files: &[(PathBuf, SystemTime)]) -> Schema {
+    let mut interpreter = Interpreter::new();
+    for (path, _) in files {
+        interpreter = interpreter
+            .parse_file(path.clone())
+            .expect("Stopping `huus` compilation due to previous error");
+    }
 
-        interpreter.build()
-            .verify()
-            .expect("Stopping `huus` validation due to previous error")
-            .into_schema()
-    };
+    interpreter
+        .build()
+        .verify()
+        .expect("Stopping `huus` validation due to previous error")
+        .into_schema()
 }
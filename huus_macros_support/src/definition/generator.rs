@@ -5,18 +5,20 @@
 
 use askama::Template;
 
-use crate::definition::output::{Entity, Enum, Schema, Struct, Union};
+use crate::definition::output::{Container, Entity, Enum, Schema, Struct, Union, Variant};
 
 // -------------------------------------------------------------------------------------------------
 
 /// Helper structure for calling rust code from within a template.
 #[derive(Clone)]
-struct GeneratorCallback;
+struct GeneratorCallback<'a> {
+    schema: &'a Schema,
+}
 
-impl GeneratorCallback {
+impl<'a> GeneratorCallback<'a> {
     /// Constructs a new `GeneratorCallback`.
-    pub fn new() -> Self {
-        Self
+    pub fn new(schema: &'a Schema) -> Self {
+        Self { schema }
     }
 
     pub fn make_coll_name(&self, string: &String) -> String {
@@ -30,6 +32,107 @@ impl GeneratorCallback {
 
         string.split('_').map(capitalize).collect::<Vec<String>>().join("")
     }
+
+    /// Checks if the data types should derive `serde` traits.
+    pub fn has_serde(&self) -> bool {
+        cfg!(feature = "serde")
+    }
+
+    /// Returns the code building `properties` of the `$jsonSchema` describing the main document of
+    /// the collection. Members holding a structure are described with the nested `properties` of
+    /// that structure. Optional members accept also `null`.
+    pub fn make_properties(&self, collection_name: &str) -> String {
+        let spec = self
+            .schema
+            .find_entity_for_collection(collection_name)
+            .expect("Find the main document of the collection");
+        self.make_nested_properties(spec, &mut vec![spec.struct_name.name.clone()])
+    }
+
+    fn make_nested_properties(&self, spec: &Struct, visited: &mut Vec<String>) -> String {
+        let mut code = String::from("{ let mut properties = bson::Document::new(); ");
+        for member in spec.members.iter() {
+            code += "{ let mut property = bson::Document::new(); ";
+            if member.is_optional {
+                // Optional members may be explicitly set to `null`.
+                code += &format!(
+                    "property.insert(\"bsonType\", vec![bson::Bson::from(\"{}\"), \
+                     bson::Bson::from(\"null\")]); ",
+                    member.to_bson_type()
+                );
+            } else {
+                code += &format!("property.insert(\"bsonType\", \"{}\"); ", member.to_bson_type());
+            }
+            if let (Container::Plain, Variant::Struct(name)) = (&member.container, &member.variant)
+            {
+                if let Some(Entity::Struct(nested)) = self.schema.find_entity(&name.name) {
+                    // Recursive structures are described only up to the first repetition.
+                    if !visited.contains(&name.name) {
+                        visited.push(name.name.clone());
+                        let properties = self.make_nested_properties(nested, visited);
+                        code += &format!("property.insert(\"properties\", {}); ", properties);
+                        visited.pop();
+                    }
+                }
+            }
+            code += &format!("properties.insert(\"{}\", property); }} ", member.db_name);
+        }
+        code + "properties }"
+    }
+
+    /// Returns the code building the list of paths of all the fields of the structure with their
+    /// BSON types. Fields of structures and unions are listed with their nested paths (e.g.
+    /// `data.int`) while the elements of arrays and maps are not.
+    pub fn make_field_types(&self, struct_name: &str) -> String {
+        let mut types = Vec::new();
+        if let Some(Entity::Struct(spec)) = self.schema.find_entity(struct_name) {
+            let mut visited = vec![spec.struct_name.name.clone()];
+            self.collect_field_types(spec, "", &mut types, &mut visited);
+        }
+        let types: Vec<String> =
+            types.iter().map(|(path, ty)| format!("(\"{}\", \"{}\")", path, ty)).collect();
+        format!("vec![{}]", types.join(", "))
+    }
+
+    fn collect_field_types(
+        &self,
+        spec: &Struct,
+        prefix: &str,
+        types: &mut Vec<(String, &'static str)>,
+        visited: &mut Vec<String>,
+    ) {
+        for member in spec.members.iter() {
+            let path = prefix.to_string() + &member.db_name;
+            if types.iter().any(|(known, _)| *known == path) {
+                continue;
+            }
+            types.push((path.clone(), member.to_bson_type()));
+
+            let nested = match (&member.container, &member.variant) {
+                (Container::Plain, Variant::Struct(name)) => vec![name.name.clone()],
+                (Container::Plain, Variant::Union(name)) => {
+                    match self.schema.find_entity(&name.name) {
+                        Some(Entity::Union(union)) => {
+                            union.choices.iter().map(|choice| choice.variant.name.clone()).collect()
+                        }
+                        _ => Vec::new(),
+                    }
+                }
+                _ => Vec::new(),
+            };
+            for name in nested {
+                // Recursive structures are described only up to the first repetition.
+                if visited.contains(&name) {
+                    continue;
+                }
+                if let Some(Entity::Struct(nested)) = self.schema.find_entity(&name) {
+                    visited.push(name);
+                    self.collect_field_types(nested, &(path.clone() + "."), types, visited);
+                    visited.pop();
+                }
+            }
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -39,11 +142,11 @@ impl GeneratorCallback {
 #[template(path = "struct_definition.rs", escape = "none")]
 struct StructDefinitionTemplate<'a> {
     pub spec: Struct,
-    pub generator: &'a GeneratorCallback,
+    pub generator: &'a GeneratorCallback<'a>,
 }
 
 impl<'a> StructDefinitionTemplate<'a> {
-    pub fn new(spec: Struct, generator: &'a GeneratorCallback) -> Self {
+    pub fn new(spec: Struct, generator: &'a GeneratorCallback<'a>) -> Self {
         Self { spec, generator }
     }
 }
@@ -57,18 +160,19 @@ fn make_struct_definition_output(spec: Struct, generator: &GeneratorCallback) ->
 /// Template used for enum definition code generation.
 #[derive(Template)]
 #[template(path = "enum_definition.rs", escape = "none")]
-struct EnumDefinitionTemplate {
+struct EnumDefinitionTemplate<'a> {
     pub spec: Enum,
+    pub generator: &'a GeneratorCallback<'a>,
 }
 
-impl EnumDefinitionTemplate {
-    pub fn new(spec: Enum) -> Self {
-        Self { spec }
+impl<'a> EnumDefinitionTemplate<'a> {
+    pub fn new(spec: Enum, generator: &'a GeneratorCallback<'a>) -> Self {
+        Self { spec, generator }
     }
 }
 
-fn make_enum_definition_output(spec: Enum) -> String {
-    EnumDefinitionTemplate::new(spec).render().expect("Render enum template")
+fn make_enum_definition_output(spec: Enum, generator: &GeneratorCallback) -> String {
+    EnumDefinitionTemplate::new(spec, generator).render().expect("Render enum template")
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -76,18 +180,19 @@ fn make_enum_definition_output(spec: Enum) -> String {
 /// Template used for union definition code generation.
 #[derive(Template)]
 #[template(path = "union_definition.rs", escape = "none")]
-struct UnionDefinitionTemplate {
+struct UnionDefinitionTemplate<'a> {
     pub spec: Union,
+    pub generator: &'a GeneratorCallback<'a>,
 }
 
-impl UnionDefinitionTemplate {
-    pub fn new(spec: Union) -> Self {
-        Self { spec }
+impl<'a> UnionDefinitionTemplate<'a> {
+    pub fn new(spec: Union, generator: &'a GeneratorCallback<'a>) -> Self {
+        Self { spec, generator }
     }
 }
 
-fn make_union_definition_output(spec: Union) -> String {
-    UnionDefinitionTemplate::new(spec).render().expect("Render union template")
+fn make_union_definition_output(spec: Union, generator: &GeneratorCallback) -> String {
+    UnionDefinitionTemplate::new(spec, generator).render().expect("Render union template")
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -97,11 +202,11 @@ fn make_union_definition_output(spec: Union) -> String {
 #[template(path = "struct_formulation.rs", escape = "none")]
 struct StructFormulationTemplate<'a> {
     pub spec: Struct,
-    pub generator: &'a GeneratorCallback,
+    pub generator: &'a GeneratorCallback<'a>,
 }
 
 impl<'a> StructFormulationTemplate<'a> {
-    pub fn new(spec: Struct, generator: &'a GeneratorCallback) -> Self {
+    pub fn new(spec: Struct, generator: &'a GeneratorCallback<'a>) -> Self {
         Self { spec, generator }
     }
 }
@@ -115,18 +220,19 @@ fn make_struct_formulation_output(spec: Struct, generator: &GeneratorCallback) -
 /// Template used for enum formulation code generation.
 #[derive(Template)]
 #[template(path = "enum_formulation.rs", escape = "none")]
-struct EnumFormulationTemplate {
+struct EnumFormulationTemplate<'a> {
     pub spec: Enum,
+    pub generator: &'a GeneratorCallback<'a>,
 }
 
-impl EnumFormulationTemplate {
-    pub fn new(spec: Enum) -> Self {
-        Self { spec }
+impl<'a> EnumFormulationTemplate<'a> {
+    pub fn new(spec: Enum, generator: &'a GeneratorCallback<'a>) -> Self {
+        Self { spec, generator }
     }
 }
 
-fn make_enum_formulation_output(spec: Enum) -> String {
-    EnumFormulationTemplate::new(spec).render().expect("Render enum template")
+fn make_enum_formulation_output(spec: Enum, generator: &GeneratorCallback) -> String {
+    EnumFormulationTemplate::new(spec, generator).render().expect("Render enum template")
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -134,18 +240,19 @@ fn make_enum_formulation_output(spec: Enum) -> String {
 /// Template used for union formulation code generation.
 #[derive(Template)]
 #[template(path = "union_formulation.rs", escape = "none")]
-struct UnionFormulationTemplate {
+struct UnionFormulationTemplate<'a> {
     pub spec: Union,
+    pub generator: &'a GeneratorCallback<'a>,
 }
 
-impl UnionFormulationTemplate {
-    pub fn new(spec: Union) -> Self {
-        Self { spec }
+impl<'a> UnionFormulationTemplate<'a> {
+    pub fn new(spec: Union, generator: &'a GeneratorCallback<'a>) -> Self {
+        Self { spec, generator }
     }
 }
 
-fn make_union_formulation_output(spec: Union) -> String {
-    UnionFormulationTemplate::new(spec).render().expect("Render union template")
+fn make_union_formulation_output(spec: Union, generator: &GeneratorCallback) -> String {
+    UnionFormulationTemplate::new(spec, generator).render().expect("Render union template")
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -171,15 +278,15 @@ impl Generator {
     /// By `definition` we mean structures with members corresponding to database fields generating
     /// query BSONs.
     pub fn generate_definition(self) -> proc_macro::TokenStream {
-        let generator = GeneratorCallback::new();
+        let generator = GeneratorCallback::new(&self.schema);
         let mut entities = Vec::new();
-        for entity in self.schema.entities {
+        for entity in self.schema.entities.iter().cloned() {
             entities.push(match entity {
                 Entity::Struct(struct_spec) => {
                     make_struct_definition_output(struct_spec, &generator)
                 }
-                Entity::Enum(enum_spec) => make_enum_definition_output(enum_spec),
-                Entity::Union(union_spec) => make_union_definition_output(union_spec),
+                Entity::Enum(enum_spec) => make_enum_definition_output(enum_spec, &generator),
+                Entity::Union(union_spec) => make_union_definition_output(union_spec, &generator),
             });
         }
         entities.join("\n\n").parse().expect("Parse into TokenStream")
@@ -190,15 +297,15 @@ impl Generator {
     /// By `formulation` we mean structures build by `data`, `filter` and `update` macros from
     /// `huus_macro`.
     pub fn generate_formulation(self) -> proc_macro::TokenStream {
-        let generator = GeneratorCallback::new();
+        let generator = GeneratorCallback::new(&self.schema);
         let mut entities = Vec::new();
-        for entity in self.schema.entities {
+        for entity in self.schema.entities.iter().cloned() {
             entities.push(match entity {
                 Entity::Struct(struct_spec) => {
                     make_struct_formulation_output(struct_spec, &generator)
                 }
-                Entity::Enum(enum_spec) => make_enum_formulation_output(enum_spec),
-                Entity::Union(union_spec) => make_union_formulation_output(union_spec),
+                Entity::Enum(enum_spec) => make_enum_formulation_output(enum_spec, &generator),
+                Entity::Union(union_spec) => make_union_formulation_output(union_spec, &generator),
             });
         }
         entities.join("\n\n").parse().expect("Parse into TokenStream")
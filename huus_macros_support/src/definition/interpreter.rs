@@ -6,7 +6,7 @@
 use std::{path::PathBuf, str::FromStr};
 
 use crate::{
-    definition::{input::*, validator::Validator},
+    definition::{config::Config, input::*, output::LoadError, validator::Validator},
     parser::{ExpectedTokenTree, Parser},
 };
 
@@ -73,19 +73,40 @@ impl Interpreter {
         self.parse_instruction_stream(stream)
     }
 
-    /// Parses out a file name, reads it in and parses as a schema definition.
+    /// Parses out a file name, reads it in and parses as a schema definition. The file is looked up
+    /// as described by the schema `Config`.
     pub fn parse_file_stream(self, stream: proc_macro::TokenStream) -> Result<Self, ()> {
         let mut parser = Parser::new(stream);
         let name = parser.expect_string()?;
+        let span = parser.span().expect(SPAN);
         parser.expect_eof()?;
 
-        let mut path = PathBuf::new();
-        path.push(std::env::var("CARGO_MANIFEST_DIR").expect("Read CARGO_MANIFEST_DIR variable"));
-        path.push("huus");
-        path.push(name);
-        path.set_extension("huus.rs");
+        self.parse_named_file(&name, span).map_err(|error| error.emit(span))
+    }
 
-        self.parse_file(path)
+    /// Looks up a schema file with the given name as described by the schema `Config`, reads it in
+    /// and parses as a schema definition. Problems found inside the file are reported as
+    /// compilation errors.
+    pub fn parse_named_file(self, name: &str, span: proc_macro::Span) -> Result<Self, LoadError> {
+        let config = Config::load().map_err(|error| LoadError::new(error.message))?;
+        match config.resolve(name, &span) {
+            Some(path) => {
+                crate::definition::data::add_resolved_file(path.clone());
+                self.parse_file(path)
+                    .map_err(|_| LoadError::new(format!("Schema file `{}` contains errors", name)))
+            }
+            None => {
+                let help = if config.is_relative(name) {
+                    "Relative names are resolved against the directory of the invoking file".into()
+                } else {
+                    let dirs: Vec<String> =
+                        config.dirs.iter().map(|dir| dir.to_string_lossy().into_owned()).collect();
+                    format!("Searched in: \"{}\"", dirs.join("\", \""))
+                };
+                let message = format!("Schema file `{}.{}` not found", name, config.extension);
+                Err(LoadError { message, help: Some(help) })
+            }
+        }
     }
 
     /// Returns the validator for the parsed data.
@@ -3,6 +3,8 @@
 
 //! Structures for code generation.
 
+use crate::definition::interpreter::Interpreter;
+
 /// Represents a parsing error pointing to a part that failed aiding the error handler display pin
 /// the error message to correct place in the code.
 pub enum ParseError {
@@ -16,6 +18,40 @@ pub enum ParseError {
     Type(String),
 }
 
+/// Describes why a schema could not be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadError {
+    /// Explains the failure.
+    pub message: String,
+
+    /// Hints how to fix the failure.
+    pub help: Option<String>,
+}
+
+impl LoadError {
+    /// Constructs a new `LoadError` without a hint.
+    pub fn new(message: String) -> Self {
+        Self { message, help: None }
+    }
+
+    /// Reports the error as a compilation error at the given span.
+    pub fn emit(&self, span: proc_macro::Span) {
+        let diagnostic = span.error(self.message.clone());
+        match &self.help {
+            Some(help) => diagnostic.help(help.clone()).emit(),
+            None => diagnostic.emit(),
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for LoadError {}
+
 /// Represent build-in (mongodb) type.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BuiltInType {
@@ -40,6 +76,9 @@ pub enum BuiltInType {
     /// Corresponds to a 64-bit integer.
     I64,
 
+    /// Corresponds to a GeoJSON point.
+    GeoPoint,
+
     /// Corresponds to a BSON object.
     Bson,
 }
@@ -55,11 +94,20 @@ impl BuiltInType {
             "Date" => Ok(BuiltInType::Date),
             "i32" => Ok(BuiltInType::I32),
             "i64" => Ok(BuiltInType::I64),
+            "GeoPoint" => Ok(BuiltInType::GeoPoint),
             "Bson" => Ok(BuiltInType::Bson),
             _ => return Err(()),
         }
     }
 
+    /// Returns `true` if the type represents a number.
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            BuiltInType::F64 | BuiltInType::I32 | BuiltInType::I64 => true,
+            _ => false,
+        }
+    }
+
     /// Specifies if the given type supports indexing.
     fn allows_indexing(&self) -> bool {
         match self {
@@ -78,6 +126,7 @@ impl BuiltInType {
             BuiltInType::Date => "huus::types::Date",
             BuiltInType::I32 => "i32",
             BuiltInType::I64 => "i64",
+            BuiltInType::GeoPoint => "huus::types::GeoPoint",
             BuiltInType::Bson => "bson::Document",
         }
     }
@@ -92,6 +141,7 @@ impl BuiltInType {
             BuiltInType::Date => "huus::filters::DateEntry",
             BuiltInType::I32 => "huus::filters::I32Entry",
             BuiltInType::I64 => "huus::filters::I64Entry",
+            BuiltInType::GeoPoint => "huus::filters::GeoPointEntry",
             BuiltInType::Bson => "huus::filters::BsonEntry",
         }
     }
@@ -106,6 +156,7 @@ impl BuiltInType {
             BuiltInType::Date => "huus::types::Date",
             BuiltInType::I32 => "i32",
             BuiltInType::I64 => "i64",
+            BuiltInType::GeoPoint => "huus::types::GeoPoint",
             BuiltInType::Bson => "bson::Document",
         }
     }
@@ -120,6 +171,7 @@ impl BuiltInType {
             BuiltInType::Date => "huus::updates::DateEntry",
             BuiltInType::I32 => "huus::updates::I32Entry",
             BuiltInType::I64 => "huus::updates::I64Entry",
+            BuiltInType::GeoPoint => "huus::updates::GeoPointEntry",
             BuiltInType::Bson => "huus::updates::BsonEntry",
         }
     }
@@ -134,10 +186,26 @@ impl BuiltInType {
             BuiltInType::Date => "get_utc_datetime",
             BuiltInType::I32 => "get_i32",
             BuiltInType::I64 => "get_i64",
+            BuiltInType::GeoPoint => "get_document",
             BuiltInType::Bson => "get_document",
         }
     }
 
+    /// Returns the BSON type name of the type as used in `$jsonSchema` validators.
+    pub fn to_bson_type(&self) -> &'static str {
+        match self {
+            BuiltInType::F64 => "double",
+            BuiltInType::String => "string",
+            BuiltInType::ObjectId => "objectId",
+            BuiltInType::Bool => "bool",
+            BuiltInType::Date => "date",
+            BuiltInType::I32 => "int",
+            BuiltInType::I64 => "long",
+            BuiltInType::GeoPoint => "object",
+            BuiltInType::Bson => "object",
+        }
+    }
+
     /// Returns a code to converting thus BSON value to the underlying type.
     pub fn to_conversion(&self) -> &'static str {
         let output = match self {
@@ -148,6 +216,7 @@ impl BuiltInType {
             BuiltInType::Date => "value.clone()",
             BuiltInType::I32 => "value",
             BuiltInType::I64 => "value",
+            BuiltInType::GeoPoint => "value.clone().huus_into_struct()?",
             BuiltInType::Bson => "value.clone()",
         };
         output.into()
@@ -191,6 +260,11 @@ impl DefinedType {
     pub fn to_update(&self) -> String {
         self.name.clone() + "Update"
     }
+
+    /// Returns a name of `Projection` type.
+    pub fn to_projection(&self) -> String {
+        self.name.clone() + "Projection"
+    }
 }
 
 impl PartialEq<str> for DefinedType {
@@ -303,6 +377,16 @@ impl Variant {
         }
     }
 
+    /// Returns the BSON type name of the type as used in `$jsonSchema` validators.
+    pub fn to_bson_type(&self) -> &'static str {
+        match self {
+            Variant::Field(field) => field.to_bson_type(),
+            Variant::Struct(_) => "object",
+            Variant::Enum(_) => "string",
+            Variant::Union(_) => "object",
+        }
+    }
+
     /// Returns a code to converting thus BSON value to the underlying type.
     pub fn to_conversion(&self) -> String {
         match self {
@@ -491,6 +575,22 @@ impl Member {
         }
     }
 
+    /// Returns the BSON type name of the field as used in `$jsonSchema` validators.
+    pub fn to_bson_type(&self) -> &'static str {
+        match self.container {
+            Container::Array => "array",
+            Container::HashMap(_) => "object",
+            Container::BTreeMap(_) => "object",
+            Container::Plain => self.variant.to_bson_type(),
+        }
+    }
+
+    /// Returns `true` if the field has to be present in every document. Optional fields and
+    /// containers, which default to empty ones, may be missing.
+    pub fn is_required(&self) -> bool {
+        !self.is_optional && self.to_default().is_none()
+    }
+
     /// Returns a code to converting thus BSON value to the underlying type.
     pub fn to_conversion(&self) -> String {
         match self.container {
@@ -501,6 +601,32 @@ impl Member {
         }
     }
 
+    /// Returns a code checking strictly the nested documents stored in the BSON value, or `None` if
+    /// the member does not contain structures.
+    pub fn to_strict_check(&self) -> Option<String> {
+        let name = match &self.variant {
+            Variant::Struct(name) | Variant::Union(name) => name.to_data(),
+            Variant::Field(_) | Variant::Enum(_) => return None,
+        };
+        let check =
+            format!("<{} as huus::conversions::FromDoc>::from_doc_strict(element.clone())?;", name);
+        Some(match self.container {
+            Container::Array => format!(
+                "for element in value.iter() {{ \
+                    if let bson::Bson::Document(element) = element {{ {} }} \
+                }}",
+                check
+            ),
+            Container::HashMap(_) | Container::BTreeMap(_) => format!(
+                "for (_, element) in value.iter() {{ \
+                    if let bson::Bson::Document(element) = element {{ {} }} \
+                }}",
+                check
+            ),
+            Container::Plain => format!("let element = value; {}", check),
+        })
+    }
+
     /// Returns a code initializing a default value of the underlying type.
     pub fn to_default(&self) -> Option<&str> {
         match self.container {
@@ -611,6 +737,11 @@ pub enum Entity {
 }
 
 /// Holds information about all parsed entities.
+///
+/// Besides being used internally by `huus` macros the schema can be loaded by other procedural
+/// macros to generate code from the same schema source. Loading has to happen inside a procedural
+/// macro invocation as the schema files are tokenized by the compiler.
+#[derive(Clone, Debug)]
 pub struct Schema {
     /// A list of all parsed entities.
     pub entities: Vec<Entity>,
@@ -622,6 +753,54 @@ impl Schema {
         Self { entities: Vec::new() }
     }
 
+    /// Loads the schema from a single file. The name is resolved the same way as in `define_from`
+    /// macro. Problems found inside the file are reported as compilation errors; the returned error
+    /// only describes why loading failed and can be reported with `LoadError::emit`.
+    pub fn load(name: &str) -> Result<Self, LoadError> {
+        let interpreter =
+            Interpreter::new().parse_named_file(name, proc_macro::Span::call_site())?;
+        let validator = interpreter
+            .build()
+            .verify()
+            .map_err(|_| LoadError::new(format!("Schema file `{}` contains errors", name)))?;
+        Ok(validator.into_schema())
+    }
+
+    /// Loads the schema from all the files found in the configured schema directories. This is the
+    /// schema used for validation of formulation macros.
+    pub fn load_all() -> &'static Self {
+        crate::definition::data::schema()
+    }
+
+    /// Returns all the defined structures.
+    pub fn structs(&self) -> impl Iterator<Item = &Struct> {
+        self.entities.iter().filter_map(|entity| match entity {
+            Entity::Struct(struct_spec) => Some(struct_spec),
+            _ => None,
+        })
+    }
+
+    /// Returns all the defined enums.
+    pub fn enums(&self) -> impl Iterator<Item = &Enum> {
+        self.entities.iter().filter_map(|entity| match entity {
+            Entity::Enum(enum_spec) => Some(enum_spec),
+            _ => None,
+        })
+    }
+
+    /// Returns all the defined unions.
+    pub fn unions(&self) -> impl Iterator<Item = &Union> {
+        self.entities.iter().filter_map(|entity| match entity {
+            Entity::Union(union_spec) => Some(union_spec),
+            _ => None,
+        })
+    }
+
+    /// Returns the structures defining main documents of collections.
+    pub fn collections(&self) -> impl Iterator<Item = &Struct> {
+        self.structs().filter(|struct_spec| struct_spec.collection_name.is_some())
+    }
+
     /// Searches for an entity with the given name.
     pub fn find_entity(&self, name: &str) -> Option<&Entity> {
         for entity in self.entities.iter() {
@@ -662,4 +841,11 @@ impl Schema {
         }
         None
     }
+
+    /// Returns names of all the collections defined in the schema.
+    pub fn collection_names(&self) -> Vec<&str> {
+        self.collections()
+            .filter_map(|struct_spec| struct_spec.collection_name.as_deref())
+            .collect()
+    }
 }
@@ -6,7 +6,10 @@
 use askama::Template;
 
 use crate::definition::output::DefinedType;
-use crate::formulation::output::{Object, Part, Value};
+use crate::formulation::output::{
+    Aggregate, Count, CountDocuments, Delete, Distinct, Field, Find, FindAndModify, InsertMany,
+    Lookup, Modification, Object, Part, Value,
+};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -24,6 +27,24 @@ impl GeneratorCallback {
     pub fn object(&self, object: &Object) -> String {
         ObjectTemplate::new(object, self).render().expect("Render object template")
     }
+
+    /// Renders the value template with the give value.
+    pub fn value(&self, value: &Value) -> String {
+        ValueTemplate::new(value, self).render().expect("Render value template")
+    }
+
+    /// Renders the value template with the value of the given field.
+    pub fn field_value(&self, field: &Field) -> String {
+        self.value(&field.value)
+    }
+
+    /// Renders a count.
+    pub fn count(&self, count: &Count) -> String {
+        match count {
+            Count::Literal(number) => format!("{}u32", number),
+            Count::Code(code) => format!("{{ let count: u32 = {{ {} }}; count }}", code),
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -43,6 +64,21 @@ impl<'a> ObjectTemplate<'a> {
 
 // -------------------------------------------------------------------------------------------------
 
+#[derive(Template)]
+#[template(path = "value.rs", escape = "none")]
+struct ValueTemplate<'a> {
+    pub value: &'a Value,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> ValueTemplate<'a> {
+    pub fn new(value: &'a Value, generator: &'a GeneratorCallback) -> Self {
+        Self { value, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Template used for data query generation.
 #[derive(Template)]
 #[template(path = "data.rs", escape = "none")]
@@ -65,6 +101,40 @@ impl<'a> DataTemplate<'a> {
 
 // -------------------------------------------------------------------------------------------------
 
+/// Template used for bulk data insert generation.
+#[derive(Template)]
+#[template(path = "data_many.rs", escape = "none")]
+struct DataManyTemplate<'a> {
+    pub insert_many: &'a InsertMany,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> DataManyTemplate<'a> {
+    /// Constructs a new `DataManyTemplate`.
+    pub fn new(insert_many: &'a InsertMany, generator: &'a GeneratorCallback) -> Self {
+        Self { insert_many, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for aggregation pipeline generation.
+#[derive(Template)]
+#[template(path = "aggregate.rs", escape = "none")]
+struct AggregateTemplate<'a> {
+    pub aggregate: &'a Aggregate,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> AggregateTemplate<'a> {
+    /// Constructs a new `AggregateTemplate`.
+    pub fn new(aggregate: &'a Aggregate, generator: &'a GeneratorCallback) -> Self {
+        Self { aggregate, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Template used for filter query generation.
 #[derive(Template)]
 #[template(path = "filter.rs", escape = "none")]
@@ -93,11 +163,35 @@ impl<'a> FilterTemplate<'a> {
 struct UpdateTemplate<'a> {
     pub name: &'a DefinedType,
     pub object: &'a Object,
+    pub array_filters: &'a Vec<Object>,
     pub generator: &'a GeneratorCallback,
 }
 
 impl<'a> UpdateTemplate<'a> {
     /// Constructs a new `UpdateTemplate`.
+    pub fn new(
+        name: &'a DefinedType,
+        object: &'a Object,
+        array_filters: &'a Vec<Object>,
+        generator: &'a GeneratorCallback,
+    ) -> Self {
+        Self { name, object, array_filters, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for projection generation.
+#[derive(Template)]
+#[template(path = "projection.rs", escape = "none")]
+struct ProjectionTemplate<'a> {
+    pub name: &'a DefinedType,
+    pub object: &'a Object,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> ProjectionTemplate<'a> {
+    /// Constructs a new `ProjectionTemplate`.
     pub fn new(
         name: &'a DefinedType,
         object: &'a Object,
@@ -109,16 +203,268 @@ impl<'a> UpdateTemplate<'a> {
 
 // -------------------------------------------------------------------------------------------------
 
+/// Template used for find query generation.
+#[derive(Template)]
+#[template(path = "find.rs", escape = "none")]
+struct FindTemplate<'a> {
+    pub name: &'a DefinedType,
+    pub object: &'a Object,
+    pub find: &'a Find,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> FindTemplate<'a> {
+    /// Constructs a new `FindTemplate`.
+    pub fn new(
+        name: &'a DefinedType,
+        object: &'a Object,
+        find: &'a Find,
+        generator: &'a GeneratorCallback,
+    ) -> Self {
+        Self { name, object, find, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for distinct query generation.
+#[derive(Template)]
+#[template(path = "distinct.rs", escape = "none")]
+struct DistinctTemplate<'a> {
+    pub object: &'a Object,
+    pub distinct: &'a Distinct,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> DistinctTemplate<'a> {
+    /// Constructs a new `DistinctTemplate`.
+    pub fn new(
+        object: &'a Object,
+        distinct: &'a Distinct,
+        generator: &'a GeneratorCallback,
+    ) -> Self {
+        Self { object, distinct, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for count query generation.
+#[derive(Template)]
+#[template(path = "count.rs", escape = "none")]
+struct CountTemplate<'a> {
+    pub object: &'a Object,
+    pub count: &'a CountDocuments,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> CountTemplate<'a> {
+    /// Constructs a new `CountTemplate`.
+    pub fn new(
+        object: &'a Object,
+        count: &'a CountDocuments,
+        generator: &'a GeneratorCallback,
+    ) -> Self {
+        Self { object, count, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for delete query generation.
+#[derive(Template)]
+#[template(path = "delete.rs", escape = "none")]
+struct DeleteTemplate<'a> {
+    pub object: &'a Object,
+    pub delete: &'a Delete,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> DeleteTemplate<'a> {
+    /// Constructs a new `DeleteTemplate`.
+    pub fn new(object: &'a Object, delete: &'a Delete, generator: &'a GeneratorCallback) -> Self {
+        Self { object, delete, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for find and modify query generation.
+#[derive(Template)]
+#[template(path = "find_and_modify.rs", escape = "none")]
+struct FindAndModifyTemplate<'a> {
+    pub name: &'a DefinedType,
+    pub object: &'a Object,
+    pub find_and_modify: &'a FindAndModify,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> FindAndModifyTemplate<'a> {
+    /// Constructs a new `FindAndModifyTemplate`.
+    pub fn new(
+        name: &'a DefinedType,
+        object: &'a Object,
+        find_and_modify: &'a FindAndModify,
+        generator: &'a GeneratorCallback,
+    ) -> Self {
+        Self { name, object, find_and_modify, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for lookup query generation.
+#[derive(Template)]
+#[template(path = "lookup.rs", escape = "none")]
+struct LookupTemplate<'a> {
+    pub name: &'a DefinedType,
+    pub object: &'a Object,
+    pub lookup: &'a Lookup,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> LookupTemplate<'a> {
+    /// Constructs a new `LookupTemplate`.
+    pub fn new(
+        name: &'a DefinedType,
+        object: &'a Object,
+        lookup: &'a Lookup,
+        generator: &'a GeneratorCallback,
+    ) -> Self {
+        Self { name, object, lookup, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for sort document generation.
+#[derive(Template)]
+#[template(path = "sort.rs", escape = "none")]
+struct SortTemplate<'a> {
+    pub object: &'a Object,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> SortTemplate<'a> {
+    /// Constructs a new `SortTemplate`.
+    pub fn new(object: &'a Object, generator: &'a GeneratorCallback) -> Self {
+        Self { object, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Template used for pipeline update query generation.
+#[derive(Template)]
+#[template(path = "pipeline.rs", escape = "none")]
+struct PipelineTemplate<'a> {
+    pub pipeline: &'a Vec<Object>,
+    pub generator: &'a GeneratorCallback,
+}
+
+impl<'a> PipelineTemplate<'a> {
+    /// Constructs a new `PipelineTemplate`.
+    pub fn new(pipeline: &'a Vec<Object>, generator: &'a GeneratorCallback) -> Self {
+        Self { pipeline, generator }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Query generator.
 pub struct Generator {
     name: DefinedType,
     object: Object,
+    array_filters: Vec<Object>,
+    pipeline: Option<Vec<Object>>,
+    find: Option<Find>,
+    find_and_modify: Option<FindAndModify>,
+    lookup: Option<Lookup>,
+    distinct: Option<Distinct>,
+    count: Option<CountDocuments>,
+    delete: Option<Delete>,
+    insert_many: Option<InsertMany>,
+    aggregate: Option<Aggregate>,
 }
 
 impl Generator {
     /// Constructs a new `Generator`.
     pub fn new(name: DefinedType, object: Object) -> Self {
-        Self { name, object }
+        Self {
+            name,
+            object,
+            array_filters: Vec::new(),
+            pipeline: None,
+            find: None,
+            find_and_modify: None,
+            lookup: None,
+            distinct: None,
+            count: None,
+            delete: None,
+            insert_many: None,
+            aggregate: None,
+        }
+    }
+
+    /// Sets the stages of an update pipeline. If set, a pipeline update is generated instead of
+    /// the update document.
+    pub fn with_pipeline(mut self, pipeline: Vec<Object>) -> Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    /// Sets the array filters accompanying an update query.
+    pub fn with_array_filters(mut self, array_filters: Vec<Object>) -> Self {
+        self.array_filters = array_filters;
+        self
+    }
+
+    /// Sets the options of a find query. The object is used as the filter.
+    pub fn with_find(mut self, find: Find) -> Self {
+        self.find = Some(find);
+        self
+    }
+
+    /// Sets the options of a find and modify query. The object is used as the filter.
+    pub fn with_find_and_modify(mut self, find_and_modify: FindAndModify) -> Self {
+        self.find_and_modify = Some(find_and_modify);
+        self
+    }
+
+    /// Sets the options of a lookup query. The object is used as the filter.
+    pub fn with_lookup(mut self, lookup: Lookup) -> Self {
+        self.lookup = Some(lookup);
+        self
+    }
+
+    /// Sets the options of a distinct query. The object is used as the filter.
+    pub fn with_distinct(mut self, distinct: Distinct) -> Self {
+        self.distinct = Some(distinct);
+        self
+    }
+
+    /// Sets the options of a count query. The object is used as the filter.
+    pub fn with_count(mut self, count: CountDocuments) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Sets the options of a delete query. The object is used as the filter.
+    pub fn with_delete(mut self, delete: Delete) -> Self {
+        self.delete = Some(delete);
+        self
+    }
+
+    /// Sets the documents of a bulk insert.
+    pub fn with_insert_many(mut self, insert_many: InsertMany) -> Self {
+        self.insert_many = Some(insert_many);
+        self
+    }
+
+    /// Sets the stages of an aggregation pipeline.
+    pub fn with_aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = Some(aggregate);
+        self
     }
 
     /// Generates a data query.
@@ -131,6 +477,17 @@ impl Generator {
             .expect("Parse into TokenStream")
     }
 
+    /// Generates a bulk insert command.
+    pub fn generate_data_many(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let insert_many = self.insert_many.as_ref().expect("Documents should be set");
+        DataManyTemplate::new(insert_many, &callback)
+            .render()
+            .expect("Render data many template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
     /// Generates a filter query.
     pub fn generate_filter(self) -> proc_macro::TokenStream {
         let callback = GeneratorCallback::new();
@@ -144,10 +501,116 @@ impl Generator {
     /// Generates an update query.
     pub fn generate_update(self) -> proc_macro::TokenStream {
         let callback = GeneratorCallback::new();
-        UpdateTemplate::new(&self.name, &self.object, &callback)
+        if let Some(pipeline) = &self.pipeline {
+            return PipelineTemplate::new(pipeline, &callback)
+                .render()
+                .expect("Render pipeline template")
+                .parse()
+                .expect("Parse into TokenStream");
+        }
+
+        UpdateTemplate::new(&self.name, &self.object, &self.array_filters, &callback)
             .render()
             .expect("Render update template")
             .parse()
             .expect("Parse into TokenStream")
     }
+
+    /// Generates a find query.
+    pub fn generate_find(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let find = self.find.as_ref().expect("Find options should be set");
+        FindTemplate::new(&self.name, &self.object, find, &callback)
+            .render()
+            .expect("Render find template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a find and modify query.
+    pub fn generate_find_and_modify(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let find_and_modify =
+            self.find_and_modify.as_ref().expect("Find and modify options should be set");
+        FindAndModifyTemplate::new(&self.name, &self.object, find_and_modify, &callback)
+            .render()
+            .expect("Render find and modify template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a lookup query.
+    pub fn generate_lookup(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let lookup = self.lookup.as_ref().expect("Lookup options should be set");
+        LookupTemplate::new(&self.name, &self.object, lookup, &callback)
+            .render()
+            .expect("Render lookup template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a distinct query.
+    pub fn generate_distinct(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let distinct = self.distinct.as_ref().expect("Distinct options should be set");
+        DistinctTemplate::new(&self.object, distinct, &callback)
+            .render()
+            .expect("Render distinct template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a count query.
+    pub fn generate_count(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let count = self.count.as_ref().expect("Count options should be set");
+        CountTemplate::new(&self.object, count, &callback)
+            .render()
+            .expect("Render count template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a delete query.
+    pub fn generate_delete(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let delete = self.delete.as_ref().expect("Delete options should be set");
+        DeleteTemplate::new(&self.object, delete, &callback)
+            .render()
+            .expect("Render delete template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates an aggregation command.
+    pub fn generate_aggregate(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        let aggregate = self.aggregate.as_ref().expect("Stages should be set");
+        AggregateTemplate::new(aggregate, &callback)
+            .render()
+            .expect("Render aggregate template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a sort document.
+    pub fn generate_sort(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        SortTemplate::new(&self.object, &callback)
+            .render()
+            .expect("Render sort template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
+
+    /// Generates a projection.
+    pub fn generate_projection(self) -> proc_macro::TokenStream {
+        let callback = GeneratorCallback::new();
+        ProjectionTemplate::new(&self.name, &self.object, &callback)
+            .render()
+            .expect("Render projection template")
+            .parse()
+            .expect("Parse into TokenStream")
+    }
 }
@@ -143,6 +143,12 @@ pub enum ValueTemplate {
 
     /// Corresponds to code mode (bound by parenthesis "()")
     Code(String),
+
+    /// Corresponds to arrays (bound by square brackets "[]")
+    Array(Vec<SpannedValue>),
+
+    /// Corresponds to object ID literals (strings prefixed with `oid`, e.g. `oid"..."`).
+    ObjectId(String),
 }
 
 impl ValueTemplate {
@@ -153,6 +159,22 @@ impl ValueTemplate {
             _ => false,
         }
     }
+
+    /// Return `true` if the value corresponds to an array.
+    pub fn is_array(&self) -> bool {
+        match self {
+            Self::Array(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Return `true` if the value corresponds to the `null` literal.
+    pub fn is_null(&self) -> bool {
+        match self {
+            Self::Unquoted(string) => string == "null",
+            _ => false,
+        }
+    }
 }
 
 /// Helps in parsing and reporting errors related to values.
@@ -195,6 +217,12 @@ pub struct ObjectTemplate {
     /// List of the object's fields.
     pub fields: Vec<FieldTemplate>,
 
+    /// List of documents to be merged into the object (passed as `...(code)`).
+    pub spreads: Vec<SpannedValue>,
+
+    /// Field path given without a value (e.g. the field of `distinct` formulation).
+    pub key: Option<SpannedAttribute>,
+
     /// Span of the object.
     pub span: proc_macro::Span,
 }
@@ -202,6 +230,6 @@ pub struct ObjectTemplate {
 impl ObjectTemplate {
     /// Constructs a new `ObjectTemplate`.
     pub fn new(span: proc_macro::Span) -> Self {
-        Self { fields: Vec::new(), span }
+        Self { fields: Vec::new(), spreads: Vec::new(), key: None, span }
     }
 }
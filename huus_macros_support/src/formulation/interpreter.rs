@@ -4,8 +4,11 @@
 //! Parsing the token stream for macros generating BSON queries.
 
 use crate::{
-    definition::data::SCHEMA,
-    formulation::{input::*, validator::Validator},
+    definition::{data::schema, output::Entity},
+    formulation::{
+        input::*,
+        validator::{Problem, Validator},
+    },
     parser::{ExpectedTokenTree, Parser},
 };
 
@@ -17,6 +20,10 @@ const SPAN: &str = "Span should be present";
 pub struct Interpreter {
     collection: SpannedCollection,
     object: ObjectTemplate,
+    pipeline: Option<Vec<SpannedValue>>,
+    strict: bool,
+    upsert: bool,
+    allowed: Vec<Problem>,
     testing: bool,
 }
 
@@ -26,18 +33,40 @@ impl Interpreter {
         Self {
             collection: SpannedCollection::new(),
             object: ObjectTemplate::new(proc_macro::Span::call_site()),
+            pipeline: None,
+            strict: false,
+            upsert: false,
+            allowed: Vec::new(),
             testing: testing,
         }
     }
 
     /// Parses the macro input containing the query.
     pub fn parse(mut self, stream: proc_macro::TokenStream) -> Result<Self, ()> {
+        let is_json = is_json(&stream);
         let mut parser = Parser::new(stream);
-        self.collection = self.parse_prelude(parser.expect_group()?)?;
-        self.object = if parser.is_group() {
+        let (collection, strict, upsert, allowed) = self.parse_prelude(parser.expect_group()?)?;
+        self.collection = collection;
+        self.strict = strict;
+        self.upsert = upsert;
+        self.allowed = allowed;
+        self.object = if is_json {
+            let _ = parser.expect_ident(Some("json"))?;
+            let text = match parser.expect() {
+                ExpectedTokenTree::String(string) => unescape(&string),
+                ExpectedTokenTree::Value(string) => unraw(&string),
+                _ => unreachable!("JSON mode requires a literal"),
+            };
+            self.parse_json(&text, parser.span().expect(SPAN))?
+        } else if parser.is_group() {
             let group = parser.expect_group()?;
             let next_parser = Parser::new(group.stream());
-            self.parse_object(next_parser, group.span().clone())?
+            if group.delimiter() == proc_macro::Delimiter::Bracket {
+                self.pipeline = Some(self.parse_array(next_parser)?);
+                ObjectTemplate::new(group.span().clone())
+            } else {
+                self.parse_object(next_parser, group.span().clone())?
+            }
         } else {
             self.parse_object(parser, proc_macro::Span::call_site())?
         };
@@ -46,7 +75,11 @@ impl Interpreter {
 
     /// Returns the validator for the parsed data.
     pub fn build(self) -> Validator<'static> {
-        Validator::new(self.collection, self.object, &*SCHEMA, self.testing)
+        Validator::new(self.collection, self.object, schema(), self.testing)
+            .with_pipeline(self.pipeline)
+            .with_strict(self.strict)
+            .with_upsert(self.upsert)
+            .with_allowed(self.allowed)
     }
 }
 
@@ -54,13 +87,157 @@ impl Interpreter {
 // Helper parse methods
 
 impl Interpreter {
-    /// Parses the name of collection the data will refer to.
-    fn parse_prelude(&self, group: proc_macro::Group) -> Result<SpannedCollection, ()> {
+    /// Parses the name of collection the data will refer to followed by optional `strict` and
+    /// `upsert` flags and `allow(...)` list of warnings which should not be reported. The
+    /// collection may be given either as a string or as a name of the structure defined for it.
+    fn parse_prelude(
+        &self,
+        group: proc_macro::Group,
+    ) -> Result<(SpannedCollection, bool, bool, Vec<Problem>), ()> {
+        let mut parser = Parser::new(group.stream());
+        let collection = if parser.is_ident() {
+            self.parse_collection_struct(parser.expect_ident(None)?)?
+        } else {
+            SpannedCollection { name: parser.expect_string()?, span: parser.span().expect(SPAN) }
+        };
+        let mut strict = false;
+        let mut upsert = false;
+        let mut allowed = Vec::new();
+        while !parser.is_end() {
+            let _ = parser.expect_punctuation(Some(','))?;
+            let ident = parser.expect_ident(None)?;
+            match ident.to_string().as_ref() {
+                "strict" => strict = true,
+                "upsert" => upsert = true,
+                "allow" => allowed.extend(self.parse_allowed(parser.expect_group()?)?),
+                _ => {
+                    ident.span().error("Expected `strict`, `upsert` or `allow(...)`").emit();
+                    return Err(());
+                }
+            }
+        }
+        Ok((collection, strict, upsert, allowed))
+    }
+
+    /// Resolves the name of a structure to the name of the collection it was defined for.
+    fn parse_collection_struct(&self, ident: proc_macro::Ident) -> Result<SpannedCollection, ()> {
+        let name = ident.to_string();
+        match schema().find_entity(&name) {
+            Some(Entity::Struct(struct_spec)) => match &struct_spec.collection_name {
+                Some(collection_name) => {
+                    Ok(SpannedCollection { name: collection_name.clone(), span: ident.span() })
+                }
+                None => {
+                    ident.span().error("The structure is not defined for any collection").emit();
+                    Err(())
+                }
+            },
+            _ => {
+                ident.span().error(format!("No structure named `{}` was defined", name)).emit();
+                Err(())
+            }
+        }
+    }
+
+    /// Parses a comma-separated list of names of warnings.
+    fn parse_allowed(&self, group: proc_macro::Group) -> Result<Vec<Problem>, ()> {
         let mut parser = Parser::new(group.stream());
-        let collection =
-            SpannedCollection { name: parser.expect_string()?, span: parser.span().expect(SPAN) };
-        parser.expect_eof()?;
-        Ok(collection)
+        let mut allowed = Vec::new();
+        while !parser.is_end() {
+            let ident = parser.expect_ident(None)?;
+            match Problem::from_warning_name(&ident.to_string()) {
+                Some(problem) => allowed.push(problem),
+                None => {
+                    ident.span().error("Unknown warning").emit();
+                    return Err(());
+                }
+            }
+            if !parser.is_end() {
+                let _ = parser.expect_punctuation(Some(','))?;
+            }
+        }
+        Ok(allowed)
+    }
+
+    /// Parses a query given as MongoDB extended JSON. Objects become the formulation while arrays
+    /// are treated the same way as top-level arrays given directly.
+    fn parse_json(&mut self, text: &str, span: proc_macro::Span) -> Result<ObjectTemplate, ()> {
+        let json = match serde_json::from_str(text) {
+            Ok(json) => json,
+            Err(err) => {
+                span.error(format!("Failed to parse JSON: {}", err)).emit();
+                return Err(());
+            }
+        };
+
+        match self.convert_json(json, &span) {
+            ValueTemplate::Object(object) => Ok(object),
+            ValueTemplate::Array(values) => {
+                self.pipeline = Some(values);
+                Ok(ObjectTemplate::new(span))
+            }
+            _ => {
+                span.error("Expected a JSON object or array").emit();
+                Err(())
+            }
+        }
+    }
+
+    /// Converts a JSON value to a template. Canonical and relaxed extended JSON representations
+    /// of object IDs, numbers and dates are converted to their literal counterparts.
+    fn convert_json(&self, json: serde_json::Value, span: &proc_macro::Span) -> ValueTemplate {
+        match json {
+            serde_json::Value::Null => ValueTemplate::Unquoted("null".to_string()),
+            serde_json::Value::Bool(value) => ValueTemplate::Unquoted(value.to_string()),
+            serde_json::Value::Number(value) => ValueTemplate::Unquoted(value.to_string()),
+            serde_json::Value::String(value) => ValueTemplate::Quoted(value),
+            serde_json::Value::Array(values) => ValueTemplate::Array(
+                values
+                    .into_iter()
+                    .map(|v| SpannedValue::new(self.convert_json(v, span), *span))
+                    .collect(),
+            ),
+            serde_json::Value::Object(map) => {
+                if map.len() == 1 {
+                    let (key, value) = map.iter().next().expect("Map has one entry");
+                    if let Some(template) = self.convert_extended_json(key, value) {
+                        return template;
+                    }
+                }
+
+                let mut object = ObjectTemplate::new(*span);
+                for (key, value) in map {
+                    let attr = SpannedAttribute::from_str(&key, *span);
+                    let value = SpannedValue::new(self.convert_json(value, span), *span);
+                    object.fields.push(FieldTemplate::new(attr, value));
+                }
+                ValueTemplate::Object(object)
+            }
+        }
+    }
+
+    /// Converts extended JSON type wrappers (e.g. `{"$oid": "..."}`) to templates. Returns `None`
+    /// if the key does not correspond to any supported type wrapper.
+    fn convert_extended_json(&self, key: &str, value: &serde_json::Value) -> Option<ValueTemplate> {
+        match (key, value) {
+            ("$oid", serde_json::Value::String(oid)) => Some(ValueTemplate::ObjectId(oid.clone())),
+            ("$numberInt", serde_json::Value::String(number))
+            | ("$numberLong", serde_json::Value::String(number))
+            | ("$numberDouble", serde_json::Value::String(number)) => {
+                Some(ValueTemplate::Unquoted(number.clone()))
+            }
+            ("$date", serde_json::Value::String(date)) => Some(ValueTemplate::Quoted(date.clone())),
+            ("$date", serde_json::Value::Object(map)) => match map.get("$numberLong") {
+                Some(serde_json::Value::String(millis)) if map.len() == 1 => {
+                    let millis = millis.parse().ok()?;
+                    let date =
+                        chrono::TimeZone::timestamp_millis_opt(&chrono::Utc, millis).single()?;
+                    Some(ValueTemplate::Quoted(date.to_rfc3339()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
     }
 
     /// Parse the code from code mode.
@@ -80,12 +257,26 @@ impl Interpreter {
             // TODO: Allow also attributes provided without parentesis (idents separated by a
             // single dot).
 
-            let attribute = self.parse_attribute(&mut parser)?;
-            let value = self.parse_value(&mut parser)?;
-
-            let value = SpannedValue::new(value, parser.span().expect(SPAN));
-            let field = FieldTemplate::new(attribute, value);
-            object.fields.push(field);
+            if parser.is_punctuation('.') {
+                object.spreads.push(self.parse_spread(&mut parser)?);
+            } else if parser.is_literal() {
+                let attribute = SpannedAttribute::from_str(
+                    &parser.expect_string()?,
+                    parser.span().expect(SPAN),
+                );
+                if parser.is_punctuation(':') {
+                    let _ = parser.expect_punctuation(Some(':'))?;
+                    object.fields.push(self.parse_field(&mut parser, attribute)?);
+                } else if object.fields.is_empty() && object.key.is_none() {
+                    object.key = Some(attribute);
+                } else {
+                    attribute.span.error("Expected a colon (':')").emit();
+                    return Err(());
+                }
+            } else {
+                let attribute = self.parse_attribute(&mut parser)?;
+                object.fields.push(self.parse_field(&mut parser, attribute)?);
+            }
 
             if !parser.is_end() {
                 let _ = parser.expect_punctuation(Some(','))?;
@@ -94,68 +285,133 @@ impl Interpreter {
         Ok(object)
     }
 
-    /// Parses an attribute.
-    fn parse_attribute(&self, parser: &mut Parser) -> Result<SpannedAttribute, ()> {
-        if parser.is_literal() {
-            let attr =
-                SpannedAttribute::from_str(&parser.expect_string()?, parser.span().expect(SPAN));
-            let _ = parser.expect_punctuation(Some(':'))?;
-            Ok(attr)
+    /// Parses an array.
+    fn parse_array(&self, mut parser: Parser) -> Result<Vec<SpannedValue>, ()> {
+        let mut values = Vec::new();
+
+        while !parser.is_end() {
+            let value = self.parse_value(&mut parser)?;
+            values.push(SpannedValue::new(value, parser.span().expect(SPAN)));
+
+            if !parser.is_end() {
+                let _ = parser.expect_punctuation(Some(','))?;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Parses a spread (`...(code)`) of a document to be merged into an object.
+    fn parse_spread(&self, parser: &mut Parser) -> Result<SpannedValue, ()> {
+        for _ in 0..3 {
+            let _ = parser.expect_punctuation(Some('.'))?;
+        }
+        let group = parser.expect_group()?;
+        let span = group.span();
+        if group.delimiter() == proc_macro::Delimiter::Parenthesis {
+            Ok(SpannedValue::new(ValueTemplate::Code(self.parse_code(group)?), span))
         } else {
-            let mut attr = SpannedAttribute::new();
-            loop {
-                let part = match parser.expect() {
-                    ExpectedTokenTree::Ident(ident) => {
-                        SpannedPart::from_str(&ident.to_string(), parser.span().expect(SPAN))
-                    }
-                    ExpectedTokenTree::Group(group) => SpannedPart::from_code(
-                        group.stream().to_string(),
-                        parser.span().expect(SPAN),
-                    ),
-                    _ => {
-                        parser
-                            .span()
-                            .expect(SPAN)
-                            .error("Expected an identifier or parenthesis '()'")
-                            .emit();
-                        return Err(());
-                    }
-                };
-                attr.push(part);
+            span.error("Expected '()' block after '...'").emit();
+            Err(())
+        }
+    }
 
-                match parser.expect_punctuation(None)? {
-                    ':' => break,
-                    '.' => {} // continue
-                    _ => {
-                        parser
-                            .span()
-                            .expect(SPAN)
-                            .error("Expected a colon (':') or a dot ('.')")
-                            .emit();
-                        return Err(());
-                    }
+    /// Parses the value of a field with the given attribute.
+    fn parse_field(
+        &self,
+        parser: &mut Parser,
+        attribute: SpannedAttribute,
+    ) -> Result<FieldTemplate, ()> {
+        let value = self.parse_value(parser)?;
+        let value = SpannedValue::new(value, parser.span().expect(SPAN));
+        Ok(FieldTemplate::new(attribute, value))
+    }
+
+    /// Parses an attribute given as identifiers and code parts separated by dots.
+    fn parse_attribute(&self, parser: &mut Parser) -> Result<SpannedAttribute, ()> {
+        let mut attr = SpannedAttribute::new();
+        loop {
+            let part = match parser.expect() {
+                ExpectedTokenTree::Ident(ident) => {
+                    SpannedPart::from_str(&ident.to_string(), parser.span().expect(SPAN))
+                }
+                ExpectedTokenTree::Group(group) => {
+                    SpannedPart::from_code(group.stream().to_string(), parser.span().expect(SPAN))
+                }
+                _ => {
+                    parser
+                        .span()
+                        .expect(SPAN)
+                        .error("Expected an identifier or parenthesis '()'")
+                        .emit();
+                    return Err(());
+                }
+            };
+            attr.push(part);
+
+            match parser.expect_punctuation(None)? {
+                ':' => break,
+                '.' => {} // continue
+                _ => {
+                    parser
+                        .span()
+                        .expect(SPAN)
+                        .error("Expected a colon (':') or a dot ('.')")
+                        .emit();
+                    return Err(());
                 }
             }
-            Ok(attr)
         }
+        Ok(attr)
     }
 
     /// Parse a value.
     fn parse_value(&self, parser: &mut Parser) -> Result<ValueTemplate, ()> {
         match parser.expect() {
             ExpectedTokenTree::String(string) => Ok(ValueTemplate::Quoted(string)),
-            ExpectedTokenTree::Value(string) => Ok(ValueTemplate::Unquoted(string)),
+            ExpectedTokenTree::Value(string) => {
+                Ok(ValueTemplate::Unquoted(normalize_number(string)))
+            }
+            ExpectedTokenTree::Ident(ref ident) if ident == "oid" && parser.is_literal() => {
+                Ok(ValueTemplate::ObjectId(parser.expect_string()?))
+            }
             ExpectedTokenTree::Ident(ident) => Ok(ValueTemplate::Unquoted(ident.to_string())),
+            ExpectedTokenTree::Punct(sign @ '-') | ExpectedTokenTree::Punct(sign @ '+')
+                if parser.is_literal() =>
+            {
+                match parser.expect() {
+                    ExpectedTokenTree::Value(ref string) if is_number(string) => {
+                        let number = normalize_number(string.clone());
+                        if sign == '-' {
+                            Ok(ValueTemplate::Unquoted("-".to_string() + &number))
+                        } else {
+                            Ok(ValueTemplate::Unquoted(number))
+                        }
+                    }
+                    _ => {
+                        parser.span().expect(SPAN).error("Expected a numeric literal").emit();
+                        Err(())
+                    }
+                }
+            }
             ExpectedTokenTree::Group(group) => match group.delimiter() {
                 proc_macro::Delimiter::Parenthesis => {
                     Ok(ValueTemplate::Code(self.parse_code(group)?))
                 }
                 proc_macro::Delimiter::Brace => {
                     let next_parser = Parser::new(group.stream());
-                    Ok(ValueTemplate::Object(self.parse_object(next_parser, group.span().clone())?))
+                    let object = self.parse_object(next_parser, group.span().clone())?;
+                    if let Some(key) = &object.key {
+                        key.span.error("Expected a colon (':')").emit();
+                        return Err(());
+                    }
+                    Ok(ValueTemplate::Object(object))
+                }
+                proc_macro::Delimiter::Bracket => {
+                    let next_parser = Parser::new(group.stream());
+                    Ok(ValueTemplate::Array(self.parse_array(next_parser)?))
                 }
                 _ => {
-                    parser.span().expect(SPAN).error("Expected '()' or '{}' block").emit();
+                    parser.span().expect(SPAN).error("Expected '()', '{}' or '[]' block").emit();
                     Err(())
                 }
             },
@@ -163,10 +419,71 @@ impl Interpreter {
                 parser
                     .span()
                     .expect(SPAN)
-                    .error("Expected a literal value or '()' or '{}' block")
+                    .error("Expected a literal value or '()', '{}' or '[]' block")
                     .emit();
                 Err(())
             }
         }
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+// Helper functions
+
+/// Suffixes allowed in Rust numeric literals.
+const NUMBER_SUFFIXES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64",
+];
+
+/// Returns `true` if the literal is a decimal number.
+fn is_number(string: &str) -> bool {
+    string.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false)
+        && !string.starts_with("0x")
+        && !string.starts_with("0o")
+        && !string.starts_with("0b")
+}
+
+/// Removes underscores and type suffixes from decimal number literals so they can be parsed into
+/// numbers, e.g. `1_000i64` becomes `1000` and `2.5e-3f64` becomes `2.5e-3`. Other literals are
+/// returned unchanged.
+fn normalize_number(string: String) -> String {
+    if !is_number(&string) {
+        return string;
+    }
+
+    let mut number = string.replace("_", "");
+    for suffix in NUMBER_SUFFIXES {
+        if number.ends_with(suffix) {
+            number.truncate(number.len() - suffix.len());
+            break;
+        }
+    }
+    number
+}
+
+/// Checks if the macro input is a collection followed by the `json` keyword and a string literal.
+fn is_json(stream: &proc_macro::TokenStream) -> bool {
+    let tokens: Vec<proc_macro::TokenTree> = stream.clone().into_iter().collect();
+    match tokens.as_slice() {
+        [_, proc_macro::TokenTree::Ident(ident), proc_macro::TokenTree::Literal(_)] => {
+            ident.to_string() == "json"
+        }
+        _ => false,
+    }
+}
+
+/// Returns contents of a raw string literal, e.g. `r#"{}"#` becomes `{}`.
+fn unraw(literal: &str) -> String {
+    let literal = literal.trim_start_matches('r');
+    let hashes = literal.len() - literal.trim_start_matches('#').len();
+    if literal.len() < 2 * (hashes + 1) {
+        return String::new();
+    }
+    literal[hashes + 1..literal.len() - hashes - 1].to_string()
+}
+
+/// Resolves escaped quotes and backslashes in contents of a string literal.
+fn unescape(string: &str) -> String {
+    string.replace("\\\"", "\"").replace("\\\\", "\\")
+}
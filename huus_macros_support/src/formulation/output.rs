@@ -5,7 +5,7 @@
 
 use std::collections::VecDeque;
 
-pub use crate::definition::output::{BuiltInType, Container, Variant};
+pub use crate::definition::output::{BuiltInType, Container, DefinedType, Variant};
 
 /// Represents a part of an attribute. Parts are separated by dots (".").
 #[derive(Clone, Debug)]
@@ -19,8 +19,24 @@ pub enum Part {
     /// Corresponds to an index passed in code mode (inside parenthesis "()").
     Code(String),
 
+    /// Corresponds to a map key passed in code mode (inside parenthesis "()").
+    KeyCode {
+        /// The code.
+        code: String,
+
+        /// Expected type of the key.
+        cast: String,
+    },
+
     /// Corresponds to a dollar ("$") operator.
     Dollar,
+
+    /// Corresponds to an all-positional ("$[]") operator.
+    All,
+
+    /// Corresponds to an identified positional operator (e.g. "$[element]"). Holds the whole
+    /// operator text including the dollar and brackets.
+    Identifier(String),
 }
 
 impl Part {
@@ -28,6 +44,10 @@ impl Part {
     pub fn from_str(string: &str) -> Self {
         if string == "$" {
             Self::Dollar
+        } else if string == "$[]" {
+            Self::All
+        } else if string.starts_with("$[") && string.ends_with("]") && (string.len() > 3) {
+            Self::Identifier(string.to_string())
         } else if let Ok(_) = string.parse::<usize>() {
             Self::Index(string.to_string())
         } else {
@@ -46,7 +66,26 @@ impl Part {
             Self::Key(name) => name.as_str(),
             Self::Index(index) => index.as_str(),
             Self::Code(string) => string.as_str(),
+            Self::KeyCode { code, .. } => code.as_str(),
             Self::Dollar => "$",
+            Self::All => "$[]",
+            Self::Identifier(text) => text.as_str(),
+        }
+    }
+
+    /// Returns `true` if the part is an all-positional or identified positional operator.
+    pub fn is_positional(&self) -> bool {
+        match self {
+            Self::All | Self::Identifier(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the identifier if the part is an identified positional operator.
+    pub fn identifier(&self) -> Option<&str> {
+        match self {
+            Self::Identifier(text) => Some(&text[2..text.len() - 1]),
+            _ => None,
         }
     }
 
@@ -73,6 +112,15 @@ pub struct Attribute {
     pub parts: VecDeque<Part>,
 }
 
+impl Attribute {
+    /// Constructs a new `Attribute` consisting of a single key.
+    pub fn from_key(key: &str) -> Self {
+        let mut parts = VecDeque::new();
+        parts.push_back(Part::Key(key.to_string()));
+        Self { parts }
+    }
+}
+
 /// Represents type of code data. There is not check for validity of data passed in the code mode.
 /// The only check is done by casting the result to the expected type.
 pub struct CodeType {
@@ -119,6 +167,15 @@ pub enum Value {
     /// Corresponds to a date.
     Date(chrono::DateTime<chrono::Utc>),
 
+    /// Corresponds to a date evaluated at runtime relatively to the current time.
+    RelativeDate {
+        /// Offset from the current time (or the start of the current day) in seconds.
+        offset: i64,
+
+        /// If `true` the current time is truncated to the start of the day.
+        today: bool,
+    },
+
     /// Corresponds to a 32-bit integer.
     I32(i32),
 
@@ -128,6 +185,12 @@ pub enum Value {
     /// Corresponds to an object.
     Object(Object),
 
+    /// Corresponds to an array.
+    Array(Vec<Value>),
+
+    /// Corresponds to the `null` literal.
+    Null,
+
     /// Corresponds to the code mode. Code mode it indicated by parentesis "()". There can be any
     /// code provided inside the parentesis.
     Code {
@@ -145,6 +208,184 @@ impl Value {
         let cast = CodeType { variant: Variant::Field(builtin.clone()), container: container };
         Value::Code { code, cast }
     }
+
+    /// Constructs a new GeoJSON point value.
+    pub fn new_geo_point(longitude: f64, latitude: f64) -> Self {
+        let mut object = Object::new();
+        object.fields.push(Field::new(Attribute::from_key("type"), Value::String("Point".into())));
+        let coordinates = Value::Array(vec![Value::F64(longitude), Value::F64(latitude)]);
+        object.fields.push(Field::new(Attribute::from_key("coordinates"), coordinates));
+        Value::Object(object)
+    }
+}
+
+/// Represents a non-negative count used in queries (e.g. limit or skip).
+pub enum Count {
+    /// Corresponds to a literal number.
+    Literal(u32),
+
+    /// Corresponds to the code mode.
+    Code(String),
+}
+
+/// Represents the parts of a find query other than the filter.
+pub struct Find {
+    /// Name of the queried collection.
+    pub collection_name: String,
+
+    /// The sort document.
+    pub sort: Option<Object>,
+
+    /// The projection document. If present, raw documents are returned.
+    pub projection: Option<Object>,
+
+    /// Maximal number of returned documents.
+    pub limit: Option<Count>,
+
+    /// Number of documents to skip.
+    pub skip: Option<Count>,
+}
+
+impl Find {
+    /// Constructs a new `Find` without any options.
+    pub fn new(collection_name: String) -> Self {
+        Self { collection_name, sort: None, projection: None, limit: None, skip: None }
+    }
+}
+
+/// Represents options of a distinct query.
+pub struct Distinct {
+    /// Name of the queried collection.
+    pub collection_name: String,
+
+    /// Path of the field to collect the distinct values of.
+    pub key: String,
+
+    /// Type of the distinct values.
+    pub cast: CodeType,
+}
+
+impl Distinct {
+    /// Constructs a new `Distinct`.
+    pub fn new(collection_name: String, key: String, cast: CodeType) -> Self {
+        Self { collection_name, key, cast }
+    }
+}
+
+/// Represents options of a count query.
+pub struct CountDocuments {
+    /// Name of the queried collection.
+    pub collection_name: String,
+}
+
+impl CountDocuments {
+    /// Constructs a new `CountDocuments`.
+    pub fn new(collection_name: String) -> Self {
+        Self { collection_name }
+    }
+}
+
+/// Represents options of a delete query.
+pub struct Delete {
+    /// Name of the collection to delete from.
+    pub collection_name: String,
+}
+
+impl Delete {
+    /// Constructs a new `Delete`.
+    pub fn new(collection_name: String) -> Self {
+        Self { collection_name }
+    }
+}
+
+/// Represents the modification done by a find and modify query.
+pub enum Modification {
+    /// Corresponds to an update document.
+    Update(Object),
+
+    /// Corresponds to a replacement document.
+    Replace(Object),
+
+    /// Corresponds to deletion of the found document.
+    Delete,
+}
+
+/// Represents options of a find and modify query.
+pub struct FindAndModify {
+    /// Name of the queried collection.
+    pub collection_name: String,
+
+    /// The modification of the found document.
+    pub modification: Modification,
+
+    /// The sort document.
+    pub sort: Option<Object>,
+
+    /// If `true` the document is returned as it was after the modification.
+    pub return_after: bool,
+
+    /// If `true` a new document is inserted if none matches the filter.
+    pub upsert: bool,
+}
+
+impl FindAndModify {
+    /// Constructs a new `FindAndModify` without any options.
+    pub fn new(collection_name: String, modification: Modification) -> Self {
+        Self { collection_name, modification, sort: None, return_after: false, upsert: false }
+    }
+}
+
+/// Represents options of a query joining documents from another collection.
+pub struct Lookup {
+    /// Name of the queried collection.
+    pub collection_name: String,
+
+    /// Name of the joined collection.
+    pub from: String,
+
+    /// Name of the main document of the joined collection.
+    pub foreign: DefinedType,
+
+    /// Path of the field in the queried collection.
+    pub local_field: String,
+
+    /// Path of the field in the joined collection.
+    pub foreign_field: String,
+
+    /// Name of the field holding the joined documents.
+    pub as_field: String,
+}
+
+/// Represents documents to be inserted with a single command.
+pub struct InsertMany {
+    /// Name of the collection to insert to.
+    pub collection_name: String,
+
+    /// The inserted documents.
+    pub documents: Vec<Object>,
+}
+
+impl InsertMany {
+    /// Constructs a new `InsertMany`.
+    pub fn new(collection_name: String, documents: Vec<Object>) -> Self {
+        Self { collection_name, documents }
+    }
+}
+
+/// Represents stages of an aggregation pipeline.
+pub struct Aggregate {
+    /// Name of the aggregated collection.
+    pub collection_name: String,
+
+    /// The pipeline stages.
+    pub stages: Vec<Object>,
+}
+
+impl Aggregate {
+    /// Constructs a new `Aggregate`.
+    pub fn new(collection_name: String, stages: Vec<Object>) -> Self {
+        Self { collection_name, stages }
+    }
 }
 
 /// Represents an object field.
@@ -167,11 +408,30 @@ impl Field {
 pub struct Object {
     /// List of the object's fields.
     pub fields: Vec<Field>,
+
+    /// List of documents merged into the object before its fields.
+    pub spreads: Vec<Spread>,
 }
 
 impl Object {
     /// Constructs a new `Object`.
     pub fn new() -> Self {
-        Self { fields: Vec::new() }
+        Self { fields: Vec::new(), spreads: Vec::new() }
+    }
+}
+
+/// Represents a document passed in code mode to be merged into an object.
+pub struct Spread {
+    /// The code evaluating to a value implementing `IntoDoc`.
+    pub code: String,
+
+    /// Keys allowed to be present in the merged document.
+    pub keys: Vec<String>,
+}
+
+impl Spread {
+    /// Constructs a new `Spread`.
+    pub fn new(code: String, keys: Vec<String>) -> Self {
+        Self { code, keys }
     }
 }
@@ -3,7 +3,10 @@
 
 //! Verification for instructions integrity.
 
-use std::{cell::RefCell, collections::BTreeSet};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use chrono::{DateTime, Utc};
 
@@ -13,6 +16,7 @@ use crate::{
 };
 
 const ENTITY: &str = "Failed to find an entity";
+const VARIANT_KEY: &str = "_huus_variant";
 
 // -------------------------------------------------------------------------------------------------
 
@@ -40,25 +44,120 @@ enum QueryOperator {
     Lte,
     Ne,
     Nin,
+    Not,
+    Mod,
+    Near,
+    GeoWithin,
+    GeoIntersects,
 }
 
 impl QueryOperator {
     /// Check if the given query operator can be applied to the given type on the given container.
     fn matches(&self, builtin: &BuiltInType, container: &Container) -> bool {
-        if container.is_plain() {
+        if self.is_geospatial() {
+            match builtin {
+                BuiltInType::GeoPoint | BuiltInType::Bson => {
+                    container.is_plain() || container.is_array()
+                }
+                _ => false,
+            }
+        } else if *self == Self::Mod {
+            builtin.is_numeric() && (container.is_plain() || container.is_array())
+        } else if container.is_plain() {
             match builtin {
                 BuiltInType::Bson => false,
+                BuiltInType::GeoPoint => {
+                    matches!(self, Self::Eq | Self::Ne | Self::In | Self::Nin | Self::Not)
+                }
                 _ => true,
             }
         } else if container.is_array() {
             match self {
-                Self::In | Self::Nin => true,
+                Self::In | Self::Nin | Self::Not => true,
                 _ => false,
             }
         } else {
             false
         }
     }
+
+    /// Returns `true` if the operator is a geospatial query operator.
+    fn is_geospatial(&self) -> bool {
+        matches!(self, Self::Near | Self::GeoWithin | Self::GeoIntersects)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Represents a top-level logical filter query operator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LogicalOperator {
+    And,
+    Nor,
+    Or,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Represents an aggregation expression operator allowed inside the `$expr` filter operator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExpressionOperator {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Ne,
+    Cmp,
+    And,
+    Or,
+    Not,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Mod,
+}
+
+impl ExpressionOperator {
+    /// Returns `true` if the operator accepts the given number of arguments.
+    fn accepts(&self, num_args: usize) -> bool {
+        match self {
+            Self::And | Self::Or | Self::Add | Self::Multiply => num_args > 0,
+            Self::Not => num_args == 1,
+            _ => num_args == 2,
+        }
+    }
+
+    /// Returns `true` if the operator expects only numerical arguments.
+    fn is_arithmetic(&self) -> bool {
+        matches!(self, Self::Add | Self::Subtract | Self::Multiply | Self::Divide | Self::Mod)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Represents an accumulator operator allowed inside the `$group` aggregation stage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AccumulatorOperator {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    First,
+    Last,
+    Push,
+    AddToSet,
+}
+
+impl AccumulatorOperator {
+    /// Returns `true` if the operator expects only numerical arguments.
+    fn is_arithmetic(&self) -> bool {
+        match self {
+            Self::Sum | Self::Avg => true,
+            _ => false,
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -91,6 +190,14 @@ impl UpdateOperator {
             _ => false,
         }
     }
+
+    /// Returns true if the operator can assign `null` to a member.
+    pub fn accepts_null(&self) -> bool {
+        match self {
+            Self::Set | Self::SetOnInsert => true,
+            _ => false,
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -124,6 +231,14 @@ impl Conversion {
             _ => false,
         }
     }
+
+    /// Returns `true` if `null` can be assigned to or compared with optional members.
+    pub fn accepts_null(&self) -> bool {
+        match self {
+            Self::Update(operator) => operator.accepts_null(),
+            _ => true,
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -141,6 +256,9 @@ struct MemberInfo {
     pub info: VariantInfo,
     pub variant: Variant,
     pub container: Container,
+    pub is_optional: bool,
+    pub map_keys: Vec<(usize, Variant)>,
+    pub in_array: bool,
 }
 
 impl MemberInfo {
@@ -153,7 +271,33 @@ impl MemberInfo {
             Variant::Field(builtin) => VariantInfo::Field(*builtin),
         };
 
-        Ok(Self { info, variant, container })
+        Ok(Self {
+            info,
+            variant,
+            container,
+            is_optional: false,
+            map_keys: Vec::new(),
+            in_array: false,
+        })
+    }
+
+    /// Marks the member as optional or required.
+    pub fn with_optional(mut self, is_optional: bool) -> Self {
+        self.is_optional = is_optional;
+        self
+    }
+
+    /// Adds the map keys found on the way to the member. Each key is described by the number of
+    /// attribute parts following it and the type of the key.
+    pub fn with_map_keys(mut self, map_keys: Vec<(usize, Variant)>) -> Self {
+        self.map_keys.extend(map_keys);
+        self
+    }
+
+    /// Marks the member as reached through elements of an array.
+    pub fn with_in_array(mut self, in_array: bool) -> Self {
+        self.in_array |= in_array;
+        self
     }
 
     /// Returns the type that is expected to be returned by the code passed  in the code mode.
@@ -215,10 +359,12 @@ pub enum Problem {
     /// A literal value was used where only code mode is accepted.
     ExpCodeComp,
 
-    /// A literal value was used where only code mode is accepted.
+    /// A value other than a string was used for an enum where only strings or code mode are
+    /// accepted.
     ExpCodeEnum,
 
-    /// A literal value was used where only code mode is accepted.
+    /// A literal value without the variant key was used for a union where only objects with the
+    /// variant key or code mode are accepted.
     ExpCodeUnion,
 
     /// A literal value was used where only object is accepted..
@@ -262,10 +408,154 @@ pub enum Problem {
 
     /// Failed to parse the rename operator parameters.
     ExpEmptyString,
+
+    /// Failed to parse the modulo operator parameters.
+    ExpModArray,
+
+    /// Failed to parse an aggregation expression.
+    ExpExpression,
+
+    /// Failed to parse the arguments of an aggregation expression.
+    ExpExpressionArgs,
+
+    /// Failed to parse the value as a geographical point.
+    ExpGeoPoint,
+
+    /// Failed to parse the value as a distance.
+    ExpDistance,
+
+    /// Failed to parse the sort order.
+    ExpSort,
+
+    /// Text search was requested on a collection without any text index.
+    TextIndexMissing,
+
+    /// An all-positional or identified positional operator was used outside of an update
+    /// operator.
+    PositionalIncorrect,
+
+    /// An identifier of a positional operator has incorrect format.
+    IdentifierIncorrect,
+
+    /// An array filter referred to an unknown identifier or to more than one identifier.
+    ArrayFilterIncorrect,
+
+    /// An identified positional operator was used without a corresponding array filter.
+    ArrayFilterMissing,
+
+    /// Failed to parse a stage of an update pipeline.
+    ExpPipelineStage,
+
+    /// Failed to parse the value as a projection flag.
+    ExpProjection,
+
+    /// Projection contained both inclusions and exclusions of fields other than `_id`.
+    ProjectionMixed,
+
+    /// Failed to parse the value as a non-negative 32-bit integer.
+    ExpU32,
+
+    /// An unknown part of a find query was used.
+    FindPartUnknown,
+
+    /// A replacement document was used where only update operators are allowed.
+    ReplacementInUpdate,
+
+    /// An update operator was used in a replacement document.
+    OperatorInReplacement,
+
+    /// The `null` value was used for a member that is not optional.
+    NullOnRequired,
+
+    /// The string does not correspond to any of the enum choices.
+    EnumChoiceUnknown,
+
+    /// The variant key does not correspond to any of the union choices.
+    UnionChoiceUnknown,
+
+    /// An object ID literal does not consist of 24 characters.
+    OidLength,
+
+    /// An object ID literal contains characters other than hexadecimal digits.
+    OidNotHex,
+
+    /// A document was spread into an object that does not represent a structure.
+    SpreadIncorrect,
+
+    /// A field path was given without a value outside of a distinct query.
+    FieldPathUnexpected,
+
+    /// A distinct query was given without the field path.
+    FieldPathMissing,
+
+    /// An unknown part of a distinct query was used.
+    DistinctPartUnknown,
+
+    /// An unknown part of a find and modify query was used.
+    FindAndModifyPartUnknown,
+
+    /// A find and modify query did not specify exactly one modification.
+    ModificationIncorrect,
+
+    /// Failed to parse the value as a choice of the returned document.
+    ExpReturnDocument,
+
+    /// An option applicable only to updates and replacements was used together with deletion.
+    OptionWithDelete,
+
+    /// An unknown part of a lookup query was used.
+    LookupPartUnknown,
+
+    /// A required part of a lookup query is missing.
+    LookupPartMissing,
+
+    /// Failed to parse a stage of an aggregation pipeline.
+    ExpAggregationStage,
+
+    /// Failed to parse the value as a field reference.
+    ExpFieldRef,
+
+    /// Failed to parse the parameters of the `$unwind` stage.
+    ExpUnwind,
+
+    /// The `$unwind` stage was applied to a member that is not an array.
+    UnwindNotArray,
+
+    /// The `$group` stage does not specify the `_id` field.
+    GroupIdMissing,
+
+    /// Failed to parse the `_id` field of the `$group` stage.
+    ExpGroupId,
+
+    /// Failed to parse a field of the `$group` stage as an accumulator.
+    ExpAccumulator,
+
+    /// A stage referring to the schema fields was used after the documents were grouped.
+    StageAfterGroup,
+
+    /// A numeric literal does not fit into the declared type.
+    NumericOverflow,
+
+    /// The `$ne` operator was applied to a field of documents stored in an array. Reported as a
+    /// warning.
+    NeOnArray,
+
+    /// The `$rename` operator targets a field present in the schema. Reported as a warning.
+    RenameOverwrites,
+
+    /// The `$rename` operator targets a field of the schema with a different type.
+    RenameIncompatible,
+
+    /// The `$rename` operator targets a field not present in the schema.
+    RenameUnknown,
+
+    /// The `$setOnInsert` operator was used without enabling upserts. Reported as a warning.
+    SetOnInsertWithoutUpsert,
 }
 
 impl Problem {
-    fn as_str(&self) -> &'static str {
+    /// Returns the message reported to the user together with the problem.
+    pub fn as_str(&self) -> &'static str {
         match self {
             Self::MacroEmpty => "The macro seems to be empty",
             Self::MainDocNotDefined => "Main document for this collection is not defined",
@@ -281,8 +571,8 @@ impl Problem {
             Self::OperatorIncorrect => "This operator cannot be used with the declared type",
             Self::ExpCode => "This value is supported only in `code` mode",
             Self::ExpCodeComp => "Composed data are supported only in `code` mode",
-            Self::ExpCodeEnum => "Enums are supported only in `code` mode",
-            Self::ExpCodeUnion => "Unions are supported only in `code` mode",
+            Self::ExpCodeEnum => "Enums are supported only as strings or in `code` mode",
+            Self::ExpCodeUnion => "Unions require `_huus_variant` key or `code` mode",
             Self::ExpObject => "Expected an object",
             Self::ExpKey => "Expected a literal key",
             Self::ExpPlain => "Expected a type without container",
@@ -297,15 +587,142 @@ impl Problem {
             Self::ExpBson => "BSON objects are supported only in `code` mode",
             Self::ExpDateObj => r#"Expected `true` or object `{"$type":"timestamp"|"datetime"}`"#,
             Self::ExpEmptyString => "Expected an empty string",
+            Self::ExpModArray => "Expected an array `[divisor, remainder]` with non-zero divisor",
+            Self::ExpExpression => "Expected an object with a single expression operator",
+            Self::ExpExpressionArgs => "Expected an array of expression operator arguments",
+            Self::ExpGeoPoint => "Expected a point as `[longitude, latitude]`",
+            Self::ExpDistance => "Expected a non-negative distance",
+            Self::ExpSort => "Expected `1`, `-1` or an object mapping fields to them",
+            Self::TextIndexMissing => "Text search requires at least one indexed field",
+            Self::PositionalIncorrect => "Positional operators are allowed only in updates",
+            Self::IdentifierIncorrect => {
+                "Identifiers must start with a lowercase letter and contain only letters and digits"
+            }
+            Self::ArrayFilterIncorrect => {
+                "Array filter must refer to exactly one identifier used in the update"
+            }
+            Self::ArrayFilterMissing => "No array filter was given for this identifier",
+            Self::ExpPipelineStage => "Expected an object with a single `$set` or `$unset` stage",
+            Self::ExpProjection => "Expected `1`, `0`, `true` or `false`",
+            Self::ProjectionMixed => "Projection cannot both include and exclude fields",
+            Self::ExpU32 => "Expected a non-negative 32-bit integer",
+            Self::FindPartUnknown => "Expected `filter`, `sort`, `projection`, `limit` or `skip`",
+            Self::ReplacementInUpdate => "Expected update operators, use `replace!` instead",
+            Self::OperatorInReplacement => "Operators are not allowed here, use `update!` instead",
+            Self::NullOnRequired => "Only optional members can be null",
+            Self::EnumChoiceUnknown => "The value is not one of the enum choices",
+            Self::UnionChoiceUnknown => "The variant is not one of the union choices",
+            Self::OidLength => "Object ID must consist of exactly 24 hexadecimal digits",
+            Self::OidNotHex => "Object ID may contain only hexadecimal digits",
+            Self::SpreadIncorrect => "Spreading documents is not supported here",
+            Self::FieldPathUnexpected => "Expected a colon (':') and a value",
+            Self::FieldPathMissing => "Expected a path of the field to collect values of",
+            Self::DistinctPartUnknown => "Expected `filter`",
+            Self::FindAndModifyPartUnknown => {
+                "Expected `filter`, `sort`, `returnDocument`, `upsert` or a modification"
+            }
+            Self::ModificationIncorrect => {
+                "Expected exactly one of `update`, `replace` or `delete`"
+            }
+            Self::ExpReturnDocument => r#"Expected "before" or "after""#,
+            Self::OptionWithDelete => "This option cannot be used together with `delete`",
+            Self::LookupPartUnknown => {
+                "Expected `filter`, `from`, `localField`, `foreignField` or `as`"
+            }
+            Self::LookupPartMissing => "Expected `from`, `localField`, `foreignField` and `as`",
+            Self::ExpAggregationStage => "Expected an object with a single aggregation stage",
+            Self::ExpFieldRef => "Expected a field reference (a string starting with `$`)",
+            Self::ExpUnwind => "Expected a field reference or an object with the `path` field",
+            Self::UnwindNotArray => "Only arrays can be unwound",
+            Self::GroupIdMissing => "The `$group` stage requires the `_id` field",
+            Self::ExpGroupId => "Expected `null`, a field reference, an expression or an object",
+            Self::ExpAccumulator => "Expected an object with a single accumulator operator",
+            Self::StageAfterGroup => {
+                "Only `$sort`, `$limit` and `$skip` stages are supported after `$group`"
+            }
+            Self::NumericOverflow => "The number does not fit into the declared type",
+            Self::NeOnArray => {
+                "This matches only documents where no array element is equal, consider `$elemMatch`"
+            }
+            Self::RenameOverwrites => {
+                "The target field is present in the schema and will be overwritten"
+            }
+            Self::RenameIncompatible => "The target field has a different type in the schema",
+            Self::RenameUnknown => "The target field is not present in the schema",
+            Self::SetOnInsertWithoutUpsert => {
+                "`$setOnInsert` has no effect unless `upsert` is enabled"
+            }
+        }
+    }
+
+    /// Returns the name used to suppress the problem with `allow(...)` if the problem is a warning.
+    pub fn warning_name(&self) -> Option<&'static str> {
+        match self {
+            Self::NeOnArray => Some("ne_on_array"),
+            Self::RenameOverwrites => Some("rename_overwrites"),
+            Self::SetOnInsertWithoutUpsert => Some("set_on_insert_without_upsert"),
+            _ => None,
+        }
+    }
+
+    /// Returns the warning with the given name.
+    pub fn from_warning_name(name: &str) -> Option<Self> {
+        match name {
+            "ne_on_array" => Some(Self::NeOnArray),
+            "rename_overwrites" => Some(Self::RenameOverwrites),
+            "set_on_insert_without_upsert" => Some(Self::SetOnInsertWithoutUpsert),
+            _ => None,
         }
     }
 }
 
+/// A problem as returned by the testing macros: the problem itself, line and column where the
+/// offending code starts and the path of the field that was being validated.
+pub type LocatedProblem = (Problem, usize, usize, String);
+
+impl PartialEq<Problem> for LocatedProblem {
+    fn eq(&self, other: &Problem) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Describes a single problem together with the place where it was found.
+#[derive(Clone)]
+pub struct Report {
+    /// The found problem.
+    pub problem: Problem,
+
+    /// Span of the code the problem refers to.
+    pub span: proc_macro::Span,
+
+    /// Path of the innermost field being validated when the problem was found. Empty if the
+    /// problem does not concern any field.
+    pub path: String,
+}
+
+impl Report {
+    /// Constructs a new `Report`.
+    pub fn new(problem: Problem, span: proc_macro::Span, path: String) -> Self {
+        Self { problem, span, path }
+    }
+
+    /// Generates a code representing the `Report` as a `LocatedProblem`.
+    pub fn format(&self) -> String {
+        format!(
+            "(huus_macros_support::Problem::{:?}, {}usize, {}usize, {:?}.to_string())",
+            self.problem,
+            self.span.line(),
+            self.span.column(),
+            self.path
+        )
+    }
+}
+
 /// Stores all the problems found.
 #[derive(Clone)]
 pub struct Verdict {
     /// List of the found problems.
-    pub problems: Vec<Problem>,
+    pub problems: Vec<Report>,
 }
 
 impl Verdict {
@@ -314,15 +731,9 @@ impl Verdict {
         Self { problems: Vec::new() }
     }
 
-    /// Generates a code representing the `Verdict` as a vector of `Problem`s.
+    /// Generates a code representing the `Verdict` as a vector of `LocatedProblem`s.
     pub fn format(&self) -> String {
-        let contents = self
-            .problems
-            .iter()
-            .map(|p| "huus_macros_support::Problem::".to_string() + &format!("{:?}", p))
-            .collect::<Vec<String>>()
-            .join(", ");
-
+        let contents = self.problems.iter().map(|r| r.format()).collect::<Vec<String>>().join(", ");
         "vec![".to_string() + &contents + "]"
     }
 }
@@ -333,8 +744,15 @@ impl Verdict {
 pub struct Validator<'a> {
     collection: SpannedCollection,
     object: ObjectTemplate,
+    pipeline: Option<Vec<SpannedValue>>,
+    strict: bool,
+    upsert: bool,
     schema: &'a Schema,
     verdict: RefCell<Verdict>,
+    identifiers: RefCell<BTreeMap<String, (Variant, proc_macro::Span)>>,
+    hint: RefCell<Option<String>>,
+    path: RefCell<Vec<String>>,
+    allowed: Vec<Problem>,
     testing: bool,
 }
 
@@ -346,101 +764,673 @@ impl<'a> Validator<'a> {
         schema: &'a Schema,
         testing: bool,
     ) -> Self {
-        Self { collection, object, schema, verdict: RefCell::new(Verdict::new()), testing }
+        Self {
+            collection,
+            object,
+            pipeline: None,
+            strict: false,
+            upsert: false,
+            schema,
+            verdict: RefCell::new(Verdict::new()),
+            identifiers: RefCell::new(BTreeMap::new()),
+            hint: RefCell::new(None),
+            path: RefCell::new(Vec::new()),
+            allowed: Vec::new(),
+            testing,
+        }
+    }
+
+    /// Sets the top-level array. It holds stages of an update pipeline in update formulations or
+    /// documents in bulk data formulations and is not accepted elsewhere.
+    pub fn with_pipeline(mut self, pipeline: Option<Vec<SpannedValue>>) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// Sets the warnings which should not be reported.
+    pub fn with_allowed(mut self, allowed: Vec<Problem>) -> Self {
+        self.allowed = allowed;
+        self
+    }
+
+    /// Sets the strict mode. In strict mode update formulations must not be replacements.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Declares that update formulations are meant for upserts, so `$setOnInsert` is expected.
+    pub fn with_upsert(mut self, upsert: bool) -> Self {
+        self.upsert = upsert;
+        self
     }
 
     /// Validates if the object is a correct data formulation, i.e. can be used in `insert`
     /// operation for the specified collection.
     pub fn verify_data(self) -> Result<Generator, Verdict> {
         let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
         let object = self.convert_object(&struct_spec, self.object.clone(), Conversion::Data);
         self.make_generator(struct_spec.struct_name.clone(), object)
     }
 
+    /// Validates if the array contains correct data formulations, i.e. can be used in bulk
+    /// `insert` operation for the specified collection.
+    pub fn verify_data_many(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        let elements = match self.pipeline.clone() {
+            Some(elements) => elements,
+            None => {
+                self.error(&proc_macro::Span::call_site(), Problem::ExpArray);
+                return Err(self.verdict.borrow().clone());
+            }
+        };
+
+        let mut documents = Vec::with_capacity(elements.len());
+        for element in elements {
+            match element.value {
+                ValueTemplate::Object(template) => {
+                    documents.push(self.convert_object(&struct_spec, template, Conversion::Data));
+                }
+                _ => self.error(&element.span, Problem::ExpObject),
+            }
+        }
+
+        if documents.is_empty() {
+            self.error(&proc_macro::Span::call_site(), Problem::MacroEmpty);
+        }
+
+        if self.verdict.borrow().problems.len() == 0 {
+            let insert_many = InsertMany::new(self.collection.name.clone(), documents);
+            let name = struct_spec.struct_name.clone();
+            Ok(Generator::new(name, Object::new()).with_insert_many(insert_many))
+        } else {
+            Err(self.verdict.borrow().clone())
+        }
+    }
+
     /// Validates if the object is a correct filter formulation, i.e. can be used as a filter in
     /// `find` or `update` operation for the specified collection.
     pub fn verify_filter(self) -> Result<Generator, Verdict> {
         let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
-        let object = self.convert_object(&struct_spec, self.object.clone(), Conversion::Filter);
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+        let object = self.convert_filter_root(&struct_spec, self.object.clone());
         self.make_generator(struct_spec.struct_name.clone(), object)
     }
 
-    /// Validates if the object is a correct update formulation, i.e. can be used as an update in
-    /// `update` operation for the specified collection.
-    pub fn verify_update(self) -> Result<Generator, Verdict> {
+    /// Validates if the object is a correct count formulation, i.e. can be used as a filter
+    /// selecting the counted documents of the specified collection. An empty filter counts all the
+    /// documents.
+    pub fn verify_count(self) -> Result<Generator, Verdict> {
         let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
-        let object = match self.verify_update_type()? {
-            UpdateType::Update => self.convert_update_object(&struct_spec, self.object.clone()),
-            UpdateType::Replacement => {
-                self.convert_object(&struct_spec, self.object.clone(), Conversion::Replacement)
-            }
-        };
-        self.make_generator(struct_spec.struct_name.clone(), object)
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+        let object = self.convert_filter_root(&struct_spec, self.object.clone());
+
+        if self.verdict.borrow().problems.len() == 0 {
+            let count = CountDocuments::new(self.collection.name.clone());
+            Ok(Generator::new(struct_spec.struct_name.clone(), object).with_count(count))
+        } else {
+            Err(self.verdict.borrow().clone())
+        }
     }
-}
 
-// -------------------------------------------------------------------------------------------------
-// Helper search methods
+    /// Validates if the object is a correct delete formulation, i.e. can be used as a filter
+    /// selecting documents to be deleted from the specified collection. Empty filters are not
+    /// accepted to prevent accidental deletion of all the documents.
+    pub fn verify_delete(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+        let object = self.convert_filter_root(&struct_spec, self.object.clone());
+        let generator = self.make_generator(struct_spec.struct_name.clone(), object)?;
+        Ok(generator.with_delete(Delete::new(self.collection.name.clone())))
+    }
 
-impl<'a> Validator<'a> {
-    /// Searches for a structure representing the main document for the given collection.
-    fn find_struct_for_collection(&self, collection_name: &str) -> Result<&Struct, Verdict> {
-        match self.schema.find_entity_for_collection(&collection_name) {
-            Some(struct_spec) => Ok(struct_spec),
-            None => {
-                self.error(&self.collection.span, Problem::MainDocNotDefined);
-                Err(self.verdict.borrow().clone())
-            }
-        }
+    /// Validates if the object is a correct projection formulation, i.e. can be used to select
+    /// fields returned by `find` operation for the specified collection.
+    pub fn verify_projection(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+        let object = self.convert_projection(&struct_spec, self.object.clone());
+        self.make_generator(struct_spec.struct_name.clone(), object)
     }
 
-    /// Searches for a member given the attribute. The attribute may be composed so the search is
-    /// done trough many objects.
-    fn find_member(
-        &self,
-        struct_spec: &'a Struct,
-        mut attribute: SpannedAttribute,
-    ) -> Result<MemberInfo, Problem> {
-        let part = attribute.pop().expect("No more attribute parts to check");
-        if let Part::Key(key) = part.part {
-            for member in struct_spec.members.iter() {
-                if member.db_name == key {
-                    // Ignore index parts in arrays
-                    let mut container = member.container.clone();
-                    if member.container.is_array() {
-                        if attribute.next().map(|p| !p.is_key()).unwrap_or(false) {
-                            let _ = attribute.pop();
-                            container = Container::Plain;
-                        }
-                    }
+    /// Validates if the object is a correct sort formulation, i.e. can be used to order results of
+    /// `find` operation for the specified collection.
+    pub fn verify_sort(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+        let object = self.convert_sort(&struct_spec, self.object.clone());
+        self.make_generator(struct_spec.struct_name.clone(), object)
+    }
 
-                    let info = MemberInfo::new(&self.schema, member.variant.clone(), container)?;
-                    return if attribute.len() == 0 {
-                        // No more attribute parts to check - return the current member
-                        Ok(info)
-                    } else {
-                        match &info.info {
-                            VariantInfo::Entity(entity) => match entity {
-                                Entity::Struct(struct_spec) => {
-                                    self.find_member(struct_spec, attribute)
-                                }
-                                Entity::Union(union_spec) => {
-                                    self.peek_member(union_spec, attribute)
-                                }
-                                Entity::Enum(_) => Err(Problem::FieldOnEnum),
-                            },
-                            VariantInfo::Field(_) => Err(Problem::FieldOnPlain),
-                        }
-                    };
+    /// Validates if the object is a correct find formulation, i.e. contains a valid filter and
+    /// options of `find` operation for the specified collection.
+    pub fn verify_find(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+
+        self.verify_no_key(&self.object);
+        self.verify_no_spreads(&self.object);
+        let mut filter = Object::new();
+        let mut find = Find::new(self.collection.name.clone());
+        for field in self.object.fields.clone() {
+            let part = field.attr.to_composed();
+            let result = match (part.as_ref(), field.value.value) {
+                ("filter", ValueTemplate::Object(template)) => {
+                    filter = self.convert_filter_root(&struct_spec, template);
+                    Ok(())
+                }
+                ("sort", ValueTemplate::Object(template)) => {
+                    let sort = self.convert_sort(&struct_spec, template);
+                    find.sort = Some(sort);
+                    Ok(())
+                }
+                ("projection", ValueTemplate::Object(template)) => {
+                    let projection = self.convert_projection(&struct_spec, template);
+                    find.projection = Some(projection);
+                    Ok(())
+                }
+                ("filter", _) | ("sort", _) | ("projection", _) => Err(Problem::ExpObject),
+                ("limit", template) => self.convert_count(template).map(|c| find.limit = Some(c)),
+                ("skip", template) => self.convert_count(template).map(|c| find.skip = Some(c)),
+                _ => {
+                    self.error(&field.attr.span, Problem::FindPartUnknown);
+                    continue;
                 }
+            };
+
+            if let Err(problem) = result {
+                self.error(&field.value.span, problem);
             }
-            Err(Problem::FieldNotFound)
+        }
+
+        if self.object.fields.is_empty() {
+            self.error(&proc_macro::Span::call_site(), Problem::MacroEmpty);
+        }
+
+        if self.verdict.borrow().problems.len() == 0 {
+            Ok(Generator::new(struct_spec.struct_name.clone(), filter).with_find(find))
         } else {
-            Err(Problem::ExpKey)
+            Err(self.verdict.borrow().clone())
         }
     }
 
-    /// Searches for a member inside a union. If the members is ambiguous the search is considered
+    /// Validates if the object is a correct find and modify formulation, i.e. contains a valid
+    /// filter, exactly one modification (an update, a replacement or deletion) and options of
+    /// `findAndModify` operation for the specified collection.
+    pub fn verify_find_and_modify(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+
+        self.verify_no_spreads(&self.object);
+        let mut filter = Object::new();
+        let mut modifications = Vec::new();
+        let mut options = Vec::new();
+        let mut sort = None;
+        let mut return_after = false;
+        let mut upsert = false;
+        let mut set_on_insert = None;
+        for field in self.object.fields.clone() {
+            let part = field.attr.to_composed();
+            let span = field.attr.span.clone();
+            let result = match (part.as_ref(), field.value.value) {
+                ("filter", ValueTemplate::Object(template)) => {
+                    filter = self.convert_filter_root(&struct_spec, template);
+                    Ok(())
+                }
+                ("sort", ValueTemplate::Object(template)) => {
+                    sort = Some(self.convert_sort(&struct_spec, template));
+                    Ok(())
+                }
+                ("update", ValueTemplate::Object(template)) => {
+                    set_on_insert = template
+                        .fields
+                        .iter()
+                        .find(|field| field.attr.to_composed() == "$setOnInsert")
+                        .map(|field| field.attr.span.clone());
+                    let update = self.convert_update_object(&struct_spec, template);
+                    let _ = self.convert_array_filters(Vec::new());
+                    modifications.push(Modification::Update(update));
+                    Ok(())
+                }
+                ("replace", ValueTemplate::Object(template)) => {
+                    for field in template.fields.iter().filter(|field| field.attr.is_operator()) {
+                        self.error(&field.attr.span, Problem::OperatorInReplacement);
+                    }
+                    let mut template = template;
+                    template.fields.retain(|field| !field.attr.is_operator());
+                    let replacement =
+                        self.convert_object(&struct_spec, template, Conversion::Replacement);
+                    modifications.push(Modification::Replace(replacement));
+                    Ok(())
+                }
+                ("filter", _) | ("sort", _) | ("update", _) | ("replace", _) => {
+                    Err(Problem::ExpObject)
+                }
+                ("delete", template) => self.convert_flag(template).map(|delete| {
+                    if delete {
+                        modifications.push(Modification::Delete);
+                    }
+                }),
+                ("returnDocument", ValueTemplate::Quoted(string)) => match string.as_ref() {
+                    "before" | "after" => {
+                        return_after = string == "after";
+                        options.push(span);
+                        Ok(())
+                    }
+                    _ => Err(Problem::ExpReturnDocument),
+                },
+                ("returnDocument", _) => Err(Problem::ExpReturnDocument),
+                ("upsert", template) => self.convert_flag(template).map(|flag| {
+                    upsert = flag;
+                    options.push(span);
+                }),
+                _ => {
+                    self.error(&span, Problem::FindAndModifyPartUnknown);
+                    continue;
+                }
+            };
+
+            if let Err(problem) = result {
+                self.error(&field.value.span, problem);
+            }
+        }
+
+        let modification = if modifications.len() == 1 {
+            modifications.pop()
+        } else {
+            self.error(&proc_macro::Span::call_site(), Problem::ModificationIncorrect);
+            None
+        };
+
+        if let Some(Modification::Delete) = modification {
+            for span in options.iter() {
+                self.error(span, Problem::OptionWithDelete);
+            }
+        }
+
+        if let (Some(span), false) = (set_on_insert, upsert) {
+            self.warning(&span, Problem::SetOnInsertWithoutUpsert);
+        }
+
+        match modification {
+            Some(modification) if self.verdict.borrow().problems.len() == 0 => {
+                let mut find_and_modify =
+                    FindAndModify::new(self.collection.name.clone(), modification);
+                find_and_modify.sort = sort;
+                find_and_modify.return_after = return_after;
+                find_and_modify.upsert = upsert;
+                let name = struct_spec.struct_name.clone();
+                Ok(Generator::new(name, filter).with_find_and_modify(find_and_modify))
+            }
+            _ => Err(self.verdict.borrow().clone()),
+        }
+    }
+
+    /// Validates if the object is a correct lookup formulation, i.e. joins the specified collection
+    /// with another collection defined in the schema basing on fields existing in both of them.
+    pub fn verify_lookup(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+
+        self.verify_no_spreads(&self.object);
+        let mut filter = Object::new();
+        let mut from = None;
+        let mut local_field = None;
+        let mut foreign_field = None;
+        let mut as_field = None;
+        for field in self.object.fields.clone() {
+            let part = field.attr.to_composed();
+            let span = field.value.span.clone();
+            let result = match (part.as_ref(), field.value.value) {
+                ("filter", ValueTemplate::Object(template)) => {
+                    filter = self.convert_filter_root(&struct_spec, template);
+                    Ok(())
+                }
+                ("filter", _) => Err(Problem::ExpObject),
+                ("from", ValueTemplate::Quoted(name)) => {
+                    match self.schema.find_entity_for_collection(&name) {
+                        Some(foreign_spec) => {
+                            from = Some((name, foreign_spec));
+                            Ok(())
+                        }
+                        None => Err(Problem::MainDocNotDefined),
+                    }
+                }
+                ("localField", ValueTemplate::Quoted(path)) => {
+                    local_field = Some(SpannedAttribute::from_str(&path, span));
+                    Ok(())
+                }
+                ("foreignField", ValueTemplate::Quoted(path)) => {
+                    foreign_field = Some(SpannedAttribute::from_str(&path, span));
+                    Ok(())
+                }
+                ("as", ValueTemplate::Quoted(name)) => {
+                    if name.contains('.') {
+                        Err(Problem::AttrWithDots)
+                    } else {
+                        as_field = Some(name);
+                        Ok(())
+                    }
+                }
+                ("from", _) | ("localField", _) | ("foreignField", _) | ("as", _) => {
+                    Err(Problem::ExpString)
+                }
+                _ => {
+                    self.error(&field.attr.span, Problem::LookupPartUnknown);
+                    continue;
+                }
+            };
+
+            if let Err(problem) = result {
+                self.error(&field.value.span, problem);
+            }
+        }
+
+        if let Some(attr) = &local_field {
+            if let Err(problem) = self.find_member(&struct_spec, attr.clone()) {
+                self.error(&attr.span, problem);
+            }
+        }
+        if let (Some((_, foreign_spec)), Some(attr)) = (&from, &foreign_field) {
+            if let Err(problem) = self.find_member(foreign_spec, attr.clone()) {
+                self.error(&attr.span, problem);
+            }
+        }
+
+        match (from, local_field, foreign_field, as_field) {
+            (Some((from, spec)), Some(local), Some(foreign), Some(as_field)) => {
+                if self.verdict.borrow().problems.len() == 0 {
+                    let lookup = Lookup {
+                        collection_name: self.collection.name.clone(),
+                        from,
+                        foreign: spec.struct_name.clone(),
+                        local_field: local.to_composed(),
+                        foreign_field: foreign.to_composed(),
+                        as_field,
+                    };
+                    let name = struct_spec.struct_name.clone();
+                    Ok(Generator::new(name, filter).with_lookup(lookup))
+                } else {
+                    Err(self.verdict.borrow().clone())
+                }
+            }
+            _ => {
+                self.error(&proc_macro::Span::call_site(), Problem::LookupPartMissing);
+                Err(self.verdict.borrow().clone())
+            }
+        }
+    }
+
+    /// Validates if the object is a correct distinct formulation, i.e. contains a path of an
+    /// existing field and optionally a valid filter of `distinct` operation for the specified
+    /// collection.
+    pub fn verify_distinct(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+
+        self.verify_no_spreads(&self.object);
+        let mut filter = Object::new();
+        for field in self.object.fields.clone() {
+            match (field.attr.to_composed().as_ref(), field.value.value) {
+                ("filter", ValueTemplate::Object(template)) => {
+                    filter = self.convert_filter_root(&struct_spec, template);
+                }
+                ("filter", _) => self.error(&field.value.span, Problem::ExpObject),
+                _ => self.error(&field.attr.span, Problem::DistinctPartUnknown),
+            }
+        }
+
+        let cast = match self.object.key.clone() {
+            Some(key) => match self.convert_distinct_key(&struct_spec, key.clone()) {
+                Ok(cast) => Some(cast),
+                Err(problem) => {
+                    self.error(&key.span, problem);
+                    None
+                }
+            },
+            None => {
+                self.error(&proc_macro::Span::call_site(), Problem::FieldPathMissing);
+                None
+            }
+        };
+
+        match cast {
+            Some(cast) if self.verdict.borrow().problems.len() == 0 => {
+                let key = self.object.key.as_ref().expect("Key should be set").to_composed();
+                let distinct = Distinct::new(self.collection.name.clone(), key, cast);
+                Ok(Generator::new(struct_spec.struct_name.clone(), filter).with_distinct(distinct))
+            }
+            _ => Err(self.verdict.borrow().clone()),
+        }
+    }
+
+    /// Validates if the array contains correct stages of an aggregation pipeline for the specified
+    /// collection. Stages following `$group` may refer only to the fields it outputs.
+    pub fn verify_aggregate(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        let stages = match self.pipeline.clone() {
+            Some(stages) => stages,
+            None => {
+                self.error(&proc_macro::Span::call_site(), Problem::ExpArray);
+                return Err(self.verdict.borrow().clone());
+            }
+        };
+
+        if stages.is_empty() {
+            self.error(&proc_macro::Span::call_site(), Problem::MacroEmpty);
+        }
+
+        let stages = self.convert_aggregation_pipeline(&struct_spec, stages);
+        if self.verdict.borrow().problems.len() == 0 {
+            let aggregate = Aggregate::new(self.collection.name.clone(), stages);
+            let name = struct_spec.struct_name.clone();
+            Ok(Generator::new(name, Object::new()).with_aggregate(aggregate))
+        } else {
+            Err(self.verdict.borrow().clone())
+        }
+    }
+
+    /// Validates if the object is a correct update formulation, i.e. can be used as an update in
+    /// `update` operation for the specified collection.
+    pub fn verify_update(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        if let Some(stages) = self.pipeline.clone() {
+            let pipeline = self.convert_pipeline(&struct_spec, stages);
+            return self.make_pipeline_generator(struct_spec.struct_name.clone(), pipeline);
+        }
+
+        self.verify_no_key(&self.object);
+        let (template, array_filters) = self.split_array_filters(self.object.clone());
+        let object = match self.verify_update_type(&template)? {
+            UpdateType::Update => {
+                let set_on_insert =
+                    template.fields.iter().find(|field| field.attr.to_composed() == "$setOnInsert");
+                if let (Some(field), false) = (set_on_insert, self.upsert) {
+                    self.warning(&field.attr.span, Problem::SetOnInsertWithoutUpsert);
+                }
+                self.convert_update_object(&struct_spec, template)
+            }
+            UpdateType::Replacement => {
+                if self.strict {
+                    self.error(&template.span, Problem::ReplacementInUpdate);
+                }
+                self.convert_object(&struct_spec, template, Conversion::Replacement)
+            }
+        };
+        let array_filters = self.convert_array_filters(array_filters);
+        let generator = self.make_generator(struct_spec.struct_name.clone(), object)?;
+        Ok(generator.with_array_filters(array_filters))
+    }
+
+    /// Validates if the object is a correct replacement formulation, i.e. can be used to replace
+    /// whole documents in `update` operation for the specified collection. Contrary to
+    /// `verify_update` operators are never accepted.
+    pub fn verify_replacement(self) -> Result<Generator, Verdict> {
+        let struct_spec = self.find_struct_for_collection(&self.collection.name)?;
+        self.verify_no_pipeline()?;
+        self.verify_no_key(&self.object);
+
+        let mut template = self.object.clone();
+        if template.fields.is_empty() && template.spreads.is_empty() {
+            self.error(&proc_macro::Span::call_site(), Problem::QueryEmpty);
+            return Err(self.verdict.borrow().clone());
+        }
+        for field in template.fields.iter().filter(|field| field.attr.is_operator()) {
+            self.error(&field.attr.span, Problem::OperatorInReplacement);
+        }
+        template.fields.retain(|field| !field.attr.is_operator());
+
+        let object = self.convert_object(&struct_spec, template, Conversion::Replacement);
+        self.make_generator(struct_spec.struct_name.clone(), object)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Helper search methods
+
+impl<'a> Validator<'a> {
+    /// Searches for a structure representing the main document for the given collection.
+    fn find_struct_for_collection(&self, collection_name: &str) -> Result<&Struct, Verdict> {
+        match self.schema.find_entity_for_collection(&collection_name) {
+            Some(struct_spec) => Ok(struct_spec),
+            None => {
+                let names = self.schema.collection_names();
+                let closest = names
+                    .iter()
+                    .map(|name| (edit_distance(collection_name, name), name))
+                    .filter(|(distance, name)| *distance <= name.len() / 3 + 1)
+                    .min_by_key(|(distance, _)| *distance);
+                let known = format!("Known collections are: \"{}\"", names.join("\", \""));
+                match closest {
+                    Some((_, name)) => self.hint(format!("Did you mean \"{}\"? {}", name, known)),
+                    None => self.hint(known),
+                }
+                self.error(&self.collection.span, Problem::MainDocNotDefined);
+                Err(self.verdict.borrow().clone())
+            }
+        }
+    }
+
+    /// Searches for a member given the attribute. The attribute may be composed so the search is
+    /// done trough many objects.
+    fn find_member(
+        &self,
+        struct_spec: &'a Struct,
+        mut attribute: SpannedAttribute,
+    ) -> Result<MemberInfo, Problem> {
+        let part = attribute.pop().expect("No more attribute parts to check");
+        if let Part::Key(key) = part.part {
+            for member in struct_spec.members.iter() {
+                if member.db_name == key {
+                    // Ignore index parts in arrays
+                    let mut container = member.container.clone();
+                    let mut is_optional = member.is_optional;
+                    let mut map_keys = Vec::new();
+                    let mut in_array = false;
+                    match &member.container {
+                        Container::Array => {
+                            if attribute.next().map(|p| !p.is_key()).unwrap_or(false) {
+                                let part = attribute.pop().expect("No more attribute parts");
+                                if let Some(identifier) = part.part.identifier() {
+                                    self.bind_identifier(identifier, &member.variant, &part.span);
+                                }
+                                container = Container::Plain;
+                                is_optional = false;
+                            } else {
+                                in_array = attribute.len() > 0;
+                            }
+                        }
+                        Container::BTreeMap(key) | Container::HashMap(key) => {
+                            // Literal or code parts following a map select its values
+                            if let Some(part) = attribute.pop() {
+                                self.verify_map_key(key, &part.part)?;
+                                map_keys.push((attribute.len(), key.clone()));
+                                container = Container::Plain;
+                                is_optional = false;
+                            }
+                        }
+                        Container::Plain => {}
+                    }
+
+                    let info = MemberInfo::new(&self.schema, member.variant.clone(), container)?
+                        .with_optional(is_optional)
+                        .with_map_keys(map_keys.clone());
+                    return if attribute.len() == 0 {
+                        // No more attribute parts to check - return the current member
+                        Ok(info)
+                    } else {
+                        let info = match &info.info {
+                            VariantInfo::Entity(entity) => match entity {
+                                Entity::Struct(struct_spec) => {
+                                    self.find_member(struct_spec, attribute)
+                                }
+                                Entity::Union(union_spec) => {
+                                    self.peek_member(union_spec, attribute)
+                                }
+                                Entity::Enum(_) => Err(Problem::FieldOnEnum),
+                            },
+                            VariantInfo::Field(_) => Err(Problem::FieldOnPlain),
+                        };
+                        info.map(|info| info.with_map_keys(map_keys).with_in_array(in_array))
+                    };
+                }
+            }
+            Err(Problem::FieldNotFound)
+        } else {
+            Err(Problem::ExpKey)
+        }
+    }
+
+    /// Checks if the attribute part can be used as a key of a map with keys of the given type. Keys
+    /// of enum type given literally have to be one of the enum choices.
+    fn verify_map_key(&self, key: &Variant, part: &Part) -> Result<(), Problem> {
+        match part {
+            Part::Key(string) | Part::Index(string) => match key {
+                Variant::Enum(name) => match self.schema.find_entity(&name.name).expect(ENTITY) {
+                    Entity::Enum(enum_spec) if !enum_spec.to_db_names().contains(string) => {
+                        Err(Problem::EnumChoiceUnknown)
+                    }
+                    _ => Ok(()),
+                },
+                _ => Ok(()),
+            },
+            Part::Code(_) => Ok(()),
+            _ => Err(Problem::ExpKey),
+        }
+    }
+
+    /// Searches for a member inside an element of an array. An empty attribute refers to the
+    /// element itself.
+    fn find_element_member(
+        &self,
+        variant: Variant,
+        attribute: SpannedAttribute,
+    ) -> Result<MemberInfo, Problem> {
+        let info = MemberInfo::new(&self.schema, variant, Container::Plain)?;
+        if attribute.len() == 0 {
+            return Ok(info);
+        }
+
+        match &info.info {
+            VariantInfo::Entity(entity) => match entity {
+                Entity::Struct(struct_spec) => self.find_member(struct_spec, attribute),
+                Entity::Union(union_spec) => self.peek_member(union_spec, attribute),
+                Entity::Enum(_) => Err(Problem::FieldOnEnum),
+            },
+            VariantInfo::Field(_) => Err(Problem::FieldOnPlain),
+        }
+    }
+
+    /// Searches for a member inside a union. If the members is ambiguous the search is considered
     /// to be failed.
     fn peek_member(
         &self,
@@ -474,11 +1464,11 @@ impl<'a> Validator<'a> {
     /// Determines the type of an update. If all attributes are operators then it's an `Update`. If
     /// all attributes are non-updates then it's `Replacement`. It's not allowed to have both
     /// `Update` and `Replacement` type attributes.
-    fn verify_update_type(&self) -> Result<UpdateType, Verdict> {
+    fn verify_update_type(&self, template: &ObjectTemplate) -> Result<UpdateType, Verdict> {
         let mut has_updates = false;
         let mut has_replacements = false;
 
-        for field in self.object.fields.iter() {
+        for field in template.fields.iter() {
             if field.attr.is_operator() {
                 has_updates = true;
             } else {
@@ -499,15 +1489,89 @@ impl<'a> Validator<'a> {
         }
     }
 
+    /// Checks that no documents are spread into an object where spreads are not supported.
+    fn verify_no_spreads(&self, template: &ObjectTemplate) {
+        for spread in template.spreads.iter() {
+            self.error(&spread.span, Problem::SpreadIncorrect);
+        }
+    }
+
+    /// Checks that no field path without a value was given where only fields are accepted.
+    fn verify_no_key(&self, template: &ObjectTemplate) {
+        if let Some(key) = &template.key {
+            self.error(&key.span, Problem::FieldPathUnexpected);
+        }
+    }
+
+    /// Checks for formulations which are valid, but likely to behave differently than intended.
+    fn verify_pitfalls(
+        &self,
+        struct_spec: &Struct,
+        member: &MemberInfo,
+        value: &SpannedValue,
+        conversion: Conversion,
+    ) {
+        match (conversion, &value.value) {
+            (Conversion::Filter, ValueTemplate::Object(object)) if member.in_array => {
+                for field in object.fields.iter().filter(|f| f.attr.to_composed() == "$ne") {
+                    self.warning(&field.attr.span, Problem::NeOnArray);
+                }
+            }
+            (Conversion::Update(UpdateOperator::Rename), ValueTemplate::Quoted(target)) => {
+                let attr = SpannedAttribute::from_str(target, value.span.clone());
+                match self.find_member(struct_spec, attr) {
+                    Ok(target)
+                        if target.variant != member.variant
+                            || target.container != member.container =>
+                    {
+                        self.error(&value.span, Problem::RenameIncompatible);
+                    }
+                    Ok(_) => self.warning(&value.span, Problem::RenameOverwrites),
+                    Err(_) => self.error(&value.span, Problem::RenameUnknown),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that no update pipeline was given where only an object is accepted.
+    fn verify_no_pipeline(&self) -> Result<(), Verdict> {
+        if self.pipeline.is_some() {
+            self.error(&proc_macro::Span::call_site(), Problem::ExpObject);
+            Err(self.verdict.borrow().clone())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Splits the top-level `arrayFilters` fields out of an update template.
+    fn split_array_filters(&self, template: ObjectTemplate) -> (ObjectTemplate, Vec<SpannedValue>) {
+        let mut object = ObjectTemplate::new(template.span);
+        object.spreads = template.spreads;
+        let mut array_filters = Vec::new();
+        for field in template.fields {
+            if field.attr.to_composed() == "arrayFilters" {
+                array_filters.push(field.value);
+            } else {
+                object.fields.push(field);
+            }
+        }
+        (object, array_filters)
+    }
+
     /// Verifies if the attribute is correct for the given `Conversion`.
     ///
     /// Checks that:
     /// - for `replacement` and `data` conversions  the attribute does not contain dots.
+    /// - for `filter` conversion the attribute does not contain all-positional or identified
+    ///   positional operators.
+    /// - for `update` conversion the identifiers of positional operators are well-formed.
     fn verify_attribute(
         &self,
         attr: &SpannedAttribute,
         conversion: Conversion,
     ) -> Result<(), Problem> {
+        let mut identifiers = attr.parts.iter().filter_map(|p| p.part.identifier());
         match conversion {
             Conversion::Replacement | Conversion::Data => {
                 if attr.len() == 1 {
@@ -516,10 +1580,40 @@ impl<'a> Validator<'a> {
                     Err(Problem::AttrWithDots)
                 }
             }
-            _ => Ok(()),
+            Conversion::Filter => {
+                if !attr.parts.iter().any(|p| p.part.is_positional()) {
+                    Ok(())
+                } else {
+                    Err(Problem::PositionalIncorrect)
+                }
+            }
+            Conversion::Update(_) => {
+                if identifiers.all(|identifier| self.is_identifier_correct(identifier)) {
+                    Ok(())
+                } else {
+                    Err(Problem::IdentifierIncorrect)
+                }
+            }
         }
     }
 
+    /// Checks if the identifier of a positional operator starts with a lowercase letter and
+    /// contains only alphanumeric characters.
+    fn is_identifier_correct(&self, identifier: &str) -> bool {
+        let mut chars = identifier.chars();
+        chars.next().map(|c| c.is_ascii_lowercase()).unwrap_or(false)
+            && chars.all(|c| c.is_ascii_alphanumeric())
+    }
+
+    /// Remembers the type of array elements selected by an identified positional operator so
+    /// that the array filters can be validated against it.
+    fn bind_identifier(&self, identifier: &str, variant: &Variant, span: &proc_macro::Span) {
+        self.identifiers
+            .borrow_mut()
+            .entry(identifier.to_string())
+            .or_insert_with(|| (variant.clone(), span.clone()));
+    }
+
     /// Parses a filter query operator out of passed attribute.
     fn convert_query_operator(&self, attr: &SpannedAttribute) -> Option<QueryOperator> {
         let composed = attr.to_composed();
@@ -532,26 +1626,81 @@ impl<'a> Validator<'a> {
             "$lte" => Some(QueryOperator::Lte),
             "$ne" => Some(QueryOperator::Ne),
             "$nin" => Some(QueryOperator::Nin),
+            "$not" => Some(QueryOperator::Not),
+            "$mod" => Some(QueryOperator::Mod),
+            "$near" => Some(QueryOperator::Near),
+            "$geoWithin" => Some(QueryOperator::GeoWithin),
+            "$geoIntersects" => Some(QueryOperator::GeoIntersects),
             _ => None,
         }
     }
 
-    /// Parses a update query operator out of passed attribute.
-    fn convert_update_operator(&self, attr: &SpannedAttribute) -> Option<UpdateOperator> {
+    /// Parses a top-level logical filter query operator out of passed attribute.
+    fn convert_logical_operator(&self, attr: &SpannedAttribute) -> Option<LogicalOperator> {
         let composed = attr.to_composed();
         match composed.as_ref() {
-            "$addToSet" => Some(UpdateOperator::AddToSet),
-            "$currentDate" => Some(UpdateOperator::CurrentDate),
-            "$inc" => Some(UpdateOperator::Inc),
-            "$max" => Some(UpdateOperator::Max),
-            "$min" => Some(UpdateOperator::Min),
-            "$mul" => Some(UpdateOperator::Mul),
-            "$pop" => Some(UpdateOperator::Pop),
-            "$pull" => Some(UpdateOperator::Pull),
-            "$pullAll" => Some(UpdateOperator::PullAll),
-            "$push" => Some(UpdateOperator::Push),
-            "$rename" => Some(UpdateOperator::Rename),
-            "$set" => Some(UpdateOperator::Set),
+            "$and" => Some(LogicalOperator::And),
+            "$nor" => Some(LogicalOperator::Nor),
+            "$or" => Some(LogicalOperator::Or),
+            _ => None,
+        }
+    }
+
+    /// Parses an aggregation expression operator out of passed attribute.
+    fn convert_expression_operator(&self, attr: &SpannedAttribute) -> Option<ExpressionOperator> {
+        let composed = attr.to_composed();
+        match composed.as_ref() {
+            "$eq" => Some(ExpressionOperator::Eq),
+            "$gt" => Some(ExpressionOperator::Gt),
+            "$gte" => Some(ExpressionOperator::Gte),
+            "$lt" => Some(ExpressionOperator::Lt),
+            "$lte" => Some(ExpressionOperator::Lte),
+            "$ne" => Some(ExpressionOperator::Ne),
+            "$cmp" => Some(ExpressionOperator::Cmp),
+            "$and" => Some(ExpressionOperator::And),
+            "$or" => Some(ExpressionOperator::Or),
+            "$not" => Some(ExpressionOperator::Not),
+            "$add" => Some(ExpressionOperator::Add),
+            "$subtract" => Some(ExpressionOperator::Subtract),
+            "$multiply" => Some(ExpressionOperator::Multiply),
+            "$divide" => Some(ExpressionOperator::Divide),
+            "$mod" => Some(ExpressionOperator::Mod),
+            _ => None,
+        }
+    }
+
+    /// Parses an accumulator operator out of passed attribute.
+    fn convert_accumulator_operator(&self, attr: &SpannedAttribute) -> Option<AccumulatorOperator> {
+        let composed = attr.to_composed();
+        match composed.as_ref() {
+            "$sum" => Some(AccumulatorOperator::Sum),
+            "$avg" => Some(AccumulatorOperator::Avg),
+            "$min" => Some(AccumulatorOperator::Min),
+            "$max" => Some(AccumulatorOperator::Max),
+            "$first" => Some(AccumulatorOperator::First),
+            "$last" => Some(AccumulatorOperator::Last),
+            "$push" => Some(AccumulatorOperator::Push),
+            "$addToSet" => Some(AccumulatorOperator::AddToSet),
+            _ => None,
+        }
+    }
+
+    /// Parses a update query operator out of passed attribute.
+    fn convert_update_operator(&self, attr: &SpannedAttribute) -> Option<UpdateOperator> {
+        let composed = attr.to_composed();
+        match composed.as_ref() {
+            "$addToSet" => Some(UpdateOperator::AddToSet),
+            "$currentDate" => Some(UpdateOperator::CurrentDate),
+            "$inc" => Some(UpdateOperator::Inc),
+            "$max" => Some(UpdateOperator::Max),
+            "$min" => Some(UpdateOperator::Min),
+            "$mul" => Some(UpdateOperator::Mul),
+            "$pop" => Some(UpdateOperator::Pop),
+            "$pull" => Some(UpdateOperator::Pull),
+            "$pullAll" => Some(UpdateOperator::PullAll),
+            "$push" => Some(UpdateOperator::Push),
+            "$rename" => Some(UpdateOperator::Rename),
+            "$set" => Some(UpdateOperator::Set),
             "$setOnInsert" => Some(UpdateOperator::SetOnInsert),
             "$unset" => Some(UpdateOperator::Unset),
             _ => None,
@@ -567,23 +1716,28 @@ impl<'a> Validator<'a> {
     ) -> Object {
         let mut object = Object::new();
 
+        self.convert_spreads(struct_spec, &template, &mut object);
+
         let required_fields = self.prepare_required_members(struct_spec, conversion);
         let mut visited_fields = BTreeSet::new();
         for field in template.fields {
+            self.path.borrow_mut().push(field.attr.to_composed());
             match self.verify_attribute(&field.attr, conversion) {
                 Ok(conversion) => conversion,
                 Err(problem) => {
                     self.error(&field.attr.span, problem);
+                    self.path.borrow_mut().pop();
                     continue;
                 }
             }
 
             match self.find_member(struct_spec, field.attr.clone()) {
                 Ok(member) => {
+                    self.verify_pitfalls(struct_spec, &member, &field.value, conversion);
                     visited_fields.insert(field.attr.to_composed());
                     match self.convert_value(&member, field.value.value, conversion) {
                         Ok(value) => {
-                            let attribute = field.attr.into_attribute();
+                            let attribute = self.convert_attribute(field.attr, &member);
                             let field = Field::new(attribute, value);
                             object.fields.push(field);
                         }
@@ -596,15 +1750,120 @@ impl<'a> Validator<'a> {
                     self.error(&field.attr.span, problem);
                 }
             }
+            self.path.borrow_mut().pop();
         }
 
-        if !required_fields.is_subset(&visited_fields) {
+        // Required fields may be provided by the spread documents
+        if template.spreads.is_empty() && !required_fields.is_subset(&visited_fields) {
             self.error(&template.span, Problem::FieldsMissing);
         }
 
         object
     }
 
+    /// Prepares the attribute for code generation. Parts in code mode used as map keys are marked
+    /// so that they are converted to strings instead of indices.
+    fn convert_attribute(&self, attr: SpannedAttribute, member: &MemberInfo) -> Attribute {
+        let mut attribute = attr.into_attribute();
+        let len = attribute.parts.len();
+        for (following, key) in member.map_keys.iter() {
+            let part = &mut attribute.parts[len - following - 1];
+            if let Part::Code(code) = part {
+                *part = Part::KeyCode { code: code.clone(), cast: key.to_data() };
+            }
+        }
+        attribute
+    }
+
+    /// Prepares the documents spread into an object. Only fields of the given structure are
+    /// allowed to be present in them.
+    fn convert_spreads(
+        &self,
+        struct_spec: &Struct,
+        template: &ObjectTemplate,
+        object: &mut Object,
+    ) {
+        let keys: Vec<String> = struct_spec.members.iter().map(|m| m.db_name.clone()).collect();
+        for spread in template.spreads.iter() {
+            if let ValueTemplate::Code(code) = &spread.value {
+                object.spreads.push(Spread::new(code.clone(), keys.clone()));
+            }
+        }
+    }
+
+    /// Prepares a `Object` used in code generation basing on parsed `ObjectTemplate`. The objects
+    /// here are top-level filter documents so apart from fields they may contain logical operators
+    /// whose branches are validated against the same structure.
+    fn convert_filter_root(&self, struct_spec: &Struct, template: ObjectTemplate) -> Object {
+        let mut object = Object::new();
+        self.convert_spreads(struct_spec, &template, &mut object);
+
+        for field in template.fields {
+            if !field.attr.is_operator() {
+                let mut single = ObjectTemplate::new(template.span.clone());
+                single.fields.push(field);
+                let converted = self.convert_object(struct_spec, single, Conversion::Filter);
+                object.fields.extend(converted.fields);
+                continue;
+            }
+
+            let composed = field.attr.to_composed();
+            if (composed == "$text") || (composed == "$expr") {
+                let value = if composed == "$text" {
+                    self.convert_text_value(struct_spec, field.value.value)
+                } else {
+                    self.convert_expression(struct_spec, field.value.value)
+                };
+
+                match value {
+                    Ok(value) => {
+                        let attribute = field.attr.into_attribute();
+                        let field = Field::new(attribute, value);
+                        object.fields.push(field);
+                    }
+                    Err(problem) => {
+                        self.error(&field.value.span, problem);
+                    }
+                }
+                continue;
+            }
+
+            if self.convert_logical_operator(&field.attr).is_none() {
+                self.error(&field.attr.span, Problem::OperatorUnknown);
+                continue;
+            }
+
+            match field.value.value {
+                ValueTemplate::Array(branches) => {
+                    if branches.is_empty() {
+                        self.error(&field.value.span, Problem::QueryEmpty);
+                        continue;
+                    }
+
+                    let mut values = Vec::with_capacity(branches.len());
+                    for branch in branches {
+                        match branch.value {
+                            ValueTemplate::Object(obj) => {
+                                let obj = self.convert_filter_root(struct_spec, obj);
+                                values.push(Value::Object(obj));
+                            }
+                            _ => self.error(&branch.span, Problem::ExpObject),
+                        }
+                    }
+
+                    let attribute = field.attr.into_attribute();
+                    let field = Field::new(attribute, Value::Array(values));
+                    object.fields.push(field);
+                }
+                _ => {
+                    self.error(&field.value.span, Problem::ExpArray);
+                }
+            }
+        }
+
+        object
+    }
+
     /// Prepares a `Object` used in code generation basing on parsed `ObjectTemplate`. The objects
     /// here are used in filter mode on a built-in type so they are expected to contain filter
     /// operators.
@@ -615,6 +1874,7 @@ impl<'a> Validator<'a> {
         template: ObjectTemplate,
     ) -> Object {
         let mut object = Object::new();
+        self.verify_no_spreads(&template);
 
         for field in template.fields {
             let operator = match self.convert_query_operator(&field.attr) {
@@ -626,7 +1886,7 @@ impl<'a> Validator<'a> {
             };
 
             if operator.matches(builtin, container) {
-                match self.convert_filter_value(operator, builtin, field.value.value) {
+                match self.convert_filter_value(operator, builtin, container, field.value.value) {
                     Ok(value) => {
                         let attribute = field.attr.into_attribute();
                         let field = Field::new(attribute, value);
@@ -649,6 +1909,7 @@ impl<'a> Validator<'a> {
     /// operators.
     fn convert_update_object(&self, struct_spec: &Struct, template: ObjectTemplate) -> Object {
         let mut object = Object::new();
+        self.verify_no_spreads(&template);
 
         for field in template.fields {
             let operator = match self.convert_update_operator(&field.attr) {
@@ -677,255 +1938,1574 @@ impl<'a> Validator<'a> {
         object
     }
 
-    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`.
-    fn convert_value(
+    /// Prepares a projection `Object` used in code generation basing on parsed `ObjectTemplate`.
+    /// Projections may either include or exclude fields, with exception of `_id` which can be
+    /// excluded in both cases.
+    fn convert_projection(&self, struct_spec: &Struct, template: ObjectTemplate) -> Object {
+        let mut object = Object::new();
+        self.verify_no_spreads(&template);
+        let mut inclusions = Vec::new();
+        let mut exclusions = Vec::new();
+
+        for field in template.fields {
+            if let Err(problem) = self.verify_attribute(&field.attr, Conversion::Filter) {
+                self.error(&field.attr.span, problem);
+                continue;
+            }
+
+            if let Err(problem) = self.find_member(struct_spec, field.attr.clone()) {
+                self.error(&field.attr.span, problem);
+                continue;
+            }
+
+            let value = match field.value.value {
+                ValueTemplate::Unquoted(string) => match string.as_ref() {
+                    "1" => Value::I32(1),
+                    "0" => Value::I32(0),
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    _ => {
+                        self.error(&field.value.span, Problem::ExpProjection);
+                        continue;
+                    }
+                },
+                ValueTemplate::Code(code) => {
+                    Value::new_builtin_code(BuiltInType::Bool, Container::Plain, code)
+                }
+                _ => {
+                    self.error(&field.value.span, Problem::ExpProjection);
+                    continue;
+                }
+            };
+
+            match (&value, field.attr.to_composed() == "_id") {
+                (Value::I32(1), false) | (Value::Bool(true), false) => {
+                    inclusions.push(field.attr.span.clone())
+                }
+                (Value::I32(0), false) | (Value::Bool(false), false) => {
+                    exclusions.push(field.attr.span.clone())
+                }
+                _ => {}
+            }
+
+            object.fields.push(Field::new(field.attr.into_attribute(), value));
+        }
+
+        if !inclusions.is_empty() && !exclusions.is_empty() {
+            for span in exclusions {
+                self.error(&span, Problem::ProjectionMixed);
+            }
+        }
+
+        object
+    }
+
+    /// Prepares a sort `Object` used in code generation basing on parsed `ObjectTemplate`. Fields
+    /// are sorted in ascending (`1`) or descending (`-1`) order or by the text search score
+    /// (`{ "$meta": "textScore" }`). The latter may be stored under any name.
+    fn convert_sort(&self, struct_spec: &Struct, template: ObjectTemplate) -> Object {
+        let mut object = Object::new();
+        self.verify_no_spreads(&template);
+
+        for field in template.fields {
+            let value = match field.value.value {
+                ValueTemplate::Object(template) => self.convert_sort_meta(struct_spec, template),
+                value => {
+                    if let Err(problem) = self.verify_attribute(&field.attr, Conversion::Filter) {
+                        self.error(&field.attr.span, problem);
+                        continue;
+                    }
+
+                    if let Err(problem) = self.find_member(struct_spec, field.attr.clone()) {
+                        self.error(&field.attr.span, problem);
+                        continue;
+                    }
+
+                    match value {
+                        ValueTemplate::Unquoted(string) => self.convert_sort_order(&string),
+                        ValueTemplate::Code(code) => {
+                            Ok(Value::new_builtin_code(BuiltInType::I32, Container::Plain, code))
+                        }
+                        _ => Err(Problem::ExpSort),
+                    }
+                }
+            };
+
+            match value {
+                Ok(value) => {
+                    object.fields.push(Field::new(field.attr.into_attribute(), value));
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
+                }
+            }
+        }
+
+        object
+    }
+
+    /// Prepares the text search score sort order. Expects `{ "$meta": "textScore" }`.
+    fn convert_sort_meta(
         &self,
-        member: &MemberInfo,
+        struct_spec: &Struct,
+        template: ObjectTemplate,
+    ) -> Result<Value, Problem> {
+        let mut fields = template.fields;
+        match (fields.pop(), fields.is_empty()) {
+            (Some(field), true) if field.attr.to_composed() == "$meta" => match field.value.value {
+                ValueTemplate::Quoted(ref meta) if meta == "textScore" => {
+                    if struct_spec.indexed_fields.is_empty() {
+                        return Err(Problem::TextIndexMissing);
+                    }
+                    let mut object = Object::new();
+                    let value = Value::String(meta.clone());
+                    object.fields.push(Field::new(field.attr.into_attribute(), value));
+                    Ok(Value::Object(object))
+                }
+                _ => Err(Problem::ExpSort),
+            },
+            _ => Err(Problem::ExpSort),
+        }
+    }
+
+    /// Prepares the type of values returned by a distinct query for the field with the given path.
+    /// Values of array fields are returned element by element.
+    fn convert_distinct_key(
+        &self,
+        struct_spec: &'a Struct,
+        key: SpannedAttribute,
+    ) -> Result<CodeType, Problem> {
+        let is_literal = key.parts.iter().all(|part| match part.part {
+            Part::Key(_) | Part::Index(_) => true,
+            _ => false,
+        });
+        if !is_literal {
+            return Err(Problem::PositionalIncorrect);
+        }
+
+        let member = self.find_member(struct_spec, key)?;
+        Ok(member.to_code_type(member.container == Container::Array))
+    }
+
+    /// Parses a boolean flag of a query option.
+    fn convert_flag(&self, template: ValueTemplate) -> Result<bool, Problem> {
+        match template {
+            ValueTemplate::Unquoted(ref string) if string == "true" => Ok(true),
+            ValueTemplate::Unquoted(ref string) if string == "false" => Ok(false),
+            _ => Err(Problem::ExpBool),
+        }
+    }
+
+    /// Prepares a `Count` used in code generation basing on parsed `ValueTemplate`.
+    fn convert_count(&self, template: ValueTemplate) -> Result<Count, Problem> {
+        match template {
+            ValueTemplate::Unquoted(string) => match string.parse() {
+                Ok(number) => Ok(Count::Literal(number)),
+                Err(_) => Err(Problem::ExpU32),
+            },
+            ValueTemplate::Code(code) => Ok(Count::Code(code)),
+            _ => Err(Problem::ExpU32),
+        }
+    }
+
+    /// Prepares stages of an update pipeline used in code generation. Each stage is expected to be
+    /// an object with a single `$set` (or its alias `$addFields`) or `$unset` operator.
+    fn convert_pipeline(&self, struct_spec: &Struct, stages: Vec<SpannedValue>) -> Vec<Object> {
+        let mut pipeline = Vec::with_capacity(stages.len());
+
+        for stage in stages {
+            let mut template = match stage.value {
+                ValueTemplate::Object(template) if template.fields.len() == 1 => template,
+                _ => {
+                    self.error(&stage.span, Problem::ExpPipelineStage);
+                    continue;
+                }
+            };
+
+            let field = template.fields.pop().expect("Stage has one field");
+            let value = match field.attr.to_composed().as_ref() {
+                "$set" | "$addFields" => match field.value.value {
+                    ValueTemplate::Object(object) => {
+                        Ok(Value::Object(self.convert_pipeline_set(struct_spec, object)))
+                    }
+                    _ => Err(Problem::ExpObject),
+                },
+                "$unset" => self.convert_pipeline_unset(struct_spec, field.value.value),
+                _ => {
+                    self.error(&field.attr.span, Problem::OperatorUnknown);
+                    continue;
+                }
+            };
+
+            match value {
+                Ok(value) => {
+                    let mut object = Object::new();
+                    object.fields.push(Field::new(field.attr.into_attribute(), value));
+                    pipeline.push(object);
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
+                }
+            }
+        }
+
+        pipeline
+    }
+
+    /// Prepares a `$set` stage of an update pipeline. Apart from literal values and code mode the
+    /// fields may be assigned field references (strings starting with a dollar sign) and
+    /// aggregation expressions.
+    fn convert_pipeline_set(&self, struct_spec: &Struct, template: ObjectTemplate) -> Object {
+        let mut object = Object::new();
+        self.verify_no_spreads(&template);
+
+        for field in template.fields {
+            if let Err(problem) = self.verify_attribute(&field.attr, Conversion::Filter) {
+                self.error(&field.attr.span, problem);
+                continue;
+            }
+
+            let member = match self.find_member(struct_spec, field.attr.clone()) {
+                Ok(member) => member,
+                Err(problem) => {
+                    self.error(&field.attr.span, problem);
+                    continue;
+                }
+            };
+
+            let value = match field.value.value {
+                ValueTemplate::Quoted(string) if string.starts_with('$') => {
+                    let attr = SpannedAttribute::from_str(&string[1..], field.value.span.clone());
+                    self.find_member(struct_spec, attr).map(|_| Value::String(string))
+                }
+                ValueTemplate::Object(obj) if obj.fields.iter().all(|f| f.attr.is_operator()) => {
+                    self.convert_expression(struct_spec, ValueTemplate::Object(obj))
+                }
+                template => {
+                    let conversion = Conversion::Update(UpdateOperator::Set);
+                    self.convert_value(&member, template, conversion)
+                }
+            };
+
+            match value {
+                Ok(value) => {
+                    object.fields.push(Field::new(field.attr.into_attribute(), value));
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
+                }
+            }
+        }
+
+        object
+    }
+
+    /// Prepares a `$unset` stage of an update pipeline. The value is expected to be a field name
+    /// or an array of field names.
+    fn convert_pipeline_unset(
+        &self,
+        struct_spec: &Struct,
         template: ValueTemplate,
-        conversion: Conversion,
     ) -> Result<Value, Problem> {
-        // In case of `code` mode - the data will be checked at compile time
-        if let ValueTemplate::Code(code) = template {
-            let cast = member.to_code_type(conversion.escapes_container());
-            return Ok(Value::Code { code, cast });
+        let span = proc_macro::Span::call_site();
+        match template {
+            ValueTemplate::Quoted(string) => {
+                self.find_member(struct_spec, SpannedAttribute::from_str(&string, span))?;
+                Ok(Value::String(string))
+            }
+            ValueTemplate::Array(names) => {
+                let mut values = Vec::with_capacity(names.len());
+                for name in names {
+                    match name.value {
+                        ValueTemplate::Quoted(string) => {
+                            let attr = SpannedAttribute::from_str(&string, name.span.clone());
+                            match self.find_member(struct_spec, attr) {
+                                Ok(_) => values.push(Value::String(string)),
+                                Err(problem) => self.error(&name.span, problem),
+                            }
+                        }
+                        _ => self.error(&name.span, Problem::ExpString),
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            _ => Err(Problem::ExpString),
+        }
+    }
+
+    /// Prepares stages of an aggregation pipeline used in code generation. Each stage is expected
+    /// to be an object with a single `$match`, `$unwind`, `$group`, `$sort`, `$limit` or `$skip`
+    /// operator. Until the documents are grouped the stages are checked against the schema.
+    fn convert_aggregation_pipeline(
+        &self,
+        struct_spec: &Struct,
+        stages: Vec<SpannedValue>,
+    ) -> Vec<Object> {
+        let mut pipeline = Vec::with_capacity(stages.len());
+        let mut unwound = Vec::new();
+        let mut grouped: Option<BTreeSet<String>> = None;
+
+        for stage in stages {
+            let mut template = match stage.value {
+                ValueTemplate::Object(template) if template.fields.len() == 1 => template,
+                _ => {
+                    self.error(&stage.span, Problem::ExpAggregationStage);
+                    continue;
+                }
+            };
+
+            let field = template.fields.pop().expect("Stage has one field");
+            let value = match (field.attr.to_composed().as_ref(), field.value.value) {
+                ("$limit", template) | ("$skip", template) => {
+                    self.convert_aggregation_count(template)
+                }
+                ("$match", _) | ("$unwind", _) | ("$group", _) if grouped.is_some() => {
+                    self.error(&field.attr.span, Problem::StageAfterGroup);
+                    continue;
+                }
+                ("$match", ValueTemplate::Object(object)) => {
+                    Ok(Value::Object(self.convert_filter_root(struct_spec, object)))
+                }
+                ("$unwind", template) => self.convert_unwind(struct_spec, &mut unwound, template),
+                ("$group", ValueTemplate::Object(object)) => {
+                    let (object, names) = self.convert_group(struct_spec, &unwound, object);
+                    grouped = Some(names);
+                    Ok(Value::Object(object))
+                }
+                ("$sort", ValueTemplate::Object(object)) => match &grouped {
+                    Some(names) => Ok(Value::Object(self.convert_grouped_sort(names, object))),
+                    None => Ok(Value::Object(self.convert_sort(struct_spec, object))),
+                },
+                ("$match", _) | ("$group", _) | ("$sort", _) => Err(Problem::ExpObject),
+                _ => {
+                    self.error(&field.attr.span, Problem::OperatorUnknown);
+                    continue;
+                }
+            };
+
+            match value {
+                Ok(value) => {
+                    let mut object = Object::new();
+                    object.fields.push(Field::new(field.attr.into_attribute(), value));
+                    pipeline.push(object);
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
+                }
+            }
+        }
+
+        pipeline
+    }
+
+    /// Prepares a `$limit` or `$skip` stage of an aggregation pipeline.
+    fn convert_aggregation_count(&self, template: ValueTemplate) -> Result<Value, Problem> {
+        match self.convert_count(template)? {
+            Count::Literal(number) => Ok(Value::I64(i64::from(number))),
+            Count::Code(code) => {
+                Ok(Value::new_builtin_code(BuiltInType::I64, Container::Plain, code))
+            }
+        }
+    }
+
+    /// Prepares a `$unwind` stage of an aggregation pipeline. The value is expected to be a
+    /// reference to an array member or an object with the reference under the `path` key.
+    fn convert_unwind(
+        &self,
+        struct_spec: &Struct,
+        unwound: &mut Vec<String>,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match template {
+            ValueTemplate::Quoted(path) => self.convert_unwind_path(struct_spec, unwound, path),
+            ValueTemplate::Object(template) => {
+                self.verify_no_spreads(&template);
+                if !template.fields.iter().any(|f| f.attr.to_composed() == "path") {
+                    return Err(Problem::ExpUnwind);
+                }
+
+                let mut object = Object::new();
+                for field in template.fields {
+                    let value = match (field.attr.to_composed().as_ref(), field.value.value) {
+                        ("path", ValueTemplate::Quoted(path)) => {
+                            self.convert_unwind_path(struct_spec, unwound, path)
+                        }
+                        ("path", _) => Err(Problem::ExpFieldRef),
+                        ("preserveNullAndEmptyArrays", template) => {
+                            self.convert_flag(template).map(Value::Bool)
+                        }
+                        _ => {
+                            self.error(&field.attr.span, Problem::ExpUnwind);
+                            continue;
+                        }
+                    };
+
+                    match value {
+                        Ok(value) => {
+                            object.fields.push(Field::new(field.attr.into_attribute(), value));
+                        }
+                        Err(problem) => self.error(&field.value.span, problem),
+                    }
+                }
+                Ok(Value::Object(object))
+            }
+            _ => Err(Problem::ExpUnwind),
+        }
+    }
+
+    /// Checks if the path passed to the `$unwind` stage refers to an array member which was not
+    /// unwound yet.
+    fn convert_unwind_path(
+        &self,
+        struct_spec: &Struct,
+        unwound: &mut Vec<String>,
+        path: String,
+    ) -> Result<Value, Problem> {
+        let member = self.find_referenced_member(struct_spec, &path)?;
+        if !member.container.is_array() || unwound.iter().any(|p| *p == path[1..]) {
+            return Err(Problem::UnwindNotArray);
+        }
+        unwound.push(path[1..].to_string());
+        Ok(Value::String(path))
+    }
+
+    /// Prepares a `$group` stage of an aggregation pipeline. Returns the stage together with names
+    /// of the fields present in the grouped documents.
+    fn convert_group(
+        &self,
+        struct_spec: &Struct,
+        unwound: &[String],
+        template: ObjectTemplate,
+    ) -> (Object, BTreeSet<String>) {
+        let mut object = Object::new();
+        let mut names = BTreeSet::new();
+        let span = template.span.clone();
+        self.verify_no_spreads(&template);
+
+        for field in template.fields {
+            let name = field.attr.to_composed();
+            if field.attr.len() > 1 {
+                self.error(&field.attr.span, Problem::AttrWithDots);
+                continue;
+            } else if field.attr.is_operator() {
+                self.error(&field.attr.span, Problem::ExpKey);
+                continue;
+            }
+
+            let value = if name == "_id" {
+                self.convert_group_id(struct_spec, field.value.value)
+            } else {
+                self.convert_accumulator(struct_spec, unwound, field.value.value)
+            };
+
+            match value {
+                Ok(value) => {
+                    object.fields.push(Field::new(field.attr.into_attribute(), value));
+                }
+                Err(problem) => self.error(&field.value.span, problem),
+            }
+            names.insert(name);
+        }
+
+        if !names.contains("_id") {
+            self.error(&span, Problem::GroupIdMissing);
+        }
+
+        (object, names)
+    }
+
+    /// Prepares the `_id` field of a `$group` stage. The documents may be grouped by `null`, a
+    /// field reference, an aggregation expression or an object holding any of the latter two.
+    fn convert_group_id(
+        &self,
+        struct_spec: &Struct,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match template {
+            ValueTemplate::Unquoted(ref string) if string == "null" => Ok(Value::Null),
+            ValueTemplate::Quoted(reference) => {
+                self.find_referenced_member(struct_spec, &reference)?;
+                Ok(Value::String(reference))
+            }
+            ValueTemplate::Object(obj) if obj.fields.iter().all(|f| f.attr.is_operator()) => {
+                self.convert_expression(struct_spec, ValueTemplate::Object(obj))
+            }
+            ValueTemplate::Object(template) => {
+                self.verify_no_spreads(&template);
+                let mut object = Object::new();
+                for field in template.fields {
+                    if field.attr.len() > 1 {
+                        self.error(&field.attr.span, Problem::AttrWithDots);
+                        continue;
+                    }
+
+                    let value = match field.value.value {
+                        ValueTemplate::Quoted(reference) => self
+                            .find_referenced_member(struct_spec, &reference)
+                            .map(|_| Value::String(reference)),
+                        template @ ValueTemplate::Object(_) => {
+                            self.convert_expression(struct_spec, template)
+                        }
+                        _ => Err(Problem::ExpFieldRef),
+                    };
+
+                    match value {
+                        Ok(value) => {
+                            object.fields.push(Field::new(field.attr.into_attribute(), value));
+                        }
+                        Err(problem) => self.error(&field.value.span, problem),
+                    }
+                }
+                Ok(Value::Object(object))
+            }
+            _ => Err(Problem::ExpGroupId),
+        }
+    }
+
+    /// Prepares an accumulator of a `$group` stage, i.e. an object with a single accumulator
+    /// operator.
+    fn convert_accumulator(
+        &self,
+        struct_spec: &Struct,
+        unwound: &[String],
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        let field = match template {
+            ValueTemplate::Object(mut object) if object.fields.len() == 1 => {
+                object.fields.pop().expect("Accumulator has one field")
+            }
+            _ => return Err(Problem::ExpAccumulator),
+        };
+
+        let operator = match self.convert_accumulator_operator(&field.attr) {
+            Some(operator) => operator,
+            None => return Err(Problem::OperatorUnknown),
+        };
+
+        let value =
+            self.convert_accumulator_argument(struct_spec, unwound, operator, field.value.value)?;
+        let mut object = Object::new();
+        object.fields.push(Field::new(field.attr.into_attribute(), value));
+        Ok(Value::Object(object))
+    }
+
+    /// Prepares the argument of an accumulator operator. Arithmetic accumulators accept only
+    /// numbers and references to numerical members which are not arrays (unless unwound).
+    fn convert_accumulator_argument(
+        &self,
+        struct_spec: &Struct,
+        unwound: &[String],
+        operator: AccumulatorOperator,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match template {
+            ValueTemplate::Quoted(string) => {
+                if string.starts_with("$$") {
+                    if operator.is_arithmetic() {
+                        return Err(Problem::OperatorIncorrect);
+                    }
+                } else if string.starts_with('$') {
+                    let member = self.find_referenced_member(struct_spec, &string)?;
+                    if operator.is_arithmetic() {
+                        let is_plain = member.container.is_plain()
+                            || unwound.iter().any(|path| *path == string[1..]);
+                        match member.info {
+                            VariantInfo::Field(builtin) if builtin.is_numeric() && is_plain => {}
+                            _ => return Err(Problem::OperatorIncorrect),
+                        }
+                    }
+                } else if operator.is_arithmetic() {
+                    return Err(Problem::OperatorIncorrect);
+                }
+                Ok(Value::String(string))
+            }
+            ValueTemplate::Unquoted(string) => {
+                if let Ok(value) = string.parse::<i64>() {
+                    Ok(Value::I64(value))
+                } else if let Ok(value) = string.parse::<f64>() {
+                    Ok(Value::F64(value))
+                } else if operator.is_arithmetic() {
+                    Err(Problem::OperatorIncorrect)
+                } else {
+                    self.convert_builtin_value(&BuiltInType::Bool, ValueTemplate::Unquoted(string))
+                }
+            }
+            template @ ValueTemplate::Object(_) => self.convert_expression(struct_spec, template),
+            _ => Err(Problem::ExpExpression),
+        }
+    }
+
+    /// Prepares a `$sort` stage following the `$group` stage. Only the fields output by the
+    /// grouping can be sorted by.
+    fn convert_grouped_sort(&self, names: &BTreeSet<String>, template: ObjectTemplate) -> Object {
+        let mut object = Object::new();
+        self.verify_no_spreads(&template);
+
+        for field in template.fields {
+            let is_known = field.attr.next().map(|p| names.contains(p.to_str())).unwrap_or(false);
+            if !is_known {
+                self.error(&field.attr.span, Problem::FieldNotFound);
+                continue;
+            }
+
+            let value = match field.value.value {
+                ValueTemplate::Unquoted(ref string) => self.convert_sort_order(string),
+                _ => Err(Problem::ExpSort),
+            };
+
+            match value {
+                Ok(value) => object.fields.push(Field::new(field.attr.into_attribute(), value)),
+                Err(problem) => self.error(&field.value.span, problem),
+            }
+        }
+
+        object
+    }
+
+    /// Searches for a member referred to by a field reference, i.e. a string starting with a
+    /// single dollar sign.
+    fn find_referenced_member(
+        &self,
+        struct_spec: &Struct,
+        reference: &str,
+    ) -> Result<MemberInfo, Problem> {
+        if reference.len() > 1 && reference.starts_with('$') && !reference.starts_with("$$") {
+            let span = proc_macro::Span::call_site();
+            self.find_member(struct_spec, SpannedAttribute::from_str(&reference[1..], span))
+        } else {
+            Err(Problem::ExpFieldRef)
+        }
+    }
+
+    /// Prepares array filters used in code generation basing on parsed `arrayFilters` values. Every
+    /// identifier bound in the update is expected to have a filter.
+    fn convert_array_filters(&self, templates: Vec<SpannedValue>) -> Vec<Object> {
+        let mut array_filters = Vec::new();
+        let mut filtered = BTreeSet::new();
+
+        for template in templates {
+            let filters = match template.value {
+                ValueTemplate::Array(filters) => filters,
+                _ => {
+                    self.error(&template.span, Problem::ExpArray);
+                    continue;
+                }
+            };
+
+            for filter in filters {
+                match filter.value {
+                    ValueTemplate::Object(obj) => {
+                        if obj.fields.is_empty() {
+                            self.error(&filter.span, Problem::QueryEmpty);
+                        } else {
+                            array_filters.push(self.convert_array_filter(obj, &mut filtered));
+                        }
+                    }
+                    _ => self.error(&filter.span, Problem::ExpObject),
+                }
+            }
+        }
+
+        for (identifier, (_, span)) in self.identifiers.borrow().iter() {
+            if !filtered.contains(identifier) {
+                self.error(span, Problem::ArrayFilterMissing);
+            }
+        }
+
+        array_filters
+    }
+
+    /// Prepares a single array filter. The first part of each attribute is an identifier and the
+    /// rest is validated against the type of the array elements the identifier was bound to.
+    fn convert_array_filter(
+        &self,
+        template: ObjectTemplate,
+        filtered: &mut BTreeSet<String>,
+    ) -> Object {
+        let mut object = Object::new();
+        let mut filter_identifier: Option<String> = None;
+
+        for field in template.fields {
+            let mut attr = field.attr.clone();
+            let identifier = attr.pop().expect("Attribute without parts").to_str().to_string();
+            let same = filter_identifier.as_ref().map(|i| *i == identifier).unwrap_or(true);
+            let variant = match self.identifiers.borrow().get(&identifier) {
+                Some((variant, _)) if same => variant.clone(),
+                _ => {
+                    self.error(&field.attr.span, Problem::ArrayFilterIncorrect);
+                    continue;
+                }
+            };
+
+            filtered.insert(identifier.clone());
+            filter_identifier = Some(identifier);
+
+            if let Err(problem) = self.verify_attribute(&attr, Conversion::Filter) {
+                self.error(&field.attr.span, problem);
+                continue;
+            }
+
+            match self.find_element_member(variant, attr) {
+                Ok(member) => {
+                    match self.convert_value(&member, field.value.value, Conversion::Filter) {
+                        Ok(value) => {
+                            let attribute = field.attr.into_attribute();
+                            object.fields.push(Field::new(attribute, value));
+                        }
+                        Err(problem) => {
+                            self.error(&field.value.span, problem);
+                        }
+                    }
+                }
+                Err(problem) => {
+                    self.error(&field.attr.span, problem);
+                }
+            }
+        }
+
+        object
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`.
+    fn convert_value(
+        &self,
+        member: &MemberInfo,
+        template: ValueTemplate,
+        conversion: Conversion,
+    ) -> Result<Value, Problem> {
+        // In case of `code` mode - the data will be checked at compile time
+        if let ValueTemplate::Code(code) = template {
+            let cast = member.to_code_type(conversion.escapes_container());
+            return Ok(Value::Code { code, cast });
+        }
+
+        // In case of `null` - only optional members can be set to it
+        if template.is_null() && conversion.accepts_null() {
+            return if member.is_optional { Ok(Value::Null) } else { Err(Problem::NullOnRequired) };
+        }
+
+        // In case of hard-coded data - try to convert
+        match conversion {
+            Conversion::Update(op) => self.convert_update(&member, template, op),
+            Conversion::Filter => self.convert_filter(&member.info, &member.container, template),
+            _ => {
+                if member.container.is_plain() {
+                    match &member.info {
+                        VariantInfo::Field(builtin) => {
+                            self.convert_builtin_value(builtin, template)
+                        }
+                        VariantInfo::Entity(entity) => {
+                            self.convert_defined_value(entity, template, conversion)
+                        }
+                    }
+                } else if member.container.is_array() && template.is_array() {
+                    self.convert_each_value(member, template)
+                } else {
+                    Err(Problem::ExpCodeComp)
+                }
+            }
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to be literal values.
+    fn convert_builtin_value(
+        &self,
+        builtin: &BuiltInType,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match builtin {
+            BuiltInType::F64 => match template {
+                ValueTemplate::Unquoted(string) => match string.parse::<f64>() {
+                    Ok(value) if value.is_finite() => Ok(Value::F64(value)),
+                    Ok(_) if string.chars().any(|c| c.is_ascii_digit()) => {
+                        Err(Problem::NumericOverflow)
+                    }
+                    _ => Err(Problem::ExpF64),
+                },
+                _ => Err(Problem::ExpF64),
+            },
+            BuiltInType::String => match template {
+                ValueTemplate::Quoted(value) => Ok(Value::String(value)),
+                _ => Err(Problem::ExpString),
+            },
+            BuiltInType::ObjectId => match template {
+                ValueTemplate::Quoted(string) | ValueTemplate::Unquoted(string) => {
+                    match bson::oid::ObjectId::with_string(&string) {
+                        Ok(value) => Ok(Value::ObjectId(value)),
+                        Err(_) => Err(Problem::ExpOid),
+                    }
+                }
+                ValueTemplate::ObjectId(string) => self.convert_object_id(&string),
+                _ => Err(Problem::ExpOid),
+            },
+            BuiltInType::Bool => match template {
+                ValueTemplate::Unquoted(string) => {
+                    if string == "true" {
+                        Ok(Value::Bool(true))
+                    } else if string == "false" {
+                        Ok(Value::Bool(false))
+                    } else {
+                        Err(Problem::ExpBool)
+                    }
+                }
+                _ => Err(Problem::ExpBool),
+            },
+            BuiltInType::Date => match template {
+                ValueTemplate::Quoted(string) => match string.parse::<DateTime<Utc>>() {
+                    Ok(date) => Ok(Value::Date(date)),
+                    Err(_) => self.convert_relative_date(&string).ok_or(Problem::ExpDate),
+                },
+                ValueTemplate::Unquoted(ref string) if string == "now" => {
+                    Ok(Value::RelativeDate { offset: 0, today: false })
+                }
+                _ => Err(Problem::ExpDate),
+            },
+            BuiltInType::I32 => match template {
+                ValueTemplate::Unquoted(string) => {
+                    self.convert_integer(&string, Problem::ExpI32).map(Value::I32)
+                }
+                _ => Err(Problem::ExpI32),
+            },
+            BuiltInType::I64 => match template {
+                ValueTemplate::Unquoted(string) => {
+                    self.convert_integer(&string, Problem::ExpI64).map(Value::I64)
+                }
+                _ => Err(Problem::ExpI64),
+            },
+            BuiltInType::GeoPoint => self.convert_geo_point(template),
+            BuiltInType::Bson => Err(Problem::ExpBson),
+        }
+    }
+
+    /// Parses an integer literal into the declared type. Literals which do not fit into the type
+    /// are reported as an overflow rather than as a type mismatch.
+    fn convert_integer<T>(&self, string: &str, problem: Problem) -> Result<T, Problem>
+    where
+        T: std::str::FromStr,
+    {
+        let digits = string.trim_start_matches('-');
+        match string.parse::<T>() {
+            Ok(value) => Ok(value),
+            Err(_) if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) => {
+                Err(Problem::NumericOverflow)
+            }
+            Err(_) => Err(problem),
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to belong to predefined types, so except of case of structure a code mode
+    /// is expected.
+    fn convert_defined_value(
+        &self,
+        entity: &Entity,
+        template: ValueTemplate,
+        conversion: Conversion,
+    ) -> Result<Value, Problem> {
+        match entity {
+            Entity::Struct(struct_spec) => match template {
+                ValueTemplate::Object(object) => {
+                    Ok(Value::Object(self.convert_object(struct_spec, object, conversion)))
+                }
+                _ => Err(Problem::ExpObject),
+            },
+            Entity::Enum(enum_spec) => match template {
+                ValueTemplate::Quoted(string) => {
+                    let choices = enum_spec.to_db_names();
+                    if choices.contains(&string) {
+                        Ok(Value::String(string))
+                    } else {
+                        self.hint(format!("Valid choices are: \"{}\"", choices.join("\", \"")));
+                        Err(Problem::EnumChoiceUnknown)
+                    }
+                }
+                _ => Err(Problem::ExpCodeEnum),
+            },
+            Entity::Union(union_spec) => match template {
+                ValueTemplate::Object(object) => {
+                    self.convert_union_object(union_spec, object, conversion)
+                }
+                _ => Err(Problem::ExpCodeUnion),
+            },
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ObjectTemplate`. The object is
+    /// expected to contain the `_huus_variant` key selecting the union choice. The remaining fields
+    /// are checked against the structure of the selected choice.
+    fn convert_union_object(
+        &self,
+        union_spec: &Union,
+        mut template: ObjectTemplate,
+        conversion: Conversion,
+    ) -> Result<Value, Problem> {
+        let position = template
+            .fields
+            .iter()
+            .position(|field| field.attr.to_composed() == VARIANT_KEY)
+            .ok_or(Problem::ExpCodeUnion)?;
+        let variant = match template.fields.remove(position).value.value {
+            ValueTemplate::Quoted(variant) => variant,
+            _ => return Err(Problem::ExpString),
+        };
+
+        let choice = match union_spec.choices.iter().find(|choice| choice.db_name == variant) {
+            Some(choice) => choice,
+            None => {
+                let choices: Vec<&str> =
+                    union_spec.choices.iter().map(|choice| choice.db_name.as_str()).collect();
+                self.hint(format!("Valid choices are: \"{}\"", choices.join("\", \"")));
+                return Err(Problem::UnionChoiceUnknown);
+            }
+        };
+
+        match self.schema.find_entity(&choice.variant.name).expect(ENTITY) {
+            Entity::Struct(struct_spec) => {
+                let mut object = self.convert_object(struct_spec, template, conversion);
+                let attribute = Attribute::from_key(VARIANT_KEY);
+                object.fields.push(Field::new(attribute, Value::String(variant)));
+                Ok(Value::Object(object))
+            }
+            _ => panic!("Union should be composed only of structures"),
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain a filter operator.
+    fn convert_filter(
+        &self,
+        variant: &VariantInfo,
+        container: &Container,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match variant {
+            VariantInfo::Field(builtin) => match template {
+                ValueTemplate::Object(object) => {
+                    Ok(Value::Object(self.convert_filter_object(builtin, container, object)))
+                }
+                _ => {
+                    if container.is_plain() {
+                        self.convert_builtin_value(builtin, template)
+                    } else {
+                        return Err(Problem::ExpCodeComp);
+                    }
+                }
+            },
+            VariantInfo::Entity(entity) => {
+                self.convert_defined_value(entity, template, Conversion::Filter)
+            }
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain an update operator.
+    fn convert_update(
+        &self,
+        member: &MemberInfo,
+        template: ValueTemplate,
+        operator: UpdateOperator,
+    ) -> Result<Value, Problem> {
+        match operator {
+            UpdateOperator::Inc
+            | UpdateOperator::Min
+            | UpdateOperator::Max
+            | UpdateOperator::Mul
+            | UpdateOperator::Set
+            | UpdateOperator::SetOnInsert => {
+                if member.container.is_plain() {
+                    match &member.info {
+                        VariantInfo::Field(builtin) => {
+                            self.convert_builtin_value(builtin, template)
+                        }
+                        VariantInfo::Entity(entity) => {
+                            self.convert_defined_value(entity, template, Conversion::Data)
+                        }
+                    }
+                } else if member.container.is_array() && template.is_array() {
+                    self.convert_each_value(member, template)
+                } else {
+                    Err(Problem::ExpPlain)
+                }
+            }
+            UpdateOperator::CurrentDate => {
+                if member.container.is_plain() {
+                    match &member.info {
+                        VariantInfo::Field(BuiltInType::Date) => self.convert_date_value(template),
+                        _ => Err(Problem::OperatorIncorrect),
+                    }
+                } else {
+                    Err(Problem::ExpPlain)
+                }
+            }
+            UpdateOperator::AddToSet | UpdateOperator::Pop | UpdateOperator::Push => {
+                if member.container.is_array() {
+                    if operator == UpdateOperator::Push {
+                        if let ValueTemplate::Object(object) = &template {
+                            if object.fields.iter().any(|field| field.attr.is_operator()) {
+                                return self.convert_push_modifiers(member, object.clone());
+                            }
+                        }
+                    }
+
+                    match &member.info {
+                        VariantInfo::Field(builtin) => {
+                            self.convert_builtin_value(builtin, template)
+                        }
+                        VariantInfo::Entity(entity) => {
+                            self.convert_defined_value(entity, template, Conversion::Data)
+                        }
+                    }
+                } else {
+                    Err(Problem::OperatorIncorrect)
+                }
+            }
+            UpdateOperator::Pull => {
+                if member.container.is_array() {
+                    self.convert_filter(&member.info, &Container::Plain, template)
+                } else {
+                    Err(Problem::ExpArray)
+                }
+            }
+            UpdateOperator::PullAll => Err(Problem::ExpCode),
+            UpdateOperator::Unset => {
+                if template.is_empty_string() {
+                    Ok(Value::String(String::new()))
+                } else {
+                    Err(Problem::ExpEmptyString)
+                }
+            }
+            UpdateOperator::Rename => match template {
+                ValueTemplate::Quoted(string) => Ok(Value::String(string)),
+                _ => Err(Problem::ExpString),
+            },
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ObjectTemplate`. The objects
+    /// here are expected to contain the `$each` modifier of the push update operator optionally
+    /// accompanied by `$position`, `$slice` and `$sort` modifiers.
+    fn convert_push_modifiers(
+        &self,
+        member: &MemberInfo,
+        template: ObjectTemplate,
+    ) -> Result<Value, Problem> {
+        let mut object = Object::new();
+        let mut has_each = false;
+
+        for field in template.fields {
+            let value = match field.attr.to_composed().as_ref() {
+                "$each" => {
+                    has_each = true;
+                    self.convert_each_value(member, field.value.value)
+                }
+                "$position" | "$slice" => match field.value.value {
+                    ValueTemplate::Code(code) => {
+                        Ok(Value::new_builtin_code(BuiltInType::I64, Container::Plain, code))
+                    }
+                    value => self.convert_builtin_value(&BuiltInType::I64, value),
+                },
+                "$sort" => self.convert_push_sort_value(member, field.value.value),
+                _ => {
+                    self.error(&field.attr.span, Problem::OperatorUnknown);
+                    continue;
+                }
+            };
+
+            match value {
+                Ok(value) => {
+                    let attribute = field.attr.into_attribute();
+                    let field = Field::new(attribute, value);
+                    object.fields.push(field);
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
+                }
+            }
+        }
+
+        if has_each {
+            Ok(Value::Object(object))
+        } else {
+            Err(Problem::FieldsMissing)
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain elements to be inserted into an array or an array literal to
+    /// be assigned to an array member.
+    fn convert_each_value(
+        &self,
+        member: &MemberInfo,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match template {
+            ValueTemplate::Code(code) => Ok(Value::Code { code, cast: member.to_code_type(false) }),
+            ValueTemplate::Array(elements) => {
+                let mut values = Vec::with_capacity(elements.len());
+                for element in elements {
+                    let value = match element.value {
+                        ValueTemplate::Code(code) => {
+                            Ok(Value::Code { code, cast: member.to_code_type(true) })
+                        }
+                        value => match &member.info {
+                            VariantInfo::Field(builtin) => {
+                                self.convert_builtin_value(builtin, value)
+                            }
+                            VariantInfo::Entity(entity) => {
+                                self.convert_defined_value(entity, value, Conversion::Data)
+                            }
+                        },
+                    };
+
+                    match value {
+                        Ok(value) => values.push(value),
+                        Err(problem) => self.error(&element.span, problem),
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            _ => Err(Problem::ExpArray),
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain the sort order: `1` or `-1` for built-in types or an object
+    /// mapping fields of the embedded documents to the sort order.
+    fn convert_push_sort_value(
+        &self,
+        member: &MemberInfo,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match (&member.info, template) {
+            (_, ValueTemplate::Unquoted(string)) => self.convert_sort_order(&string),
+            (VariantInfo::Entity(Entity::Struct(struct_spec)), ValueTemplate::Object(template)) => {
+                let mut object = Object::new();
+                for field in template.fields {
+                    if let Err(problem) = self.find_member(struct_spec, field.attr.clone()) {
+                        self.error(&field.attr.span, problem);
+                        continue;
+                    }
+
+                    let value = match field.value.value {
+                        ValueTemplate::Unquoted(string) => self.convert_sort_order(&string),
+                        _ => Err(Problem::ExpSort),
+                    };
+
+                    match value {
+                        Ok(value) => {
+                            let attribute = field.attr.into_attribute();
+                            object.fields.push(Field::new(attribute, value));
+                        }
+                        Err(problem) => self.error(&field.value.span, problem),
+                    }
+                }
+                Ok(Value::Object(object))
+            }
+            _ => Err(Problem::ExpSort),
+        }
+    }
+
+    /// Parses the sort order: `1` for ascending or `-1` for descending.
+    fn convert_sort_order(&self, string: &str) -> Result<Value, Problem> {
+        match string {
+            "1" => Ok(Value::I32(1)),
+            "-1" => Ok(Value::I32(-1)),
+            _ => Err(Problem::ExpSort),
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain parameters of a filter operator.
+    fn convert_filter_value(
+        &self,
+        operator: QueryOperator,
+        builtin: &BuiltInType,
+        container: &Container,
+        value: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match operator {
+            QueryOperator::Eq
+            | QueryOperator::Ne
+            | QueryOperator::Gt
+            | QueryOperator::Gte
+            | QueryOperator::Lt
+            | QueryOperator::Lte => {
+                if let ValueTemplate::Code(code) = value {
+                    Ok(Value::new_builtin_code(builtin.clone(), Container::Plain, code))
+                } else {
+                    self.convert_builtin_value(builtin, value)
+                }
+            }
+            QueryOperator::In | QueryOperator::Nin => {
+                if let ValueTemplate::Code(code) = value {
+                    Ok(Value::new_builtin_code(builtin.clone(), Container::Array, code))
+                } else {
+                    Err(Problem::ExpCode)
+                }
+            }
+            QueryOperator::Not => match value {
+                ValueTemplate::Object(object) => {
+                    if object.fields.is_empty() {
+                        Err(Problem::QueryEmpty)
+                    } else {
+                        Ok(Value::Object(self.convert_filter_object(builtin, container, object)))
+                    }
+                }
+                _ => Err(Problem::ExpObject),
+            },
+            QueryOperator::Mod => self.convert_mod_value(value),
+            QueryOperator::Near | QueryOperator::GeoWithin | QueryOperator::GeoIntersects => {
+                self.convert_geo_value(operator, value)
+            }
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain parameters of a geospatial filter operator.
+    fn convert_geo_value(
+        &self,
+        operator: QueryOperator,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        let template = match template {
+            ValueTemplate::Object(object) => object,
+            _ => return Err(Problem::ExpObject),
+        };
+
+        let mut object = Object::new();
+        let mut has_shape = false;
+        for field in template.fields {
+            let value = match (operator, field.attr.to_composed().as_ref()) {
+                (_, "$geometry") => {
+                    has_shape = true;
+                    self.convert_geometry_value(operator, field.value.value)
+                }
+                (QueryOperator::GeoWithin, "$centerSphere") => {
+                    has_shape = true;
+                    self.convert_center_sphere_value(field.value.value)
+                }
+                (QueryOperator::Near, "$maxDistance") | (QueryOperator::Near, "$minDistance") => {
+                    self.convert_distance_value(field.value.value)
+                }
+                (_, "$maxDistance") | (_, "$minDistance") | (_, "$centerSphere") => {
+                    self.error(&field.attr.span, Problem::OperatorIncorrect);
+                    continue;
+                }
+                _ => {
+                    self.error(&field.attr.span, Problem::OperatorUnknown);
+                    continue;
+                }
+            };
+
+            match value {
+                Ok(value) => {
+                    let attribute = field.attr.into_attribute();
+                    let field = Field::new(attribute, value);
+                    object.fields.push(field);
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
+                }
+            }
+        }
+
+        if has_shape {
+            Ok(Value::Object(object))
+        } else {
+            Err(Problem::FieldsMissing)
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain a GeoJSON geometry. Points can be given as literal
+    /// `[longitude, latitude]` arrays, other geometries are supported only in code mode.
+    fn convert_geometry_value(
+        &self,
+        operator: QueryOperator,
+        template: ValueTemplate,
+    ) -> Result<Value, Problem> {
+        match template {
+            ValueTemplate::Code(code) => {
+                let builtin = match operator {
+                    QueryOperator::Near => BuiltInType::GeoPoint,
+                    _ => BuiltInType::Bson,
+                };
+                Ok(Value::new_builtin_code(builtin, Container::Plain, code))
+            }
+            ValueTemplate::Array(_) => match operator {
+                QueryOperator::GeoWithin => Err(Problem::ExpCode),
+                _ => self.convert_geo_point(template),
+            },
+            _ => Err(Problem::ExpGeoPoint),
+        }
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain a `[[longitude, latitude], radius]` array.
+    fn convert_center_sphere_value(&self, template: ValueTemplate) -> Result<Value, Problem> {
+        if let ValueTemplate::Array(mut values) = template {
+            if values.len() == 2 {
+                let radius = self.convert_distance_value(values.pop().unwrap().value)?;
+                let (longitude, latitude) =
+                    self.convert_coordinates(values.pop().unwrap().value)?;
+                let center = Value::Array(vec![Value::F64(longitude), Value::F64(latitude)]);
+                return Ok(Value::Array(vec![center, radius]));
+            }
+        }
+        Err(Problem::ExpGeoPoint)
+    }
+
+    /// Prepares an object ID from an `oid"..."` literal checking its length and characters.
+    fn convert_object_id(&self, string: &str) -> Result<Value, Problem> {
+        if string.len() != 24 {
+            Err(Problem::OidLength)
+        } else if !string.chars().all(|c| c.is_ascii_hexdigit()) {
+            Err(Problem::OidNotHex)
+        } else {
+            bson::oid::ObjectId::with_string(string)
+                .map(Value::ObjectId)
+                .map_err(|_| Problem::ExpOid)
+        }
+    }
+
+    /// Prepares a date relative to the current time. Accepts `"today"` (the start of the current
+    /// day) and offsets from the current time consisting of a sign, a number and a unit (`s`, `m`,
+    /// `h`, `d` or `w`), e.g. `"+2h"` or `"-30d"`.
+    fn convert_relative_date(&self, string: &str) -> Option<Value> {
+        if string == "today" {
+            return Some(Value::RelativeDate { offset: 0, today: true });
+        }
+
+        let sign = match string.chars().next()? {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        let unit = match string.chars().last()? {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        let number = string[1..string.len() - 1].parse::<u32>().ok()?;
+        Some(Value::RelativeDate { offset: sign * unit * i64::from(number), today: false })
+    }
+
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain a non-negative distance.
+    fn convert_distance_value(&self, template: ValueTemplate) -> Result<Value, Problem> {
+        match template {
+            ValueTemplate::Code(code) => {
+                Ok(Value::new_builtin_code(BuiltInType::F64, Container::Plain, code))
+            }
+            ValueTemplate::Unquoted(string) => match string.parse::<f64>() {
+                Ok(distance) if distance >= 0.0 => Ok(Value::F64(distance)),
+                _ => Err(Problem::ExpDistance),
+            },
+            _ => Err(Problem::ExpDistance),
         }
+    }
 
-        // In case of hard-coded data - try to convert
-        match conversion {
-            Conversion::Update(op) => self.convert_update(&member, template, op),
-            Conversion::Filter => self.convert_filter(&member.info, &member.container, template),
-            _ => {
-                if member.container.is_plain() {
-                    match &member.info {
-                        VariantInfo::Field(builtin) => {
-                            self.convert_builtin_value(builtin, template)
-                        }
-                        VariantInfo::Entity(entity) => {
-                            self.convert_defined_value(entity, template, conversion)
-                        }
-                    }
-                } else {
-                    Err(Problem::ExpCodeComp)
+    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
+    /// here are expected to contain a `[longitude, latitude]` array.
+    fn convert_geo_point(&self, template: ValueTemplate) -> Result<Value, Problem> {
+        let (longitude, latitude) = self.convert_coordinates(template)?;
+        Ok(Value::new_geo_point(longitude, latitude))
+    }
+
+    /// Parses a `[longitude, latitude]` array.
+    fn convert_coordinates(&self, template: ValueTemplate) -> Result<(f64, f64), Problem> {
+        if let ValueTemplate::Array(values) = template {
+            let coordinates: Option<Vec<f64>> = values
+                .into_iter()
+                .map(|value| match value.value {
+                    ValueTemplate::Unquoted(string) => string.parse().ok(),
+                    _ => None,
+                })
+                .collect();
+
+            if let Some(&[longitude, latitude]) = coordinates.as_deref() {
+                if (longitude.abs() <= 180.0) && (latitude.abs() <= 90.0) {
+                    return Ok((longitude, latitude));
                 }
             }
         }
+        Err(Problem::ExpGeoPoint)
     }
 
     /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
-    /// here are expected to be literal values.
-    fn convert_builtin_value(
-        &self,
-        builtin: &BuiltInType,
-        template: ValueTemplate,
-    ) -> Result<Value, Problem> {
-        match builtin {
-            BuiltInType::F64 => match template {
-                ValueTemplate::Unquoted(string) => match string.parse() {
-                    Ok(value) => Ok(Value::F64(value)),
-                    Err(_) => Err(Problem::ExpF64),
-                },
-                _ => Err(Problem::ExpF64),
-            },
-            BuiltInType::String => match template {
-                ValueTemplate::Quoted(value) => Ok(Value::String(value)),
-                _ => Err(Problem::ExpString),
-            },
-            BuiltInType::ObjectId => match template {
-                ValueTemplate::Quoted(string) | ValueTemplate::Unquoted(string) => {
-                    match bson::oid::ObjectId::with_string(&string) {
-                        Ok(value) => Ok(Value::ObjectId(value)),
-                        Err(_) => Err(Problem::ExpOid),
-                    }
+    /// here are expected to contain parameters of a modulo filter operator.
+    fn convert_mod_value(&self, template: ValueTemplate) -> Result<Value, Problem> {
+        let values = match template {
+            ValueTemplate::Array(values) => values,
+            _ => return Err(Problem::ExpModArray),
+        };
+
+        if values.len() != 2 {
+            return Err(Problem::ExpModArray);
+        }
+
+        let mut result = Vec::with_capacity(values.len());
+        for (i, value) in values.into_iter().enumerate() {
+            match value.value {
+                ValueTemplate::Code(code) => {
+                    result.push(Value::new_builtin_code(BuiltInType::I64, Container::Plain, code));
                 }
-                _ => Err(Problem::ExpOid),
-            },
-            BuiltInType::Bool => match template {
                 ValueTemplate::Unquoted(string) => {
-                    if string == "true" {
-                        Ok(Value::Bool(true))
-                    } else if string == "false" {
-                        Ok(Value::Bool(false))
-                    } else {
-                        Err(Problem::ExpBool)
+                    match self.convert_integer(&string, Problem::ExpI64)? {
+                        0 if i == 0 => return Err(Problem::ExpModArray),
+                        number => result.push(Value::I64(number)),
                     }
                 }
-                _ => Err(Problem::ExpBool),
-            },
-            BuiltInType::Date => match template {
-                ValueTemplate::Quoted(string) => match string.parse::<DateTime<Utc>>() {
-                    Ok(date) => Ok(Value::Date(date)),
-                    Err(_) => Err(Problem::ExpDate),
-                },
-                _ => Err(Problem::ExpDate),
-            },
-            BuiltInType::I32 => match template {
-                ValueTemplate::Unquoted(string) => match string.parse() {
-                    Ok(value) => Ok(Value::I32(value)),
-                    Err(_) => Err(Problem::ExpI32),
-                },
-                _ => Err(Problem::ExpI32),
-            },
-            BuiltInType::I64 => match template {
-                ValueTemplate::Unquoted(string) => match string.parse() {
-                    Ok(value) => Ok(Value::I64(value)),
-                    Err(_) => Err(Problem::ExpI64),
-                },
-                _ => Err(Problem::ExpI64),
-            },
-            BuiltInType::Bson => Err(Problem::ExpBson),
+                _ => return Err(Problem::ExpI64),
+            }
         }
+        Ok(Value::Array(result))
     }
 
     /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
-    /// here are expected to belong to predefined types, so except of case of structure a code mode
-    /// is expected.
-    fn convert_defined_value(
+    /// here are expected to contain parameters of a text search filter operator.
+    fn convert_text_value(
         &self,
-        entity: &Entity,
+        struct_spec: &Struct,
         template: ValueTemplate,
-        conversion: Conversion,
     ) -> Result<Value, Problem> {
-        match entity {
-            Entity::Struct(struct_spec) => match template {
-                ValueTemplate::Object(object) => {
-                    Ok(Value::Object(self.convert_object(struct_spec, object, conversion)))
-                }
-                _ => Err(Problem::ExpObject),
-            },
-            Entity::Enum(_) => Err(Problem::ExpCodeEnum),
-            Entity::Union(_) => Err(Problem::ExpCodeUnion),
+        if struct_spec.indexed_fields.is_empty() {
+            return Err(Problem::TextIndexMissing);
         }
-    }
 
-    /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
-    /// here are expected to contain a filter operator.
-    fn convert_filter(
-        &self,
-        variant: &VariantInfo,
-        container: &Container,
-        template: ValueTemplate,
-    ) -> Result<Value, Problem> {
-        match variant {
-            VariantInfo::Field(builtin) => match template {
-                ValueTemplate::Object(object) => {
-                    Ok(Value::Object(self.convert_filter_object(builtin, container, object)))
+        let template = match template {
+            ValueTemplate::Object(object) => object,
+            _ => return Err(Problem::ExpObject),
+        };
+
+        let mut object = Object::new();
+        let mut has_search = false;
+        for field in template.fields {
+            let builtin = match field.attr.to_composed().as_ref() {
+                "$search" => {
+                    has_search = true;
+                    BuiltInType::String
                 }
+                "$language" => BuiltInType::String,
+                "$caseSensitive" | "$diacriticSensitive" => BuiltInType::Bool,
                 _ => {
-                    if container.is_plain() {
-                        self.convert_builtin_value(builtin, template)
-                    } else {
-                        return Err(Problem::ExpCodeComp);
-                    }
+                    self.error(&field.attr.span, Problem::OperatorUnknown);
+                    continue;
+                }
+            };
+
+            let value = match field.value.value {
+                ValueTemplate::Code(code) => {
+                    Ok(Value::new_builtin_code(builtin, Container::Plain, code))
+                }
+                value => self.convert_builtin_value(&builtin, value),
+            };
+
+            match value {
+                Ok(value) => {
+                    let attribute = field.attr.into_attribute();
+                    let field = Field::new(attribute, value);
+                    object.fields.push(field);
+                }
+                Err(problem) => {
+                    self.error(&field.value.span, problem);
                 }
-            },
-            VariantInfo::Entity(entity) => {
-                self.convert_defined_value(entity, template, Conversion::Filter)
             }
         }
+
+        if has_search {
+            Ok(Value::Object(object))
+        } else {
+            Err(Problem::FieldsMissing)
+        }
     }
 
     /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
-    /// here are expected to contain an update operator.
-    fn convert_update(
+    /// here are expected to contain an aggregation expression as used by the `$expr` filter
+    /// operator. Only a restricted set of expression operators is supported.
+    fn convert_expression(
         &self,
-        member: &MemberInfo,
+        struct_spec: &Struct,
         template: ValueTemplate,
-        operator: UpdateOperator,
     ) -> Result<Value, Problem> {
-        match operator {
-            UpdateOperator::Inc
-            | UpdateOperator::Min
-            | UpdateOperator::Max
-            | UpdateOperator::Mul
-            | UpdateOperator::Set
-            | UpdateOperator::SetOnInsert => {
-                if member.container.is_plain() {
-                    match &member.info {
-                        VariantInfo::Field(builtin) => {
-                            self.convert_builtin_value(builtin, template)
-                        }
-                        VariantInfo::Entity(entity) => {
-                            self.convert_defined_value(entity, template, Conversion::Data)
-                        }
-                    }
-                } else {
-                    Err(Problem::ExpPlain)
-                }
-            }
-            UpdateOperator::CurrentDate => {
-                if member.container.is_plain() {
-                    match &member.info {
-                        VariantInfo::Field(BuiltInType::Date) => self.convert_date_value(template),
-                        _ => Err(Problem::OperatorIncorrect),
-                    }
-                } else {
-                    Err(Problem::ExpPlain)
-                }
-            }
-            UpdateOperator::AddToSet | UpdateOperator::Pop | UpdateOperator::Push => {
-                if member.container.is_array() {
-                    match &member.info {
-                        VariantInfo::Field(builtin) => {
-                            self.convert_builtin_value(builtin, template)
-                        }
-                        VariantInfo::Entity(entity) => {
-                            self.convert_defined_value(entity, template, Conversion::Data)
-                        }
-                    }
-                } else {
-                    Err(Problem::OperatorIncorrect)
-                }
-            }
-            UpdateOperator::Pull => {
-                if member.container.is_array() {
-                    self.convert_filter(&member.info, &Container::Plain, template)
-                } else {
-                    Err(Problem::ExpArray)
-                }
+        let field = match template {
+            ValueTemplate::Object(mut object) if object.fields.len() == 1 => {
+                object.fields.pop().unwrap()
             }
-            UpdateOperator::PullAll => Err(Problem::ExpCode),
-            UpdateOperator::Unset => {
-                if template.is_empty_string() {
-                    Ok(Value::String(String::new()))
-                } else {
-                    Err(Problem::ExpEmptyString)
-                }
+            _ => return Err(Problem::ExpExpression),
+        };
+
+        let operator = match self.convert_expression_operator(&field.attr) {
+            Some(operator) => operator,
+            None => return Err(Problem::OperatorUnknown),
+        };
+
+        let arguments = match field.value.value {
+            ValueTemplate::Array(arguments) => arguments,
+            _ => return Err(Problem::ExpExpressionArgs),
+        };
+
+        if !operator.accepts(arguments.len()) {
+            return Err(Problem::ExpExpressionArgs);
+        }
+
+        let mut values = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            match self.convert_expression_argument(struct_spec, operator, argument.value) {
+                Ok(value) => values.push(value),
+                Err(problem) => self.error(&argument.span, problem),
             }
-            UpdateOperator::Rename => match template {
-                ValueTemplate::Quoted(string) => Ok(Value::String(string)),
-                _ => Err(Problem::ExpString),
-            },
         }
+
+        let mut object = Object::new();
+        object.fields.push(Field::new(field.attr.into_attribute(), Value::Array(values)));
+        Ok(Value::Object(object))
     }
 
     /// Prepares a `Value` used in code generation basing on parsed `ValueTemplate`. The values
-    /// here are expected to contain parameters of a filter operator.
-    fn convert_filter_value(
+    /// here are expected to be arguments of an aggregation expression: field references (strings
+    /// starting with a dollar sign), literal values or other expressions.
+    fn convert_expression_argument(
         &self,
-        operator: QueryOperator,
-        builtin: &BuiltInType,
-        value: ValueTemplate,
+        struct_spec: &Struct,
+        operator: ExpressionOperator,
+        template: ValueTemplate,
     ) -> Result<Value, Problem> {
-        match operator {
-            QueryOperator::Eq
-            | QueryOperator::Ne
-            | QueryOperator::Gt
-            | QueryOperator::Gte
-            | QueryOperator::Lt
-            | QueryOperator::Lte => {
-                if let ValueTemplate::Code(code) = value {
-                    Ok(Value::new_builtin_code(builtin.clone(), Container::Plain, code))
-                } else {
-                    self.convert_builtin_value(builtin, value)
+        match template {
+            ValueTemplate::Quoted(string) => {
+                if let Some(path) = string.strip_prefix('$') {
+                    let span = proc_macro::Span::call_site();
+                    let attr = SpannedAttribute::from_str(path, span);
+                    let member = self.find_member(struct_spec, attr)?;
+                    if operator.is_arithmetic() {
+                        match member.info {
+                            VariantInfo::Field(builtin)
+                                if builtin.is_numeric() && member.container.is_plain() => {}
+                            _ => return Err(Problem::OperatorIncorrect),
+                        }
+                    }
+                } else if operator.is_arithmetic() {
+                    return Err(Problem::OperatorIncorrect);
                 }
+                Ok(Value::String(string))
             }
-            QueryOperator::In | QueryOperator::Nin => {
-                if let ValueTemplate::Code(code) = value {
-                    Ok(Value::new_builtin_code(builtin.clone(), Container::Array, code))
+            ValueTemplate::Unquoted(string) => {
+                if let Ok(value) = string.parse::<i64>() {
+                    Ok(Value::I64(value))
+                } else if let Ok(value) = string.parse::<f64>() {
+                    Ok(Value::F64(value))
+                } else if operator.is_arithmetic() {
+                    Err(Problem::OperatorIncorrect)
                 } else {
-                    Err(Problem::ExpCode)
+                    self.convert_builtin_value(&BuiltInType::Bool, ValueTemplate::Unquoted(string))
                 }
             }
+            template @ ValueTemplate::Object(_) => self.convert_expression(struct_spec, template),
+            _ => Err(Problem::ExpExpression),
         }
     }
 
@@ -965,7 +3545,7 @@ impl<'a> Validator<'a> {
 impl<'a> Validator<'a> {
     /// Builds the generator containing the validated data.
     fn make_generator(&self, name: DefinedType, object: Object) -> Result<Generator, Verdict> {
-        if object.fields.len() == 0 {
+        if object.fields.is_empty() && object.spreads.is_empty() {
             self.error(&proc_macro::Span::call_site(), Problem::MacroEmpty);
         }
 
@@ -976,6 +3556,23 @@ impl<'a> Validator<'a> {
         }
     }
 
+    /// Builds the generator containing the validated update pipeline.
+    fn make_pipeline_generator(
+        &self,
+        name: DefinedType,
+        pipeline: Vec<Object>,
+    ) -> Result<Generator, Verdict> {
+        if pipeline.len() == 0 {
+            self.error(&proc_macro::Span::call_site(), Problem::MacroEmpty);
+        }
+
+        if self.verdict.borrow().problems.len() == 0 {
+            Ok(Generator::new(name, Object::new()).with_pipeline(pipeline))
+        } else {
+            Err(self.verdict.borrow().clone())
+        }
+    }
+
     /// Returns a set of attributes that are required for correct formulation of the query.
     /// Only in data mode any fields are required.
     fn prepare_required_members(
@@ -999,9 +3596,54 @@ impl<'a> Validator<'a> {
 
     /// Emits a compilation error.
     fn error(&self, span: &proc_macro::Span, problem: Problem) {
-        self.verdict.borrow_mut().problems.push(problem);
+        let path = self.path.borrow().join(".");
+        self.verdict.borrow_mut().problems.push(Report::new(problem, span.clone(), path));
+        let hint = self.hint.borrow_mut().take();
         if !self.testing {
-            span.error(problem.as_str()).emit();
+            match hint {
+                Some(hint) => span.error(problem.as_str()).help(hint).emit(),
+                None => span.error(problem.as_str()).emit(),
+            }
+        }
+    }
+
+    /// Reports a problem which does not prevent generation of the code, unless it was allowed. In
+    /// testing mode warnings are collected together with the errors.
+    fn warning(&self, span: &proc_macro::Span, problem: Problem) {
+        if self.allowed.contains(&problem) {
+            return;
+        }
+
+        if self.testing {
+            self.error(span, problem);
+        } else {
+            let name = problem.warning_name().expect("Problem should be a warning");
+            span.warning(problem.as_str())
+                .note(format!("add `allow({})` after the collection name to suppress it", name))
+                .emit();
+        }
+    }
+
+    /// Sets a help message to be attached to the next reported problem.
+    fn hint(&self, message: String) {
+        *self.hint.borrow_mut() = Some(message);
+    }
+}
+
+/// Computes the Levenshtein distance between two strings. Used to suggest corrections of mistyped
+/// names.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            let substitution = if ca == *cb { previous } else { previous + 1 };
+            row[j + 1] = substitution.min(row[j] + 1).min(current + 1);
+            previous = current;
         }
     }
+    row[b.len()]
 }
@@ -15,5 +15,11 @@ mod parser;
 pub mod definition;
 pub mod formulation;
 
-pub use definition::interpreter::Interpreter as Definition;
-pub use formulation::{interpreter::Interpreter as Formulation, validator::Problem};
+pub use definition::{
+    interpreter::Interpreter as Definition,
+    output::{LoadError, Schema},
+};
+pub use formulation::{
+    interpreter::Interpreter as Formulation,
+    validator::{LocatedProblem, Problem},
+};
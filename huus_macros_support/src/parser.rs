@@ -100,6 +100,13 @@ impl Parser {
         }
     }
 
+    pub fn is_punctuation(&self, expected: char) -> bool {
+        match &self.next {
+            Some(proc_macro::TokenTree::Punct(item)) => item.as_char() == expected,
+            _ => false,
+        }
+    }
+
     pub fn span(&self) -> Option<proc_macro::Span> {
         match &self.prev {
             Some(proc_macro::TokenTree::Group(item)) => Some(item.span()),
@@ -0,0 +1,8 @@
+huus::commands::AggregateCommand::new(
+    "{{ aggregate.collection_name }}".to_string(),
+    vec![
+        {% for stage in aggregate.stages %}
+            {{ generator.object(stage) }},
+        {% endfor %}
+    ],
+)
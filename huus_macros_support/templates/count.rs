@@ -0,0 +1,4 @@
+huus::commands::CountDocumentsCommand::new(
+    "{{ count.collection_name }}".to_string(),
+    {{ generator.object(object) }},
+)
@@ -0,0 +1,8 @@
+huus::commands::InsertManyCommand::new(
+    "{{ insert_many.collection_name }}".to_string(),
+    vec![
+        {% for document in insert_many.documents %}
+            {{ generator.object(document) }},
+        {% endfor %}
+    ],
+)
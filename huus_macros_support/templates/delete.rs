@@ -0,0 +1,5 @@
+huus::commands::DeleteCommand::new(
+    "{{ delete.collection_name }}".to_string(),
+    {{ generator.object(object) }},
+    huus::commands::DeleteOptions::DeleteMany,
+)
@@ -0,0 +1,5 @@
+huus::commands::DistinctCommand::<{{ distinct.cast.to_data() }}>::new(
+    "{{ distinct.collection_name }}".to_string(),
+    "{{ distinct.key }}".to_string(),
+    {{ generator.object(object) }},
+)
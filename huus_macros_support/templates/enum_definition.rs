@@ -2,8 +2,14 @@
 {% let value_name = spec.name.to_value() %}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+{% if generator.has_serde() %}
+    #[derive(serde::Serialize, serde::Deserialize)]
+{% endif %}
 pub enum {{ data_name }} {
     {% for choice in spec.choices %}
+        {% if generator.has_serde() %}
+            #[serde(rename = "{{ choice.db_name }}")]
+        {% endif %}
         {{ choice.rust_name }},
     {% endfor %}
 }
@@ -26,6 +32,16 @@ impl huus::conversions::HuusKey for {{ data_name }} {
     }
 }
 
+impl huus::conversions::HuusFromBson for {{ data_name }} {
+    fn huus_from_bson(bson: bson::Bson) -> Result<Self, huus::errors::ConversionError> {
+        use huus::conversions::HuusKey;
+        match bson {
+            bson::Bson::String(value) => Self::from_str(&value),
+            _ => Err(huus::errors::ConversionError::wrong_type_for_unknown_key()),
+        }
+    }
+}
+
 impl huus::conversions::HuusIntoBson for {{ data_name }} {
     fn huus_into_bson(self) -> bson::Bson {
         use huus::conversions::HuusKey;
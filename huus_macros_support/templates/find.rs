@@ -0,0 +1,32 @@
+huus::commands::FindCommand::<
+    {% match find.projection %}
+        {% when Some with (projection) %}
+            bson::Document
+        {% when None %}
+            {{ name.to_data() }}
+    {% endmatch %}
+>::new(
+    "{{ find.collection_name }}".to_string(),
+    {{ generator.object(object) }},
+    {% match find.limit %}
+        {% when Some with (limit) %}
+            Some({{ generator.count(limit) }}),
+        {% when None %}
+            None,
+    {% endmatch %}
+)
+{% match find.skip %}
+    {% when Some with (skip) %}
+        .with_skip({{ generator.count(skip) }})
+    {% when None %}
+{% endmatch %}
+{% match find.sort %}
+    {% when Some with (sort) %}
+        .with_sort({{ generator.object(sort) }})
+    {% when None %}
+{% endmatch %}
+{% match find.projection %}
+    {% when Some with (projection) %}
+        .with_projection({{ generator.object(projection) }})
+    {% when None %}
+{% endmatch %}
@@ -0,0 +1,23 @@
+huus::commands::FindAndModifyCommand::<{{ name.to_data() }}>::new(
+    "{{ find_and_modify.collection_name }}".to_string(),
+    {{ generator.object(object) }},
+    {% match find_and_modify.modification %}
+        {% when Modification::Update with (update) %}
+            huus::commands::FindAndModifyAction::Update({{ generator.object(update) }}),
+        {% when Modification::Replace with (replacement) %}
+            huus::commands::FindAndModifyAction::Replace({{ generator.object(replacement) }}),
+        {% when Modification::Delete %}
+            huus::commands::FindAndModifyAction::Delete,
+    {% endmatch %}
+)
+{% match find_and_modify.sort %}
+    {% when Some with (sort) %}
+        .with_sort({{ generator.object(sort) }})
+    {% when None %}
+{% endmatch %}
+{% if find_and_modify.return_after %}
+    .with_return_document(huus::commands::ReturnDocument::After)
+{% endif %}
+{% if find_and_modify.upsert %}
+    .with_upsert(true)
+{% endif %}
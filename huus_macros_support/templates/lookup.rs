@@ -0,0 +1,8 @@
+huus::commands::LookupCommand::<{{ name.to_data() }}, {{ lookup.foreign.to_data() }}>::new(
+    "{{ lookup.collection_name }}".to_string(),
+    {{ generator.object(object) }},
+    "{{ lookup.from }}".to_string(),
+    "{{ lookup.local_field }}".to_string(),
+    "{{ lookup.foreign_field }}".to_string(),
+    "{{ lookup.as_field }}".to_string(),
+)
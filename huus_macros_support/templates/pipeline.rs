@@ -0,0 +1,5 @@
+huus::updates::PipelineUpdate::new(vec![
+    {% for stage in pipeline %}
+        {{ generator.object(stage) }},
+    {% endfor %}
+])
@@ -0,0 +1 @@
+{{ name.to_projection() }}::new({{ generator.object(object) }})
@@ -0,0 +1 @@
+{{ generator.object(object) }}
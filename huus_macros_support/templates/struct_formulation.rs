@@ -2,11 +2,21 @@
 {% let data_name = spec.struct_name.to_data() %}
 {% let filter_name = spec.struct_name.to_filter() %}
 {% let update_name = spec.struct_name.to_update() %}
+{% let projection_name = spec.struct_name.to_projection() %}
 
 #[derive(Clone, Debug, PartialEq)]
+{% if generator.has_serde() %}
+    #[derive(serde::Serialize, serde::Deserialize)]
+{% endif %}
 pub struct {{ data_name }} {
     {% for member in spec.members %}
+        {% if generator.has_serde() %}
+            #[serde(rename = "{{ member.db_name }}")]
+        {% endif %}
         {% if member.is_optional %}
+            {% if generator.has_serde() %}
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+            {% endif %}
             pub {{ member.rust_name }}: Option<{{ member.to_data() }}>,
         {% else %}
             pub {{ member.rust_name }}: {{ member.to_data() }},
@@ -23,17 +33,25 @@ impl huus::conversions::FromDoc for {{ data_name }} {
                 {{ member.rust_name }}:
                 {% if member.is_optional %}
                     match doc.{{ member.from_doc_getter() }}("{{ member.db_name }}") {
-                        Ok(value) => Some({ {{ member.to_conversion() }} }),
+                        Ok(value) => Some(huus::errors::ConversionError::within(
+                            "{{ member.db_name }}",
+                            || Ok({ {{ member.to_conversion() }} }),
+                        )?),
                         Err(bson::ordered::ValueAccessError::NotPresent) => None,
                         Err(bson::ordered::ValueAccessError::UnexpectedType) => {
-                            return Err(huus::errors::ConversionError::wrong_type(
-                                "{{ member.db_name }}".to_string()
+                            return Err(huus::errors::ConversionError::decode(
+                                "{{ member.db_name }}".to_string(),
+                                "{{ member.to_bson_type() }}",
+                                doc.get("{{ member.db_name }}"),
                             ))
                         }
                     },
                 {% else %}
                     match doc.{{ member.from_doc_getter() }}("{{ member.db_name }}") {
-                        Ok(value) => { {{ member.to_conversion() }} }
+                        Ok(value) => huus::errors::ConversionError::within(
+                            "{{ member.db_name }}",
+                            || Ok({ {{ member.to_conversion() }} }),
+                        )?,
                         Err(bson::ordered::ValueAccessError::NotPresent) => {
                             {% match member.to_default() %}
                                 {% when Some with (default) %}
@@ -45,8 +63,10 @@ impl huus::conversions::FromDoc for {{ data_name }} {
                             {% endmatch %}
                         }
                         Err(bson::ordered::ValueAccessError::UnexpectedType) => {
-                            return Err(huus::errors::ConversionError::wrong_type(
-                                "{{ member.db_name }}".to_string()
+                            return Err(huus::errors::ConversionError::decode(
+                                "{{ member.db_name }}".to_string(),
+                                "{{ member.to_bson_type() }}",
+                                doc.get("{{ member.db_name }}"),
                             ))
                         }
                     },
@@ -54,6 +74,31 @@ impl huus::conversions::FromDoc for {{ data_name }} {
             {% endfor %}
         })
     }
+
+    fn from_doc_strict(doc: bson::Document)
+    -> Result<{{ data_name }}, huus::errors::ConversionError> {
+        for key in doc.keys() {
+            match key.as_str() {
+                {% for member in spec.members %}
+                    "{{ member.db_name }}" => {}
+                {% endfor %}
+                _ => return Err(huus::errors::ConversionError::unexpected_key(key.clone())),
+            }
+        }
+        {% for member in spec.members %}
+            {% match member.to_strict_check() %}
+                {% when Some with (check) %}
+                    if let Ok(value) = doc.{{ member.from_doc_getter() }}("{{ member.db_name }}") {
+                        huus::errors::ConversionError::within("{{ member.db_name }}", || {
+                            {{ check }}
+                            Ok(())
+                        })?;
+                    }
+                {% when None %}
+            {% endmatch %}
+        {% endfor %}
+        Self::from_doc(doc)
+    }
 }
 
 impl huus::conversions::IntoDoc for {{ data_name }} {
@@ -112,11 +157,17 @@ impl huus::conversions::IntoDoc for {{ data_name }} {
         #[derive(Clone, Debug)]
         pub struct {{ update_name }} {
             doc: bson::Document,
+            array_filters: Vec<bson::Document>,
         }
 
         impl {{ update_name }} {
             pub fn new(doc: bson::Document) -> Self {
-                Self { doc }
+                Self { doc, array_filters: Vec::new() }
+            }
+
+            pub fn with_array_filters(mut self, array_filters: Vec<bson::Document>) -> Self {
+                self.array_filters = array_filters;
+                self
             }
         }
 
@@ -126,14 +177,34 @@ impl huus::conversions::IntoDoc for {{ data_name }} {
             }
         }
 
+        impl huus::updates::BuildArrayFilters for {{ update_name }} {
+            fn build_array_filters(&self) -> Vec<bson::Document> {
+                self.array_filters.clone()
+            }
+        }
+
+        #[derive(Clone, Debug)]
+        pub struct {{ projection_name }} {
+            doc: bson::Document,
+        }
+
+        impl {{ projection_name }} {
+            pub fn new(doc: bson::Document) -> Self {
+                Self { doc }
+            }
+        }
+
+        impl huus::conversions::IntoDoc for {{ projection_name }} {
+            fn into_doc(self) -> bson::Document {
+                self.doc
+            }
+        }
+
         {% let coll_name = generator.make_coll_name(collection_name) %}
         pub struct {{ coll_name }};
 
-        impl huus::query::Query for {{ coll_name }} {
+        impl huus::query::Schema for {{ coll_name }} {
             type Data = {{ data_name }};
-            type Insert = {{ insert_name }};
-            type Filter = {{ filter_name }};
-            type Update = {{ update_name }};
             fn get_collection_name() -> &'static str {
                 "{{ collection_name }}"
             }
@@ -144,6 +215,40 @@ impl huus::conversions::IntoDoc for {{ data_name }} {
                 {% endfor %}
                 fields
             }
+            fn get_field_types() -> Vec<(&'static str, &'static str)> {
+                {{ generator.make_field_types(spec.struct_name.name.as_str()) }}
+            }
+            fn get_validator() -> Option<bson::Document> {
+                let mut required = Vec::new();
+                {% for member in spec.members %}
+                    {% if member.is_required() %}
+                        required.push(bson::Bson::String("{{ member.db_name }}".to_string()));
+                    {% endif %}
+                {% endfor %}
+                let properties = {{ generator.make_properties(collection_name) }};
+                let mut schema = bson::Document::new();
+                schema.insert("bsonType", "object");
+                if !required.is_empty() {
+                    schema.insert("required", required);
+                }
+                schema.insert("properties", properties);
+                let mut validator = bson::Document::new();
+                validator.insert("$jsonSchema", schema);
+                Some(validator)
+            }
+        }
+
+        impl huus::query::ReadQuery for {{ coll_name }} {
+            type Filter = {{ filter_name }};
+            type Projection = {{ projection_name }};
+        }
+
+        impl huus::query::WriteQuery for {{ coll_name }} {
+            type Insert = {{ insert_name }};
+        }
+
+        impl huus::query::UpdateQuery for {{ coll_name }} {
+            type Update = {{ update_name }};
         }
     {% when None %}
 {% endmatch %}
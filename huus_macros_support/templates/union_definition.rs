@@ -4,8 +4,15 @@
 {% let update_name = spec.name.to_update() %}
 
 #[derive(Clone, Debug, PartialEq)]
+{% if generator.has_serde() %}
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(tag = "_huus_variant")]
+{% endif %}
 pub enum {{ data_name }} {
     {% for choice in spec.choices %}
+        {% if generator.has_serde() %}
+            #[serde(rename = "{{ choice.db_name }}")]
+        {% endif %}
         {{ choice.rust_name }}({{ choice.variant.to_data() }}),
     {% endfor %}
 }
@@ -30,6 +37,23 @@ impl huus::conversions::FromDoc for {{ data_name }} {
             }
         }
     }
+
+    fn from_doc_strict(doc: bson::Document)
+    -> Result<{{ data_name }}, huus::errors::ConversionError> {
+        let mut inner = doc.clone();
+        inner.remove("_huus_variant");
+        match doc.get_str("_huus_variant") {
+            {% for choice in spec.choices %}
+                Ok("{{ choice.db_name }}") => {
+                    <{{ choice.variant.to_data() }} as huus::conversions::FromDoc>::from_doc_strict(
+                        inner
+                    )?;
+                }
+            {% endfor %}
+            _ => {}
+        }
+        Self::from_doc(doc)
+    }
 }
 
 impl huus::conversions::IntoDoc for {{ data_name }} {
@@ -1 +1,8 @@
 {{ name.to_update() }}::new({{ generator.object(object) }})
+{% if !array_filters.is_empty() %}
+    .with_array_filters(vec![
+        {% for filter in array_filters %}
+            {{ generator.object(filter) }},
+        {% endfor %}
+    ])
+{% endif %}
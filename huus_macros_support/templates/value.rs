@@ -0,0 +1,48 @@
+{% match value -%}
+    {%- when Value::F64 with (value) -%}
+        bson::Bson::FloatingPoint({{ value }}f64)
+    {%- when Value::String with (string) -%}
+        bson::Bson::String("{{ string }}".to_string())
+    {%- when Value::ObjectId with (value) -%}
+    {
+        let oid = bson::oid::ObjectId::with_string("{{ value }}")
+            .expect("Huus: Failed to convert the given string to an ObjectId");
+        bson::Bson::ObjectId(oid)
+    }
+    {%- when Value::Bool with (value) -%}
+        bson::Bson::Boolean({{ value }})
+    {%- when Value::Date with (value) -%}
+    {
+        let date = "{{ value.to_rfc3339() }}".parse::<chrono::DateTime<chrono::Utc>>();
+        bson::Bson::UtcDatetime(date.expect("Huus: Failed"))
+    }
+    {%- when Value::RelativeDate with { offset, today } -%}
+    {
+        let now = chrono::Utc::now();
+        {% if today.clone() %}
+            let now = now
+                - chrono::Duration::seconds(chrono::Timelike::num_seconds_from_midnight(&now).into())
+                - chrono::Duration::nanoseconds(chrono::Timelike::nanosecond(&now).into());
+        {% endif %}
+        bson::Bson::UtcDatetime(now + chrono::Duration::seconds({{ offset }}))
+    }
+    {%- when Value::I32 with (value) -%}
+        bson::Bson::I32({{ value }})
+    {%- when Value::I64 with (value) -%}
+        bson::Bson::I64({{ value }})
+    {%- when Value::Object with (object) -%}
+        bson::Bson::Document({{ generator.object(object) }})
+    {%- when Value::Array with (values) -%}
+        bson::Bson::Array(vec![
+            {% for value in values %}
+                {{ generator.value(value) }},
+            {% endfor %}
+        ])
+    {%- when Value::Null -%}
+        bson::Bson::Null
+    {%- when Value::Code with { code, cast } -%}
+    {
+        let value: {{ cast.to_data() }} = {{ code }};
+        value.huus_into_bson()
+    }
+{%- endmatch %}